@@ -0,0 +1,31 @@
+use std::process::Command;
+
+/// Embed the git commit for `/version`. Falls back to `GITHUB_SHA` (CI builds)
+/// and then "unknown" when building outside a git checkout, e.g. in Docker.
+fn main() {
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short=10", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .or_else(|| {
+            std::env::var("GITHUB_SHA")
+                .ok()
+                .map(|sha| sha.chars().take(10).collect())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=BUILD_COMMIT={commit}");
+    println!(
+        "cargo:rustc-env=BUILD_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    println!(
+        "cargo:rustc-env=BUILD_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_default()
+    );
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-env-changed=GITHUB_SHA");
+}
@@ -0,0 +1,209 @@
+//! Dispute alerts in Prometheus Alertmanager's webhook format (version 4).
+//!
+//! Every dispute event is posted as a one-alert notification: `firing` while the
+//! dispute is open, `resolved` once it reaches a terminal status or is canceled.
+//! Alerts are identified by `alertname` and `dispute_id`, so the fingerprint stays
+//! the same from the first alert to its resolution while `status` and `severity`
+//! follow the dispute.
+
+use crate::channels;
+use crate::config::{AlertSeverity, AlertmanagerConfig};
+use crate::dry_run;
+use crate::record_delivery;
+use crate::retry;
+use crate::timestamp;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::warn;
+
+const ALERT_NAME: &str = "MostroDispute";
+
+/// One dispute event to report
+pub struct DisputeAlert {
+    pub dispute_id: String,
+    pub status: String,
+    pub severity: AlertSeverity,
+    pub initiator: String,
+    /// Unix timestamp the dispute was first seen
+    pub starts_at: u64,
+    /// Unix timestamp the dispute ended, for resolved alerts
+    pub ends_at: Option<u64>,
+    /// Link back to the Nostr event
+    pub generator_url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Payload {
+    version: &'static str,
+    group_key: String,
+    truncated_alerts: u64,
+    status: &'static str,
+    receiver: String,
+    group_labels: BTreeMap<&'static str, String>,
+    common_labels: BTreeMap<&'static str, String>,
+    common_annotations: BTreeMap<&'static str, String>,
+    #[serde(rename = "externalURL")]
+    external_url: String,
+    alerts: Vec<Alert>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Alert {
+    status: &'static str,
+    labels: BTreeMap<&'static str, String>,
+    annotations: BTreeMap<&'static str, String>,
+    starts_at: String,
+    ends_at: String,
+    #[serde(rename = "generatorURL")]
+    generator_url: String,
+    fingerprint: String,
+}
+
+pub struct Alertmanager {
+    url: String,
+    receiver: String,
+    external_url: String,
+    http: reqwest::Client,
+}
+
+impl Alertmanager {
+    pub fn new(config: &AlertmanagerConfig) -> Self {
+        Self {
+            url: config.url.clone(),
+            receiver: config.receiver.clone(),
+            external_url: config.external_url.clone().unwrap_or_default(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    fn payload(&self, alert: &DisputeAlert) -> Payload {
+        let status = match alert.ends_at {
+            Some(_) => "resolved",
+            None => "firing",
+        };
+        let labels = BTreeMap::from([
+            ("alertname", ALERT_NAME.to_string()),
+            ("dispute_id", alert.dispute_id.clone()),
+            ("status", alert.status.clone()),
+            ("severity", alert.severity.as_str().to_string()),
+            ("initiator", alert.initiator.clone()),
+        ]);
+        let annotations = BTreeMap::from([(
+            "summary",
+            format!("Dispute {} is {}", alert.dispute_id, alert.status),
+        )]);
+        let group_labels = BTreeMap::from([("alertname", ALERT_NAME.to_string())]);
+
+        Payload {
+            version: "4",
+            group_key: format!("{{}}:{{alertname=\"{ALERT_NAME}\"}}"),
+            truncated_alerts: 0,
+            status,
+            receiver: self.receiver.clone(),
+            group_labels,
+            common_labels: labels.clone(),
+            common_annotations: annotations.clone(),
+            external_url: self.external_url.clone(),
+            alerts: vec![Alert {
+                status,
+                labels,
+                annotations,
+                starts_at: timestamp::iso8601(alert.starts_at),
+                // Alertmanager's zero time for alerts that haven't ended
+                ends_at: alert
+                    .ends_at
+                    .map_or("0001-01-01T00:00:00Z".to_string(), timestamp::iso8601),
+                generator_url: alert.generator_url.clone(),
+                fingerprint: fingerprint(&alert.dispute_id),
+            }],
+        }
+    }
+
+    /// Post the alert, logging failures
+    pub async fn send(&self, alert: &DisputeAlert) {
+        let payload = self.payload(alert);
+        let json = serde_json::to_string(&payload).unwrap_or_default();
+        if dry_run::intercepted("Alertmanager", "POST", &json) {
+            return;
+        }
+        let payload = &payload;
+        let result = retry::POLICY
+            .run("Alertmanager alert", || async move {
+                self.http
+                    .post(&self.url)
+                    .timeout(channels::TIMEOUT)
+                    .json(payload)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+            })
+            .await;
+        if let Err(ref e) = result {
+            warn!(
+                "Failed to send dispute {} to Alertmanager receiver: {}",
+                alert.dispute_id, e
+            );
+        }
+        record_delivery("alertmanager", result.err().map(|e| e.to_string()));
+    }
+}
+
+/// Stable identifier for a dispute's alert (64-bit FNV-1a of its identifying labels)
+fn fingerprint(dispute_id: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in ALERT_NAME.bytes().chain([0]).chain(dispute_id.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alertmanager() -> Alertmanager {
+        Alertmanager {
+            url: "http://localhost:9093".into(),
+            receiver: "mostro-watchdog".into(),
+            external_url: String::new(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    #[test]
+    fn test_payload() {
+        let mut alert = DisputeAlert {
+            dispute_id: "abc-123".into(),
+            status: "initiated".into(),
+            severity: AlertSeverity::Critical,
+            initiator: "buyer".into(),
+            starts_at: 1708425600,
+            ends_at: None,
+            generator_url: "nostr:note1xyz".into(),
+        };
+        let firing = serde_json::to_value(alertmanager().payload(&alert)).unwrap();
+        assert_eq!(firing["version"], "4");
+        assert_eq!(firing["status"], "firing");
+        assert_eq!(firing["alerts"][0]["labels"]["dispute_id"], "abc-123");
+        assert_eq!(firing["alerts"][0]["labels"]["severity"], "critical");
+        assert_eq!(firing["alerts"][0]["startsAt"], "2024-02-20T10:40:00Z");
+        assert_eq!(firing["alerts"][0]["endsAt"], "0001-01-01T00:00:00Z");
+        assert_eq!(firing["alerts"][0]["generatorURL"], "nostr:note1xyz");
+
+        alert.status = "settled".into();
+        alert.severity = AlertSeverity::Info;
+        alert.ends_at = Some(1708429200);
+        let resolved = serde_json::to_value(alertmanager().payload(&alert)).unwrap();
+        assert_eq!(resolved["status"], "resolved");
+        assert_eq!(resolved["alerts"][0]["labels"]["status"], "settled");
+        assert_eq!(resolved["alerts"][0]["endsAt"], "2024-02-20T11:40:00Z");
+        // Same alert from first notification to resolution
+        assert_eq!(
+            firing["alerts"][0]["fingerprint"],
+            resolved["alerts"][0]["fingerprint"]
+        );
+    }
+}
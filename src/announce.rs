@@ -0,0 +1,211 @@
+//! `mostro-watchdog announce "<message>"`: send an operator message, e.g. planned
+//! maintenance, through every configured channel.
+//!
+//! The message goes to the alert chat, the health chat when it is separate, and
+//! each Telegram chat, Pushover user and webhook used by escalation steps or
+//! degraded fallbacks, once each. Telegram gets it through the `announcement`
+//! template, so it is escaped like any other alert.
+
+use crate::branding::Branding;
+use crate::channels;
+use crate::config::{Config, EscalationAction};
+use crate::proxy;
+use crate::templates::Templates;
+use crate::{send_markdown, Destination};
+use std::fmt;
+
+/// Pushover notification title
+const TITLE: &str = "Mostro watchdog announcement";
+
+/// Send `message` everywhere, printing a line per channel. Returns whether every
+/// delivery succeeded.
+pub async fn run(config: &Config, message: &str) -> bool {
+    let templates = match Templates::load(config.templates.as_ref().unwrap_or(&Default::default()))
+    {
+        Ok(templates) => templates.with_branding(Branding::from_config(
+            config.branding.as_ref().unwrap_or(&Default::default()),
+        )),
+        Err(e) => {
+            eprintln!("❌ Failed to load templates: {e}");
+            return false;
+        }
+    };
+    let mut context = tera::Context::new();
+    context.insert("message", message);
+    let text = templates.render("announcement", &context);
+
+    let bot = match proxy::bot(&config.telegram) {
+        Ok(bot) => bot,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            return false;
+        }
+    };
+    let http = reqwest::Client::new();
+    let mut delivered = true;
+    for target in targets(config) {
+        let result = match target {
+            Target::Telegram { chat_id, topic_id } => {
+                let destination = Destination {
+                    chat_id,
+                    topic_id,
+                    silent: false,
+                };
+                send_markdown(&bot, destination, &text)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            Target::Pushover { token, user } => {
+                channels::pushover(&http, token, user, 0, TITLE, message)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            Target::Webhook { url } => {
+                let payload = serde_json::json!({ "announcement": message, "text": message });
+                channels::webhook(&http, url, &payload)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        };
+        match result {
+            Ok(()) => println!("✅ {target}"),
+            Err(e) => {
+                eprintln!("❌ {target}: {e}");
+                delivered = false;
+            }
+        }
+    }
+    delivered
+}
+
+/// One place an announcement is delivered to
+#[derive(Debug, PartialEq, Eq)]
+enum Target<'a> {
+    Telegram { chat_id: i64, topic_id: Option<i32> },
+    Pushover { token: &'a str, user: &'a str },
+    Webhook { url: &'a str },
+}
+
+impl fmt::Display for Target<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Telegram {
+                chat_id,
+                topic_id: Some(topic_id),
+            } => write!(f, "Telegram chat {chat_id} (topic {topic_id})"),
+            Target::Telegram { chat_id, .. } => write!(f, "Telegram chat {chat_id}"),
+            Target::Pushover { .. } => write!(f, "Pushover"),
+            // Webhook URLs often carry credentials, so only name the host
+            Target::Webhook { url } => match url::Url::parse(url) {
+                Ok(url) => write!(f, "Webhook {}", url.host_str().unwrap_or("")),
+                Err(_) => write!(f, "Webhook"),
+            },
+        }
+    }
+}
+
+/// Every distinct channel in the config, alert chat first
+fn targets(config: &Config) -> Vec<Target<'_>> {
+    let telegram = &config.telegram;
+    let mut targets = vec![
+        Target::Telegram {
+            chat_id: telegram.chat_id,
+            topic_id: telegram.disputes_topic(),
+        },
+        Target::Telegram {
+            chat_id: telegram.health_chat(),
+            topic_id: telegram.health_topic(),
+        },
+    ];
+
+    let escalation = config
+        .escalation
+        .iter()
+        .flat_map(|escalation| escalation.steps.iter().map(|step| &step.action));
+    let fallback = config
+        .health
+        .iter()
+        .flat_map(|health| health.degraded_fallback.iter());
+    for action in escalation.chain(fallback) {
+        targets.push(match action {
+            // Resending goes to the alert chat, which is already included
+            EscalationAction::Resend => continue,
+            EscalationAction::Telegram { chat_id, topic_id } => Target::Telegram {
+                chat_id: *chat_id,
+                topic_id: *topic_id,
+            },
+            EscalationAction::Pushover { token, user, .. } => Target::Pushover { token, user },
+            EscalationAction::Webhook { url } => Target::Webhook { url },
+        });
+    }
+
+    let mut distinct = Vec::new();
+    for target in targets {
+        if !distinct.contains(&target) {
+            distinct.push(target);
+        }
+    }
+    distinct
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_targets() {
+        let config: Config = toml::from_str(
+            r#"
+            [mostro]
+            pubkey = "npub1test"
+
+            [nostr]
+            relays = ["wss://relay.mostro.network"]
+
+            [telegram]
+            bot_token = "123:ABC"
+            chat_id = -100
+
+            [health]
+            degraded_fallback = [
+                { action = "webhook", url = "https://sms.example.com/send?key=abc" },
+            ]
+
+            [[escalation.steps]]
+            after_minutes = 5
+            action = "resend"
+
+            [[escalation.steps]]
+            after_minutes = 15
+            action = "telegram"
+            chat_id = -200
+
+            [[escalation.steps]]
+            after_minutes = 30
+            action = "webhook"
+            url = "https://sms.example.com/send?key=abc"
+            "#,
+        )
+        .unwrap();
+
+        let targets = targets(&config);
+        assert_eq!(
+            targets,
+            vec![
+                Target::Telegram {
+                    chat_id: -100,
+                    topic_id: None
+                },
+                Target::Telegram {
+                    chat_id: -200,
+                    topic_id: None
+                },
+                Target::Webhook {
+                    url: "https://sms.example.com/send?key=abc"
+                },
+            ]
+        );
+        assert_eq!(targets[2].to_string(), "Webhook sms.example.com");
+    }
+}
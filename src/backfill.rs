@@ -0,0 +1,79 @@
+//! Catching up on dispute events published while the watchdog wasn't running.
+//!
+//! Dispute events already seen are kept in the dispute store. Relays that support
+//! negentropy (NIP-77) are reconciled against them, so after a long outage only
+//! the missing events are downloaded; relays that don't are asked for everything
+//! since the last event seen.
+
+use crate::db::DisputeMessageStore;
+use crate::FETCH_TIMEOUT;
+use nostr_sdk::prelude::*;
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+/// Dispute events matching `filter` created since `since` that are on the relays of
+/// `client`, oldest first. `client` should be a dedicated one: its database is
+/// filled with the stored events.
+pub async fn missed_events(
+    client: &Client,
+    store: &DisputeMessageStore,
+    filter: Filter,
+    since: Timestamp,
+) -> Result<Vec<Event>, Box<dyn std::error::Error>> {
+    let filter = filter.since(since);
+
+    // Negentropy reconciles the relays' events against the client's database
+    let database = client.database();
+    for json in store.events_since(since.as_u64()).await? {
+        match Event::from_json(&json) {
+            Ok(event) => {
+                database.save_event(&event).await?;
+            }
+            Err(e) => warn!("Skipping unreadable stored event: {}", e),
+        }
+    }
+
+    let mut events = Vec::new();
+    let relays: Vec<String> = client
+        .relays()
+        .await
+        .into_keys()
+        .map(|url| url.to_string())
+        .collect();
+    let fallback: Vec<String> = match client.sync(filter.clone(), &SyncOptions::default()).await {
+        Ok(output) => {
+            for id in &output.received {
+                if let Some(event) = database.event_by_id(id).await? {
+                    events.push(event);
+                }
+            }
+            info!(
+                "Negentropy sync with {} relay(s) found {} missed event(s)",
+                output.success.len(),
+                output.received.len()
+            );
+            output.failed.keys().map(|url| url.to_string()).collect()
+        }
+        Err(e) => {
+            warn!("Negentropy sync failed: {}", e);
+            relays
+        }
+    };
+
+    if !fallback.is_empty() {
+        info!(
+            "Fetching missed events from {} relay(s) without negentropy",
+            fallback.len()
+        );
+        events.extend(
+            client
+                .fetch_events_from(fallback, vec![filter], Some(FETCH_TIMEOUT))
+                .await?,
+        );
+    }
+
+    let mut seen = HashSet::new();
+    events.retain(|event| seen.insert(event.id));
+    events.sort_by_key(|event| event.created_at);
+    Ok(events)
+}
@@ -0,0 +1,130 @@
+//! Relay reconnection with exponential backoff.
+//!
+//! A relay found disconnected is reconnected right away; each attempt that doesn't
+//! bring it back doubles the wait before the next one, up to a cap. Waits are
+//! randomly shortened by up to half so relays that dropped together (or several
+//! watchdogs behind the same outage) don't retry in lockstep.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Doubling delays from `base` up to `max`
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl Backoff {
+    /// Wait after `failures` failed attempts: `base * 2^(failures - 1)`, capped at
+    /// `max`, minus up to half of it at random
+    pub fn delay(&self, failures: u32) -> Duration {
+        let full = self
+            .base
+            .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+            .min(self.max);
+        full - full.mul_f64(random() / 2.0)
+    }
+}
+
+/// Uniform in `[0, 1)`; every `RandomState` is seeded differently
+fn random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Reconnection state of the relays currently down
+#[derive(Debug)]
+pub struct RelayReconnects {
+    policy: Backoff,
+    down: HashMap<String, Down>,
+}
+
+#[derive(Debug)]
+struct Down {
+    /// Reconnection attempts that didn't bring the relay back
+    failures: u32,
+    retry_at: Instant,
+}
+
+impl RelayReconnects {
+    pub fn new(policy: Backoff) -> Self {
+        Self {
+            policy,
+            down: HashMap::new(),
+        }
+    }
+
+    /// The relay is connected: forget its failures
+    pub fn connected(&mut self, relay: &str) {
+        self.down.remove(relay);
+    }
+
+    /// The relay was seen disconnected at `now`. Returns the number of failed
+    /// attempts so far when another attempt is due, `None` while backing off.
+    pub fn disconnected(&mut self, relay: &str, now: Instant) -> Option<u32> {
+        match self.down.get_mut(relay) {
+            None => {
+                self.down.insert(
+                    relay.to_string(),
+                    Down {
+                        failures: 0,
+                        retry_at: now + self.policy.base,
+                    },
+                );
+                Some(0)
+            }
+            Some(down) if now >= down.retry_at => {
+                down.failures += 1;
+                down.retry_at = now + self.policy.delay(down.failures);
+                Some(down.failures)
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: Backoff = Backoff {
+        base: Duration::from_secs(30),
+        max: Duration::from_secs(300),
+    };
+
+    #[test]
+    fn test_delay() {
+        for (failures, full) in [(1, 30), (2, 60), (3, 120), (4, 240), (5, 300), (40, 300)] {
+            let full = Duration::from_secs(full);
+            for _ in 0..20 {
+                let delay = POLICY.delay(failures);
+                assert!(delay <= full && delay >= full / 2, "{failures}: {delay:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconnects() {
+        let mut reconnects = RelayReconnects::new(POLICY);
+        let start = Instant::now();
+        let relay = "wss://relay.mostro.network";
+
+        // First seen down: reconnect at once, then wait at least the base delay
+        assert_eq!(reconnects.disconnected(relay, start), Some(0));
+        assert_eq!(
+            reconnects.disconnected(relay, start + POLICY.base / 2),
+            None
+        );
+        assert_eq!(reconnects.disconnected(relay, start + POLICY.base), Some(1));
+        // The next wait is 30s shortened by up to half
+        let next = start + POLICY.base + POLICY.base;
+        assert_eq!(reconnects.disconnected(relay, next), Some(2));
+
+        reconnects.connected(relay);
+        assert_eq!(reconnects.disconnected(relay, next), Some(0));
+    }
+}
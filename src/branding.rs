@@ -0,0 +1,214 @@
+//! Operator branding applied to every rendered message: emoji replacement or
+//! removal, a name prefix and header capitalization.
+
+use crate::config::{BrandingConfig, HeaderCase};
+use crate::escape_markdown;
+
+#[derive(Debug, Clone)]
+pub struct Branding {
+    emoji: bool,
+    /// Replacements, longest emoji first so ZWJ sequences win over their parts
+    emoji_map: Vec<(String, String)>,
+    /// MarkdownV2-escaped prefix, including the separating space
+    prefix: Option<String>,
+    headers: Option<HeaderCase>,
+}
+
+impl Branding {
+    pub fn from_config(config: &BrandingConfig) -> Self {
+        let mut emoji_map: Vec<(String, String)> = config
+            .emoji_map
+            .iter()
+            .map(|(from, to)| (from.clone(), to.clone()))
+            .collect();
+        emoji_map.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+
+        Self {
+            emoji: config.emoji,
+            emoji_map,
+            prefix: config
+                .prefix
+                .as_deref()
+                .filter(|prefix| !prefix.is_empty())
+                .map(|prefix| format!("{} ", escape_markdown(prefix))),
+            headers: config.headers,
+        }
+    }
+
+    /// Apply branding to a rendered MarkdownV2 message
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = if self.emoji {
+            let mut text = text.to_string();
+            for (from, to) in &self.emoji_map {
+                text = text.replace(from, to);
+            }
+            text
+        } else {
+            strip_emoji(text)
+        };
+
+        if let Some(case) = self.headers {
+            text = header_case(&text, case);
+        }
+
+        match self.prefix {
+            Some(ref prefix) if !text.starts_with(prefix.as_str()) => format!("{prefix}{text}"),
+            _ => text,
+        }
+    }
+
+    /// Remove the prefix from a branded message that is embedded in another one
+    pub fn strip_prefix<'a>(&self, text: &'a str) -> &'a str {
+        match self.prefix {
+            Some(ref prefix) => text.strip_prefix(prefix.as_str()).unwrap_or(text),
+            None => text,
+        }
+    }
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Self::from_config(&BrandingConfig::default())
+    }
+}
+
+/// Emoji, variation selectors and joiners (approximate, covers the common blocks)
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // pictographs, emoticons, transport, supplemental symbols
+        | 0x2300..=0x23FF // misc technical (⏰, ⌛)
+        | 0x2600..=0x27BF // misc symbols and dingbats (⚠, ✅, ➖)
+        | 0x2B00..=0x2BFF // arrows and stars (⭐)
+        | 0x200D          // zero width joiner
+        | 0xFE0F          // emoji presentation selector
+    )
+}
+
+/// Remove emoji along with the space that separated them from the text
+fn strip_emoji(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !is_emoji(c) {
+            stripped.push(c);
+            continue;
+        }
+        while chars.peek().copied().is_some_and(is_emoji) {
+            chars.next();
+        }
+        if chars.peek() == Some(&' ') {
+            chars.next();
+        }
+    }
+    stripped
+}
+
+/// Re-case the first bold span on the first line, which built-in templates use as header.
+/// Sentence case only touches headers written entirely in capitals.
+fn header_case(text: &str, case: HeaderCase) -> String {
+    let first_line = text.lines().next().unwrap_or_default();
+    let Some(start) = first_line.find('*') else {
+        return text.to_string();
+    };
+    let Some(len) = first_line[start + 1..].find('*') else {
+        return text.to_string();
+    };
+    let header = &first_line[start + 1..start + 1 + len];
+
+    let recased = match case {
+        HeaderCase::Upper => header.to_uppercase(),
+        HeaderCase::Sentence if !header.chars().any(char::is_lowercase) => {
+            let lower = header.to_lowercase();
+            let mut recased = String::with_capacity(lower.len());
+            let mut capitalized = false;
+            for c in lower.chars() {
+                if !capitalized && c.is_alphabetic() {
+                    recased.extend(c.to_uppercase());
+                    capitalized = true;
+                } else {
+                    recased.push(c);
+                }
+            }
+            recased
+        }
+        HeaderCase::Sentence => return text.to_string(),
+    };
+
+    format!(
+        "{}{}{}",
+        &text[..start + 1],
+        recased,
+        &text[start + 1 + len..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALERT: &str = "🚨 *NEW DISPUTE*\n\n📋 *Dispute ID:* `abc`\n👨‍⚖️ *Taken by:* x";
+
+    fn branding(toml: &str) -> Branding {
+        Branding::from_config(&toml::from_str(toml).unwrap())
+    }
+
+    #[test]
+    fn test_default_branding_is_noop() {
+        assert_eq!(branding("").apply(ALERT), ALERT);
+    }
+
+    #[test]
+    fn test_strip_emoji() {
+        assert_eq!(
+            branding("emoji = false").apply(ALERT),
+            "*NEW DISPUTE*\n\n*Dispute ID:* `abc`\n*Taken by:* x"
+        );
+        assert_eq!(strip_emoji("⚠️ *Alert* \\- ok"), "*Alert* \\- ok");
+    }
+
+    #[test]
+    fn test_emoji_map() {
+        let branding = branding(
+            r#"
+            [emoji_map]
+            "🚨" = "🔴"
+            "👨‍⚖️" = "[solver]"
+            "#,
+        );
+        assert_eq!(
+            branding.apply(ALERT),
+            "🔴 *NEW DISPUTE*\n\n📋 *Dispute ID:* `abc`\n[solver] *Taken by:* x"
+        );
+    }
+
+    #[test]
+    fn test_header_case() {
+        let sentence = branding("headers = \"sentence\"");
+        assert_eq!(
+            sentence.apply("✅ *DISPUTE RESOLVED \\- SETTLED*\n\n*ID:* 1"),
+            "✅ *Dispute resolved \\- settled*\n\n*ID:* 1"
+        );
+        // Mixed-case headers are left alone
+        assert_eq!(
+            sentence.apply("🐕 *mostro\\-watchdog 0\\.2\\.5*"),
+            "🐕 *mostro\\-watchdog 0\\.2\\.5*"
+        );
+
+        let upper = branding("headers = \"upper\"");
+        assert_eq!(
+            upper.apply("💓 *Health Check*\n\n*Uptime:* 1h"),
+            "💓 *HEALTH CHECK*\n\n*Uptime:* 1h"
+        );
+        assert_eq!(upper.apply("no header"), "no header");
+    }
+
+    #[test]
+    fn test_prefix() {
+        let branding = branding("prefix = \"[Acme Ops]\"");
+        let branded = branding.apply(ALERT);
+        assert!(branded.starts_with("\\[Acme Ops\\] 🚨 *NEW DISPUTE*"));
+        // Applying twice doesn't repeat the prefix
+        assert_eq!(branding.apply(&branded), branded);
+        assert_eq!(branding.strip_prefix(&branded), ALERT);
+    }
+}
@@ -0,0 +1,81 @@
+//! Delivery outside Telegram: Pushover push notifications and JSON webhooks
+//! (e.g. an SMS gateway). Used by escalation steps and degradation alerts.
+//!
+//! Each attempt is cut off after `TIMEOUT`, so a hung endpoint fails on its own
+//! instead of holding up the channels delivered alongside it, and failures that may
+//! pass are retried under the shared [`retry::POLICY`].
+
+use crate::dry_run;
+use crate::retry;
+use std::future::Future;
+use std::time::Duration;
+
+const PUSHOVER_API: &str = "https://api.pushover.net/1/messages.json";
+
+/// Longest one channel gets to deliver a message
+pub const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Run a delivery that has no timeout of its own (e.g. a Telegram send), giving
+/// up after `TIMEOUT`
+pub async fn timed<T, E: std::fmt::Display>(
+    delivery: impl Future<Output = Result<T, E>>,
+) -> Result<T, String> {
+    match tokio::time::timeout(TIMEOUT, delivery).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("timed out after {}s", TIMEOUT.as_secs())),
+    }
+}
+
+/// Send a push notification through Pushover
+pub async fn pushover(
+    http: &reqwest::Client,
+    token: &str,
+    user: &str,
+    priority: i8,
+    title: &str,
+    message: &str,
+) -> Result<(), reqwest::Error> {
+    if dry_run::intercepted("Pushover", "push", &format!("{title}\n{message}")) {
+        return Ok(());
+    }
+    let priority = &priority.to_string();
+    retry::POLICY
+        .run("Pushover notification", || async move {
+            http.post(PUSHOVER_API)
+                .timeout(TIMEOUT)
+                .form(&[
+                    ("token", token),
+                    ("user", user),
+                    ("title", title),
+                    ("message", message),
+                    ("priority", priority.as_str()),
+                ])
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        })
+        .await
+}
+
+/// POST a JSON payload to a webhook
+pub async fn webhook(
+    http: &reqwest::Client,
+    url: &str,
+    payload: &serde_json::Value,
+) -> Result<(), reqwest::Error> {
+    if dry_run::intercepted(url, "POST", &payload.to_string()) {
+        return Ok(());
+    }
+    retry::POLICY
+        .run("Webhook", || async move {
+            http.post(url)
+                .timeout(TIMEOUT)
+                .json(payload)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        })
+        .await
+}
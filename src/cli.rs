@@ -0,0 +1,343 @@
+//! Command-line interface.
+//!
+//! `mostro-watchdog [CONFIG_PATH]` keeps working as before and runs the watchdog;
+//! everything else is a subcommand. The config path can come from the positional
+//! argument, `--config`, or `MOSTRO_WATCHDOG_CONFIG`, and otherwise falls back to the
+//! search order in [`default_config_path`]. `--profile` selects a `[profile.<name>]`
+//! table of the config, and `--chat-id` and `--relay` override the file for a quick
+//! test against a personal chat or a single relay.
+
+use crate::config::Config;
+use clap::{Args, Parser, Subcommand};
+use std::path::{Path, PathBuf};
+
+const AFTER_HELP: &str = "\
+CONFIG SEARCH ORDER:
+    1. ./config.toml (current directory)
+    2. ~/.config/mostro-watchdog/config.toml
+
+EXAMPLES:
+    mostro-watchdog
+    mostro-watchdog /etc/mostro-watchdog/config.toml
+    mostro-watchdog --config ~/my-config.toml --dry-run
+    mostro-watchdog --chat-id 123456789 --relay wss://relay.mostro.network
+    mostro-watchdog --profile staging
+    mostro-watchdog validate /etc/mostro-watchdog/config.toml
+    mostro-watchdog disputes --status initiated
+    mostro-watchdog replay --limit 5 --notify
+    mostro-watchdog announce \"Maintenance at 20:00 UTC\"
+    mostro-watchdog --once    (from cron, e.g. every 5 minutes)
+    RUST_LOG=debug mostro-watchdog";
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "mostro-watchdog",
+    version,
+    about = "🐕 Dispute notification bot for Mostro admins",
+    after_help = AFTER_HELP,
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    /// Alert on dispute events published since the last run, then exit (for cron)
+    #[arg(long)]
+    once: bool,
+
+    /// Process events as usual but log alerts instead of sending them
+    #[arg(long, global = true, env = "MOSTRO_WATCHDOG_DRY_RUN")]
+    pub dry_run: bool,
+
+    #[command(flatten)]
+    pub overrides: Overrides,
+}
+
+/// Config values given on the command line, taking precedence over the file
+#[derive(Debug, Clone, Default, Args)]
+pub struct Overrides {
+    /// Merge the config's `[profile.<NAME>]` table over its base settings
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        env = "MOSTRO_WATCHDOG_PROFILE"
+    )]
+    pub profile: Option<String>,
+
+    /// Send every message to this chat instead of the configured chats and topics
+    #[arg(long, global = true, value_name = "ID", allow_hyphen_values = true)]
+    pub chat_id: Option<i64>,
+
+    /// Use this relay, with every role and `nostr.proxy`, instead of `nostr.relays`;
+    /// repeat for several
+    #[arg(long = "relay", global = true, value_name = "URL")]
+    pub relays: Vec<String>,
+}
+
+impl Overrides {
+    /// Load the config at `path` with the overrides applied and checked
+    pub fn load(&self, path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        let mut config = Config::load_profile(path, self.profile.as_deref())?;
+        if self.apply(&mut config) {
+            config.validate()?;
+        }
+        Ok(config)
+    }
+
+    /// Apply the overrides to `config`. Returns whether anything was overridden.
+    fn apply(&self, config: &mut Config) -> bool {
+        if let Some(chat_id) = self.chat_id {
+            // A personal chat has no forum topics, and health alerts come along too
+            let telegram = &mut config.telegram;
+            telegram.chat_id = chat_id;
+            telegram.health_chat_id = None;
+            telegram.topic_id = None;
+            telegram.topics = Default::default();
+        }
+        if !self.relays.is_empty() {
+            config.nostr.relays = self.relays.clone();
+            config.nostr.relay_roles.clear();
+            config.nostr.relay_filters.clear();
+            config.nostr.relay_proxies.clear();
+            config.nostr.secondary_relays.clear();
+            config.nostr.target_relays = None;
+        }
+        self.chat_id.is_some() || !self.relays.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Watch for disputes and send alerts (the default)
+    Run {
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Alert on dispute events published since the last run, then exit (for cron)
+        #[arg(long)]
+        once: bool,
+    },
+    /// Check the config (formats, Telegram token) and exit non-zero on errors
+    Validate(ConfigArgs),
+    /// Check Telegram, each relay and the Mostro pubkey's events, and print a report
+    Doctor(ConfigArgs),
+    /// Prompt for the essentials and write a new config
+    Init(ConfigArgs),
+    /// Print the effective config as JSON, with secrets redacted
+    Export(ConfigArgs),
+    /// List disputes tracked in the local store, newest first
+    Disputes {
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Only disputes with this status, e.g. `initiated`
+        #[arg(long)]
+        status: Option<String>,
+        /// Number of disputes to list
+        #[arg(long, default_value_t = 20)]
+        limit: u32,
+    },
+    /// Print the latest dispute events on the relays, to check what they serve
+    Replay {
+        #[command(flatten)]
+        config: ConfigArgs,
+        /// Number of events to print
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+        /// Also send the alerts again, e.g. to recover from missed ones
+        #[arg(long)]
+        notify: bool,
+    },
+    /// Send a message to every configured channel, e.g. planned maintenance
+    Announce {
+        /// The message; Markdown characters are escaped
+        message: String,
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+    /// Send a test dispute alert through the configured destinations
+    TestAlert(ConfigArgs),
+}
+
+#[derive(Debug, Clone, Default, Args)]
+pub struct ConfigArgs {
+    /// Path to config.toml
+    #[arg(value_name = "CONFIG_PATH")]
+    path: Option<PathBuf>,
+
+    /// Path to config.toml
+    #[arg(
+        short = 'c',
+        long = "config",
+        value_name = "PATH",
+        env = "MOSTRO_WATCHDOG_CONFIG"
+    )]
+    flag: Option<PathBuf>,
+}
+
+impl ConfigArgs {
+    /// The config path given (positional first), or the default search order
+    pub fn path(&self) -> PathBuf {
+        self.path
+            .clone()
+            .or_else(|| self.flag.clone())
+            .unwrap_or_else(default_config_path)
+    }
+}
+
+impl Cli {
+    /// The subcommand, `run` when none was given
+    pub fn command(&self) -> Command {
+        self.command.clone().unwrap_or_else(|| Command::Run {
+            config: self.config.clone(),
+            once: self.once,
+        })
+    }
+}
+
+impl Command {
+    pub fn config_path(&self) -> PathBuf {
+        match self {
+            Command::Validate(config)
+            | Command::Doctor(config)
+            | Command::Init(config)
+            | Command::Export(config)
+            | Command::TestAlert(config)
+            | Command::Run { config, .. }
+            | Command::Disputes { config, .. }
+            | Command::Replay { config, .. }
+            | Command::Announce { config, .. } => config.path(),
+        }
+    }
+}
+
+/// Resolve the default config path with fallback:
+/// 1. ./config.toml (current directory)
+/// 2. ~/.config/mostro-watchdog/config.toml
+pub fn default_config_path() -> PathBuf {
+    let local = PathBuf::from("config.toml");
+    if local.exists() {
+        return local;
+    }
+
+    if let Some(home) = std::env::var_os("HOME") {
+        let xdg = PathBuf::from(home).join(".config/mostro-watchdog/config.toml");
+        if xdg.exists() {
+            return xdg;
+        }
+    }
+
+    // Return local path anyway — Config::load will produce a helpful error
+    local
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("mostro-watchdog").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        <Cli as clap::CommandFactory>::command().debug_assert();
+    }
+
+    #[test]
+    fn test_config_path() {
+        let cli = parse(&["/etc/watchdog.toml"]);
+        assert!(matches!(cli.command(), Command::Run { once: false, .. }));
+        assert_eq!(
+            cli.command().config_path(),
+            PathBuf::from("/etc/watchdog.toml")
+        );
+
+        let cli = parse(&["--config", "a.toml", "--dry-run"]);
+        assert_eq!(cli.command().config_path(), PathBuf::from("a.toml"));
+        assert!(cli.dry_run);
+
+        let cli = parse(&["--once", "a.toml"]);
+        assert!(matches!(cli.command(), Command::Run { once: true, .. }));
+        let cli = parse(&["run", "--once"]);
+        assert!(matches!(cli.command(), Command::Run { once: true, .. }));
+
+        let cli = parse(&["validate", "-c", "b.toml"]);
+        assert!(matches!(cli.command(), Command::Validate(_)));
+        assert_eq!(cli.command().config_path(), PathBuf::from("b.toml"));
+
+        let cli = parse(&["disputes", "c.toml", "--status", "initiated"]);
+        match cli.command() {
+            Command::Disputes { status, limit, .. } => {
+                assert_eq!(status.as_deref(), Some("initiated"));
+                assert_eq!(limit, 20);
+            }
+            command => panic!("unexpected {command:?}"),
+        }
+        assert_eq!(cli.command().config_path(), PathBuf::from("c.toml"));
+
+        let cli = parse(&["announce", "Maintenance at 20:00 UTC", "d.toml"]);
+        match cli.command() {
+            Command::Announce { message, .. } => assert_eq!(message, "Maintenance at 20:00 UTC"),
+            command => panic!("unexpected {command:?}"),
+        }
+        assert_eq!(cli.command().config_path(), PathBuf::from("d.toml"));
+
+        let cli = parse(&["replay", "--limit", "5", "--notify"]);
+        assert!(matches!(
+            cli.command(),
+            Command::Replay {
+                limit: 5,
+                notify: true,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let cli = parse(&[
+            "test-alert",
+            "--chat-id",
+            "-100123",
+            "--relay",
+            "wss://nos.lol",
+            "--relay",
+            "wss://relay.damus.io",
+        ]);
+        assert!(matches!(cli.command(), Command::TestAlert(_)));
+        assert_eq!(cli.overrides.chat_id, Some(-100123));
+
+        let mut config: Config = toml::from_str(
+            r#"
+            [mostro]
+            pubkey = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+
+            [nostr]
+            relays = ["wss://relay.mostro.network"]
+
+            [telegram]
+            bot_token = "123:ABC"
+            chat_id = -100
+            health_chat_id = -200
+            topic_id = 5
+            "#,
+        )
+        .unwrap();
+        assert!(!Overrides::default().apply(&mut config));
+        assert!(cli.overrides.apply(&mut config));
+        assert_eq!(config.telegram.chat_id, -100123);
+        assert_eq!(config.telegram.health_chat(), -100123);
+        assert_eq!(config.telegram.disputes_topic(), None);
+        assert_eq!(
+            config.nostr.relays,
+            vec!["wss://nos.lol", "wss://relay.damus.io"]
+        );
+
+        let overrides = parse(&["--relay", "nos.lol"]).overrides;
+        assert!(overrides.apply(&mut config));
+        assert!(config.validate().is_err());
+    }
+}
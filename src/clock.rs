@@ -0,0 +1,138 @@
+//! Clock drift detection.
+//!
+//! The subscription filter starts at `Timestamp::now()` and SLAs are measured against
+//! event `created_at`, so a skewed local clock silently drops events or distorts
+//! timings. The offset is estimated from the HTTP `Date` header of the relays' NIP-11
+//! endpoints, falling back to the `created_at` of received events.
+
+use crate::timestamp;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+/// Event samples kept for the fallback estimate
+const MAX_EVENT_SAMPLES: usize = 20;
+/// Fewer event samples than this are too noisy to judge
+const MIN_EVENT_SAMPLES: usize = 3;
+
+/// Where an offset estimate came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffsetSource {
+    Relays,
+    Events,
+}
+
+impl OffsetSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Relays => "relays",
+            Self::Events => "events",
+        }
+    }
+}
+
+/// Offsets of received events' `created_at` from the local receive time
+#[derive(Debug, Default)]
+pub struct EventOffsets {
+    samples: Mutex<VecDeque<i64>>,
+}
+
+impl EventOffsets {
+    pub fn record(&self, created_at: u64) {
+        let offset = created_at as i64 - unix_now() as i64;
+        let mut samples = self.samples.lock().expect("clock samples lock poisoned");
+        if samples.len() == MAX_EVENT_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(offset);
+    }
+
+    /// Median offset, positive when events are stamped ahead of the local clock.
+    /// Propagation delay biases this towards negative values.
+    pub fn median(&self) -> Option<i64> {
+        let samples = self.samples.lock().expect("clock samples lock poisoned");
+        if samples.len() < MIN_EVENT_SAMPLES {
+            return None;
+        }
+        median(samples.iter().copied().collect())
+    }
+}
+
+/// Median offset of the relays' clocks from ours, positive when the local clock is behind
+pub async fn relay_offset(http: &reqwest::Client, relays: &[String]) -> Option<i64> {
+    let mut offsets = Vec::new();
+    for relay in relays {
+        match probe_relay(http, relay).await {
+            Some(offset) => offsets.push(offset),
+            None => debug!("No usable Date header from {}", relay),
+        }
+    }
+    median(offsets)
+}
+
+/// Offset of one relay's clock, read from the `Date` header of its NIP-11 document
+async fn probe_relay(http: &reqwest::Client, relay: &str) -> Option<i64> {
+    let url = nip11_url(relay)?;
+    let sent = unix_now();
+    let response = http
+        .get(&url)
+        .header(reqwest::header::ACCEPT, "application/nostr+json")
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .ok()?;
+    // The header was produced somewhere between sending and receiving
+    let local = (sent + unix_now()) / 2;
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)?
+        .to_str()
+        .ok()?;
+    Some(timestamp::parse_http_date(date)? as i64 - local as i64)
+}
+
+/// HTTP(S) URL serving a relay's NIP-11 document
+pub fn nip11_url(relay: &str) -> Option<String> {
+    if let Some(rest) = relay.strip_prefix("wss://") {
+        Some(format!("https://{rest}"))
+    } else {
+        relay
+            .strip_prefix("ws://")
+            .map(|rest| format!("http://{rest}"))
+    }
+}
+
+fn median(mut values: Vec<i64>) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    Some(values[values.len() / 2])
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_offsets() {
+        let offsets = EventOffsets::default();
+        let now = unix_now();
+        offsets.record(now + 120);
+        offsets.record(now + 118);
+        assert_eq!(offsets.median(), None);
+
+        // A single delayed event doesn't move the estimate
+        offsets.record(now - 3600);
+        let median = offsets.median().unwrap();
+        assert!((115..=120).contains(&median), "{median}");
+    }
+}
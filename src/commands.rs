@@ -0,0 +1,247 @@
+//! Interactive Telegram bot commands.
+//!
+//! Commands are only answered for allowlisted users (plus the on-call rotation);
+//! anyone else is rejected and optionally reported to the alert chat.
+
+use crate::oncall::OnCall;
+use crate::stats::stats;
+use crate::templates::Templates;
+use crate::{
+    send_heartbeat, send_markdown, send_with_fallback, Destination, HealthMonitor, BUILD_COMMIT,
+    BUILD_PROFILE, BUILD_TARGET, VERSION,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+use teloxide::prelude::*;
+use teloxide::types::{ParseMode, ReplyParameters};
+use teloxide::utils::command::BotCommands;
+use tracing::{info, warn};
+
+#[derive(BotCommands, Clone, Debug, PartialEq)]
+#[command(rename_rule = "lowercase", description = "mostro-watchdog commands:")]
+pub enum Command {
+    #[command(description = "show who is on call")]
+    OnCall,
+    #[command(description = "show watchdog health status")]
+    Health,
+    #[command(description = "send a heartbeat now to confirm delivery")]
+    Heartbeat,
+    #[command(description = "show the running version and build info")]
+    Version,
+    #[command(description = "show runtime counters")]
+    Stats,
+}
+
+/// State shared by command handlers
+pub struct CommandContext {
+    pub templates: Arc<Templates>,
+    pub oncall: Option<Arc<OnCall>>,
+    pub health_monitor: Arc<HealthMonitor>,
+    /// Where heartbeats are delivered
+    pub heartbeat_dest: Destination,
+    /// Telegram user IDs allowed to use commands
+    pub allowed_users: HashSet<i64>,
+    /// Where rejected command attempts are reported, if enabled
+    pub report_to: Option<Destination>,
+}
+
+impl CommandContext {
+    /// Messages without a sender (anonymous group admins, channels) are never authorized
+    fn is_authorized(&self, user_id: Option<i64>) -> bool {
+        user_id.is_some_and(|id| self.allowed_users.contains(&id))
+    }
+}
+
+/// Register the command list with Telegram and answer commands until shutdown
+pub async fn run(bot: Bot, ctx: Arc<CommandContext>) {
+    if let Err(e) = bot.set_my_commands(Command::bot_commands()).await {
+        warn!("Failed to register bot commands: {}", e);
+    }
+
+    let handler = Update::filter_message()
+        .filter_command::<Command>()
+        .endpoint(handle_command);
+
+    Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![ctx])
+        .default_handler(|_| async {})
+        .build()
+        .dispatch()
+        .await;
+}
+
+async fn handle_command(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    ctx: Arc<CommandContext>,
+) -> ResponseResult<()> {
+    let user_id = msg.from.as_ref().map(|user| user.id.0 as i64);
+    if !ctx.is_authorized(user_id) {
+        reject(&bot, &msg, &ctx).await?;
+        return Ok(());
+    }
+
+    info!("Command {:?} received in chat {}", cmd, msg.chat.id);
+
+    let reply = match cmd {
+        Command::OnCall => oncall_reply(&ctx),
+        Command::Health => health_reply(&ctx),
+        Command::Version => version_reply(&ctx),
+        Command::Stats => stats_reply(&ctx),
+        Command::Heartbeat => {
+            match send_heartbeat(
+                &ctx.health_monitor,
+                &bot,
+                ctx.heartbeat_dest,
+                &ctx.templates,
+            )
+            .await
+            {
+                // The heartbeat itself is the answer
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    warn!("Failed to send on-demand heartbeat: {}", e);
+                    let mut context = tera::Context::new();
+                    context.insert("error", &e.to_string());
+                    ctx.templates.render("heartbeat_failed", &context)
+                }
+            }
+        }
+    };
+
+    reply_markdown(&bot, &msg, reply).await?;
+    Ok(())
+}
+
+/// Answer a command message with MarkdownV2 text
+async fn reply_markdown(bot: &Bot, msg: &Message, text: String) -> ResponseResult<Message> {
+    send_with_fallback(
+        bot.send_message(msg.chat.id, text)
+            .parse_mode(ParseMode::MarkdownV2)
+            .reply_parameters(ReplyParameters::new(msg.id)),
+    )
+    .await
+}
+
+/// Refuse an unauthorized command and report the attempt
+async fn reject(bot: &Bot, msg: &Message, ctx: &CommandContext) -> ResponseResult<()> {
+    let user = msg
+        .from
+        .as_ref()
+        .map(|user| user.full_name())
+        .unwrap_or_else(|| "unknown".to_string());
+    let user_id = msg.from.as_ref().map(|user| user.id.0).unwrap_or_default();
+    let command = msg.text().unwrap_or_default();
+    warn!(
+        "⛔ Rejected command {:?} from {} ({}) in chat {}",
+        command, user, user_id, msg.chat.id
+    );
+
+    let reply = ctx.templates.render("unauthorized", &tera::Context::new());
+    reply_markdown(bot, msg, reply).await?;
+
+    if let Some(report_to) = ctx.report_to {
+        let mut context = tera::Context::new();
+        context.insert("user", &user);
+        context.insert("user_id", &user_id);
+        context.insert("command", command);
+        let report = ctx.templates.render("unauthorized_report", &context);
+        if let Err(e) = send_markdown(bot, report_to, &report).await {
+            warn!("Failed to report unauthorized command: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Same data as the HTTP health endpoint
+fn health_reply(ctx: &CommandContext) -> String {
+    let status = ctx.health_monitor.status();
+
+    let mut context = tera::Context::new();
+    context.insert("healthy", &status.healthy);
+    context.insert("version", VERSION);
+    context.insert("uptime_hours", &(status.uptime_secs / 3600));
+    context.insert("uptime_minutes", &((status.uptime_secs % 3600) / 60));
+    context.insert("events_processed", &status.events_processed);
+    let time = |unix| ctx.templates.timestamp(unix);
+    context.insert("last_event", &status.last_event.map(time));
+    context.insert("last_heartbeat", &status.last_heartbeat.map(time));
+    ctx.templates.render("health", &context)
+}
+
+fn version_reply(ctx: &CommandContext) -> String {
+    let mut context = tera::Context::new();
+    context.insert("version", VERSION);
+    context.insert("commit", BUILD_COMMIT);
+    context.insert("target", BUILD_TARGET);
+    context.insert("profile", BUILD_PROFILE);
+    ctx.templates.render("version", &context)
+}
+
+/// Runtime counters as a monospace table
+fn stats_reply(ctx: &CommandContext) -> String {
+    let uptime_secs = ctx.health_monitor.status().uptime_secs;
+
+    let mut context = tera::Context::new();
+    context.insert("uptime_hours", &(uptime_secs / 3600));
+    context.insert("uptime_minutes", &((uptime_secs % 3600) / 60));
+    context.insert("table", &stats().snapshot().table());
+    ctx.templates.render("stats", &context)
+}
+
+/// Who is on call, without mentioning them
+fn oncall_reply(ctx: &CommandContext) -> String {
+    let mut context = tera::Context::new();
+    match ctx.oncall {
+        Some(ref oncall) => {
+            let shift = oncall.current();
+            context.insert("name", &shift.admin.name);
+            context.insert("until", &ctx.templates.timestamp(shift.until as u64));
+            context.insert("next", &shift.next.name);
+        }
+        None => context.insert("name", &None::<String>),
+    }
+    ctx.templates.render("oncall", &context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(allowed_users: HashSet<i64>) -> CommandContext {
+        CommandContext {
+            templates: Arc::new(Templates::default()),
+            oncall: None,
+            health_monitor: Arc::new(HealthMonitor::new()),
+            heartbeat_dest: Destination {
+                chat_id: 1,
+                topic_id: None,
+                silent: true,
+            },
+            allowed_users,
+            report_to: None,
+        }
+    }
+
+    #[test]
+    fn test_is_authorized() {
+        let ctx = context(HashSet::from([42]));
+
+        assert!(ctx.is_authorized(Some(42)));
+        assert!(!ctx.is_authorized(Some(7)));
+        assert!(!ctx.is_authorized(None));
+    }
+
+    #[test]
+    fn test_health_reply() {
+        let ctx = context(HashSet::new());
+        ctx.health_monitor.record_event();
+
+        let reply = health_reply(&ctx);
+        assert!(reply.starts_with("✅ *mostro\\-watchdog"));
+        assert!(reply.contains("📊 Events processed: 1\n"));
+        assert!(reply.ends_with("💓 Last heartbeat: never"));
+    }
+}
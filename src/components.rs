@@ -0,0 +1,80 @@
+//! Per-component health for the verbose health endpoint.
+//!
+//! Each component (nostr, telegram, store, http, and the out-of-band channels once
+//! used) keeps the outcome of its last operation, the last time one succeeded and
+//! the last error seen, so operators can tell which part is failing and since when.
+
+use serde::Serialize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Outcome of a component's most recent operation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ComponentStatus {
+    /// Nothing recorded yet
+    #[default]
+    Unknown,
+    Ok,
+    Error,
+}
+
+/// Outcome history of one component
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ComponentHealth {
+    pub status: ComponentStatus,
+    /// Unix timestamp of the last successful operation
+    pub last_ok_timestamp: Option<u64>,
+    /// Last error seen, kept after the component recovers
+    pub last_error: Option<String>,
+    /// Unix timestamp of `last_error`
+    pub last_error_timestamp: Option<u64>,
+}
+
+impl ComponentHealth {
+    /// Record an operation outcome at `now` (Unix seconds)
+    pub fn record(&mut self, result: Result<(), String>, now: u64) {
+        match result {
+            Ok(()) => {
+                self.status = ComponentStatus::Ok;
+                self.last_ok_timestamp = Some(now);
+            }
+            Err(e) => {
+                self.status = ComponentStatus::Error;
+                self.last_error = Some(e);
+                self.last_error_timestamp = Some(now);
+            }
+        }
+    }
+}
+
+/// Current Unix time in seconds
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut component = ComponentHealth::default();
+        assert_eq!(component.status, ComponentStatus::Unknown);
+
+        component.record(Err("timed out".into()), 100);
+        component.record(Ok(()), 160);
+        assert_eq!(
+            component,
+            ComponentHealth {
+                status: ComponentStatus::Ok,
+                last_ok_timestamp: Some(160),
+                last_error: Some("timed out".into()),
+                last_error_timestamp: Some(100),
+            }
+        );
+        assert_eq!(serde_json::to_value(&component).unwrap()["status"], "ok");
+    }
+}
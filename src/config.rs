@@ -1,16 +1,326 @@
-use serde::Deserialize;
-use std::path::Path;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
     pub mostro: MostroConfig,
     pub nostr: NostrConfig,
     pub telegram: TelegramConfig,
     pub alerts: Option<AlertsConfig>,
     pub health: Option<HealthConfig>,
+    pub templates: Option<TemplatesConfig>,
+    pub branding: Option<BrandingConfig>,
+    pub quiet_hours: Option<QuietHoursConfig>,
+    pub escalation: Option<EscalationConfig>,
+    pub oncall: Option<OnCallConfig>,
+    pub commands: Option<CommandsConfig>,
+    pub updates: Option<UpdatesConfig>,
+    pub telemetry: Option<TelemetryConfig>,
+    pub sentry: Option<SentryConfig>,
+    pub statsd: Option<StatsdConfig>,
+    pub influxdb: Option<InfluxConfig>,
+    pub alertmanager: Option<AlertmanagerConfig>,
+    pub reload: Option<ReloadConfig>,
+    pub secrets: Option<SecretsConfig>,
+    /// Every file the config was read from, in merge order (see [`Config::load`])
+    #[serde(skip)]
+    pub sources: Vec<PathBuf>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Applying config file changes without a restart
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReloadConfig {
+    /// Watch the config file and apply safe changes automatically
+    #[serde(default = "default_false")]
+    pub watch: bool,
+}
+
+/// Decryption of age-encrypted secrets (see [`crate::secrets`])
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SecretsConfig {
+    /// age identity file (`age-keygen` output) to decrypt secrets with
+    /// (default: the `AGE_IDENTITY_FILE` environment variable)
+    pub age_identity_file: Option<PathBuf>,
+}
+
+/// Dispute alerts posted in Alertmanager's webhook format
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AlertmanagerConfig {
+    /// Receiver URL the payloads are POSTed to
+    pub url: String,
+    /// `receiver` field of the payload (default: "mostro-watchdog")
+    #[serde(default = "default_alertmanager_receiver")]
+    pub receiver: String,
+    /// `externalURL` field of the payload, e.g. a dashboard link (optional)
+    pub external_url: Option<String>,
+}
+
+fn default_alertmanager_receiver() -> String {
+    "mostro-watchdog".to_string()
+}
+
+/// StatsD/DogStatsD metrics pushed over UDP
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StatsdConfig {
+    /// Agent address, e.g. "127.0.0.1:8125"
+    pub address: String,
+    /// Prefix for every metric name (default: "mostro_watchdog")
+    #[serde(default = "default_statsd_prefix")]
+    pub prefix: String,
+    /// Send labels as DogStatsD tags instead of metric name segments
+    #[serde(default = "default_false")]
+    pub dogstatsd: bool,
+    /// Seconds between pushes (default: 10)
+    #[serde(default = "default_statsd_interval")]
+    pub interval: u64,
+}
+
+fn default_statsd_prefix() -> String {
+    "mostro_watchdog".to_string()
+}
+
+fn default_statsd_interval() -> u64 {
+    10
+}
+
+/// InfluxDB line-protocol writes; set `bucket` for the v2 API or `database` for v1
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InfluxConfig {
+    /// Server URL, e.g. "http://localhost:8086"
+    pub url: String,
+    /// v2 organization
+    pub org: Option<String>,
+    /// v2 bucket
+    pub bucket: Option<String>,
+    /// v2 API token
+    #[serde(serialize_with = "redact_option")]
+    pub token: Option<String>,
+    /// File to read `token` from
+    pub token_file: Option<PathBuf>,
+    /// v1 database
+    pub database: Option<String>,
+    /// v1 credentials
+    pub username: Option<String>,
+    #[serde(serialize_with = "redact_option")]
+    pub password: Option<String>,
+    /// File to read `password` from
+    pub password_file: Option<PathBuf>,
+    /// v1 retention policy (default: the database default)
+    pub retention_policy: Option<String>,
+    /// Seconds between writes (default: 60)
+    #[serde(default = "default_influx_interval")]
+    pub interval: u64,
+}
+
+fn default_influx_interval() -> u64 {
+    60
+}
+
+/// Sentry error reporting; requires building with the `sentry` feature
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SentryConfig {
+    /// Project DSN
+    #[serde(default, serialize_with = "redact")]
+    pub dsn: String,
+    /// File to read `dsn` from
+    pub dsn_file: Option<PathBuf>,
+    /// Environment name shown in Sentry, e.g. "production"
+    pub environment: Option<String>,
+    /// Fraction of error events sent, 0.0 to 1.0 (default: 1.0)
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f32,
+}
+
+fn default_sample_rate() -> f32 {
+    1.0
+}
+
+/// OpenTelemetry export; requires building with the `otel` feature
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP collector base URL, e.g. "http://localhost:4318"
+    pub endpoint: String,
+    /// Extra HTTP headers sent to the collector, e.g. for authentication
+    #[serde(default, serialize_with = "redact_values")]
+    pub headers: HashMap<String, String>,
+    /// Reported `service.name` (default: "mostro-watchdog")
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    /// Seconds between metric exports (default: 60)
+    #[serde(default = "default_metrics_interval")]
+    pub metrics_interval: u64,
+}
+
+fn default_service_name() -> String {
+    "mostro-watchdog".to_string()
+}
+
+fn default_metrics_interval() -> u64 {
+    60
+}
+
+/// Opt-in notifications about new mostro-watchdog releases
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpdatesConfig {
+    /// Check the GitHub releases API for newer versions
+    #[serde(default = "default_false")]
+    pub check: bool,
+    /// Hours between checks (default: 24)
+    #[serde(default = "default_update_interval")]
+    pub interval_hours: u64,
+}
+
+fn default_update_interval() -> u64 {
+    24
+}
+
+/// Access control for interactive bot commands
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommandsConfig {
+    /// Telegram user IDs allowed to use commands (on-call admins are always allowed)
+    #[serde(default)]
+    pub allowed_users: Vec<i64>,
+    /// Report rejected command attempts to the alert chat
+    #[serde(default = "default_true")]
+    pub report_unauthorized: bool,
+}
+
+impl Default for CommandsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_users: Vec::new(),
+            report_unauthorized: true,
+        }
+    }
+}
+
+/// On-call rotation: new-dispute alerts notify only the admin currently on call
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OnCallConfig {
+    /// Shift length (default: weekly)
+    #[serde(default)]
+    pub rotation: Rotation,
+    /// Start of the first admin's shift, "YYYY-MM-DD" or "YYYY-MM-DD HH:MM"
+    pub start: String,
+    /// Fixed UTC offset for `start`, e.g. "-03:00" (default: "+00:00")
+    #[serde(default = "default_utc_offset")]
+    pub utc_offset: String,
+    /// How the admin on call is notified
+    #[serde(default)]
+    pub notify: OnCallNotify,
+    /// Admins in rotation order
+    pub admins: Vec<OnCallAdmin>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OnCallAdmin {
+    /// Display name used in mentions
+    pub name: String,
+    /// Telegram user ID
+    pub user_id: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    Daily,
+    #[default]
+    Weekly,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnCallNotify {
+    /// Mention the admin in the alert
+    #[default]
+    Mention,
+    /// Send the alert to the admin as a direct message
+    Dm,
+    /// Mention and direct message
+    Both,
+}
+
+impl OnCallNotify {
+    pub fn mention(self) -> bool {
+        matches!(self, Self::Mention | Self::Both)
+    }
+
+    pub fn dm(self) -> bool {
+        matches!(self, Self::Dm | Self::Both)
+    }
+}
+
+/// Escalation of new-dispute alerts that nobody takes.
+///
+/// A dispute counts as acknowledged once it moves past `initiated`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EscalationConfig {
+    #[serde(default)]
+    pub steps: Vec<EscalationStep>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EscalationStep {
+    /// Minutes after the original alert
+    pub after_minutes: u64,
+    #[serde(flatten)]
+    pub action: EscalationAction,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum EscalationAction {
+    /// Re-send the alert to the alert chat
+    Resend,
+    /// Send the alert to another Telegram chat
+    Telegram { chat_id: i64, topic_id: Option<i32> },
+    /// Push notification via Pushover
+    Pushover {
+        #[serde(serialize_with = "redact")]
+        token: String,
+        #[serde(serialize_with = "redact")]
+        user: String,
+        /// Pushover priority (-2 to 2, default 1 = high)
+        #[serde(default = "default_pushover_priority")]
+        priority: i8,
+    },
+    /// POST a JSON payload to a URL (e.g. an SMS gateway)
+    Webhook {
+        #[serde(serialize_with = "redact_url")]
+        url: String,
+    },
+}
+
+fn default_pushover_priority() -> i8 {
+    1
+}
+
+/// Quiet hours: dispute updates other than new disputes are held back and
+/// delivered as a digest once the window ends. Windows use `HH:MM-HH:MM`
+/// (may cross midnight) or `off`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QuietHoursConfig {
+    /// Fixed UTC offset for the schedule, e.g. "-03:00" (default: "+00:00")
+    #[serde(default = "default_utc_offset")]
+    pub utc_offset: String,
+    /// Window applied to every day without its own entry
+    pub default: Option<String>,
+    pub mon: Option<String>,
+    pub tue: Option<String>,
+    pub wed: Option<String>,
+    pub thu: Option<String>,
+    pub fri: Option<String>,
+    pub sat: Option<String>,
+    pub sun: Option<String>,
+}
+
+fn default_utc_offset() -> String {
+    "+00:00".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AlertsConfig {
     /// Enable alerts for new disputes (status: initiated)
     #[serde(default = "default_true")]
@@ -30,6 +340,262 @@ pub struct AlertsConfig {
     /// Enable alerts for unknown/other status changes
     #[serde(default = "default_true")]
     pub other: bool,
+    /// How status changes of an already-alerted dispute are delivered
+    #[serde(default)]
+    pub update_mode: UpdateMode,
+    /// Statuses delivered silently (Telegram `disable_notification`)
+    #[serde(default)]
+    pub silent: SilentConfig,
+    /// Action links attached to new-dispute alerts
+    #[serde(default)]
+    pub links: Vec<LinkConfig>,
+    /// Group new-dispute alerts arriving within this many seconds into one message (0 = disabled)
+    #[serde(default)]
+    pub digest_window: u64,
+    /// Hold each dispute's updates this many seconds and alert only on the newest
+    /// state within the window (0 = disabled)
+    #[serde(default)]
+    pub debounce_window: u64,
+    /// Workers delivering dispute alerts in parallel; one dispute's updates stay
+    /// with one worker, in order (default: 4)
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+    /// Dispute events queued for the workers before new ones are dropped
+    /// (default: 1024)
+    #[serde(default = "default_queue_capacity")]
+    pub queue_capacity: usize,
+    /// Entries kept by each in-memory cache (seen event ids, debounced and
+    /// escalating disputes); the least recently used are evicted (default: 4096)
+    #[serde(default = "default_cache_capacity")]
+    pub cache_capacity: usize,
+    /// Dispute alerts sent per minute before the rest are held back and
+    /// summarized once a minute until the rate drops (0 = disabled)
+    #[serde(default)]
+    pub flood_limit: usize,
+    /// Events whose `created_at` is more than this many seconds in the past are
+    /// recorded but not alerted (0 = disabled)
+    #[serde(default)]
+    pub max_event_age: u64,
+    /// Events whose `created_at` is more than this many seconds ahead of the local
+    /// clock are dropped and counted (default: 300, 0 = disabled)
+    #[serde(default = "default_max_future_skew")]
+    pub max_future_skew: u64,
+    /// Pin new-dispute alerts and unpin them once the dispute is resolved
+    #[serde(default = "default_false")]
+    pub pin: bool,
+    /// Severity assigned to each dispute status and health alert
+    #[serde(default)]
+    pub severity: SeverityConfig,
+    /// Attach the raw Nostr event JSON to dispute alerts for debugging
+    #[serde(default)]
+    pub raw_event: RawEventMode,
+    /// Shorten or hash dispute ids and public keys in dispute alerts
+    #[serde(default)]
+    pub redact: RedactMode,
+}
+
+impl AlertsConfig {
+    /// Whether alerts for this dispute status are enabled
+    pub fn is_enabled(&self, status: &str) -> bool {
+        match status {
+            "initiated" => self.initiated,
+            "in-progress" => self.in_progress,
+            "seller-refunded" => self.seller_refunded,
+            "settled" => self.settled,
+            "released" => self.released,
+            _ => self.other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LinkConfig {
+    /// Button or link label
+    pub label: String,
+    /// URL template; `{dispute_id}`, `{event_id}` and `{note_id}` are substituted.
+    /// http(s) URLs become inline buttons, other schemes (e.g. `nostr:`) are shown as text
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SilentConfig {
+    /// Deliver new dispute alerts silently
+    #[serde(default = "default_false")]
+    pub initiated: bool,
+    /// Deliver "dispute taken" updates silently
+    #[serde(default = "default_true")]
+    pub in_progress: bool,
+    /// Deliver seller refund resolutions silently
+    #[serde(default = "default_false")]
+    pub seller_refunded: bool,
+    /// Deliver settled resolutions silently
+    #[serde(default = "default_false")]
+    pub settled: bool,
+    /// Deliver released resolutions silently
+    #[serde(default = "default_false")]
+    pub released: bool,
+    /// Deliver unknown/other status changes silently
+    #[serde(default = "default_false")]
+    pub other: bool,
+}
+
+impl Default for SilentConfig {
+    fn default() -> Self {
+        Self {
+            initiated: false,
+            in_progress: true,
+            seller_refunded: false,
+            settled: false,
+            released: false,
+            other: false,
+        }
+    }
+}
+
+impl SilentConfig {
+    /// Whether alerts for this dispute status should be delivered without sound
+    pub fn is_silent(&self, status: &str) -> bool {
+        match status {
+            "initiated" => self.initiated,
+            "in-progress" => self.in_progress,
+            "seller-refunded" => self.seller_refunded,
+            "settled" => self.settled,
+            "released" => self.released,
+            _ => self.other,
+        }
+    }
+}
+
+/// How the raw Nostr event is attached to dispute alerts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RawEventMode {
+    /// Don't attach the event
+    #[default]
+    Off,
+    /// Send the event as a JSON file replying to the alert
+    Document,
+    /// Append the event as a collapsed blockquote
+    Quote,
+}
+
+/// How dispute ids and public keys appear in dispute alerts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedactMode {
+    /// Full values
+    #[default]
+    Off,
+    /// First and last characters only
+    Truncate,
+    /// A short hash, the same for each value
+    Hash,
+}
+
+/// How urgent an alert is.
+///
+/// Critical alerts mention and escalate to the admin on call and bypass quiet
+/// hours; info-level health alerts are delivered silently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl AlertSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertSeverity::Info => "info",
+            AlertSeverity::Warning => "warning",
+            AlertSeverity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SeverityConfig {
+    /// New disputes
+    #[serde(default = "default_critical")]
+    pub initiated: AlertSeverity,
+    /// Dispute taken by a solver
+    #[serde(default = "default_info")]
+    pub in_progress: AlertSeverity,
+    /// Dispute resolved with seller refund
+    #[serde(default = "default_info")]
+    pub seller_refunded: AlertSeverity,
+    /// Dispute settled with payment to buyer
+    #[serde(default = "default_info")]
+    pub settled: AlertSeverity,
+    /// Dispute released by seller
+    #[serde(default = "default_info")]
+    pub released: AlertSeverity,
+    /// Unknown/other status changes
+    #[serde(default = "default_warning")]
+    pub other: AlertSeverity,
+    /// No dispute events received for too long
+    #[serde(default = "default_warning")]
+    pub event_silence: AlertSeverity,
+    /// Relays disconnected
+    #[serde(default = "default_warning")]
+    pub relay_alert: AlertSeverity,
+    /// Fewer relays connected than `health.min_connected_relays`
+    #[serde(default = "default_critical")]
+    pub relay_quorum: AlertSeverity,
+}
+
+fn default_critical() -> AlertSeverity {
+    AlertSeverity::Critical
+}
+
+fn default_warning() -> AlertSeverity {
+    AlertSeverity::Warning
+}
+
+fn default_info() -> AlertSeverity {
+    AlertSeverity::Info
+}
+
+impl Default for SeverityConfig {
+    fn default() -> Self {
+        Self {
+            initiated: AlertSeverity::Critical,
+            in_progress: AlertSeverity::Info,
+            seller_refunded: AlertSeverity::Info,
+            settled: AlertSeverity::Info,
+            released: AlertSeverity::Info,
+            other: AlertSeverity::Warning,
+            event_silence: AlertSeverity::Warning,
+            relay_alert: AlertSeverity::Warning,
+            relay_quorum: AlertSeverity::Critical,
+        }
+    }
+}
+
+impl SeverityConfig {
+    /// Severity of an alert for this dispute status
+    pub fn for_status(&self, status: &str) -> AlertSeverity {
+        match status {
+            "initiated" => self.initiated,
+            "in-progress" => self.in_progress,
+            "seller-refunded" => self.seller_refunded,
+            "settled" => self.settled,
+            "released" => self.released,
+            _ => self.other,
+        }
+    }
+}
+
+/// Delivery mode for follow-up status changes of the same dispute
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateMode {
+    /// Edit the original alert message in place
+    #[default]
+    Edit,
+    /// Reply to the original alert message with the new status
+    Reply,
 }
 
 fn default_true() -> bool {
@@ -45,11 +611,205 @@ impl Default for AlertsConfig {
             settled: true,
             released: true,
             other: true,
+            update_mode: UpdateMode::default(),
+            silent: SilentConfig::default(),
+            links: Vec::new(),
+            digest_window: 0,
+            debounce_window: 0,
+            workers: default_workers(),
+            queue_capacity: default_queue_capacity(),
+            cache_capacity: default_cache_capacity(),
+            flood_limit: 0,
+            max_event_age: 0,
+            max_future_skew: default_max_future_skew(),
+            pin: false,
+            severity: SeverityConfig::default(),
+            raw_event: RawEventMode::default(),
+            redact: RedactMode::default(),
+        }
+    }
+}
+
+fn default_workers() -> usize {
+    4
+}
+
+fn default_queue_capacity() -> usize {
+    1024
+}
+
+fn default_cache_capacity() -> usize {
+    crate::lru::DEFAULT_CAPACITY
+}
+
+fn default_max_future_skew() -> u64 {
+    300
+}
+
+/// Message template overrides (Tera syntax, MarkdownV2 output).
+///
+/// Any template left unset uses the built-in wording. Substituted values are
+/// escaped automatically; see the `templates` module for available variables.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TemplatesConfig {
+    /// Language of the built-in messages
+    #[serde(default)]
+    pub language: Language,
+    /// How times appear in messages
+    #[serde(default)]
+    pub time_format: TimeFormat,
+    /// Directory with `<name>.md` template files (inline templates take precedence)
+    pub dir: Option<PathBuf>,
+    pub initiated: Option<String>,
+    pub in_progress: Option<String>,
+    pub seller_refunded: Option<String>,
+    pub settled: Option<String>,
+    pub released: Option<String>,
+    pub other: Option<String>,
+    pub heartbeat: Option<String>,
+    pub event_silence: Option<String>,
+    pub relay_alert: Option<String>,
+    pub startup: Option<String>,
+    pub digest: Option<String>,
+    pub escalation: Option<String>,
+    pub oncall: Option<String>,
+    pub unauthorized: Option<String>,
+    pub unauthorized_report: Option<String>,
+    pub health: Option<String>,
+    pub heartbeat_failed: Option<String>,
+    pub update_available: Option<String>,
+    pub version: Option<String>,
+    pub stats: Option<String>,
+    pub task_restarted: Option<String>,
+    pub clock_drift: Option<String>,
+    pub memory_alert: Option<String>,
+    pub degraded: Option<String>,
+    pub degraded_recovered: Option<String>,
+    pub relay_latency: Option<String>,
+    pub relay_blacklisted: Option<String>,
+    pub relay_ping: Option<String>,
+    pub fallback_relays: Option<String>,
+    pub relay_quorum: Option<String>,
+    pub relay_reconnected: Option<String>,
+    pub dead_subscription: Option<String>,
+    pub config_reloaded: Option<String>,
+    pub announcement: Option<String>,
+    pub shutdown: Option<String>,
+    pub crashed: Option<String>,
+    pub flood: Option<String>,
+    pub event_loop_restarted: Option<String>,
+}
+
+/// Message branding for communities that want a quieter, more professional look
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BrandingConfig {
+    /// Keep emoji in messages; `false` removes them
+    #[serde(default = "default_true")]
+    pub emoji: bool,
+    /// Replacements for individual emoji, e.g. "🚨" = "🔴"
+    #[serde(default)]
+    pub emoji_map: HashMap<String, String>,
+    /// Text prepended to every message, e.g. "[Acme Ops]"
+    pub prefix: Option<String>,
+    /// Capitalization of message headers; unset keeps templates as written
+    pub headers: Option<HeaderCase>,
+}
+
+impl Default for BrandingConfig {
+    fn default() -> Self {
+        Self {
+            emoji: true,
+            emoji_map: HashMap::new(),
+            prefix: None,
+            headers: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeaderCase {
+    /// "NEW DISPUTE"
+    Upper,
+    /// "New dispute"
+    Sentence,
+}
+
+/// Languages with bundled translations of the built-in messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    Es,
+    Pt,
+    It,
+    De,
+}
+
+/// Timestamp formats for messages (see `timestamp`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeFormat {
+    /// `2024-02-20 14:05:09 UTC`
+    #[default]
+    Utc,
+    /// `2024-02-20T14:05:09Z`
+    Iso8601,
+    /// `Feb 20, 2024, 14:05 UTC`, in the message language
+    Locale,
+    /// `5 minutes ago`, in the message language
+    Relative,
+}
+
+impl TemplatesConfig {
+    /// Inline template configured under this name, if any
+    pub fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            "initiated" => self.initiated.as_deref(),
+            "in_progress" => self.in_progress.as_deref(),
+            "seller_refunded" => self.seller_refunded.as_deref(),
+            "settled" => self.settled.as_deref(),
+            "released" => self.released.as_deref(),
+            "other" => self.other.as_deref(),
+            "heartbeat" => self.heartbeat.as_deref(),
+            "event_silence" => self.event_silence.as_deref(),
+            "relay_alert" => self.relay_alert.as_deref(),
+            "startup" => self.startup.as_deref(),
+            "digest" => self.digest.as_deref(),
+            "escalation" => self.escalation.as_deref(),
+            "oncall" => self.oncall.as_deref(),
+            "unauthorized" => self.unauthorized.as_deref(),
+            "unauthorized_report" => self.unauthorized_report.as_deref(),
+            "health" => self.health.as_deref(),
+            "heartbeat_failed" => self.heartbeat_failed.as_deref(),
+            "update_available" => self.update_available.as_deref(),
+            "version" => self.version.as_deref(),
+            "stats" => self.stats.as_deref(),
+            "task_restarted" => self.task_restarted.as_deref(),
+            "clock_drift" => self.clock_drift.as_deref(),
+            "memory_alert" => self.memory_alert.as_deref(),
+            "degraded" => self.degraded.as_deref(),
+            "degraded_recovered" => self.degraded_recovered.as_deref(),
+            "relay_latency" => self.relay_latency.as_deref(),
+            "relay_blacklisted" => self.relay_blacklisted.as_deref(),
+            "relay_ping" => self.relay_ping.as_deref(),
+            "fallback_relays" => self.fallback_relays.as_deref(),
+            "relay_quorum" => self.relay_quorum.as_deref(),
+            "relay_reconnected" => self.relay_reconnected.as_deref(),
+            "dead_subscription" => self.dead_subscription.as_deref(),
+            "config_reloaded" => self.config_reloaded.as_deref(),
+            "announcement" => self.announcement.as_deref(),
+            "shutdown" => self.shutdown.as_deref(),
+            "crashed" => self.crashed.as_deref(),
+            "flood" => self.flood.as_deref(),
+            "event_loop_restarted" => self.event_loop_restarted.as_deref(),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HealthConfig {
     /// Enable periodic heartbeat notifications (disabled by default to avoid flooding the chat)
     #[serde(default = "default_false")]
@@ -57,12 +817,30 @@ pub struct HealthConfig {
     /// Heartbeat interval in seconds (default: 3600 = 1 hour)
     #[serde(default = "default_heartbeat_interval")]
     pub heartbeat_interval: u64,
+    /// Deliver heartbeats silently (Telegram `disable_notification`)
+    #[serde(default = "default_true")]
+    pub heartbeat_silent: bool,
     /// Check relay connections periodically
     #[serde(default = "default_true")]
     pub check_relays: bool,
-    /// Relay connection timeout in seconds (default: 30)
+    /// Relay connection timeout in seconds, and the wait before retrying a relay
+    /// whose first reconnection failed (default: 30)
     #[serde(default = "default_connection_timeout")]
     pub relay_timeout: u64,
+    /// Longest wait between reconnection attempts to a relay, in seconds (default: 300)
+    #[serde(default = "default_relay_backoff_max")]
+    pub relay_backoff_max: u64,
+    /// Fewer connected relays than this sends a critical alert and fails `/ready`
+    /// (default: 1)
+    #[serde(default = "default_min_connected_relays")]
+    pub min_connected_relays: usize,
+    /// Take a relay out of the pool when its score (0-100, from uptime and the
+    /// share of dispute events it delivers) falls below this (default: 25, 0 = disabled)
+    #[serde(default = "default_relay_blacklist_score")]
+    pub relay_blacklist_score: u8,
+    /// Seconds before a blacklisted relay is tried again (default: 3600 = 1 hour)
+    #[serde(default = "default_relay_blacklist_retry")]
+    pub relay_blacklist_retry: u64,
     /// Alert if no events received for this many seconds (default: 7200 = 2 hours)
     #[serde(default = "default_event_alert_threshold")]
     pub event_alert_threshold: u64,
@@ -76,6 +854,92 @@ pub struct HealthConfig {
     /// Set to "0.0.0.0" for Docker or external access
     #[serde(default = "default_http_bind")]
     pub http_bind: String,
+    /// Bearer token required by every HTTP endpoint except `/live` and the dashboard
+    /// page; the `/disputes`, `/test-alert`, `/silence` and `/config` APIs are
+    /// disabled without one
+    #[serde(serialize_with = "redact_option")]
+    pub http_auth_token: Option<String>,
+    /// File to read `http_auth_token` from
+    pub http_auth_token_file: Option<PathBuf>,
+    /// Serve the HTTP endpoint over HTTPS; requires the `tls` build feature
+    pub http_tls: Option<HttpTlsConfig>,
+    /// Listen on this Unix socket instead of `http_bind`/`http_port` (Unix only)
+    pub http_socket: Option<PathBuf>,
+    /// Connections served at once; further clients wait to be accepted (default: 64)
+    #[serde(default = "default_http_max_connections")]
+    pub http_max_connections: usize,
+    /// Seconds a client gets to send its request headers (default: 30)
+    #[serde(default = "default_http_timeout")]
+    pub http_timeout: u64,
+    /// Serve the grpc.health.v1 service on this port at `http_bind`; requires the
+    /// `grpc` build feature
+    pub grpc_port: Option<u16>,
+    /// Dead man's switch URL (e.g. Healthchecks.io) pinged every heartbeat interval
+    /// while the watchdog is healthy; `<url>/fail` is pinged otherwise
+    #[serde(serialize_with = "redact_url_option")]
+    pub ping_url: Option<String>,
+    /// Report background tasks restarted after a panic or stall to the health topic
+    #[serde(default = "default_true")]
+    pub notify_task_restarts: bool,
+    /// Alert when the local clock is off by more than this many seconds (default: 30, 0 = disabled)
+    #[serde(default = "default_max_clock_drift")]
+    pub max_clock_drift: u64,
+    /// Alert when resident memory exceeds this many megabytes (default: 0 = disabled)
+    #[serde(default)]
+    pub max_memory_mb: u64,
+    /// Alert when a relay's 90th percentile event propagation latency exceeds this
+    /// many seconds (default: 60, 0 = disabled)
+    #[serde(default = "default_max_relay_latency")]
+    pub max_relay_latency: u64,
+    /// Ping each connected relay this often, in seconds (default: 60, 0 = disabled)
+    #[serde(default = "default_relay_ping_interval")]
+    pub relay_ping_interval: u64,
+    /// Alert when a ping's round trip takes longer than this many milliseconds
+    /// (default: 2000, 0 = only alert on failed pings)
+    #[serde(default = "default_max_relay_rtt")]
+    pub max_relay_rtt: u64,
+    /// Failed or timed out pings in a row before an alert (default: 3)
+    #[serde(default = "default_relay_ping_failures")]
+    pub relay_ping_failures: u32,
+    /// Alert when all relays disconnect, the send queue backs up or the dispute
+    /// store can't be written, and again on recovery
+    #[serde(default = "default_true")]
+    pub degraded_alerts: bool,
+    /// Messages waiting in the Telegram send queue that count as backed up (default: 50)
+    #[serde(default = "default_max_queue_backlog")]
+    pub max_queue_backlog: usize,
+    /// Channels tried in order when degradation alerts can't reach the health topic
+    /// (`telegram`, `pushover` or `webhook` actions, as in escalation steps)
+    #[serde(default)]
+    pub degraded_fallback: Vec<EscalationAction>,
+    /// Run a made-up dispute through parsing, templating, routing and a test
+    /// delivery at startup, reporting the result in the startup message
+    #[serde(default)]
+    pub self_test: bool,
+    /// Restart the Nostr event loop and reconnect the relays when it handles
+    /// nothing for this many seconds while relays are connected (default: 180,
+    /// 0 = disabled)
+    #[serde(default = "default_event_loop_stall_timeout")]
+    pub event_loop_stall_timeout: u64,
+}
+
+/// Certificate for the HTTP endpoint: PEM files, or a generated self-signed one
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HttpTlsConfig {
+    /// PEM certificate chain
+    pub cert: Option<PathBuf>,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key: Option<PathBuf>,
+    /// Generate a self-signed certificate at startup instead of loading files
+    #[serde(default = "default_false")]
+    pub self_signed: bool,
+    /// Names the self-signed certificate is issued for (default: ["localhost"])
+    #[serde(default = "default_tls_hostnames")]
+    pub hostnames: Vec<String>,
+}
+
+fn default_tls_hostnames() -> Vec<String> {
+    vec!["localhost".to_string()]
 }
 
 fn default_false() -> bool {
@@ -90,10 +954,54 @@ fn default_connection_timeout() -> u64 {
     30 // 30 seconds
 }
 
+fn default_relay_backoff_max() -> u64 {
+    300 // 5 minutes
+}
+
+fn default_min_connected_relays() -> usize {
+    1
+}
+
+fn default_relay_blacklist_score() -> u8 {
+    25
+}
+
+fn default_relay_blacklist_retry() -> u64 {
+    3600 // 1 hour
+}
+
 fn default_event_alert_threshold() -> u64 {
     7200 // 2 hours
 }
 
+fn default_max_clock_drift() -> u64 {
+    30
+}
+
+fn default_event_loop_stall_timeout() -> u64 {
+    180
+}
+
+fn default_max_relay_latency() -> u64 {
+    60
+}
+
+fn default_relay_ping_interval() -> u64 {
+    60
+}
+
+fn default_max_relay_rtt() -> u64 {
+    2000
+}
+
+fn default_relay_ping_failures() -> u32 {
+    3
+}
+
+fn default_max_queue_backlog() -> usize {
+    50
+}
+
 fn default_http_port() -> u16 {
     8080
 }
@@ -102,99 +1010,1869 @@ fn default_http_bind() -> String {
     "127.0.0.1".to_string()
 }
 
+fn default_http_max_connections() -> usize {
+    64
+}
+
+fn default_http_timeout() -> u64 {
+    30
+}
+
 impl Default for HealthConfig {
     fn default() -> Self {
         Self {
             heartbeat_enabled: false,
             heartbeat_interval: default_heartbeat_interval(),
+            heartbeat_silent: true,
             check_relays: true,
             relay_timeout: default_connection_timeout(),
+            relay_backoff_max: default_relay_backoff_max(),
+            min_connected_relays: default_min_connected_relays(),
+            relay_blacklist_score: default_relay_blacklist_score(),
+            relay_blacklist_retry: default_relay_blacklist_retry(),
             event_alert_threshold: default_event_alert_threshold(),
             enable_http_endpoint: false,
             http_port: default_http_port(),
             http_bind: default_http_bind(),
+            http_auth_token: None,
+            http_auth_token_file: None,
+            http_tls: None,
+            http_socket: None,
+            http_max_connections: default_http_max_connections(),
+            http_timeout: default_http_timeout(),
+            grpc_port: None,
+            ping_url: None,
+            notify_task_restarts: true,
+            max_clock_drift: default_max_clock_drift(),
+            max_memory_mb: 0,
+            max_relay_latency: default_max_relay_latency(),
+            relay_ping_interval: default_relay_ping_interval(),
+            max_relay_rtt: default_max_relay_rtt(),
+            relay_ping_failures: default_relay_ping_failures(),
+            degraded_alerts: true,
+            max_queue_backlog: default_max_queue_backlog(),
+            degraded_fallback: Vec::new(),
+            self_test: false,
+            event_loop_stall_timeout: default_event_loop_stall_timeout(),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct MostroConfig {
-    /// Mostro daemon's Nostr public key (hex or npub format)
-    pub pubkey: String,
+    /// Nostr public keys (hex, npub or nprofile format) of the Mostro daemons to
+    /// watch; a single key or a list. The relays an nprofile names are connected
+    /// to along with `nostr.relays`.
+    #[serde(deserialize_with = "one_or_many")]
+    pub pubkey: Vec<String>,
+}
+
+impl MostroConfig {
+    /// Relays named by the nprofile keys, in order, without duplicates
+    pub fn relay_hints(&self) -> Vec<String> {
+        use nostr_sdk::prelude::{FromBech32, Nip19Profile};
+        let mut hints: Vec<String> = Vec::new();
+        for pubkey in &self.pubkey {
+            let Ok(profile) = Nip19Profile::from_bech32(pubkey) else {
+                continue;
+            };
+            for relay in profile.relays {
+                let relay = relay.to_string().trim_end_matches('/').to_string();
+                if !hints.contains(&relay) {
+                    hints.push(relay);
+                }
+            }
+        }
+        hints
+    }
+}
+
+/// Accept either a single string or a list of them
+fn one_or_many<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NostrConfig {
-    /// List of Nostr relay URLs to connect to
+    /// List of Nostr relay URLs to connect to; may be left out when every
+    /// `mostro.pubkey` is an nprofile with relays
+    #[serde(default)]
     pub relays: Vec<String>,
+    /// What each relay is used for, keyed by its URL in `relays`; relays not listed
+    /// have every role
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub relay_roles: HashMap<String, Vec<RelayRole>>,
+    /// Narrower dispute subscription for some relays, keyed by URL in `relays`;
+    /// other relays get every dispute event
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub relay_filters: HashMap<String, RelayFilter>,
+    /// SOCKS5 proxy (e.g. Tor at `socks5h://127.0.0.1:9050`) for every relay
+    pub proxy: Option<String>,
+    /// Per-relay proxy, keyed by URL in `relays` or `fallback_relays`: a SOCKS5 URL,
+    /// or `direct` to bypass `proxy`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub relay_proxies: HashMap<String, String>,
+    /// Relays subscribed to only while every relay with the disputes role is down
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_relays: Vec<String>,
+    /// Minutes every primary relay must be down before the fallback relays are used
+    /// (default: 10)
+    #[serde(default = "default_fallback_after")]
+    pub fallback_after: u64,
+    /// Relays of `relays` kept on standby, in promotion order: connected only
+    /// while fewer than `target_relays` of the active relays are, and released
+    /// once the primaries are back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secondary_relays: Vec<String>,
+    /// Relay connections to keep active when `secondary_relays` is set
+    /// (default: the number of primary relays)
+    pub target_relays: Option<usize>,
+    /// Keep received events in a local LMDB database next to the config file, so
+    /// events already seen aren't processed again after a restart; requires the
+    /// `lmdb` build feature
+    #[serde(default)]
+    pub event_cache: bool,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct TelegramConfig {
-    /// Telegram bot token from @BotFather
-    pub bot_token: String,
-    /// Telegram chat ID where alerts will be sent (group or channel)
-    pub chat_id: i64,
+fn default_fallback_after() -> u64 {
+    10
 }
 
-impl Config {
-    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
-        if !path.exists() {
-            let mut msg = format!(
-                "Config file not found: {}\n\n\
-                 Searched in:\n\
-                 \x20   1. ./config.toml (current directory)\n\
-                 \x20   2. ~/.config/mostro-watchdog/config.toml\n\n\
-                 To fix this, either:\n\
-                 \x20   • Run from the directory containing config.toml\n\
-                 \x20   • Specify the path: mostro-watchdog --config /path/to/config.toml\n\
-                 \x20   • Copy config to: ~/.config/mostro-watchdog/config.toml\n\n\
-                 See config.example.toml for reference.",
-                path.display()
-            );
-
-            // Extra hint if HOME config dir doesn't exist
-            if let Some(home) = std::env::var_os("HOME") {
-                let xdg_dir = std::path::PathBuf::from(home).join(".config/mostro-watchdog");
-                if !xdg_dir.exists() {
-                    msg.push_str(&format!(
-                        "\n\nHint: mkdir -p {} && cp config.example.toml {}/config.toml",
-                        xdg_dir.display(),
-                        xdg_dir.display()
-                    ));
-                }
-            }
+/// What a relay is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayRole {
+    /// Subscribe to dispute events (read)
+    Disputes,
+    /// Publish events (write)
+    Publish,
+}
 
-            return Err(msg.into());
-        }
+/// Dispute events one relay is asked for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct RelayFilter {
+    /// Only these dispute statuses (the `s` tag), e.g. `["initiated"]`; all when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub statuses: Vec<String>,
+    /// Only disputes from these of the `mostro.pubkey` keys; all when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pubkeys: Vec<String>,
+}
 
-        let content = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
+impl NostrConfig {
+    /// Whether `relay` is used for `role`
+    pub fn has_role(&self, relay: &str, role: RelayRole) -> bool {
+        self.relay_roles
+            .get(relay)
+            .is_none_or(|roles| roles.contains(&role))
+    }
 
-        // Validate
-        if config.nostr.relays.is_empty() {
-            return Err("At least one Nostr relay must be configured".into());
+    /// Proxy to reach `relay` through, if any
+    pub fn relay_proxy(&self, relay: &str) -> Option<&str> {
+        match self.relay_proxies.get(relay) {
+            Some(proxy) if proxy == DIRECT => None,
+            Some(proxy) => Some(proxy),
+            None => self.proxy.as_deref(),
         }
+    }
 
-        if config.telegram.bot_token.is_empty() {
-            return Err("Telegram bot_token cannot be empty".into());
-        }
+    /// Relays connected from the start, i.e. not in `secondary_relays`
+    pub fn primary_relays(&self) -> Vec<String> {
+        self.relays
+            .iter()
+            .filter(|relay| !self.secondary_relays.contains(relay))
+            .cloned()
+            .collect()
+    }
 
-        if config.mostro.pubkey.is_empty() {
-            return Err("Mostro pubkey cannot be empty".into());
-        }
+    /// Relay connections to keep active
+    pub fn target_relays(&self) -> usize {
+        self.target_relays
+            .unwrap_or_else(|| self.primary_relays().len())
+    }
 
-        if let Some(ref health) = config.health {
-            if health.heartbeat_enabled && health.heartbeat_interval == 0 {
-                return Err("heartbeat_interval must be greater than 0".into());
-            }
-            if health.check_relays && health.relay_timeout == 0 {
-                return Err(
-                    "relay_timeout must be greater than 0 when check_relays is enabled".into(),
-                );
+    /// Relays to subscribe to dispute events on
+    pub fn dispute_relays(&self) -> Vec<String> {
+        self.relays
+            .iter()
+            .filter(|relay| self.has_role(relay, RelayRole::Disputes))
+            .cloned()
+            .collect()
+    }
+
+    /// Dispute relays grouped by the filter they are subscribed with, in
+    /// `relays` order
+    pub fn dispute_relays_by_filter(&self) -> Vec<(RelayFilter, Vec<String>)> {
+        let mut groups: Vec<(RelayFilter, Vec<String>)> = Vec::new();
+        for relay in self.dispute_relays() {
+            let filter = self.relay_filters.get(&relay).cloned().unwrap_or_default();
+            match groups.iter_mut().find(|(known, _)| *known == filter) {
+                Some((_, relays)) => relays.push(relay),
+                None => groups.push((filter, vec![relay])),
             }
         }
+        groups
+    }
+}
 
-        Ok(config)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TelegramConfig {
+    /// Telegram bot token from @BotFather
+    #[serde(default, serialize_with = "redact")]
+    pub bot_token: String,
+    /// File to read `bot_token` from, e.g. a Docker secret
+    pub bot_token_file: Option<PathBuf>,
+    /// Telegram chat ID where alerts will be sent (group or channel)
+    pub chat_id: i64,
+    /// Separate chat for heartbeats and health alerts (defaults to `chat_id`)
+    pub health_chat_id: Option<i64>,
+    /// Forum topic (message_thread_id) for all messages in a forum-enabled supergroup
+    pub topic_id: Option<i32>,
+    /// Per-category topic overrides (fall back to `topic_id`)
+    #[serde(default)]
+    pub topics: TopicsConfig,
+    /// SOCKS5 or HTTP(S) proxy for Telegram API requests
+    pub proxy: Option<String>,
+    /// Bot API server, e.g. a local `telegram-bot-api` (default: `https://api.telegram.org`)
+    pub api_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TopicsConfig {
+    /// Topic for dispute alerts
+    pub disputes: Option<i32>,
+    /// Topic for heartbeat notifications
+    pub heartbeat: Option<i32>,
+    /// Topic for health alerts (event silence, relay connectivity)
+    pub health: Option<i32>,
+}
+
+impl TelegramConfig {
+    /// Topic for dispute alerts
+    pub fn disputes_topic(&self) -> Option<i32> {
+        self.topics.disputes.or(self.topic_id)
+    }
+
+    /// Chat for heartbeats and health alerts
+    pub fn health_chat(&self) -> i64 {
+        self.health_chat_id.unwrap_or(self.chat_id)
+    }
+
+    /// Topic for heartbeat notifications
+    pub fn heartbeat_topic(&self) -> Option<i32> {
+        self.topics.heartbeat.or(self.main_chat_topic())
+    }
+
+    /// Topic for health alerts
+    pub fn health_topic(&self) -> Option<i32> {
+        self.topics.health.or(self.main_chat_topic())
+    }
+
+    /// `topic_id` belongs to the alert chat, so it doesn't apply to a separate health chat
+    fn main_chat_topic(&self) -> Option<i32> {
+        match self.health_chat_id {
+            Some(_) => None,
+            None => self.topic_id,
+        }
+    }
+}
+
+impl Config {
+    /// Parse a config (or, for merging includes, a plain tree) in the format given by
+    /// the file extension: `.yaml`/`.yml`, `.json`, or TOML for anything else
+    pub fn parse<T: DeserializeOwned>(
+        path: &Path,
+        content: &str,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        Ok(match extension.as_deref() {
+            Some("yaml" | "yml") => serde_yaml::from_str(content)?,
+            Some("json") => serde_json::from_str(content)?,
+            _ => toml::from_str(content)?,
+        })
+    }
+
+    /// Build a minimal config from environment variables, for running without a
+    /// config file (e.g. `docker run -e BOT_TOKEN=...`): `MOSTRO_PUBKEY` and
+    /// `NOSTR_RELAYS` (comma-separated), `BOT_TOKEN` or `BOT_TOKEN_FILE`, and
+    /// `CHAT_ID`. Returns `None` when none of them is set.
+    pub fn from_env(var: impl Fn(&str) -> Option<String>) -> Option<Result<Self, String>> {
+        if ENV_VARS.iter().all(|name| var(name).is_none()) {
+            return None;
+        }
+        let list = |name: &str| -> Vec<String> {
+            var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        };
+
+        let mut missing = Vec::new();
+        let pubkey = list("MOSTRO_PUBKEY");
+        if pubkey.is_empty() {
+            missing.push("MOSTRO_PUBKEY");
+        }
+        let mut relays = list("NOSTR_RELAYS");
+        if relays.is_empty() {
+            relays.push(DEFAULT_ENV_RELAY.to_string());
+        }
+        if var("BOT_TOKEN").is_none() && var("BOT_TOKEN_FILE").is_none() {
+            missing.push("BOT_TOKEN");
+        }
+        let chat_id = match var("CHAT_ID").map(|chat_id| chat_id.trim().parse::<i64>()) {
+            Some(Ok(chat_id)) => chat_id,
+            Some(Err(_)) => return Some(Err("CHAT_ID must be a number".into())),
+            None => {
+                missing.push("CHAT_ID");
+                0
+            }
+        };
+        if !missing.is_empty() {
+            return Some(Err(format!(
+                "No config file, and missing environment variables to run without one: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let config = serde_json::json!({
+            "mostro": { "pubkey": pubkey },
+            "nostr": { "relays": relays },
+            "telegram": {
+                "bot_token": var("BOT_TOKEN").unwrap_or_default(),
+                "bot_token_file": var("BOT_TOKEN_FILE"),
+                "chat_id": chat_id,
+            },
+        });
+        Some(serde_json::from_value(config).map_err(|e| e.to_string()))
+    }
+
+    /// Read `path` with the files listed in its `include`, relative to it. Each
+    /// include is merged in the order listed (after its own includes), and `path`
+    /// itself goes on top, so later files override earlier ones.
+    fn read_layered(
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        sources: &mut Vec<PathBuf>,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        if stack.contains(&canonical) {
+            return Err(format!("{} is included in a cycle", path.display()).into());
+        }
+        stack.push(canonical);
+
+        let content = std::fs::read_to_string(path)?;
+        let mut value = Self::parse::<serde_json::Value>(path, &content)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let includes: Vec<PathBuf> = match value
+            .as_object_mut()
+            .and_then(|table| table.remove("include"))
+        {
+            Some(include) => serde_json::from_value(include)
+                .map_err(|e| format!("{}: include: {}", path.display(), e))?,
+            None => Vec::new(),
+        };
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut merged = serde_json::Value::Object(Default::default());
+        for include in includes {
+            let layer = Self::read_layered(&dir.join(include), stack, sources)?;
+            merge(&mut merged, layer);
+        }
+        merge(&mut merged, value);
+
+        stack.pop();
+        sources.push(path.to_path_buf());
+        Ok(merged)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_profile(path, None)
+    }
+
+    /// Load the config with the `[profile.<name>]` table, if a profile is given,
+    /// merged over the base settings
+    pub fn load_profile(
+        path: &Path,
+        profile: Option<&str>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            let env = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+            if let Some(config) = Self::from_env(env) {
+                let mut config = config?;
+                config.load_secret_files()?;
+                config.add_relay_hints();
+                config.validate()?;
+                return Ok(config);
+            }
+
+            let mut msg = format!(
+                "Config file not found: {}\n\n\
+                 Searched in:\n\
+                 \x20   1. ./config.toml (current directory)\n\
+                 \x20   2. ~/.config/mostro-watchdog/config.toml\n\n\
+                 To fix this, either:\n\
+                 \x20   • Run from the directory containing config.toml\n\
+                 \x20   • Specify the path: mostro-watchdog --config /path/to/config.toml\n\
+                 \x20   • Copy config to: ~/.config/mostro-watchdog/config.toml\n\
+                 \x20   • Set MOSTRO_PUBKEY, BOT_TOKEN and CHAT_ID to run without a file\n\n\
+                 See config.example.toml for reference.",
+                path.display()
+            );
+
+            // Extra hint if HOME config dir doesn't exist
+            if let Some(home) = std::env::var_os("HOME") {
+                let xdg_dir = std::path::PathBuf::from(home).join(".config/mostro-watchdog");
+                if !xdg_dir.exists() {
+                    msg.push_str(&format!(
+                        "\n\nHint: mkdir -p {} && cp config.example.toml {}/config.toml",
+                        xdg_dir.display(),
+                        xdg_dir.display()
+                    ));
+                }
+            }
+
+            return Err(msg.into());
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let mut config = if profile.is_some()
+            || Self::parse::<serde_json::Value>(path, &content)?
+                .get("include")
+                .is_some()
+        {
+            let mut sources = Vec::new();
+            let mut merged = Self::read_layered(path, &mut Vec::new(), &mut sources)?;
+            if let Some(profile) = profile {
+                apply_profile(&mut merged, profile)?;
+            }
+            let mut config: Self = serde_json::from_value(merged).map_err(|e| {
+                format!(
+                    "{} (merged from {} files): {}",
+                    path.display(),
+                    sources.len(),
+                    e
+                )
+            })?;
+            config.sources = sources;
+            config
+        } else {
+            let mut config: Self = Self::parse(path, &content)?;
+            config.sources = vec![path.to_path_buf()];
+            config
+        };
+        config.load_secret_files()?;
+        config.add_relay_hints();
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check every value, collecting all problems rather than stopping at the first
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let mut errors = ConfigErrors::default();
+
+        if self.nostr.relays.is_empty() {
+            errors.add("nostr.relays", "at least one relay must be configured");
+        }
+        for (i, relay) in self.nostr.relays.iter().enumerate() {
+            if let Err(problem) = check_relay_url(relay) {
+                errors.add_value(
+                    format!("nostr.relays[{i}]"),
+                    format!("'{relay}' {problem}"),
+                    relay,
+                );
+            }
+        }
+
+        for relay in self.nostr.relay_roles.keys() {
+            if !self.nostr.relays.contains(relay) {
+                errors.add_value(
+                    format!("nostr.relay_roles.\"{relay}\""),
+                    "is not one of nostr.relays",
+                    relay,
+                );
+            }
+        }
+        if !self.nostr.relays.is_empty() && self.nostr.dispute_relays().is_empty() {
+            errors.add(
+                "nostr.relay_roles",
+                "at least one relay needs the disputes role",
+            );
+        }
+
+        for (relay, filter) in &self.nostr.relay_filters {
+            let key = format!("nostr.relay_filters.\"{relay}\"");
+            if !self.nostr.relays.contains(relay) {
+                errors.add_value(key, "is not one of nostr.relays", relay);
+            } else if !self.nostr.has_role(relay, RelayRole::Disputes) {
+                errors.add_value(key, "applies to a relay without the disputes role", relay);
+            } else {
+                for pubkey in &filter.pubkeys {
+                    if !self.mostro.pubkey.contains(pubkey) {
+                        errors.add_value(
+                            format!("{key}.pubkeys"),
+                            format!("'{pubkey}' is not one of mostro.pubkey"),
+                            pubkey,
+                        );
+                    }
+                }
+            }
+        }
+
+        for (i, relay) in self.nostr.secondary_relays.iter().enumerate() {
+            if !self.nostr.relays.contains(relay) {
+                errors.add_value(
+                    format!("nostr.secondary_relays[{i}]"),
+                    "is not one of nostr.relays",
+                    relay,
+                );
+            }
+        }
+        if !self.nostr.relays.is_empty() && self.nostr.primary_relays().is_empty() {
+            errors.add(
+                "nostr.secondary_relays",
+                "at least one relay must not be secondary",
+            );
+        }
+        if let Some(target) = self.nostr.target_relays {
+            if self.nostr.secondary_relays.is_empty() {
+                errors.add("nostr.target_relays", "requires nostr.secondary_relays");
+            } else if target == 0 || target > self.nostr.relays.len() {
+                errors.add(
+                    "nostr.target_relays",
+                    format!(
+                        "must be between 1 and the {} configured relay(s)",
+                        self.nostr.relays.len()
+                    ),
+                );
+            }
+        }
+
+        if self.nostr.event_cache && !cfg!(feature = "lmdb") {
+            errors.add(
+                "nostr.event_cache",
+                "is set but this build lacks the `lmdb` feature",
+            );
+        }
+
+        if let Some(proxy) = &self.nostr.proxy {
+            if let Err(problem) = check_proxy_url(proxy) {
+                errors.add_value("nostr.proxy", format!("'{proxy}' {problem}"), proxy);
+            }
+        }
+        for (i, relay) in self.nostr.fallback_relays.iter().enumerate() {
+            let key = format!("nostr.fallback_relays[{i}]");
+            if let Err(problem) = check_relay_url(relay) {
+                errors.add_value(key, format!("'{relay}' {problem}"), relay);
+            } else if self.nostr.relays.contains(relay) {
+                errors.add_value(key, format!("'{relay}' is also in nostr.relays"), relay);
+            }
+        }
+        if !self.nostr.fallback_relays.is_empty() && self.nostr.fallback_after == 0 {
+            errors.add("nostr.fallback_after", "must be greater than 0");
+        }
+
+        for (relay, proxy) in &self.nostr.relay_proxies {
+            let key = format!("nostr.relay_proxies.\"{relay}\"");
+            if !self.nostr.relays.contains(relay) && !self.nostr.fallback_relays.contains(relay) {
+                errors.add_value(
+                    key,
+                    "is not one of nostr.relays or nostr.fallback_relays",
+                    relay,
+                );
+            } else if proxy != DIRECT {
+                if let Err(problem) = check_proxy_url(proxy) {
+                    errors.add_value(key, format!("'{proxy}' {problem}"), proxy);
+                }
+            }
+        }
+        let lists = [
+            ("relays", &self.nostr.relays),
+            ("fallback_relays", &self.nostr.fallback_relays),
+        ];
+        for (list, relays) in lists {
+            for (i, relay) in relays.iter().enumerate() {
+                let onion = url::Url::parse(relay)
+                    .is_ok_and(|url| url.host_str().is_some_and(|host| host.ends_with(".onion")));
+                if onion && self.nostr.relay_proxy(relay).is_none() {
+                    errors.add_value(
+                        format!("nostr.{list}[{i}]"),
+                        format!("'{relay}' is an onion address and needs a Tor proxy (nostr.proxy or nostr.relay_proxies)"),
+                        relay,
+                    );
+                }
+            }
+        }
+
+        if let Some(proxy) = &self.telegram.proxy {
+            if let Err(problem) = check_telegram_proxy_url(proxy) {
+                errors.add_value("telegram.proxy", format!("'{proxy}' {problem}"), proxy);
+            }
+        }
+        if let Some(api_url) = &self.telegram.api_url {
+            if let Err(problem) = check_api_url(api_url) {
+                errors.add_value(
+                    "telegram.api_url",
+                    format!("'{api_url}' {problem}"),
+                    api_url,
+                );
+            }
+        }
+
+        if self.telegram.bot_token.is_empty() {
+            errors.add(
+                "telegram.bot_token",
+                "cannot be empty (set bot_token or bot_token_file)",
+            );
+        }
+
+        if self.mostro.pubkey.is_empty() {
+            errors.add("mostro.pubkey", "cannot be empty");
+        }
+        for (i, pubkey) in self.mostro.pubkey.iter().enumerate() {
+            let key = match self.mostro.pubkey.len() {
+                1 => "mostro.pubkey".to_string(),
+                _ => format!("mostro.pubkey[{i}]"),
+            };
+            if pubkey.is_empty() {
+                errors.add(key, "cannot be empty");
+            } else if !is_pubkey(pubkey) {
+                errors.add_value(
+                    key,
+                    format!("'{pubkey}' is not an npub, nprofile or hex public key"),
+                    pubkey,
+                );
+            }
+        }
+
+        if let Some(ref alerts) = self.alerts {
+            if alerts.workers == 0 {
+                errors.add("alerts.workers", "must be greater than 0");
+            }
+            if alerts.queue_capacity < alerts.workers {
+                errors.add("alerts.queue_capacity", "must be at least alerts.workers");
+            }
+            if alerts.cache_capacity == 0 {
+                errors.add("alerts.cache_capacity", "must be greater than 0");
+            }
+            if alerts.redact != RedactMode::Off && alerts.raw_event != RawEventMode::Off {
+                errors.add(
+                    "alerts.redact",
+                    "cannot be combined with alerts.raw_event, which sends the full event",
+                );
+            }
+            if alerts.redact != RedactMode::Off && !alerts.links.is_empty() {
+                errors.add(
+                    "alerts.redact",
+                    "cannot be combined with alerts.links, whose URLs carry the full dispute id",
+                );
+            }
+        }
+
+        if let Some(ref health) = self.health {
+            if health.http_auth_token.as_deref().is_some_and(str::is_empty) {
+                errors.add("health.http_auth_token", "cannot be empty");
+            }
+            if health.http_port == 0 {
+                errors.add("health.http_port", "must be between 1 and 65535");
+            }
+            if let Some(ref tls) = health.http_tls {
+                if !cfg!(feature = "tls") {
+                    errors.add(
+                        "health.http_tls",
+                        "is set but this build lacks the `tls` feature",
+                    );
+                }
+                match (&tls.cert, &tls.key, tls.self_signed) {
+                    (Some(_), Some(_), false) | (None, None, true) => {}
+                    _ => errors.add(
+                        "health.http_tls",
+                        "needs either cert and key, or self_signed = true",
+                    ),
+                }
+            }
+            if health.http_socket.is_some() {
+                if !cfg!(unix) {
+                    errors.add("health.http_socket", "is only supported on Unix");
+                }
+                if health.http_tls.is_some() {
+                    errors.add("health.http_socket", "can't be combined with http_tls");
+                }
+            }
+            if let Some(grpc_port) = health.grpc_port {
+                if !cfg!(feature = "grpc") {
+                    errors.add(
+                        "health.grpc_port",
+                        "is set but this build lacks the `grpc` feature",
+                    );
+                }
+                if grpc_port == 0 {
+                    errors.add("health.grpc_port", "must be between 1 and 65535");
+                } else if grpc_port == health.http_port && health.http_socket.is_none() {
+                    errors.add("health.grpc_port", "must differ from http_port");
+                }
+            }
+            if health.http_max_connections == 0 {
+                errors.add("health.http_max_connections", "must be greater than 0");
+            }
+            if health.http_timeout == 0 {
+                errors.add("health.http_timeout", "must be greater than 0");
+            }
+            if let Some(ref ping_url) = health.ping_url {
+                if let Err(e) = url::Url::parse(ping_url) {
+                    errors.add("health.ping_url", format!("is not a valid URL ({e})"));
+                }
+            }
+            if (health.heartbeat_enabled || health.ping_url.is_some())
+                && health.heartbeat_interval == 0
+            {
+                errors.add("health.heartbeat_interval", "must be greater than 0");
+            }
+            if health.check_relays {
+                if health.relay_timeout == 0 {
+                    errors.add(
+                        "health.relay_timeout",
+                        "must be greater than 0 when check_relays is enabled",
+                    );
+                }
+                if health.relay_backoff_max < health.relay_timeout {
+                    errors.add("health.relay_backoff_max", "must be at least relay_timeout");
+                }
+                if health.relay_blacklist_score > 100 {
+                    errors.add("health.relay_blacklist_score", "must be at most 100");
+                }
+                if health.relay_blacklist_score > 0 && health.relay_blacklist_retry == 0 {
+                    errors.add("health.relay_blacklist_retry", "must be greater than 0");
+                }
+            }
+            if health.min_connected_relays > self.nostr.relays.len() {
+                errors.add(
+                    "health.min_connected_relays",
+                    format!(
+                        "is {} but only {} relay(s) are configured",
+                        health.min_connected_relays,
+                        self.nostr.relays.len()
+                    ),
+                );
+            }
+            if !self.nostr.secondary_relays.is_empty()
+                && health.min_connected_relays > self.nostr.target_relays()
+            {
+                errors.add(
+                    "health.min_connected_relays",
+                    format!(
+                        "is {} but only nostr.target_relays = {} are kept connected",
+                        health.min_connected_relays,
+                        self.nostr.target_relays()
+                    ),
+                );
+            }
+            if health.relay_ping_interval > 0 && health.relay_ping_failures == 0 {
+                errors.add("health.relay_ping_failures", "must be greater than 0");
+            }
+            // The loop is ticked every 30 seconds; less than two ticks would flag a healthy one
+            if health.event_loop_stall_timeout > 0 && health.event_loop_stall_timeout < 60 {
+                errors.add(
+                    "health.event_loop_stall_timeout",
+                    "must be 0 or at least 60",
+                );
+            }
+            if health
+                .degraded_fallback
+                .iter()
+                .any(|action| matches!(action, EscalationAction::Resend))
+            {
+                errors.add(
+                    "health.degraded_fallback",
+                    "doesn't support the resend action",
+                );
+            }
+        }
+
+        if let Some(ref quiet_hours) = self.quiet_hours {
+            if let Err(e) = crate::quiet_hours::QuietHours::from_config(quiet_hours) {
+                errors.add("quiet_hours", e);
+            }
+        }
+
+        if let Some(ref updates) = self.updates {
+            if updates.check && updates.interval_hours == 0 {
+                errors.add("updates.interval_hours", "must be greater than 0");
+            }
+        }
+
+        if let Some(ref telemetry) = self.telemetry {
+            if telemetry.endpoint.is_empty() {
+                errors.add("telemetry.endpoint", "cannot be empty");
+            }
+            if telemetry.metrics_interval == 0 {
+                errors.add("telemetry.metrics_interval", "must be greater than 0");
+            }
+        }
+
+        if let Some(ref statsd) = self.statsd {
+            if statsd.address.is_empty() {
+                errors.add("statsd.address", "cannot be empty");
+            }
+            if statsd.interval == 0 {
+                errors.add("statsd.interval", "must be greater than 0");
+            }
+        }
+
+        if let Some(ref alertmanager) = self.alertmanager {
+            if !alertmanager.url.starts_with("http://") && !alertmanager.url.starts_with("https://")
+            {
+                errors.add_value(
+                    "alertmanager.url",
+                    "must be an http(s) URL",
+                    &alertmanager.url,
+                );
+            }
+        }
+
+        if let Some(ref influx) = self.influxdb {
+            if influx.url.is_empty() {
+                errors.add("influxdb.url", "cannot be empty");
+            }
+            if influx.bucket.is_some() == influx.database.is_some() {
+                errors.add(
+                    "influxdb",
+                    "needs exactly one of bucket (v2) or database (v1)",
+                );
+            }
+            if influx.interval == 0 {
+                errors.add("influxdb.interval", "must be greater than 0");
+            }
+        }
+
+        if let Some(ref sentry) = self.sentry {
+            if sentry.dsn.is_empty() {
+                errors.add("sentry.dsn", "cannot be empty");
+            }
+            if !(0.0..=1.0).contains(&sentry.sample_rate) {
+                errors.add("sentry.sample_rate", "must be between 0.0 and 1.0");
+            }
+        }
+
+        if let Some(ref oncall) = self.oncall {
+            if let Err(e) = crate::oncall::OnCall::from_config(oncall) {
+                errors.add("oncall", e);
+            }
+        }
+
+        if let Some(ref escalation) = self.escalation {
+            for (i, step) in escalation.steps.iter().enumerate() {
+                if step.after_minutes == 0 {
+                    errors.add(
+                        format!("escalation.steps[{i}].after_minutes"),
+                        "must be greater than 0",
+                    );
+                }
+                if let EscalationAction::Pushover { priority, .. } = step.action {
+                    if !(-2..=2).contains(&priority) {
+                        errors.add(
+                            format!("escalation.steps[{i}].priority"),
+                            "must be between -2 and 2",
+                        );
+                    }
+                }
+            }
+        }
+
+        errors.into_result()
+    }
+
+    /// Add the relays named by nprofile keys ahead of `nostr.relays`, as the
+    /// relays the Mostro daemons publish to
+    fn add_relay_hints(&mut self) {
+        let mut relays = self.mostro.relay_hints();
+        if relays.is_empty() {
+            return;
+        }
+        relays.retain(|relay| {
+            !self
+                .nostr
+                .relays
+                .iter()
+                .any(|known| known.trim_end_matches('/') == relay)
+        });
+        relays.append(&mut self.nostr.relays);
+        self.nostr.relays = relays;
+    }
+
+    /// Replace secrets given as `*_file` paths with the files' contents, and
+    /// decrypt age-encrypted ones
+    fn load_secret_files(&mut self) -> Result<(), String> {
+        let identity = crate::secrets::identity_file(self.secrets.as_ref());
+        let identity = identity.as_deref();
+        let telegram = &mut self.telegram;
+        if let Some(ref file) = telegram.bot_token_file {
+            if !telegram.bot_token.is_empty() {
+                return Err("Set telegram.bot_token or bot_token_file, not both".into());
+            }
+            telegram.bot_token = read_secret("telegram.bot_token_file", file, identity)?;
+        }
+        decrypt_secret("telegram.bot_token", &mut telegram.bot_token, identity)?;
+        if let Some(ref mut health) = self.health {
+            secret_from_file(
+                "health.http_auth_token",
+                &mut health.http_auth_token,
+                &health.http_auth_token_file,
+                identity,
+            )?;
+        }
+        if let Some(ref mut influx) = self.influxdb {
+            secret_from_file(
+                "influxdb.token",
+                &mut influx.token,
+                &influx.token_file,
+                identity,
+            )?;
+            secret_from_file(
+                "influxdb.password",
+                &mut influx.password,
+                &influx.password_file,
+                identity,
+            )?;
+        }
+        if let Some(ref mut sentry) = self.sentry {
+            if let Some(ref file) = sentry.dsn_file {
+                if !sentry.dsn.is_empty() {
+                    return Err("Set sentry.dsn or dsn_file, not both".into());
+                }
+                sentry.dsn = read_secret("sentry.dsn_file", file, identity)?;
+            }
+            decrypt_secret("sentry.dsn", &mut sentry.dsn, identity)?;
+        }
+        Ok(())
+    }
+
+    /// The configuration as JSON with bot tokens, keys and credentials masked,
+    /// for `GET /config`
+    pub fn redacted(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("config is serializable")
+    }
+}
+
+/// A problem with one config value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// Key path, e.g. `health.http_timeout` or `nostr.relays[1]`
+    pub key: String,
+    pub message: String,
+    /// The offending value as written, so it can be found in the file
+    pub value: Option<String>,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// Every problem found by [`Config::validate`]
+#[derive(Debug, Default)]
+pub struct ConfigErrors(pub Vec<FieldError>);
+
+impl ConfigErrors {
+    fn add(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.0.push(FieldError {
+            key: key.into(),
+            message: message.into(),
+            value: None,
+        });
+    }
+
+    fn add_value(&mut self, key: impl Into<String>, message: impl Into<String>, value: &str) {
+        self.0.push(FieldError {
+            key: key.into(),
+            message: message.into(),
+            value: Some(value.to_string()),
+        });
+    }
+
+    fn into_result(self) -> Result<(), Self> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(self),
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.as_slice() {
+            [error] => write!(f, "Invalid config: {}", error),
+            errors => {
+                write!(f, "Invalid config ({} problems):", errors.len())?;
+                for error in errors {
+                    write!(f, "\n  • {}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+/// Why a relay URL can't be used, if it can't
+pub fn check_relay_url(relay: &str) -> Result<(), String> {
+    let url = url::Url::parse(relay).map_err(|e| format!("is not a valid URL ({e})"))?;
+    if !matches!(url.scheme(), "ws" | "wss") {
+        return Err("must use the ws:// or wss:// scheme".into());
+    }
+    if url.host_str().is_none_or(str::is_empty) {
+        return Err("has no host".into());
+    }
+    Ok(())
+}
+
+/// `nostr.relay_proxies` value that connects to the relay without a proxy
+const DIRECT: &str = "direct";
+
+/// Why a proxy URL can't be used, if it can't
+pub fn check_proxy_url(proxy: &str) -> Result<(), String> {
+    let url = url::Url::parse(proxy).map_err(|e| format!("is not a valid URL ({e})"))?;
+    if !matches!(url.scheme(), "socks5" | "socks5h") {
+        return Err("must use the socks5:// or socks5h:// scheme".into());
+    }
+    if url.host_str().is_none_or(str::is_empty) || url.port().is_none() {
+        return Err("needs a host and port".into());
+    }
+    Ok(())
+}
+
+/// Why a `telegram.proxy` URL can't be used, if it can't. Unlike relays, the
+/// Telegram client can also go through an HTTP(S) proxy.
+pub fn check_telegram_proxy_url(proxy: &str) -> Result<(), String> {
+    let url = url::Url::parse(proxy).map_err(|e| format!("is not a valid URL ({e})"))?;
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        "socks5" | "socks5h" => check_proxy_url(proxy),
+        _ => Err("must use the socks5://, socks5h://, http:// or https:// scheme".into()),
+    }
+}
+
+/// Why a Bot API server URL can't be used, if it can't
+pub fn check_api_url(api_url: &str) -> Result<(), String> {
+    let url = url::Url::parse(api_url).map_err(|e| format!("is not a valid URL ({e})"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("must use the http:// or https:// scheme".into());
+    }
+    // Requests go to /bot<token>/<method> on the server, replacing any path
+    if url.path() != "/" {
+        return Err("must not have a path".into());
+    }
+    Ok(())
+}
+
+/// Parse a public key given as npub, nprofile or hex
+pub fn parse_pubkey(value: &str) -> Result<nostr_sdk::prelude::PublicKey, String> {
+    use nostr_sdk::prelude::{FromBech32, Nip19Profile, PublicKey};
+    if value.starts_with("nprofile1") {
+        return Nip19Profile::from_bech32(value)
+            .map(|profile| profile.public_key)
+            .map_err(|e| e.to_string());
+    }
+    PublicKey::from_bech32(value)
+        .or_else(|_| PublicKey::from_hex(value))
+        .map_err(|e| e.to_string())
+}
+
+/// Whether `value` is an npub, nprofile or hex public key
+fn is_pubkey(value: &str) -> bool {
+    parse_pubkey(value).is_ok()
+}
+
+/// Variables read by [`Config::from_env`]
+const ENV_VARS: &[&str] = &[
+    "MOSTRO_PUBKEY",
+    "NOSTR_RELAYS",
+    "BOT_TOKEN",
+    "BOT_TOKEN_FILE",
+    "CHAT_ID",
+];
+
+/// Relay used when `NOSTR_RELAYS` isn't set
+const DEFAULT_ENV_RELAY: &str = "wss://relay.mostro.network";
+
+/// Merge the `[profile.<name>]` table over the rest of the config
+fn apply_profile(config: &mut serde_json::Value, name: &str) -> Result<(), String> {
+    let profiles = config
+        .as_object_mut()
+        .and_then(|table| table.remove("profile"));
+    match profiles.as_ref().and_then(|profiles| profiles.get(name)) {
+        Some(profile) => {
+            merge(config, profile.clone());
+            Ok(())
+        }
+        None => {
+            let defined = profiles
+                .as_ref()
+                .and_then(serde_json::Value::as_object)
+                .map(|profiles| profiles.keys().cloned().collect::<Vec<_>>().join(", "))
+                .filter(|defined| !defined.is_empty())
+                .unwrap_or_else(|| "none".to_string());
+            Err(format!(
+                "Profile '{name}' is not defined in the config (defined: {defined})"
+            ))
+        }
+    }
+}
+
+/// Merge `layer` over `base`: tables merge key by key, anything else (including
+/// arrays) is replaced
+fn merge(base: &mut serde_json::Value, layer: serde_json::Value) {
+    match (base, layer) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Fill an optional secret from its `<name>_file` path, if one is set, and
+/// decrypt it if it's age-encrypted
+fn secret_from_file(
+    name: &str,
+    value: &mut Option<String>,
+    file: &Option<PathBuf>,
+    identity: Option<&Path>,
+) -> Result<(), String> {
+    if let Some(file) = file {
+        if value.is_some() {
+            return Err(format!("Set {name} or {name}_file, not both"));
+        }
+        *value = Some(read_secret(&format!("{name}_file"), file, identity)?);
+    }
+    if let Some(value) = value {
+        decrypt_secret(name, value, identity)?;
+    }
+    Ok(())
+}
+
+/// Decrypt a secret given inline as age ciphertext
+fn decrypt_secret(key: &str, value: &mut String, identity: Option<&Path>) -> Result<(), String> {
+    if crate::secrets::is_encrypted(value.as_bytes()) {
+        *value = crate::secrets::decrypt(key, value.as_bytes(), identity)?;
+    }
+    Ok(())
+}
+
+/// Read a secret file (Docker/Kubernetes secret, systemd credential, age-encrypted
+/// file), ignoring the trailing newline most tools write
+fn read_secret(key: &str, path: &Path, identity: Option<&Path>) -> Result<String, String> {
+    let data = std::fs::read(path)
+        .map_err(|e| format!("Failed to read {key} ({}): {e}", path.display()))?;
+    let secret = if crate::secrets::is_encrypted(&data) {
+        crate::secrets::decrypt(key, &data, identity)?
+    } else {
+        String::from_utf8(data).map_err(|_| format!("{key} ({}) is not text", path.display()))?
+    };
+    let secret = secret.trim_end_matches(['\n', '\r']);
+    if secret.is_empty() {
+        return Err(format!("{key} ({}) is empty", path.display()));
+    }
+    Ok(secret.to_string())
+}
+
+/// Stand-in for secret values in the redacted config
+const REDACTED: &str = "***";
+
+fn redact<S: Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
+fn redact_option<S: Serializer>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(_) => serializer.serialize_str(REDACTED),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Keep header names, mask their values
+fn redact_values<S: Serializer>(
+    map: &HashMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(map.keys().map(|key| (key, REDACTED)))
+}
+
+/// Keep the scheme and host of a URL, masking credentials, path and query,
+/// which often carry the secret (ping UUIDs, gateway API keys)
+fn redact_url<S: Serializer>(value: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    match url::Url::parse(value) {
+        Ok(url) if url.has_host() => serializer.serialize_str(&format!(
+            "{}://{}/{}",
+            url.scheme(),
+            url.host_str().unwrap_or_default(),
+            REDACTED
+        )),
+        _ => serializer.serialize_str(REDACTED),
+    }
+}
+
+fn redact_url_option<S: Serializer>(
+    value: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => redact_url(value, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal valid config, for tests to adjust
+    const BASE: &str = r#"
+        [mostro]
+        pubkey = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+
+        [nostr]
+        relays = ["wss://relay.mostro.network"]
+
+        [telegram]
+        bot_token = "123:ABC"
+        chat_id = -100
+    "#;
+
+    fn base_config() -> Config {
+        toml::from_str(BASE).unwrap()
+    }
+
+    /// The base config with `snippet` merged over it, as an include would be
+    fn config_with(snippet: &str) -> Config {
+        let mut config: serde_json::Value = toml::from_str(BASE).unwrap();
+        merge(&mut config, toml::from_str(snippet).unwrap());
+        serde_json::from_value(config).unwrap()
+    }
+
+    #[test]
+    fn test_config_redact() {
+        let mut config = base_config();
+        config.alerts = Some(AlertsConfig {
+            redact: RedactMode::Hash,
+            ..Default::default()
+        });
+        config.validate().unwrap();
+
+        config.alerts.as_mut().unwrap().raw_event = RawEventMode::Quote;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("alerts.raw_event"), "{err}");
+
+        let alerts = config.alerts.as_mut().unwrap();
+        alerts.raw_event = RawEventMode::Off;
+        alerts.links.push(LinkConfig {
+            label: "Open".into(),
+            url: "nostr:{note_id}".into(),
+        });
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("alerts.links"), "{err}");
+    }
+
+    #[test]
+    fn test_health_config_defaults() {
+        let config = HealthConfig::default();
+
+        assert!(!config.heartbeat_enabled); // Disabled by default to avoid flooding chat
+        assert_eq!(config.heartbeat_interval, 3600); // 1 hour
+        assert!(config.heartbeat_silent);
+        assert!(config.check_relays);
+        assert_eq!(config.relay_timeout, 30);
+        assert_eq!(config.event_alert_threshold, 7200); // 2 hours
+        assert!(!config.enable_http_endpoint); // Disabled by default
+        assert_eq!(config.http_port, 8080);
+        assert_eq!(config.event_loop_stall_timeout, 180);
+    }
+
+    #[test]
+    fn test_telegram_topic_resolution() {
+        let config: TelegramConfig = toml::from_str(
+            r#"
+            bot_token = "token"
+            chat_id = -1001234567890
+            topic_id = 5
+
+            [topics]
+            heartbeat = 7
+            "#,
+        )
+        .unwrap();
+
+        // Per-category override wins, otherwise fall back to the global topic
+        assert_eq!(config.heartbeat_topic(), Some(7));
+        assert_eq!(config.disputes_topic(), Some(5));
+        assert_eq!(config.health_topic(), Some(5));
+
+        // A separate health chat doesn't inherit the alert chat's topic
+        let config: TelegramConfig = toml::from_str(
+            r#"
+            bot_token = "token"
+            chat_id = -1001234567890
+            health_chat_id = -1009876543210
+            topic_id = 5
+
+            [topics]
+            health = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.health_chat(), -1009876543210);
+        assert_eq!(config.health_topic(), Some(3));
+        assert_eq!(config.heartbeat_topic(), None);
+        assert_eq!(config.disputes_topic(), Some(5));
+
+        // No topics configured: messages go to the main chat
+        let config: TelegramConfig = toml::from_str("bot_token = \"token\"\nchat_id = 1").unwrap();
+        assert_eq!(config.disputes_topic(), None);
+    }
+
+    #[test]
+    fn test_config_redacted() {
+        let config = config_with(
+            r#"
+            [telegram]
+            bot_token = "123:secret"
+
+            [health]
+            http_auth_token = "hunter2"
+            ping_url = "https://hc-ping.com/0e7a-uuid"
+
+            [telemetry]
+            endpoint = "http://localhost:4318"
+            headers = { authorization = "Basic abc" }
+
+            [[escalation.steps]]
+            after_minutes = 5
+            action = "webhook"
+            url = "https://sms.example.com/send?key=abc"
+            "#,
+        );
+        let redacted = config.redacted();
+        let json = redacted.to_string();
+        for secret in ["123:secret", "hunter2", "0e7a-uuid", "Basic abc", "key=abc"] {
+            assert!(!json.contains(secret), "{secret} leaked");
+        }
+        assert_eq!(redacted["telegram"]["bot_token"], "***");
+        assert_eq!(redacted["telegram"]["chat_id"], -100);
+        assert_eq!(redacted["health"]["ping_url"], "https://hc-ping.com/***");
+        assert_eq!(redacted["telemetry"]["headers"]["authorization"], "***");
+        assert_eq!(redacted["health"]["http_port"], 8080);
+    }
+
+    #[test]
+    fn test_secret_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("bot_token"), "123:from-file\n").unwrap();
+        std::fs::write(dir.path().join("http_token"), "hunter2").unwrap();
+        let config_path = dir.path().join("config.toml");
+        let write_config = |telegram: &str| {
+            let config = format!(
+                r#"
+                [mostro]
+                pubkey = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+
+                [nostr]
+                relays = ["wss://relay.mostro.network"]
+
+                [telegram]
+                chat_id = -100
+                {telegram}
+
+                [health]
+                http_auth_token_file = "{}"
+                "#,
+                dir.path().join("http_token").display()
+            );
+            std::fs::write(&config_path, config).unwrap();
+        };
+
+        let token_file = dir.path().join("bot_token");
+        write_config(&format!("bot_token_file = \"{}\"", token_file.display()));
+        let config = Config::load(&config_path).unwrap();
+        // The trailing newline is dropped
+        assert_eq!(config.telegram.bot_token, "123:from-file");
+        assert_eq!(
+            config.health.unwrap().http_auth_token.as_deref(),
+            Some("hunter2")
+        );
+
+        // Both the value and the file is ambiguous
+        write_config(&format!(
+            "bot_token = \"123:inline\"\nbot_token_file = \"{}\"",
+            token_file.display()
+        ));
+        assert!(Config::load(&config_path).is_err());
+
+        // A missing file names the key
+        write_config("bot_token_file = \"/nonexistent/bot_token\"");
+        let err = Config::load(&config_path).unwrap_err().to_string();
+        assert!(err.contains("telegram.bot_token_file"), "{err}");
+
+        // An age-encrypted file can't be read without an identity
+        std::fs::write(&token_file, "age-encryption.org/v1\n-> X25519 abc\n").unwrap();
+        write_config(&format!("bot_token_file = \"{}\"", token_file.display()));
+        let err = Config::load(&config_path).unwrap_err().to_string();
+        assert!(err.contains("secrets.age_identity_file"), "{err}");
+    }
+
+    #[test]
+    fn test_mostro_pubkeys() {
+        let config = config_with("[mostro]\npubkey = \"npub1a\"");
+        assert_eq!(config.mostro.pubkey, vec!["npub1a"]);
+        let config = config_with("[mostro]\npubkey = [\"npub1a\", \"npub1b\"]");
+        assert_eq!(config.mostro.pubkey, vec!["npub1a", "npub1b"]);
+    }
+
+    #[test]
+    fn test_config_nprofile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        // Names wss://relay.mostro.network and wss://nos.lol
+        std::fs::write(
+            &path,
+            r#"
+            [mostro]
+            pubkey = "nprofile1qqs8n0nx0muaewav2ksx99wwsu9swq5mlndjmn3gm9vl9q2mzmup0xqprfmhxue69uhhyetvv9ujumt0wd68ymewdejhgam0wf4szrthwden5te0dehhxtnvdakq2jl24l"
+
+            [nostr]
+            relays = ["wss://relay.damus.io", "wss://nos.lol/"]
+
+            [telegram]
+            bot_token = "123:ABC"
+            chat_id = -100
+            "#,
+        )
+        .unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(
+            config.nostr.relays,
+            [
+                "wss://relay.mostro.network",
+                "wss://relay.damus.io",
+                "wss://nos.lol/"
+            ]
+        );
+        assert_eq!(
+            parse_pubkey(&config.mostro.pubkey[0]).unwrap().to_hex(),
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+        );
+    }
+
+    #[test]
+    fn test_config_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("config.yaml");
+        std::fs::write(
+            &yaml,
+            r#"
+mostro:
+  pubkey: 79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798
+nostr:
+  relays:
+    - wss://relay.mostro.network
+telegram:
+  bot_token: "123:ABC"
+  chat_id: -100
+alerts:
+  settled: false
+"#,
+        )
+        .unwrap();
+        let config = Config::load(&yaml).unwrap();
+        assert_eq!(config.telegram.chat_id, -100);
+        assert!(!config.alerts.unwrap().settled);
+
+        let json = dir.path().join("config.json");
+        std::fs::write(
+            &json,
+            r#"{
+                "mostro": { "pubkey": "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798" },
+                "nostr": { "relays": ["wss://relay.mostro.network"] },
+                "telegram": { "bot_token": "123:ABC", "chat_id": -100 },
+                "health": { "max_clock_drift": 90 }
+            }"#,
+        )
+        .unwrap();
+        let config = Config::load(&json).unwrap();
+        assert_eq!(config.health.unwrap().max_clock_drift, 90);
+
+        // Anything else is TOML
+        let conf = dir.path().join("watchdog.conf");
+        std::fs::write(&conf, "[mostro]\npubkey = 1").unwrap();
+        assert!(Config::load(&conf).is_err());
+    }
+
+    #[test]
+    fn test_config_validate_collects_all() {
+        let config = config_with(
+            r#"
+            [mostro]
+            pubkey = ["not-a-key", ""]
+
+            [nostr]
+            relays = ["https://nos.lol"]
+
+            [health]
+            http_port = 0
+            http_timeout = 0
+
+            [[escalation.steps]]
+            after_minutes = 0
+            action = "resend"
+            "#,
+        );
+        let errors = config.validate().unwrap_err();
+        let keys: Vec<&str> = errors.0.iter().map(|error| error.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "nostr.relays[0]",
+                "mostro.pubkey[0]",
+                "mostro.pubkey[1]",
+                "health.http_port",
+                "health.http_timeout",
+                "escalation.steps[0].after_minutes",
+            ]
+        );
+        let message = errors.to_string();
+        assert!(
+            message.starts_with("Invalid config (6 problems):"),
+            "{message}"
+        );
+        assert!(message.contains("\n  • health.http_timeout: must be greater than 0"));
+    }
+
+    #[test]
+    fn test_check_relay_url() {
+        assert!(check_relay_url("wss://relay.mostro.network").is_ok());
+        assert!(check_relay_url("ws://127.0.0.1:7000").is_ok());
+        assert!(check_relay_url("relay.mostro.network").is_err());
+        assert!(check_relay_url("wss://").is_err());
+    }
+
+    #[test]
+    fn test_config_include() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("conf.d")).unwrap();
+        std::fs::write(
+            dir.path().join("conf.d/secrets.toml"),
+            "[telegram]\nbot_token = \"123:ABC\"\nchat_id = -100\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("conf.d/tuning.yaml"),
+            "health:\n  max_clock_drift: 90\n  http_port: 9090\nnostr:\n  relays: [wss://nos.lol]\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+            include = ["conf.d/secrets.toml", "conf.d/tuning.yaml"]
+
+            [mostro]
+            pubkey = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+
+            [nostr]
+            relays = ["wss://relay.mostro.network"]
+
+            [health]
+            http_port = 8181
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::load(&config_path).unwrap();
+        assert_eq!(config.telegram.chat_id, -100);
+        // The including file wins, and tables merge key by key
+        assert_eq!(config.nostr.relays, vec!["wss://relay.mostro.network"]);
+        let health = config.health.as_ref().unwrap();
+        assert_eq!(health.http_port, 8181);
+        assert_eq!(health.max_clock_drift, 90);
+        assert_eq!(
+            config.sources,
+            vec![
+                dir.path().join("conf.d/secrets.toml"),
+                dir.path().join("conf.d/tuning.yaml"),
+                config_path.clone(),
+            ]
+        );
+
+        // A file including itself is rejected
+        std::fs::write(
+            dir.path().join("conf.d/secrets.toml"),
+            "include = [\"../config.toml\"]\n",
+        )
+        .unwrap();
+        let err = Config::load(&config_path).unwrap_err().to_string();
+        assert!(err.contains("cycle"), "{err}");
+    }
+
+    #[test]
+    fn test_config_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            r#"
+            [mostro]
+            pubkey = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+
+            [nostr]
+            relays = ["wss://relay.mostro.network", "wss://nos.lol"]
+
+            [telegram]
+            bot_token = "123:ABC"
+            chat_id = -100
+            topic_id = 5
+
+            [profile.staging.telegram]
+            chat_id = -200
+
+            [profile.staging.nostr]
+            relays = ["wss://relay.staging.example"]
+            "#,
+        )
+        .unwrap();
+
+        let config = Config::load(&config_path).unwrap();
+        assert_eq!(config.telegram.chat_id, -100);
+        assert_eq!(config.nostr.relays.len(), 2);
+
+        let config = Config::load_profile(&config_path, Some("staging")).unwrap();
+        assert_eq!(config.telegram.chat_id, -200);
+        assert_eq!(config.telegram.topic_id, Some(5));
+        assert_eq!(config.nostr.relays, vec!["wss://relay.staging.example"]);
+
+        let err = Config::load_profile(&config_path, Some("prod"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("defined: staging"), "{err}");
+    }
+
+    #[test]
+    fn test_config_relay_roles() {
+        let mut config = base_config();
+        config.nostr.relays.push("wss://relay.damus.io".into());
+        config
+            .nostr
+            .relay_roles
+            .insert("wss://relay.damus.io".into(), vec![RelayRole::Publish]);
+        config.validate().unwrap();
+        assert_eq!(
+            config.nostr.dispute_relays(),
+            vec!["wss://relay.mostro.network"]
+        );
+        assert!(config
+            .nostr
+            .has_role("wss://relay.mostro.network", RelayRole::Publish));
+        assert!(!config
+            .nostr
+            .has_role("wss://relay.damus.io", RelayRole::Disputes));
+
+        let roles = &mut config.nostr.relay_roles;
+        roles.insert(
+            "wss://relay.mostro.network".into(),
+            vec![RelayRole::Publish],
+        );
+        roles.insert("wss://nos.lol".into(), vec![RelayRole::Disputes]);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("needs the disputes role"), "{err}");
+        assert!(err.contains("is not one of nostr.relays"), "{err}");
+    }
+
+    #[test]
+    fn test_config_relay_filters() {
+        let mut config = base_config();
+        config.nostr.relays.push("wss://relay.damus.io".into());
+        config.nostr.relays.push("wss://nos.lol".into());
+        config.nostr.relay_filters.insert(
+            "wss://relay.damus.io".into(),
+            RelayFilter {
+                statuses: vec!["initiated".into()],
+                ..Default::default()
+            },
+        );
+        config.validate().unwrap();
+        let groups = config.nostr.dispute_relays_by_filter();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].1, ["wss://relay.mostro.network", "wss://nos.lol"]);
+        assert_eq!(groups[1].0.statuses, ["initiated"]);
+        assert_eq!(groups[1].1, ["wss://relay.damus.io"]);
+
+        let filter = config
+            .nostr
+            .relay_filters
+            .get_mut("wss://relay.damus.io")
+            .unwrap();
+        filter.pubkeys.push("npub1unknown".into());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("is not one of mostro.pubkey"), "{err}");
+    }
+
+    #[test]
+    fn test_config_proxy() {
+        let mut config = base_config();
+        config.nostr.relays.push("ws://mostroxyz.onion".into());
+        config.nostr.relay_proxies.insert(
+            "ws://mostroxyz.onion".into(),
+            "socks5h://127.0.0.1:9050".into(),
+        );
+        config.telegram.proxy = Some("socks5://proxy.example.com:1080".into());
+        config.validate().unwrap();
+        assert_eq!(config.nostr.relay_proxy("wss://relay.mostro.network"), None);
+        assert_eq!(
+            config.nostr.relay_proxy("ws://mostroxyz.onion"),
+            Some("socks5h://127.0.0.1:9050")
+        );
+
+        config.nostr.proxy = Some("socks5h://tor:9050".into());
+        config
+            .nostr
+            .relay_proxies
+            .insert("ws://mostroxyz.onion".into(), "direct".into());
+        assert_eq!(
+            config.nostr.relay_proxy("wss://relay.mostro.network"),
+            Some("socks5h://tor:9050")
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("needs a Tor proxy"), "{err}");
+
+        config.nostr.relay_proxies.clear();
+        config.telegram.proxy = Some("http://proxy.example.com:3128".into());
+        config.telegram.api_url = Some("http://telegram-bot-api:8081".into());
+        config.validate().unwrap();
+
+        config.telegram.proxy = Some("ftp://proxy.example.com".into());
+        config.telegram.api_url = Some("http://telegram-bot-api:8081/bot".into());
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("telegram.proxy"), "{err}");
+        assert!(err.contains("telegram.api_url"), "{err}");
+    }
+
+    #[test]
+    fn test_config_fallback_relays() {
+        let mut config = base_config();
+        config.nostr.fallback_relays = vec!["wss://relay.damus.io".into(), "wss://nos.lol".into()];
+        config.validate().unwrap();
+        assert_eq!(config.nostr.fallback_after, 10);
+
+        config
+            .nostr
+            .fallback_relays
+            .push("wss://relay.mostro.network".into());
+        config.nostr.fallback_after = 0;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("is also in nostr.relays"), "{err}");
+        assert!(err.contains("nostr.fallback_after"), "{err}");
+    }
+
+    #[test]
+    fn test_config_secondary_relays() {
+        let mut config = base_config();
+        config.nostr.relays.push("wss://relay.damus.io".into());
+        config.nostr.relays.push("wss://nos.lol".into());
+        config.nostr.secondary_relays = vec!["wss://nos.lol".into()];
+        config.validate().unwrap();
+        assert_eq!(
+            config.nostr.primary_relays(),
+            ["wss://relay.mostro.network", "wss://relay.damus.io"]
+        );
+        assert_eq!(config.nostr.target_relays(), 2);
+
+        config
+            .nostr
+            .secondary_relays
+            .push("wss://relay.primal.net".into());
+        config.nostr.target_relays = Some(4);
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("nostr.secondary_relays[1]"), "{err}");
+        assert!(err.contains("nostr.target_relays"), "{err}");
+
+        config.nostr.secondary_relays = config.nostr.relays.clone();
+        config.nostr.target_relays = None;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("at least one relay must not be secondary"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_config_min_connected_relays() {
+        let mut config = base_config();
+        config.nostr.relays.push("wss://relay.damus.io".into());
+        config.health = Some(HealthConfig {
+            min_connected_relays: 2,
+            ..Default::default()
+        });
+        config.validate().unwrap();
+
+        config.nostr.relays.pop();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("health.min_connected_relays"), "{err}");
+    }
+
+    #[test]
+    fn test_config_from_env() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        assert!(Config::from_env(env(&[])).is_none());
+
+        let config = Config::from_env(env(&[
+            ("MOSTRO_PUBKEY", "npub1a, npub1b"),
+            ("BOT_TOKEN", "123:ABC"),
+            ("CHAT_ID", "-100123"),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.mostro.pubkey, vec!["npub1a", "npub1b"]);
+        assert_eq!(config.nostr.relays, vec!["wss://relay.mostro.network"]);
+        assert_eq!(config.telegram.bot_token, "123:ABC");
+        assert_eq!(config.telegram.chat_id, -100123);
+        assert!(config.health.is_none());
+
+        let err = Config::from_env(env(&[("BOT_TOKEN", "123:ABC")]))
+            .unwrap()
+            .unwrap_err();
+        assert!(err.ends_with("MOSTRO_PUBKEY, CHAT_ID"), "{err}");
+        let err = Config::from_env(env(&[("CHAT_ID", "group")]))
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err, "CHAT_ID must be a number");
     }
 }
@@ -0,0 +1,163 @@
+//! Last alert before the watchdog dies unexpectedly.
+//!
+//! The event loop runs on the main thread, so a panic there, or an error ending the
+//! loop, takes monitoring down. Either way a "watchdog crashed" alert (the `crashed`
+//! template) is sent to the health topic, falling back to `health.degraded_fallback`
+//! when Telegram can't be reached, before the process exits. Panics in background
+//! tasks aren't crashes: the supervisor restarts those.
+
+use crate::channels;
+use crate::config::{EscalationAction, TelegramConfig};
+use crate::proxy;
+use crate::templates::Templates;
+use crate::{send_markdown, Destination};
+use std::any::Any;
+use std::panic::Location;
+use std::sync::{Arc, OnceLock};
+use tracing::{error, warn};
+
+static NOTIFIER: OnceLock<Notifier> = OnceLock::new();
+
+/// Where crash alerts go
+struct Notifier {
+    /// A fresh bot is built per alert, as the panicking runtime's connections may
+    /// be unusable
+    telegram: TelegramConfig,
+    destination: Destination,
+    templates: Arc<Templates>,
+    fallback: Vec<EscalationAction>,
+}
+
+/// Alert on crashes from now on, after the panic hook already installed (e.g.
+/// Sentry's) has run
+pub fn install(
+    telegram: &TelegramConfig,
+    destination: Destination,
+    templates: Arc<Templates>,
+    fallback: Vec<EscalationAction>,
+) {
+    let notifier = Notifier {
+        telegram: telegram.clone(),
+        destination,
+        templates,
+        fallback,
+    };
+    if NOTIFIER.set(notifier).is_err() {
+        return;
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        if std::thread::current().name() != Some("main") {
+            return;
+        }
+        let reason = describe(info.payload(), info.location());
+        // The main thread is inside the runtime, so deliver from a thread of its own
+        let delivery =
+            std::thread::spawn(move || {
+                match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime.block_on(report(&reason)),
+                    Err(e) => error!("Failed to send crash alert: {}", e),
+                }
+            });
+        let _ = delivery.join();
+    }));
+}
+
+/// Send the crash alert for `reason`, if installed
+pub async fn report(reason: &str) {
+    let Some(notifier) = NOTIFIER.get() else {
+        return;
+    };
+    let mut context = tera::Context::new();
+    context.insert("reason", reason);
+    let message = notifier.templates.render("crashed", &context);
+    let plain = format!("mostro-watchdog crashed: {reason}");
+
+    let bot = match proxy::bot(&notifier.telegram) {
+        Ok(bot) => bot,
+        Err(e) => {
+            error!("Failed to send crash alert: {}", e);
+            return;
+        }
+    };
+    let Err(e) = channels::timed(send_markdown(&bot, notifier.destination, &message)).await else {
+        return;
+    };
+    warn!("Failed to send crash alert to Telegram: {}", e);
+
+    let http = reqwest::Client::new();
+    for action in &notifier.fallback {
+        let result = match *action {
+            EscalationAction::Telegram { chat_id, topic_id } => {
+                let destination = Destination {
+                    chat_id,
+                    topic_id,
+                    silent: false,
+                };
+                channels::timed(send_markdown(&bot, destination, &message))
+                    .await
+                    .map(|_| ())
+            }
+            EscalationAction::Pushover {
+                ref token,
+                ref user,
+                priority,
+            } => channels::pushover(&http, token, user, priority, "mostro-watchdog", &plain)
+                .await
+                .map_err(|e| e.to_string()),
+            EscalationAction::Webhook { ref url } => {
+                let payload = serde_json::json!({ "text": plain });
+                channels::webhook(&http, url, &payload)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            // Rejected when the config is loaded
+            EscalationAction::Resend => continue,
+        };
+        match result {
+            Ok(()) => return,
+            Err(e) => warn!("Crash alert fallback failed: {}", e),
+        }
+    }
+    error!("Crash alert could not be delivered on any channel");
+}
+
+/// Panic message with where it happened
+fn describe(payload: &dyn Any, location: Option<&Location<'_>>) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    match location {
+        Some(location) => format!(
+            "panicked at {}:{}: {message}",
+            location.file(),
+            location.line()
+        ),
+        None => format!("panicked: {message}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let location = Location::caller();
+        assert_eq!(
+            describe(&"boom", Some(location)),
+            format!("panicked at src/crash.rs:{}: boom", location.line())
+        );
+        assert_eq!(
+            describe(&"index out of range".to_string(), None),
+            "panicked: index out of range"
+        );
+        assert_eq!(describe(&42, None), "panicked: unknown panic");
+    }
+}
@@ -7,8 +7,20 @@ use std::path::Path;
 use std::str::FromStr;
 use tracing::info;
 
+/// How long seen dispute events are kept for catching up, in seconds (30 days)
+const SEEN_EVENTS_RETENTION: i64 = 30 * 24 * 3600;
+
+/// A tracked dispute as listed by the HTTP API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredDispute {
+    pub dispute_id: String,
+    pub status: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
 /// Stores the mapping between dispute IDs and Telegram message IDs.
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct DisputeMessageStore {
     pool: SqlitePool,
 }
@@ -40,6 +52,58 @@ impl DisputeMessageStore {
         .execute(&pool)
         .await?;
 
+        // Single-row table rewritten by write probes
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS write_probe (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                checked_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Single-row table with the newest dispute event seen, for `--once` runs
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS watch_state (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                last_seen INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Recent dispute events as JSON, reconciled against when catching up
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS seen_events (
+                id TEXT PRIMARY KEY NOT NULL,
+                created_at INTEGER NOT NULL,
+                event TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Content hash and creation time of the last event alerted on per dispute, to
+        // skip re-broadcasts
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS dispute_hashes (
+                dispute_id TEXT PRIMARY KEY NOT NULL,
+                hash TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await?;
+
         info!("Dispute message store initialized at {}", db_path.display());
         Ok(Self { pool })
     }
@@ -117,6 +181,235 @@ impl DisputeMessageStore {
         Ok(())
     }
 
+    /// Get the stored status and creation time (Unix seconds) of a dispute.
+    pub async fn get_status(&self, dispute_id: &str) -> Result<Option<(String, i64)>, sqlx::Error> {
+        let result: Option<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT status, created_at FROM dispute_messages WHERE dispute_id = ?
+            "#,
+        )
+        .bind(dispute_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Count disputes that are still waiting for or being handled by a solver.
+    pub async fn count_open(&self) -> Result<u64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM dispute_messages WHERE status IN ('initiated', 'in-progress')
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    /// Count tracked disputes, optionally only those with the given status.
+    pub async fn count(&self, status: Option<&str>) -> Result<u64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM dispute_messages WHERE ? IS NULL OR status = ?
+            "#,
+        )
+        .bind(status)
+        .bind(status)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    /// List tracked disputes, newest first, optionally only those with the given status.
+    pub async fn list(
+        &self,
+        status: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> Result<Vec<StoredDispute>, sqlx::Error> {
+        let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT dispute_id, status, created_at, updated_at FROM dispute_messages
+            WHERE ? IS NULL OR status = ?
+            ORDER BY created_at DESC, dispute_id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(status)
+        .bind(status)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(dispute_id, status, created_at, updated_at)| StoredDispute {
+                    dispute_id,
+                    status,
+                    created_at,
+                    updated_at,
+                },
+            )
+            .collect())
+    }
+
+    /// Perform a small write to check that the database is still writable.
+    pub async fn probe_write(&self) -> Result<(), sqlx::Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        sqlx::query(
+            r#"
+            INSERT INTO write_probe (id, checked_at) VALUES (1, ?)
+            ON CONFLICT(id) DO UPDATE SET checked_at = excluded.checked_at
+            "#,
+        )
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Finish pending writes and close the database, e.g. before exiting.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    /// Creation time (Unix seconds) of the newest dispute event seen, if any.
+    pub async fn last_seen(&self) -> Result<Option<u64>, sqlx::Error> {
+        let result: Option<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT last_seen FROM watch_state WHERE id = 1
+            "#,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result.map(|(last_seen,)| last_seen.max(0) as u64))
+    }
+
+    /// Record a dispute event's creation time, keeping the newest seen.
+    pub async fn record_seen(&self, created_at: u64) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO watch_state (id, last_seen) VALUES (1, ?)
+            ON CONFLICT(id) DO UPDATE SET last_seen = MAX(last_seen, excluded.last_seen)
+            "#,
+        )
+        .bind(created_at as i64)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Keep a dispute event (JSON) for catching up, dropping those past retention.
+    pub async fn store_event(
+        &self,
+        id: &str,
+        created_at: u64,
+        event: &str,
+    ) -> Result<(), sqlx::Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO seen_events (id, created_at, event) VALUES (?, ?, ?)
+            "#,
+        )
+        .bind(id)
+        .bind(created_at as i64)
+        .bind(event)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM seen_events WHERE created_at < ?
+            "#,
+        )
+        .bind(now - SEEN_EVENTS_RETENTION)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Stored dispute events (JSON) created at or after `since`.
+    pub async fn events_since(&self, since: u64) -> Result<Vec<String>, sqlx::Error> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT event FROM seen_events WHERE created_at >= ? ORDER BY created_at
+            "#,
+        )
+        .bind(since as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(event,)| event).collect())
+    }
+
+    /// Content hash and `created_at` of the newest event handled for a dispute.
+    pub async fn last_handled(
+        &self,
+        dispute_id: &str,
+    ) -> Result<Option<(String, u64)>, sqlx::Error> {
+        let result: Option<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT hash, created_at FROM dispute_hashes WHERE dispute_id = ?
+            "#,
+        )
+        .bind(dispute_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result.map(|(hash, created_at)| (hash, created_at as u64)))
+    }
+
+    /// Record a dispute's handled event, unless a newer one was recorded already.
+    pub async fn set_last_handled(
+        &self,
+        dispute_id: &str,
+        hash: &str,
+        created_at: u64,
+    ) -> Result<(), sqlx::Error> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        sqlx::query(
+            r#"
+            INSERT INTO dispute_hashes (dispute_id, hash, created_at, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(dispute_id) DO UPDATE SET
+                hash = excluded.hash,
+                created_at = excluded.created_at,
+                updated_at = excluded.updated_at
+            WHERE excluded.created_at >= dispute_hashes.created_at
+            "#,
+        )
+        .bind(dispute_id)
+        .bind(hash)
+        .bind(created_at as i64)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Delete a dispute record (after cooperative cancellation).
     pub async fn delete(&self, dispute_id: &str) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -158,6 +451,72 @@ mod tests {
             .update_status("dispute-123", "in-progress")
             .await
             .unwrap();
+        let (status, _created_at) = store.get_status("dispute-123").await.unwrap().unwrap();
+        assert_eq!(status, "in-progress");
+
+        // Resolved disputes are no longer open
+        store
+            .insert("dispute-456", 789, -100123, "initiated")
+            .await
+            .unwrap();
+        assert_eq!(store.count_open().await.unwrap(), 2);
+        store.update_status("dispute-456", "settled").await.unwrap();
+        assert_eq!(store.count_open().await.unwrap(), 1);
+        assert_eq!(store.count(None).await.unwrap(), 2);
+        let settled = store.list(Some("settled"), 10, 0).await.unwrap();
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].dispute_id, "dispute-456");
+
+        store.probe_write().await.unwrap();
+
+        // The newest event time is kept
+        assert_eq!(store.last_seen().await.unwrap(), None);
+        store.record_seen(1_700_000_100).await.unwrap();
+        store.record_seen(1_700_000_000).await.unwrap();
+        assert_eq!(store.last_seen().await.unwrap(), Some(1_700_000_100));
+
+        // Seen events are kept once, and those past retention are dropped
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        store
+            .store_event("a", now - 60, "{\"id\":\"a\"}")
+            .await
+            .unwrap();
+        store
+            .store_event("a", now - 60, "{\"id\":\"a\"}")
+            .await
+            .unwrap();
+        store
+            .store_event("b", now - 40 * 24 * 3600, "{}")
+            .await
+            .unwrap();
+        store.store_event("c", now, "{\"id\":\"c\"}").await.unwrap();
+        assert_eq!(store.events_since(0).await.unwrap().len(), 2);
+        assert_eq!(
+            store.events_since(now).await.unwrap(),
+            vec!["{\"id\":\"c\"}"]
+        );
+
+        // The newest handled event is kept
+        assert_eq!(store.last_handled("dispute-123").await.unwrap(), None);
+        store
+            .set_last_handled("dispute-123", "aa", 100)
+            .await
+            .unwrap();
+        store
+            .set_last_handled("dispute-123", "bb", 200)
+            .await
+            .unwrap();
+        store
+            .set_last_handled("dispute-123", "cc", 150)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.last_handled("dispute-123").await.unwrap(),
+            Some(("bb".to_string(), 200))
+        );
 
         // Delete
         store.delete("dispute-123").await.unwrap();
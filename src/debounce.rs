@@ -0,0 +1,92 @@
+//! Coalescing of rapid updates to one dispute.
+//!
+//! Dispute events are parameterized-replaceable, and Mostro can rewrite one several
+//! times within seconds (e.g. taken and immediately resolved). With
+//! `alerts.debounce_window` set, the first event for a dispute opens a window; later
+//! events within it replace the held one, and only the newest state is alerted on
+//! when the window closes. Held events are kept in a bounded LRU (see `lru`).
+
+use crate::lru::Lru;
+use nostr_sdk::prelude::Event;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Latest held event per dispute id
+pub struct Debouncer {
+    window: Duration,
+    pending: Mutex<Lru<String, Event>>,
+}
+
+impl Debouncer {
+    pub fn new(window: Duration, capacity: usize) -> Self {
+        Self {
+            window,
+            pending: Mutex::new(Lru::new("debounce", capacity)),
+        }
+    }
+
+    /// How long an update is held
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Hold `event` as the latest state of `dispute_id`, unless an event already
+    /// held is newer. Returns true when this opens the window, in which case the
+    /// caller takes the dispute's event once it closes.
+    pub fn hold(&self, dispute_id: &str, event: Event) -> bool {
+        let mut pending = self.pending.lock().expect("debounce lock poisoned");
+        match pending.get_mut(dispute_id) {
+            Some(held) => {
+                if event.created_at >= held.created_at {
+                    *held = event;
+                }
+                false
+            }
+            None => {
+                pending.get_or_insert_with(dispute_id.to_string(), || event);
+                true
+            }
+        }
+    }
+
+    /// The newest event held for `dispute_id`, closing its window
+    pub fn take(&self, dispute_id: &str) -> Option<Event> {
+        self.pending
+            .lock()
+            .expect("debounce lock poisoned")
+            .remove(dispute_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lru;
+    use nostr_sdk::prelude::*;
+
+    fn event(keys: &Keys, status: &str, created_at: u64) -> Event {
+        EventBuilder::new(Kind::Custom(38386), "")
+            .tags([
+                Tag::identifier("dispute-1"),
+                Tag::custom(TagKind::custom("s"), [status]),
+            ])
+            .custom_created_at(Timestamp::from(created_at))
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_keeps_newest() {
+        let keys = Keys::generate();
+        let debouncer = Debouncer::new(Duration::from_secs(5), lru::DEFAULT_CAPACITY);
+        assert!(debouncer.hold("dispute-1", event(&keys, "initiated", 100)));
+        assert!(!debouncer.hold("dispute-1", event(&keys, "settled", 102)));
+        // Arrived late but older
+        assert!(!debouncer.hold("dispute-1", event(&keys, "in-progress", 101)));
+
+        let held = debouncer.take("dispute-1").unwrap();
+        assert_eq!(held.created_at, Timestamp::from(102));
+        assert!(debouncer.take("dispute-1").is_none());
+        assert!(debouncer.hold("dispute-1", event(&keys, "released", 103)));
+    }
+}
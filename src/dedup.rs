@@ -0,0 +1,156 @@
+//! Deduplication of dispute events delivered by several relays.
+//!
+//! Every relay subscribed to disputes sends its own copy of each event, and the
+//! catch-up and fallback subscriptions can deliver it again. The ids of recent
+//! events are kept in a bounded LRU (see `lru`), with the relays that delivered
+//! each one, so an event goes through the alert pipeline exactly once.
+//!
+//! Relays can also re-broadcast an older copy of a replaceable dispute event, with
+//! a different id, after reconnecting. Such events are recognized by
+//! [`content_hash`], which only covers what an alert is about, or by being older
+//! than the last event handled for the dispute.
+
+use crate::lru::{self, Lru};
+use nostr_sdk::prelude::{Event, EventId};
+use std::sync::Mutex;
+
+/// Tags that make up a dispute's alertable state: its id, status and participants
+const CONTENT_TAGS: &[&str] = &["d", "s", "initiator", "solver", "p"];
+
+/// Stable hash (64-bit FNV-1a, hex) of the event's dispute id, status and
+/// participants, equal for events that would produce the same alert
+pub fn content_hash(event: &Event) -> String {
+    let mut fields: Vec<(&str, &str)> = event
+        .tags
+        .iter()
+        .filter_map(|tag| match tag.as_slice() {
+            [name, value, ..] if CONTENT_TAGS.contains(&name.as_str()) => {
+                Some((name.as_str(), value.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    // Tag order carries no meaning
+    fields.sort_unstable();
+
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (name, value) in fields {
+        for byte in name.bytes().chain([0]).chain(value.bytes()).chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{hash:016x}")
+}
+
+/// Recently seen dispute events
+pub struct SeenEvents {
+    lru: Mutex<Lru<EventId, Entry>>,
+}
+
+#[derive(Default)]
+struct Entry {
+    /// Relays that delivered the event, in arrival order
+    relays: Vec<String>,
+    /// Whether the event went through the alert pipeline
+    routed: bool,
+}
+
+impl Default for SeenEvents {
+    fn default() -> Self {
+        Self::new(lru::DEFAULT_CAPACITY)
+    }
+}
+
+impl SeenEvents {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lru: Mutex::new(Lru::new("seen_events", capacity)),
+        }
+    }
+
+    /// Record that `relay` delivered event `id`
+    pub fn record_delivery(&self, id: EventId, relay: &str) {
+        let mut lru = self.lru.lock().expect("seen events lock poisoned");
+        let entry = lru.get_or_insert_with(id, Entry::default);
+        if !entry.relays.iter().any(|known| known == relay) {
+            entry.relays.push(relay.to_string());
+        }
+    }
+
+    /// Mark event `id` as routed, returning false if it already was
+    pub fn first_pass(&self, id: EventId) -> bool {
+        let mut lru = self.lru.lock().expect("seen events lock poisoned");
+        let entry = lru.get_or_insert_with(id, Entry::default);
+        !std::mem::replace(&mut entry.routed, true)
+    }
+
+    /// Relays known to have delivered event `id`
+    pub fn relays(&self, id: &EventId) -> Vec<String> {
+        let lru = self.lru.lock().expect("seen events lock poisoned");
+        lru.get(id)
+            .map(|entry| entry.relays.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: u8) -> EventId {
+        EventId::from_slice(&[n; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_first_pass() {
+        let seen = SeenEvents::default();
+        seen.record_delivery(id(1), "wss://relay.mostro.network");
+        assert!(seen.first_pass(id(1)));
+        seen.record_delivery(id(1), "wss://relay.damus.io");
+        seen.record_delivery(id(1), "wss://relay.mostro.network");
+        assert!(!seen.first_pass(id(1)));
+        assert_eq!(
+            seen.relays(&id(1)),
+            ["wss://relay.mostro.network", "wss://relay.damus.io"]
+        );
+    }
+
+    #[test]
+    fn test_content_hash() {
+        use nostr_sdk::prelude::*;
+
+        let event = |keys: &Keys, status: &str, created_at: u64| {
+            EventBuilder::new(Kind::Custom(38386), "")
+                .tags([
+                    Tag::identifier("dispute-1"),
+                    Tag::custom(TagKind::custom("s"), [status]),
+                    Tag::custom(TagKind::custom("initiator"), ["buyer"]),
+                ])
+                .custom_created_at(Timestamp::from(created_at))
+                .sign_with_keys(keys)
+                .unwrap()
+        };
+        let keys = Keys::generate();
+        let initiated = event(&keys, "initiated", 100);
+        let rebroadcast = event(&keys, "initiated", 200);
+        assert_ne!(initiated.id, rebroadcast.id);
+        assert_eq!(content_hash(&initiated), content_hash(&rebroadcast));
+        assert_ne!(
+            content_hash(&initiated),
+            content_hash(&event(&keys, "in-progress", 200))
+        );
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let seen = SeenEvents::new(2);
+        assert!(seen.first_pass(id(1)));
+        assert!(seen.first_pass(id(2)));
+        // Seeing 1 again keeps it, so 2 is evicted for 3
+        seen.record_delivery(id(1), "wss://relay.mostro.network");
+        assert!(seen.first_pass(id(3)));
+        assert!(!seen.first_pass(id(1)));
+        assert!(seen.first_pass(id(2)));
+    }
+}
@@ -0,0 +1,245 @@
+//! Alerts about the watchdog's own ability to deliver alerts.
+//!
+//! The watchdog is degraded while all relays are disconnected, the Telegram send
+//! queue is backed up, or the dispute store can't be written. Entering (or changing)
+//! a degraded state is reported to the health topic, falling back to the configured
+//! out-of-band channels when Telegram is unreachable, and recovery is reported the
+//! same way.
+
+use crate::channels;
+use crate::config::EscalationAction;
+use crate::events;
+use crate::send_queue::queue;
+use crate::silence;
+use crate::supervisor::Liveness;
+use crate::templates::Templates;
+use crate::{record_delivery, send_markdown, Destination, HealthMonitor};
+use nostr_sdk::Client;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::Bot;
+use tracing::{error, info, warn};
+
+/// Seconds between self-checks
+pub const CHECK_INTERVAL: u64 = 30;
+
+/// Degradation conditions found by one self-check
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Conditions {
+    pub relays_down: bool,
+    /// Messages waiting in the send queue, when over the limit
+    pub queue_backlog: Option<usize>,
+    /// Why the dispute store rejected a write
+    pub store_error: Option<String>,
+}
+
+impl Conditions {
+    pub fn is_degraded(&self) -> bool {
+        !self.keys().is_empty()
+    }
+
+    /// Short names of the failing conditions, as reported in the health JSON
+    pub fn keys(&self) -> Vec<&'static str> {
+        let mut keys = Vec::new();
+        if self.relays_down {
+            keys.push("relays");
+        }
+        if self.queue_backlog.is_some() {
+            keys.push("queue");
+        }
+        if self.store_error.is_some() {
+            keys.push("store");
+        }
+        keys
+    }
+
+    /// One-line description for channels without templates
+    fn plain(&self) -> String {
+        let mut reasons = Vec::new();
+        if self.relays_down {
+            reasons.push("all relays disconnected".to_string());
+        }
+        if let Some(backlog) = self.queue_backlog {
+            reasons.push(format!("{backlog} messages waiting in the send queue"));
+        }
+        if let Some(ref e) = self.store_error {
+            reasons.push(format!("dispute store not writable ({e})"));
+        }
+        format!("mostro-watchdog degraded: {}", reasons.join("; "))
+    }
+}
+
+pub struct DegradationMonitor {
+    pub health_monitor: Arc<HealthMonitor>,
+    pub client: Client,
+    pub relays: Vec<String>,
+    pub max_queue_backlog: usize,
+    pub bot: Bot,
+    pub destination: Destination,
+    pub templates: Arc<Templates>,
+    /// Tried in order when the health topic can't be reached
+    pub fallback: Vec<EscalationAction>,
+    pub http: reqwest::Client,
+}
+
+impl DegradationMonitor {
+    /// Run self-checks until cancelled, alerting on changes
+    pub async fn run(self: Arc<Self>, liveness: Liveness) {
+        let mut interval = tokio::time::interval(Duration::from_secs(CHECK_INTERVAL));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        interval.tick().await; // skip the immediate first tick to let relays connect
+
+        let mut current = Conditions::default();
+        let mut degraded_since = None;
+
+        loop {
+            interval.tick().await;
+
+            let conditions = self.check().await;
+            liveness.beat();
+            self.health_monitor.set_degraded(conditions.keys());
+
+            if conditions.keys() != current.keys() {
+                if conditions.is_degraded() {
+                    warn!("⚠️ Watchdog degraded: {:?}", conditions.keys());
+                    degraded_since.get_or_insert_with(Instant::now);
+                    self.alert_degraded(&conditions).await;
+                } else {
+                    let minutes = degraded_since
+                        .take()
+                        .map(|since| since.elapsed().as_secs() / 60)
+                        .unwrap_or_default();
+                    info!("✅ Watchdog recovered after {} minutes", minutes);
+                    self.alert_recovered(minutes).await;
+                }
+            }
+            current = conditions;
+        }
+    }
+
+    async fn check(&self) -> Conditions {
+        let failed = self
+            .health_monitor
+            .refresh_relay_statuses(&self.client, &self.relays)
+            .await;
+        let backlog = queue().backlog();
+        let store_error = match self.health_monitor.dispute_store.get() {
+            Some(store) => store.probe_write().await.err().map(|e| e.to_string()),
+            None => None,
+        };
+        self.health_monitor
+            .record_component("store", store_error.clone().map_or(Ok(()), Err));
+
+        Conditions {
+            relays_down: !self.relays.is_empty() && failed.len() == self.relays.len(),
+            queue_backlog: (backlog > self.max_queue_backlog).then_some(backlog),
+            store_error,
+        }
+    }
+
+    async fn alert_degraded(&self, conditions: &Conditions) {
+        let mut context = tera::Context::new();
+        context.insert("relays_down", &conditions.relays_down);
+        context.insert("queue_backlog", &conditions.queue_backlog);
+        context.insert("store_error", &conditions.store_error);
+        events::publish_health("degraded", &context);
+        let message = self.templates.render("degraded", &context);
+        self.notify(&message, &conditions.plain()).await;
+    }
+
+    async fn alert_recovered(&self, minutes: u64) {
+        let mut context = tera::Context::new();
+        context.insert("minutes", &minutes);
+        events::publish_health("degraded_recovered", &context);
+        let message = self.templates.render("degraded_recovered", &context);
+        let plain = format!("mostro-watchdog recovered after {minutes} minutes degraded");
+        self.notify(&message, &plain).await;
+    }
+
+    /// Deliver to the health topic, or the first fallback channel that works
+    async fn notify(&self, message: &str, plain: &str) {
+        if silence::suppressed(silence::Category::Health, "degradation") {
+            return;
+        }
+        let Err(e) = send_markdown(&self.bot, self.destination, message).await else {
+            return;
+        };
+        warn!("Failed to send degradation alert to Telegram: {}", e);
+
+        for action in &self.fallback {
+            let result: Result<(), Box<dyn std::error::Error + Send + Sync>> = match *action {
+                EscalationAction::Telegram { chat_id, topic_id } => {
+                    let destination = Destination {
+                        chat_id,
+                        topic_id,
+                        silent: false,
+                    };
+                    send_markdown(&self.bot, destination, message)
+                        .await
+                        .map(|_| ())
+                        .map_err(Into::into)
+                }
+                EscalationAction::Pushover {
+                    ref token,
+                    ref user,
+                    priority,
+                } => channels::pushover(
+                    &self.http,
+                    token,
+                    user,
+                    priority,
+                    "mostro-watchdog health",
+                    plain,
+                )
+                .await
+                .map_err(Into::into),
+                EscalationAction::Webhook { ref url } => {
+                    let payload = serde_json::json!({ "text": plain });
+                    channels::webhook(&self.http, url, &payload)
+                        .await
+                        .map_err(Into::into)
+                }
+                // Rejected when the config is loaded
+                EscalationAction::Resend => continue,
+            };
+            let channel = match *action {
+                EscalationAction::Pushover { .. } => Some("pushover"),
+                EscalationAction::Webhook { .. } => Some("webhook"),
+                // Telegram deliveries are counted by the send helpers
+                _ => None,
+            };
+            if let Some(channel) = channel {
+                record_delivery(channel, result.as_ref().err().map(ToString::to_string));
+            }
+            match result {
+                Ok(()) => return,
+                Err(e) => warn!("Degradation alert fallback failed: {}", e),
+            }
+        }
+        error!("Degradation alert could not be delivered on any channel");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditions() {
+        let healthy = Conditions::default();
+        assert!(!healthy.is_degraded());
+
+        let degraded = Conditions {
+            relays_down: true,
+            queue_backlog: None,
+            store_error: Some("attempt to write a readonly database".into()),
+        };
+        assert!(degraded.is_degraded());
+        assert_eq!(degraded.keys(), ["relays", "store"]);
+        assert_eq!(
+            degraded.plain(),
+            "mostro-watchdog degraded: all relays disconnected; \
+             dispute store not writable (attempt to write a readonly database)"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! `mostro-watchdog doctor [config]`: one-command triage for "why am I not getting
+//! alerts?".
+//!
+//! Checks Telegram (token and chat), then each relay: its NIP-11 document, the
+//! WebSocket connection (through its proxy, if any) and its latency, and whether it serves recent events from
+//! each Mostro pubkey. Prints a pass/fail line per check.
+
+use crate::cli::Overrides;
+use crate::clock;
+use crate::config::{self, NostrConfig};
+use crate::proxy;
+use nostr_sdk::prelude::*;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+
+/// How long each network check may take
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run every check, printing a report. Returns whether all of them passed.
+pub async fn run(path: &Path, overrides: &Overrides) -> bool {
+    let report = Report::new();
+    println!("🩺 mostro-watchdog doctor\n");
+
+    let config = match overrides.load(path) {
+        Ok(config) => config,
+        Err(e) => {
+            report.check("Config", Err(e.to_string()));
+            return false;
+        }
+    };
+    let mut passed = report.check("Config", Ok(path.display().to_string()));
+
+    let mut mostro_pubkeys = Vec::new();
+    for pubkey in &config.mostro.pubkey {
+        match config::parse_pubkey(pubkey) {
+            Ok(pubkey) => mostro_pubkeys.push(pubkey),
+            Err(e) => passed &= report.check("Mostro pubkey", Err(format!("{pubkey}: {e}"))),
+        }
+    }
+
+    println!("\nTelegram");
+    let bot = match proxy::bot(&config.telegram) {
+        Ok(bot) => bot,
+        Err(e) => {
+            report.check("Bot token", Err(e));
+            return false;
+        }
+    };
+    match bot.get_me().await {
+        Ok(me) => {
+            passed &= report.check("Bot token", Ok(format!("@{}", me.username())));
+            let chat = bot.get_chat(ChatId(config.telegram.chat_id)).await;
+            passed &= report.check(
+                "Alert chat",
+                chat.map(|chat| {
+                    let title = chat.title().unwrap_or("private chat");
+                    format!("{} ({})", title, config.telegram.chat_id)
+                })
+                .map_err(|e| format!("{} (is the bot a member of the chat?)", e)),
+            );
+        }
+        Err(e) => passed &= report.check("Bot token", Err(e.to_string())),
+    }
+
+    for relay in &config.nostr.relays {
+        println!("\n{}", relay);
+        let proxy = config.nostr.relay_proxy(relay);
+        if let Some(proxy) = proxy {
+            report.check("Proxy", Ok(proxy.to_string()));
+        }
+        let info = match proxy::http_client(reqwest::Client::builder(), proxy) {
+            Ok(http) => relay_info(&http, relay).await,
+            Err(e) => Err(e),
+        };
+        passed &= report.check("NIP-11", info);
+        passed &= check_relay(&report, &config.nostr, relay, &mostro_pubkeys).await;
+    }
+
+    println!();
+    if passed {
+        println!("{}", report.paint("All checks passed", GREEN));
+    } else {
+        println!("{}", report.paint("Some checks failed", RED));
+    }
+    passed
+}
+
+/// Connect to one relay and look for each Mostro pubkey's latest events
+async fn check_relay(
+    report: &Report,
+    nostr: &NostrConfig,
+    relay: &str,
+    mostro_pubkeys: &[PublicKey],
+) -> bool {
+    let client = Client::default();
+    let added = match proxy::relay_options(nostr, relay) {
+        Ok(options) => client
+            .pool()
+            .add_relay(relay, options)
+            .await
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = added {
+        return report.check("Connection", Err(e));
+    }
+
+    let started = Instant::now();
+    client.connect().await;
+    let connected = loop {
+        let status = match client.pool().relay(relay).await {
+            Ok(relay) => relay.status(),
+            Err(e) => break Err(e.to_string()),
+        };
+        if status == RelayStatus::Connected {
+            break Ok(format!("{} ms", started.elapsed().as_millis()));
+        }
+        if started.elapsed() > TIMEOUT {
+            break Err(format!(
+                "not connected after {}s ({})",
+                TIMEOUT.as_secs(),
+                status
+            ));
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    };
+    let mut passed = report.check("Connection", connected);
+
+    if !passed {
+        return false;
+    }
+    for pubkey in mostro_pubkeys {
+        let filter = Filter::new().author(*pubkey).limit(10);
+        let started = Instant::now();
+        let latest = match client.fetch_events(vec![filter], Some(TIMEOUT)).await {
+            Ok(events) => match events.into_iter().max_by_key(|event| event.created_at) {
+                Some(event) => Ok(format!(
+                    "kind {} from {} ago, fetched in {} ms",
+                    event.kind.as_u16(),
+                    age(event.created_at.as_u64()),
+                    started.elapsed().as_millis()
+                )),
+                None => Err("no events from the Mostro pubkey on this relay".to_string()),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        let name = if mostro_pubkeys.len() > 1 {
+            format!("Mostro {}", short_npub(pubkey))
+        } else {
+            "Mostro events".to_string()
+        };
+        passed &= report.check(&name, latest);
+    }
+    passed
+}
+
+/// Fetch the relay's NIP-11 document and summarize it
+async fn relay_info(http: &reqwest::Client, relay: &str) -> Result<String, String> {
+    let url = clock::nip11_url(relay).ok_or("not a ws:// or wss:// URL")?;
+    let response = http
+        .get(&url)
+        .header(reqwest::header::ACCEPT, "application/nostr+json")
+        .timeout(TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let info: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("invalid document: {}", e))?;
+
+    let name = info["name"].as_str().unwrap_or("unnamed");
+    Ok(match info["software"].as_str() {
+        Some(software) => format!("{} ({})", name, software),
+        None => name.to_string(),
+    })
+}
+
+/// `npub1abcd…wxyz`, to tell several Mostro pubkeys apart
+fn short_npub(pubkey: &PublicKey) -> String {
+    let npub = pubkey.to_bech32().unwrap_or_else(|_| pubkey.to_hex());
+    format!("{}…{}", &npub[..9], &npub[npub.len() - 4..])
+}
+
+/// Rough age of a Unix timestamp, e.g. `3h 12m`
+fn age(timestamp: u64) -> String {
+    let now = Timestamp::now().as_u64();
+    let secs = now.saturating_sub(timestamp);
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m", secs / 60),
+        3600..=86399 => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+        _ => format!("{}d {}h", secs / 86400, (secs % 86400) / 3600),
+    }
+}
+
+const GREEN: &str = "32";
+const RED: &str = "31";
+
+/// Prints check results, colored when writing to a terminal
+struct Report {
+    color: bool,
+}
+
+impl Report {
+    fn new() -> Self {
+        Self {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+
+    /// Print one check and return whether it passed
+    fn check(&self, name: &str, result: Result<String, String>) -> bool {
+        match result {
+            Ok(detail) => {
+                println!("  {} {:<14} {}", self.paint("✔ pass", GREEN), name, detail);
+                true
+            }
+            Err(problem) => {
+                println!("  {} {:<14} {}", self.paint("✘ FAIL", RED), name, problem);
+                false
+            }
+        }
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", color, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_age() {
+        let now = Timestamp::now().as_u64();
+        assert_eq!(age(now - 90), "1m");
+        assert_eq!(age(now - 600), "10m");
+        assert_eq!(age(now - 3 * 3600 - 720), "3h 12m");
+        assert_eq!(age(now - 2 * 86400 - 3600), "2d 1h");
+    }
+
+    #[test]
+    fn test_paint() {
+        let report = Report { color: true };
+        assert_eq!(report.paint("ok", GREEN), "\x1b[32mok\x1b[0m");
+        let report = Report { color: false };
+        assert_eq!(report.paint("ok", GREEN), "ok");
+    }
+}
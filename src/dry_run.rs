@@ -0,0 +1,55 @@
+//! `--dry-run`: everything runs as usual (subscriptions, rendering, escalation
+//! timers), but outgoing alerts are logged instead of sent to Telegram, Pushover,
+//! webhooks or Alertmanager, and the dispute store is a copy of the real one
+//! (`disputes.dry-run.db`), so the next live run still alerts on what was only logged.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use teloxide::types::Message;
+use tracing::info;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// In dry-run mode, log what would have been delivered and return `true` so the
+/// caller skips the real delivery
+pub fn intercepted(channel: &str, action: &str, content: &str) -> bool {
+    if !is_enabled() {
+        return false;
+    }
+    info!("🧪 [dry run] Would {} to {}:\n{}", action, channel, content);
+    true
+}
+
+/// Stand-in for the message Telegram returns, so callers track it as if it was sent
+pub fn message(chat_id: i64, text: &str) -> Message {
+    let date = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    serde_json::from_value(serde_json::json!({
+        "message_id": 0,
+        "date": date,
+        "chat": { "id": chat_id, "type": "supergroup", "title": "dry run" },
+        "text": text,
+    }))
+    .expect("dry-run message is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message() {
+        let message = message(-100123, "🚨 *NEW DISPUTE*");
+        assert_eq!(message.chat.id.0, -100123);
+        assert_eq!(message.text(), Some("🚨 *NEW DISPUTE*"));
+    }
+}
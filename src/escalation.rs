@@ -0,0 +1,339 @@
+//! Escalation of unacknowledged new-dispute alerts.
+//!
+//! A new dispute is considered acknowledged once it moves past `initiated`
+//! (taken by a solver, resolved or canceled). Until then, the configured steps
+//! run in order of their delay: re-send to the alert chat, notify another
+//! Telegram chat, or reach admins outside Telegram via Pushover or a webhook
+//! (e.g. an SMS gateway).
+
+use crate::channels;
+use crate::config::{EscalationAction, EscalationStep, RedactMode};
+use crate::lru::Lru;
+use crate::redact;
+use crate::templates::Templates;
+use crate::{record_delivery, send_markdown, Destination};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use teloxide::Bot;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+/// A new-dispute alert waiting for acknowledgement
+struct PendingAlert {
+    /// Rendered alert text (MarkdownV2)
+    message: String,
+    /// Dispute id as redacted in `message`, for the plain-text channels
+    shown_id: String,
+    since: Instant,
+    /// Index of the next step to run
+    next_step: usize,
+}
+
+/// A step that became due for a pending alert
+struct DueStep {
+    dispute_id: String,
+    shown_id: String,
+    message: String,
+    step: EscalationStep,
+    minutes: u64,
+}
+
+pub struct Escalation {
+    /// Steps sorted by delay
+    steps: Vec<EscalationStep>,
+    /// Unacknowledged alerts by dispute id; if more are open than fit, the
+    /// oldest stop escalating
+    pending: Mutex<Lru<String, PendingAlert>>,
+    http: reqwest::Client,
+}
+
+impl Escalation {
+    pub fn new(mut steps: Vec<EscalationStep>, capacity: usize) -> Self {
+        steps.sort_by_key(|step| step.after_minutes);
+        Self {
+            steps,
+            pending: Mutex::new(Lru::new("escalation", capacity)),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Start tracking a new-dispute alert (repeated alerts keep the original start time)
+    pub async fn track(&self, dispute_id: &str, message: &str, mode: RedactMode) {
+        self.pending
+            .lock()
+            .await
+            .get_or_insert_with(dispute_id.to_string(), || PendingAlert {
+                message: message.to_string(),
+                shown_id: redact::redact(dispute_id, mode),
+                since: Instant::now(),
+                next_step: 0,
+            });
+    }
+
+    /// Stop escalating a dispute
+    pub async fn acknowledge(&self, dispute_id: &str) {
+        if self.pending.lock().await.remove(dispute_id).is_some() {
+            info!("✋ Dispute {} acknowledged, escalation stopped", dispute_id);
+        }
+    }
+
+    /// Collect steps that are due at `now`, dropping alerts with no steps left
+    async fn take_due_steps(&self, now: Instant) -> Vec<DueStep> {
+        let mut due = Vec::new();
+        let mut pending = self.pending.lock().await;
+
+        pending.retain(|dispute_id, alert| {
+            let elapsed = now.saturating_duration_since(alert.since);
+            while let Some(step) = self.steps.get(alert.next_step) {
+                if elapsed < Duration::from_secs(step.after_minutes * 60) {
+                    break;
+                }
+                due.push(DueStep {
+                    dispute_id: dispute_id.clone(),
+                    shown_id: alert.shown_id.clone(),
+                    message: alert.message.clone(),
+                    step: step.clone(),
+                    minutes: elapsed.as_secs() / 60,
+                });
+                alert.next_step += 1;
+            }
+            alert.next_step < self.steps.len()
+        });
+
+        due
+    }
+
+    /// Periodically run due escalation steps
+    pub async fn run(
+        self: Arc<Self>,
+        bot: Bot,
+        alert_destination: Destination,
+        templates: Arc<Templates>,
+    ) {
+        let mut interval = tokio::time::interval(Duration::from_secs(30));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+
+            // Steps due together run concurrently, so one slow channel doesn't
+            // hold up the others
+            let mut steps = tokio::task::JoinSet::new();
+            for due in self.take_due_steps(Instant::now()).await {
+                warn!(
+                    "⏰ Escalating dispute {} after {} minutes",
+                    due.dispute_id, due.minutes
+                );
+                let escalation = self.clone();
+                let bot = bot.clone();
+                let templates = templates.clone();
+                steps.spawn(async move {
+                    let result = escalation
+                        .execute(&due, &bot, alert_destination, &templates)
+                        .await;
+                    if let Err(e) = result {
+                        error!(
+                            "Failed to run escalation step for dispute {}: {}",
+                            due.dispute_id, e
+                        );
+                    }
+                });
+            }
+            while steps.join_next().await.is_some() {}
+        }
+    }
+
+    #[tracing::instrument(name = "escalation_step", skip_all, fields(dispute_id = %due.dispute_id))]
+    async fn execute(
+        &self,
+        due: &DueStep,
+        bot: &Bot,
+        alert_destination: Destination,
+        templates: &Templates,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut context = tera::Context::new();
+        context.insert("minutes", &due.minutes);
+        context.insert("alert", &templates.embeddable(&due.message));
+        let plain = plain_text(due);
+
+        let channel = match due.step.action {
+            EscalationAction::Pushover { .. } => Some("pushover"),
+            EscalationAction::Webhook { .. } => Some("webhook"),
+            // Telegram deliveries are counted by the send helpers
+            _ => None,
+        };
+        let result = self
+            .deliver(due, &context, &plain, bot, alert_destination, templates)
+            .await;
+        if let Some(channel) = channel {
+            record_delivery(channel, result.as_ref().err().map(ToString::to_string));
+        }
+        result
+    }
+
+    async fn deliver(
+        &self,
+        due: &DueStep,
+        context: &tera::Context,
+        plain: &str,
+        bot: &Bot,
+        alert_destination: Destination,
+        templates: &Templates,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match due.step.action {
+            EscalationAction::Resend => {
+                let text = templates.render("escalation", context);
+                channels::timed(send_markdown(bot, alert_destination, &text)).await?;
+            }
+            EscalationAction::Telegram { chat_id, topic_id } => {
+                let text = templates.render("escalation", context);
+                let destination = Destination {
+                    chat_id,
+                    topic_id,
+                    silent: false,
+                };
+                channels::timed(send_markdown(bot, destination, &text)).await?;
+            }
+            EscalationAction::Pushover {
+                ref token,
+                ref user,
+                priority,
+            } => {
+                channels::pushover(
+                    &self.http,
+                    token,
+                    user,
+                    priority,
+                    "Unacknowledged Mostro dispute",
+                    plain,
+                )
+                .await?;
+            }
+            EscalationAction::Webhook { ref url } => {
+                channels::webhook(&self.http, url, &webhook_payload(due, plain)).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Alert text for Pushover and webhooks
+fn plain_text(due: &DueStep) -> String {
+    format!(
+        "Dispute {} has not been taken for {} minutes",
+        due.shown_id, due.minutes
+    )
+}
+
+/// JSON body posted to escalation webhooks
+fn webhook_payload(due: &DueStep, plain: &str) -> serde_json::Value {
+    serde_json::json!({
+        "dispute_id": due.shown_id,
+        "minutes": due.minutes,
+        "text": plain,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EscalationConfig;
+
+    fn escalation() -> Escalation {
+        let config: EscalationConfig = toml::from_str(
+            r#"
+            [[steps]]
+            after_minutes = 30
+            action = "telegram"
+            chat_id = -100987654321
+
+            [[steps]]
+            after_minutes = 10
+            action = "resend"
+
+            [[steps]]
+            after_minutes = 60
+            action = "webhook"
+            url = "https://sms.example/send"
+            "#,
+        )
+        .unwrap();
+        Escalation::new(config.steps, crate::lru::DEFAULT_CAPACITY)
+    }
+
+    #[tokio::test]
+    async fn test_steps_run_in_order() {
+        let escalation = escalation();
+        escalation
+            .track("dispute-1", "alert", RedactMode::Off)
+            .await;
+        let start = Instant::now();
+
+        assert!(escalation.take_due_steps(start).await.is_empty());
+
+        let due = escalation
+            .take_due_steps(start + Duration::from_secs(11 * 60))
+            .await;
+        assert_eq!(due.len(), 1);
+        assert!(matches!(due[0].step.action, EscalationAction::Resend));
+        assert_eq!(due[0].message, "alert");
+
+        // Catching up runs every overdue step once
+        let due = escalation
+            .take_due_steps(start + Duration::from_secs(61 * 60))
+            .await;
+        assert_eq!(due.len(), 2);
+        assert!(matches!(
+            due[0].step.action,
+            EscalationAction::Telegram {
+                chat_id: -100987654321,
+                topic_id: None
+            }
+        ));
+        assert!(matches!(
+            due[1].step.action,
+            EscalationAction::Webhook { .. }
+        ));
+
+        // All steps done: the alert is no longer tracked
+        assert!(escalation.pending.lock().await.get("dispute-1").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_acknowledge_stops_escalation() {
+        let escalation = escalation();
+        escalation
+            .track("dispute-1", "alert", RedactMode::Off)
+            .await;
+        escalation.acknowledge("dispute-1").await;
+
+        let due = escalation
+            .take_due_steps(Instant::now() + Duration::from_secs(120 * 60))
+            .await;
+        assert!(due.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_redacted_payload() {
+        const DISPUTE_ID: &str = "8c3e5f2a-1b4d-4e6f-9a7b-2c1d3e4f5a6b";
+        let escalation = escalation();
+        escalation
+            .track(DISPUTE_ID, "alert", RedactMode::Truncate)
+            .await;
+
+        let due = escalation
+            .take_due_steps(Instant::now() + Duration::from_secs(61 * 60))
+            .await;
+        let webhook = due
+            .iter()
+            .find(|due| matches!(due.step.action, EscalationAction::Webhook { .. }))
+            .unwrap();
+        // Acknowledging still needs the full id
+        assert_eq!(webhook.dispute_id, DISPUTE_ID);
+
+        let payload = webhook_payload(webhook, &plain_text(webhook)).to_string();
+        assert!(payload.contains("8c3e5f…4f5a6b"), "{payload}");
+        assert!(!payload.contains(DISPUTE_ID), "{payload}");
+    }
+}
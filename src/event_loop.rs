@@ -0,0 +1,73 @@
+//! Watchdog for the Nostr event loop.
+//!
+//! Every relay message passes through `handle_notifications` on the main task,
+//! which marks its progress here. A quiet day brings no dispute events, so a check
+//! pings a connected relay to put a message through the loop; if the loop still
+//! makes no progress for `health.event_loop_stall_timeout` while relays report being
+//! connected, it is stuck. The check then has `main` start a new loop and
+//! reconnects those relays, which resubscribe since the last dispute they
+//! delivered.
+
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// How often the loop is ticked and checked
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+static LAST_PROGRESS: LazyLock<Mutex<Instant>> = LazyLock::new(|| Mutex::new(Instant::now()));
+
+static RESTART: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Report progress, once per notification handled
+pub fn beat() {
+    *LAST_PROGRESS.lock().expect("event loop lock poisoned") = Instant::now();
+}
+
+/// Time since the loop last handled a notification
+pub fn idle() -> Duration {
+    LAST_PROGRESS
+        .lock()
+        .expect("event loop lock poisoned")
+        .elapsed()
+}
+
+/// Whether a loop idle for `idle` is stuck, with `timeout` 0 never
+pub fn is_stalled(idle: Duration, timeout: Duration) -> bool {
+    !timeout.is_zero() && idle > timeout
+}
+
+/// Ask `main` to drop the running loop and start a new one
+pub fn request_restart() {
+    beat();
+    RESTART.notify_one();
+}
+
+/// Resolves once a restart was requested
+pub async fn restart_requested() {
+    RESTART.notified().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_stalled() {
+        let timeout = Duration::from_secs(120);
+        assert!(!is_stalled(Duration::from_secs(30), timeout));
+        assert!(!is_stalled(timeout, timeout));
+        assert!(is_stalled(Duration::from_secs(121), timeout));
+        assert!(!is_stalled(Duration::from_secs(3600), Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_restart_request_is_kept() {
+        // Requested before anyone waits, as when the loop is stuck in a handler
+        request_restart();
+        tokio::time::timeout(Duration::from_secs(1), restart_requested())
+            .await
+            .expect("restart request lost");
+        assert!(idle() < Duration::from_secs(1));
+    }
+}
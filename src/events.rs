@@ -0,0 +1,125 @@
+//! Live feed of processed dispute and health events, streamed by `GET /events`.
+//!
+//! Like the stats counters, the feed is process-wide so alert sites can publish
+//! without threading a sender through. The last few events are kept so the
+//! dashboard can show recent alerts on load.
+
+use crate::config::AlertSeverity;
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before a slow client starts missing them
+const CAPACITY: usize = 256;
+
+static EVENTS: LazyLock<broadcast::Sender<LiveEvent>> =
+    LazyLock::new(|| broadcast::channel(CAPACITY).0);
+
+/// Events kept for `GET /events/recent`
+const RECENT: usize = 50;
+
+static RECENT_EVENTS: LazyLock<Mutex<VecDeque<LiveEvent>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(RECENT)));
+
+/// One published event, already serialized
+#[derive(Debug, Clone)]
+pub struct LiveEvent {
+    /// `dispute` or `health`, used as the SSE event name
+    pub kind: &'static str,
+    pub json: String,
+}
+
+pub fn subscribe() -> broadcast::Receiver<LiveEvent> {
+    EVENTS.subscribe()
+}
+
+/// The most recently published events, oldest first
+pub fn recent() -> Vec<LiveEvent> {
+    RECENT_EVENTS
+        .lock()
+        .expect("recent events lock")
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// Publish a processed dispute event
+pub fn publish_dispute(dispute_id: &str, status: &str, severity: AlertSeverity, initiator: &str) {
+    publish(
+        "dispute",
+        serde_json::json!({
+            "type": "dispute",
+            "timestamp": now(),
+            "dispute_id": dispute_id,
+            "status": status,
+            "severity": severity.as_str(),
+            "initiator": initiator,
+        }),
+    );
+}
+
+/// Publish a health alert or notice with the values its template was rendered from
+pub fn publish_health(name: &str, context: &tera::Context) {
+    publish(
+        "health",
+        serde_json::json!({
+            "type": "health",
+            "timestamp": now(),
+            "name": name,
+            "data": context.clone().into_json(),
+        }),
+    );
+}
+
+fn publish(kind: &'static str, value: serde_json::Value) {
+    let event = LiveEvent {
+        kind,
+        json: value.to_string(),
+    };
+    {
+        let mut recent = RECENT_EVENTS.lock().expect("recent events lock");
+        if recent.len() == RECENT {
+            recent.pop_front();
+        }
+        recent.push_back(event.clone());
+    }
+    // Only fails when nobody is subscribed
+    let _ = EVENTS.send(event);
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish() {
+        let mut events = subscribe();
+        publish_dispute("abc", "initiated", AlertSeverity::Critical, "buyer");
+        let mut context = tera::Context::new();
+        context.insert("failed_count", &2);
+        publish_health("relay_alert", &context);
+
+        let dispute = events.recv().await.unwrap();
+        assert_eq!(dispute.kind, "dispute");
+        let json: serde_json::Value = serde_json::from_str(&dispute.json).unwrap();
+        assert_eq!(json["dispute_id"], "abc");
+        assert_eq!(json["severity"], "critical");
+
+        let health = events.recv().await.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&health.json).unwrap();
+        assert_eq!(json["name"], "relay_alert");
+        assert_eq!(json["data"]["failed_count"], 2);
+
+        let recent = recent();
+        assert!(recent.len() <= RECENT);
+        assert!(recent.iter().any(|event| event.json.contains("\"abc\"")));
+    }
+}
@@ -0,0 +1,156 @@
+//! Primary and secondary relays, for hosts that can't keep every relay connected.
+//!
+//! Relays in `nostr.secondary_relays` start on standby. When fewer than
+//! `nostr.target_relays` of the active relays are connected, the relay check
+//! promotes the next secondary; once the primaries alone have met the target for
+//! `DEMOTE_AFTER`, the promoted secondaries are demoted back to standby, the last
+//! promoted first.
+
+use crate::config::NostrConfig;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How long the primaries must meet the target before a secondary is demoted,
+/// so a flapping primary doesn't churn connections
+pub const DEMOTE_AFTER: Duration = Duration::from_secs(300);
+
+/// A connection change to make
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Connect this standby relay
+    Promote(String),
+    /// Disconnect this promoted relay
+    Demote(String),
+}
+
+/// Failover state, driven by the relay check
+#[derive(Debug)]
+pub struct Failover {
+    target: usize,
+    primaries: Vec<String>,
+    secondaries: Vec<String>,
+    /// Promoted secondaries, in promotion order, with when they were promoted
+    promoted: Vec<(String, Instant)>,
+    /// Time a promoted relay gets to connect before it counts as down
+    grace: Duration,
+    /// Since when the primaries alone have met the target
+    satisfied_since: Option<Instant>,
+}
+
+impl Failover {
+    /// Failover state, if any secondary relays are configured
+    pub fn new(nostr: &NostrConfig, grace: Duration) -> Option<Self> {
+        if nostr.secondary_relays.is_empty() {
+            return None;
+        }
+        Some(Self {
+            target: nostr.target_relays(),
+            primaries: nostr.primary_relays(),
+            secondaries: nostr.secondary_relays.clone(),
+            promoted: Vec::new(),
+            grace,
+            satisfied_since: None,
+        })
+    }
+
+    /// Secondary relays not currently promoted
+    pub fn standby(&self) -> Vec<String> {
+        self.secondaries
+            .iter()
+            .filter(|relay| !self.is_promoted(relay))
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `relay` is a promoted secondary
+    pub fn is_promoted(&self, relay: &str) -> bool {
+        self.promoted.iter().any(|(promoted, _)| promoted == relay)
+    }
+
+    /// The next change after a relay check that found `failed` relays down, one
+    /// at a time so each promotion gets its chance to connect
+    pub fn step(&mut self, failed: &[String], now: Instant) -> Option<Step> {
+        let primaries_up = self
+            .primaries
+            .iter()
+            .filter(|relay| !failed.contains(relay))
+            .count();
+        let promoted_up = self
+            .promoted
+            .iter()
+            .filter(|(relay, at)| !failed.contains(relay) || now.duration_since(*at) < self.grace)
+            .count();
+
+        if primaries_up + promoted_up < self.target {
+            self.satisfied_since = None;
+            let relay = self.standby().into_iter().next()?;
+            self.promoted.push((relay.clone(), now));
+            return Some(Step::Promote(relay));
+        }
+        if primaries_up < self.target || self.promoted.is_empty() {
+            self.satisfied_since = None;
+            return None;
+        }
+        let since = *self.satisfied_since.get_or_insert(now);
+        if now.duration_since(since) < DEMOTE_AFTER {
+            return None;
+        }
+        // Each demotion waits its own turn
+        self.satisfied_since = Some(now);
+        let (relay, _) = self.promoted.pop()?;
+        Some(Step::Demote(relay))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRACE: Duration = Duration::from_secs(10);
+
+    fn failover() -> Failover {
+        let nostr: NostrConfig = toml::from_str(
+            r#"
+            relays = ["wss://a.example", "wss://b.example", "wss://c.example", "wss://d.example"]
+            secondary_relays = ["wss://c.example", "wss://d.example"]
+            "#,
+        )
+        .unwrap();
+        Failover::new(&nostr, GRACE).unwrap()
+    }
+
+    #[test]
+    fn test_promotes_and_demotes() {
+        let mut failover = failover();
+        let now = Instant::now();
+        assert_eq!(failover.standby(), ["wss://c.example", "wss://d.example"]);
+        assert_eq!(failover.step(&[], now), None);
+
+        let failed = ["wss://a.example".to_string(), "wss://c.example".to_string()];
+        assert_eq!(
+            failover.step(&failed, now),
+            Some(Step::Promote("wss://c.example".into()))
+        );
+        // Still connecting
+        assert_eq!(failover.step(&failed, now + GRACE / 2), None);
+        assert_eq!(
+            failover.step(&failed, now + GRACE),
+            Some(Step::Promote("wss://d.example".into()))
+        );
+        assert!(failover.standby().is_empty());
+
+        // The primary is back, but the secondaries are only demoted after a while
+        let back = now + GRACE * 2;
+        assert_eq!(failover.step(&[], back), None);
+        assert_eq!(
+            failover.step(&[], back + DEMOTE_AFTER),
+            Some(Step::Demote("wss://d.example".into()))
+        );
+        assert_eq!(failover.step(&[], back + DEMOTE_AFTER), None);
+        assert_eq!(
+            failover.step(&[], back + DEMOTE_AFTER * 2),
+            Some(Step::Demote("wss://c.example".into()))
+        );
+        assert_eq!(failover.step(&[], back + DEMOTE_AFTER * 3), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! Fallback relays, used only while every primary relay is down.
+//!
+//! When all relays with the disputes role have been disconnected for
+//! `nostr.fallback_after` minutes, the fallback relays are added to the pool and
+//! subscribed to dispute events from when the outage began. As soon as a primary
+//! relay is back they are unsubscribed and removed again.
+
+use crate::config::NostrConfig;
+use nostr_sdk::prelude::*;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+/// A change of fallback state worth reporting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// The fallback relays were subscribed after the primaries were down this long
+    Engaged { down_for: Duration },
+    /// A primary relay is back and the fallback relays were released
+    Released,
+}
+
+/// Fallback relay state, driven by the relay check
+#[derive(Debug)]
+pub struct Fallback {
+    relays: Vec<String>,
+    after: Duration,
+    filter: Filter,
+    /// When every primary relay was first seen down
+    down_since: Option<(Instant, Timestamp)>,
+    subscription: Option<SubscriptionId>,
+}
+
+impl Fallback {
+    /// Fallback for `filter`, if any fallback relays are configured
+    pub fn new(nostr: &NostrConfig, filter: Filter) -> Option<Self> {
+        if nostr.fallback_relays.is_empty() {
+            return None;
+        }
+        Some(Self {
+            relays: nostr.fallback_relays.clone(),
+            after: Duration::from_secs(nostr.fallback_after * 60),
+            filter,
+            down_since: None,
+            subscription: None,
+        })
+    }
+
+    /// The fallback relays
+    pub fn relays(&self) -> &[String] {
+        &self.relays
+    }
+
+    /// Engage or release the fallback relays after a relay check that found every
+    /// primary relay down, or not
+    pub async fn update(
+        &mut self,
+        client: &Client,
+        nostr: &NostrConfig,
+        primaries_down: bool,
+        now: Instant,
+    ) -> Option<Change> {
+        if !primaries_down {
+            self.down_since = None;
+            let subscription = self.subscription.take()?;
+            client.unsubscribe(subscription).await;
+            for relay in &self.relays {
+                if let Err(e) = client.remove_relay(relay).await {
+                    warn!("Failed to remove fallback relay {}: {}", relay, e);
+                }
+            }
+            return Some(Change::Released);
+        }
+
+        let (down_at, down_timestamp) = *self
+            .down_since
+            .get_or_insert_with(|| (now, Timestamp::now()));
+        if self.subscription.is_some() || now.duration_since(down_at) < self.after {
+            return None;
+        }
+
+        for relay in &self.relays {
+            // The relay check doesn't look after fallback relays, so the client
+            // reconnects them itself
+            let added = match crate::relay_options(nostr, relay, false) {
+                Ok(options) => client
+                    .pool()
+                    .add_relay(relay, options)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = added {
+                warn!("Failed to add fallback relay {}: {}", relay, e);
+                continue;
+            }
+            if let Err(e) = client.connect_relay(relay).await {
+                warn!("Failed to connect fallback relay {}: {}", relay, e);
+            }
+        }
+        // Picks up where the primaries left off; a failure is retried on the next check
+        let filter = self.filter.clone().since(down_timestamp);
+        match client
+            .subscribe_to(self.relays.clone(), vec![filter], None)
+            .await
+        {
+            Ok(output) => {
+                self.subscription = Some(output.val);
+                Some(Change::Engaged {
+                    down_for: now.duration_since(down_at),
+                })
+            }
+            Err(e) => {
+                warn!("Failed to subscribe to fallback relays: {}", e);
+                None
+            }
+        }
+    }
+}
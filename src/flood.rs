@@ -0,0 +1,98 @@
+//! Flood protection for dispute alerts.
+//!
+//! With `alerts.flood_limit` set, the watchdog counts the dispute alerts it is
+//! about to send. Once more than the limit would go out within a minute, further
+//! alerts are held back and counted instead, and one summary (the `flood`
+//! template) is sent per minute until the rate drops back under the limit. This
+//! keeps an event storm within Telegram's limits and the admins' attention.
+
+use crate::templates::Templates;
+use crate::{send_markdown, Destination};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use teloxide::Bot;
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+/// Span the limit applies to, and how often held alerts are summarized
+pub const WINDOW: Duration = Duration::from_secs(60);
+
+/// Recent alert attempts and those held back
+#[derive(Default)]
+pub struct Flood {
+    /// When each alert within the last `WINDOW` was attempted, sent or not
+    attempts: Mutex<VecDeque<Instant>>,
+    /// Alerts held back since the last summary
+    held: AtomicUsize,
+}
+
+impl Flood {
+    /// Count an alert about to be sent at `now`. Returns false, holding it back,
+    /// when more than `limit` were attempted within the window (0 = no limit).
+    pub fn admit(&self, limit: usize, now: Instant) -> bool {
+        let mut attempts = self.attempts.lock().expect("flood lock poisoned");
+        while attempts
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= WINDOW)
+        {
+            attempts.pop_front();
+        }
+        attempts.push_back(now);
+        if limit == 0 || attempts.len() <= limit {
+            return true;
+        }
+        self.held.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    /// Alerts held back since the last call
+    pub fn take_held(&self) -> usize {
+        self.held.swap(0, Ordering::Relaxed)
+    }
+}
+
+/// Send a summary of the alerts held back, once per window
+pub async fn run(flood: Arc<Flood>, bot: Bot, destination: Destination, templates: Arc<Templates>) {
+    let mut interval = tokio::time::interval(WINDOW);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let held = flood.take_held();
+        if held == 0 {
+            continue;
+        }
+        info!("🌊 {} dispute alerts held back in the last minute", held);
+        let mut context = tera::Context::new();
+        context.insert("count", &held);
+        let message = templates.render("flood", &context);
+        if let Err(e) = send_markdown(&bot, destination, &message).await {
+            warn!("Failed to send flood summary: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admit() {
+        let flood = Flood::default();
+        let start = Instant::now();
+        assert!(flood.admit(2, start));
+        assert!(flood.admit(2, start));
+        assert!(!flood.admit(2, start + Duration::from_secs(1)));
+        assert!(!flood.admit(2, start + Duration::from_secs(2)));
+        assert_eq!(flood.take_held(), 2);
+        assert_eq!(flood.take_held(), 0);
+
+        // Held alerts count toward the rate, so the storm has to calm down first
+        assert!(!flood.admit(2, start + WINDOW));
+        assert!(flood.admit(2, start + WINDOW * 2));
+
+        // No limit
+        assert!(flood.admit(0, start + WINDOW * 2));
+    }
+}
@@ -0,0 +1,99 @@
+//! Standard `grpc.health.v1.Health` service, for environments that probe with
+//! grpc_health_probe or Kubernetes gRPC probes instead of HTTP.
+//!
+//! The overall service (`""`) and `mostro-watchdog` report `SERVING` while the
+//! watchdog is ready, as defined by `/ready`.
+
+use crate::config::HealthConfig;
+use crate::{shutdown, HealthMonitor};
+use std::sync::Arc;
+use std::time::Duration;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::info;
+
+/// Name clients can pass instead of the empty overall service
+pub const SERVICE_NAME: &str = "mostro-watchdog";
+
+/// Seconds between readiness refreshes
+const REFRESH_INTERVAL: u64 = 5;
+
+/// Serve the health service on `http_bind:grpc_port` until shutdown
+pub async fn serve(
+    health_monitor: Arc<HealthMonitor>,
+    config: &HealthConfig,
+    port: u16,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let addr = format!("{}:{}", config.http_bind, port).parse()?;
+    let (reporter, service) = tonic_health::server::health_reporter();
+    tokio::spawn(report_readiness(health_monitor, reporter));
+
+    info!("🩺 gRPC health service listening on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_shutdown(addr, shutdown::requested())
+        .await?;
+    Ok(())
+}
+
+/// Mirror readiness into the reporter, which streams changes to `Watch` callers
+async fn report_readiness(health_monitor: Arc<HealthMonitor>, mut reporter: HealthReporter) {
+    let mut interval = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL));
+    loop {
+        interval.tick().await;
+        let status = serving_status(health_monitor.readiness().await.ready);
+        for service in ["", SERVICE_NAME] {
+            reporter.set_service_status(service, status).await;
+        }
+    }
+}
+
+fn serving_status(ready: bool) -> ServingStatus {
+    if ready {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic_health::pb::health_client::HealthClient;
+    use tonic_health::pb::HealthCheckRequest;
+
+    #[tokio::test]
+    async fn test_health_check() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let health_monitor = Arc::new(HealthMonitor::new());
+        let config = HealthConfig::default();
+        tokio::spawn(async move {
+            let _ = serve(health_monitor, &config, port).await;
+        });
+
+        let mut client = loop {
+            match HealthClient::connect(format!("http://127.0.0.1:{port}")).await {
+                Ok(client) => break client,
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        };
+        // Not ready without a Nostr client attached
+        let response = loop {
+            let request = HealthCheckRequest {
+                service: SERVICE_NAME.to_string(),
+            };
+            match client.check(request).await {
+                Ok(response) => break response.into_inner(),
+                // Unknown until the first refresh
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        };
+        assert_eq!(
+            response.status,
+            tonic_health::pb::health_check_response::ServingStatus::NotServing as i32
+        );
+    }
+}
@@ -0,0 +1,996 @@
+//! HTTP endpoints: health status, liveness and readiness probes, the dispute API,
+//! the live event stream and the embedded dashboard.
+//! Served over HTTPS when `health.http_tls` is set and the `tls` feature is built.
+
+use crate::config::HealthConfig;
+use crate::db::StoredDispute;
+use crate::events;
+use crate::shutdown;
+use crate::silence;
+use crate::HealthMonitor;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Frame};
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, mpsc, OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, error, info, warn};
+
+/// Seconds between keep-alive comments on idle event streams
+const KEEPALIVE_INTERVAL: u64 = 15;
+
+type Body = BoxBody<Bytes, Infallible>;
+
+/// Single-page dashboard; it loads its data from the JSON endpoints
+const DASHBOARD: &str = include_str!("dashboard.html");
+
+/// Disputes returned per page unless `limit` is given
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+struct Server {
+    health_monitor: Arc<HealthMonitor>,
+    /// Bearer token required by every endpoint except `/live`
+    auth_token: Option<String>,
+    /// Free connection slots; accepting pauses while none are left
+    connections: Arc<Semaphore>,
+    /// Time a client gets to send request headers
+    header_timeout: Duration,
+    /// Open connections, which shutdown waits for
+    in_flight: shutdown::Tracker,
+}
+
+/// Serve HTTP requests until shutdown is requested or the listener can't be bound.
+/// Open connections finish their current request before closing.
+pub async fn serve(
+    health_monitor: Arc<HealthMonitor>,
+    config: &HealthConfig,
+    in_flight: shutdown::Tracker,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = Arc::new(Server {
+        health_monitor,
+        auth_token: config.http_auth_token.clone(),
+        connections: Arc::new(Semaphore::new(config.http_max_connections)),
+        header_timeout: Duration::from_secs(config.http_timeout),
+        in_flight,
+    });
+
+    #[cfg(unix)]
+    if let Some(ref path) = config.http_socket {
+        return serve_unix(server, path).await;
+    }
+
+    let bind = &config.http_bind;
+    #[cfg(feature = "tls")]
+    let acceptor = config
+        .http_tls
+        .as_ref()
+        .map(crate::tls::acceptor)
+        .transpose()
+        .map_err(|e| -> Box<dyn std::error::Error> { e })?;
+    #[cfg(feature = "tls")]
+    let scheme = if acceptor.is_some() { "https" } else { "http" };
+    #[cfg(not(feature = "tls"))]
+    let scheme = "http";
+
+    let addr = format!("{}:{}", bind, config.http_port);
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            server
+                .health_monitor
+                .record_component("http", Err(format!("bind {addr}: {e}")));
+            return Err(e.into());
+        }
+    };
+    info!(
+        "🌐 Health HTTP endpoint listening on {}://{}/health",
+        scheme, addr
+    );
+    if server.auth_token.is_none() && !is_loopback(bind) {
+        warn!(
+            "HTTP endpoint on {} is reachable from other hosts without authentication; \
+             set health.http_auth_token",
+            bind
+        );
+    }
+
+    while let Some(((stream, _), permit)) = server.next_connection(|| listener.accept()).await {
+        let server = server.clone();
+        #[cfg(feature = "tls")]
+        let acceptor = acceptor.clone();
+
+        tokio::spawn(async move {
+            #[cfg(feature = "tls")]
+            if let Some(acceptor) = acceptor {
+                let handshake =
+                    tokio::time::timeout(server.header_timeout, acceptor.accept(stream));
+                match handshake.await {
+                    Ok(Ok(stream)) => serve_connection(stream, server, permit).await,
+                    Ok(Err(e)) => debug!("TLS handshake failed: {}", e),
+                    Err(_) => debug!("TLS handshake timed out"),
+                }
+                return;
+            }
+            serve_connection(stream, server, permit).await;
+        });
+    }
+    info!("🌐 Health HTTP endpoint stopped accepting connections");
+    Ok(())
+}
+
+/// Serve plain HTTP on a Unix socket, for reverse proxies on the same host
+#[cfg(unix)]
+async fn serve_unix(
+    server: Arc<Server>,
+    path: &std::path::Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::FileTypeExt;
+
+    // A socket left behind by a previous run would make the bind fail
+    if std::fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+        std::fs::remove_file(path)?;
+    }
+    let listener = match bind_unix(path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            server
+                .health_monitor
+                .record_component("http", Err(format!("bind {}: {e}", path.display())));
+            return Err(e.into());
+        }
+    };
+    info!(
+        "🌐 Health HTTP endpoint listening on unix:{}",
+        path.display()
+    );
+
+    while let Some(((stream, _), permit)) = server.next_connection(|| listener.accept()).await {
+        tokio::spawn(serve_connection(stream, server.clone(), permit));
+    }
+    info!("🌐 Health HTTP endpoint stopped accepting connections");
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+/// Bind a Unix socket at `path` that is never reachable with a wider mode than
+/// 0660: it is bound in a private directory beside `path`, where no one else can
+/// connect, and moved into place once its mode is set
+#[cfg(unix)]
+fn bind_unix(path: &std::path::Path) -> std::io::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let name = path.file_name().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "no socket file name")
+    })?;
+    let private = path.with_file_name(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    std::fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let staged = private.join(name);
+    let result = tokio::net::UnixListener::bind(&staged).and_then(|listener| {
+        // Owner and group only, so the proxy can connect through a shared group
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o660))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&staged);
+    }
+    let _ = std::fs::remove_dir(&private);
+    result
+}
+
+/// Serve one connection, finishing the request in progress on shutdown. The
+/// connection slot is freed when it closes.
+async fn serve_connection<S>(io: S, server: Arc<Server>, _permit: OwnedSemaphorePermit)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let _guard = server.in_flight.guard();
+    let mut builder = Builder::new(TokioExecutor::new());
+    builder
+        .http1()
+        .timer(TokioTimer::new())
+        .header_read_timeout(server.header_timeout);
+    let service = service_fn(move |req: Request<hyper::body::Incoming>| {
+        let server = server.clone();
+        async move { Ok::<_, Infallible>(server.route(&req).await) }
+    });
+
+    let connection = builder.serve_connection(TokioIo::new(io), service);
+    tokio::pin!(connection);
+    let result = tokio::select! {
+        result = connection.as_mut() => result,
+        _ = shutdown::requested() => {
+            connection.as_mut().graceful_shutdown();
+            connection.await
+        }
+    };
+    if let Err(err) = result {
+        // Mostly clients that went away or timed out sending headers
+        debug!("HTTP connection ended with an error: {:?}", err);
+    }
+}
+
+impl Server {
+    /// Wait for a free connection slot and accept the next connection, or `None`
+    /// once shutdown is requested. Accept errors (e.g. out of file descriptors)
+    /// are logged and recorded, then retried after a short pause.
+    async fn next_connection<T, F>(
+        &self,
+        mut accept: impl FnMut() -> F,
+    ) -> Option<(T, OwnedSemaphorePermit)>
+    where
+        F: Future<Output = std::io::Result<T>>,
+    {
+        loop {
+            let permit = match self.connections.clone().try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    warn!("HTTP connection limit reached, waiting for a free slot");
+                    tokio::select! {
+                        permit = self.connections.clone().acquire_owned() => {
+                            permit.expect("connection semaphore is never closed")
+                        }
+                        _ = shutdown::requested() => return None,
+                    }
+                }
+            };
+            let result = tokio::select! {
+                result = accept() => result,
+                _ = shutdown::requested() => return None,
+            };
+            self.health_monitor.record_component(
+                "http",
+                result.as_ref().map(|_| ()).map_err(ToString::to_string),
+            );
+            match result {
+                Ok(conn) => return Some((conn, permit)),
+                Err(e) => {
+                    error!("Failed to accept HTTP connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    }
+
+    async fn route<B>(&self, req: &Request<B>) -> Response<Body> {
+        // Liveness stays open so probes don't need the token
+        if let Some(ref token) = self.auth_token {
+            if req.uri().path() != "/live" && !authorized(req, token) {
+                let mut response =
+                    error_response(StatusCode::UNAUTHORIZED, "missing or invalid bearer token");
+                response.headers_mut().insert(
+                    hyper::header::WWW_AUTHENTICATE,
+                    hyper::header::HeaderValue::from_static("Bearer"),
+                );
+                return response;
+            }
+        }
+
+        let query = query_params(req);
+        match req.uri().path() {
+            path @ ("/health" | "/health/details") => {
+                let verbose =
+                    path == "/health/details" || query.get("verbose").is_some_and(|v| v == "true");
+                let status_json = self.health_monitor.get_status_json(verbose).await;
+                json(StatusCode::OK, status_json)
+            }
+            "/live" => json(StatusCode::OK, r#"{"status":"alive"}"#.to_string()),
+            "/ready" => {
+                let readiness = self.health_monitor.readiness().await;
+                let status = if readiness.ready {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                };
+                json(
+                    status,
+                    serde_json::to_string(&readiness).expect("serializable"),
+                )
+            }
+            "/" | "/dashboard" => Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "text/html; charset=utf-8")
+                .body(full(DASHBOARD))
+                .expect("valid response"),
+            "/events" => match self.auth_token {
+                None => error_response(
+                    StatusCode::FORBIDDEN,
+                    "set health.http_auth_token to enable /events",
+                ),
+                Some(_) => event_stream(),
+            },
+            "/events/recent" => match self.auth_token {
+                None => error_response(
+                    StatusCode::FORBIDDEN,
+                    "set health.http_auth_token to enable /events/recent",
+                ),
+                Some(_) => {
+                    let recent = events::recent();
+                    let items: Vec<&str> = recent.iter().map(|event| event.json.as_str()).collect();
+                    json(StatusCode::OK, format!("[{}]", items.join(",")))
+                }
+            },
+            "/disputes" => match self.auth_token {
+                None => error_response(
+                    StatusCode::FORBIDDEN,
+                    "set health.http_auth_token to enable /disputes",
+                ),
+                Some(_) => self.disputes(&query).await,
+            },
+            "/config" => match self.auth_token {
+                None => error_response(
+                    StatusCode::FORBIDDEN,
+                    "set health.http_auth_token to enable /config",
+                ),
+                Some(_) => json(StatusCode::OK, self.health_monitor.config_json()),
+            },
+            "/silence" => match self.auth_token {
+                None => error_response(
+                    StatusCode::FORBIDDEN,
+                    "set health.http_auth_token to enable /silence",
+                ),
+                Some(_) => silence_route(req.method(), &query),
+            },
+            "/test-alert" => match self.auth_token {
+                None => error_response(
+                    StatusCode::FORBIDDEN,
+                    "set health.http_auth_token to enable /test-alert",
+                ),
+                Some(_) if req.method() != Method::POST => {
+                    error_response(StatusCode::METHOD_NOT_ALLOWED, "use POST")
+                }
+                Some(_) => self.test_alert().await,
+            },
+            _ => Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .body(full("Not Found"))
+                .expect("valid response"),
+        }
+    }
+
+    /// `POST /test-alert`: send a synthetic dispute alert to the dispute chat
+    async fn test_alert(&self) -> Response<Body> {
+        let Some(ctx) = self.health_monitor.alert_ctx.get() else {
+            return error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "alert pipeline not started yet",
+            );
+        };
+        match crate::send_test_alert(ctx).await {
+            Ok((dispute_id, message_id)) => {
+                let body = serde_json::json!({
+                    "status": "sent",
+                    "dispute_id": dispute_id,
+                    "message_id": message_id,
+                });
+                json(StatusCode::OK, body.to_string())
+            }
+            Err(e) => error_response(StatusCode::BAD_GATEWAY, &e),
+        }
+    }
+
+    /// `GET /disputes?status=&limit=&offset=`: tracked disputes, newest first
+    async fn disputes(&self, query: &HashMap<String, String>) -> Response<Body> {
+        let Some(store) = self.health_monitor.dispute_store.get() else {
+            return error_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "dispute store not open yet",
+            );
+        };
+        let Ok(page) = Page::from_query(query) else {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                "limit and offset must be non-negative integers",
+            );
+        };
+        let status = query.get("status").map(String::as_str);
+
+        let result = async {
+            let total = store.count(status).await?;
+            let disputes = store.list(status, page.limit, page.offset).await?;
+            Ok::<_, sqlx::Error>((total, disputes))
+        }
+        .await;
+        match result {
+            Ok((total, disputes)) => {
+                let body = serde_json::json!({
+                    "total": total,
+                    "limit": page.limit,
+                    "offset": page.offset,
+                    "disputes": disputes_json(&disputes, now()),
+                });
+                json(StatusCode::OK, body.to_string())
+            }
+            Err(e) => {
+                warn!("Failed to list disputes: {}", e);
+                error_response(StatusCode::INTERNAL_SERVER_ERROR, "failed to read disputes")
+            }
+        }
+    }
+}
+
+/// `POST /silence?duration=&categories=` mutes alerts, `DELETE /silence?categories=`
+/// lifts the silence and `GET /silence` lists active silences
+fn silence_route(method: &Method, query: &HashMap<String, String>) -> Response<Body> {
+    let categories =
+        match silence::Category::parse_list(query.get("categories").map(String::as_str)) {
+            Ok(categories) => categories,
+            Err(e) => return error_response(StatusCode::BAD_REQUEST, &e),
+        };
+    match *method {
+        Method::GET => {}
+        Method::POST => {
+            let Some(duration) = query.get("duration") else {
+                return error_response(StatusCode::BAD_REQUEST, "duration is required");
+            };
+            match silence::parse_duration(duration) {
+                Ok(duration) => {
+                    silence::silence(&categories, duration);
+                }
+                Err(e) => return error_response(StatusCode::BAD_REQUEST, &e),
+            }
+        }
+        Method::DELETE => silence::unsilence(&categories),
+        _ => return error_response(StatusCode::METHOD_NOT_ALLOWED, "use GET, POST or DELETE"),
+    }
+    let body = serde_json::json!({ "silenced": silence::active() });
+    json(StatusCode::OK, body.to_string())
+}
+
+/// `GET /events`: Server-Sent Events with every dispute and health event as JSON
+fn event_stream() -> Response<Body> {
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(forward_events(events::subscribe(), tx));
+    Response::builder()
+        .status(StatusCode::OK)
+        .header("Content-Type", "text/event-stream")
+        .header("Cache-Control", "no-cache")
+        .body(ChannelBody { rx }.boxed())
+        .expect("valid response")
+}
+
+/// Write published events to one client until it disconnects
+async fn forward_events(
+    mut events: broadcast::Receiver<events::LiveEvent>,
+    tx: mpsc::Sender<Bytes>,
+) {
+    let mut keepalive = tokio::time::interval(Duration::from_secs(KEEPALIVE_INTERVAL));
+    keepalive.tick().await;
+
+    loop {
+        let chunk = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => sse_event(&event),
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    format!(": {missed} events dropped\n\n")
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = keepalive.tick() => ": keepalive\n\n".to_string(),
+            // End the stream so graceful shutdown isn't held up by it
+            _ = shutdown::requested() => return,
+        };
+        // Fails once the client is gone and the body dropped
+        if tx.send(Bytes::from(chunk)).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn sse_event(event: &events::LiveEvent) -> String {
+    format!("event: {}\ndata: {}\n\n", event.kind, event.json)
+}
+
+/// Response body fed by a channel, for streaming responses
+struct ChannelBody {
+    rx: mpsc::Receiver<Bytes>,
+}
+
+impl hyper::body::Body for ChannelBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        self.rx
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|bytes| Ok(Frame::data(bytes))))
+    }
+}
+
+/// Pagination for the dispute list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Page {
+    limit: u32,
+    offset: u32,
+}
+
+impl Page {
+    fn from_query(query: &HashMap<String, String>) -> Result<Self, std::num::ParseIntError> {
+        let limit = match query.get("limit") {
+            Some(limit) => limit.parse::<u32>()?.min(MAX_PAGE_SIZE),
+            None => DEFAULT_PAGE_SIZE,
+        };
+        let offset = match query.get("offset") {
+            Some(offset) => offset.parse()?,
+            None => 0,
+        };
+        Ok(Self { limit, offset })
+    }
+}
+
+/// Disputes with their age in seconds
+fn disputes_json(disputes: &[StoredDispute], now: i64) -> Vec<serde_json::Value> {
+    disputes
+        .iter()
+        .map(|dispute| {
+            serde_json::json!({
+                "dispute_id": dispute.dispute_id,
+                "status": dispute.status,
+                "created_at": dispute.created_at,
+                "updated_at": dispute.updated_at,
+                "age_seconds": (now - dispute.created_at).max(0),
+            })
+        })
+        .collect()
+}
+
+/// Whether `req` carries `token` as a bearer token or, for the dashboard page a
+/// browser opens without headers, as the `token` query parameter
+fn authorized<B>(req: &Request<B>, token: &str) -> bool {
+    let bearer = req
+        .headers()
+        .get(hyper::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    let given = bearer.or_else(|| {
+        matches!(req.uri().path(), "/" | "/dashboard")
+            .then(|| query_params(req).remove("token"))
+            .flatten()
+    });
+    given.is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()))
+}
+
+/// Whether the bind address only accepts local connections
+fn is_loopback(bind: &str) -> bool {
+    bind == "localhost"
+        || bind
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Compare without returning early, so timing doesn't reveal the token
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Decoded query string parameters; the last value wins for repeated keys
+fn query_params<B>(req: &Request<B>) -> HashMap<String, String> {
+    url::form_urlencoded::parse(req.uri().query().unwrap_or_default().as_bytes())
+        .into_owned()
+        .collect()
+}
+
+fn json(status: StatusCode, body: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(full(body))
+        .expect("valid response")
+}
+
+fn full(body: impl Into<Bytes>) -> Body {
+    Full::new(body.into()).boxed()
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Body> {
+    json(status, serde_json::json!({ "error": message }).to_string())
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DisputeMessageStore;
+
+    async fn test_server(auth_token: Option<&str>) -> (Server, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = DisputeMessageStore::new(&dir.path().join("test.db"))
+            .await
+            .unwrap();
+        store.insert("d1", 1, -100, "initiated").await.unwrap();
+        store.insert("d2", 2, -100, "settled").await.unwrap();
+        store.insert("d3", 3, -100, "initiated").await.unwrap();
+
+        let health_monitor = Arc::new(HealthMonitor::new());
+        health_monitor.attach_dispute_store(Arc::new(store));
+        let config = HealthConfig::default();
+        let server = Server {
+            health_monitor,
+            auth_token: auth_token.map(String::from),
+            connections: Arc::new(Semaphore::new(config.http_max_connections)),
+            header_timeout: Duration::from_secs(config.http_timeout),
+            in_flight: shutdown::Tracker::new(),
+        };
+        (server, dir)
+    }
+
+    fn get(uri: &str, token: Option<&str>) -> Request<()> {
+        request(Method::GET, uri, token)
+    }
+
+    fn post(uri: &str, token: Option<&str>) -> Request<()> {
+        request(Method::POST, uri, token)
+    }
+
+    fn request(method: Method, uri: &str, token: Option<&str>) -> Request<()> {
+        let mut req = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            req = req.header("Authorization", format!("Bearer {token}"));
+        }
+        req.body(()).unwrap()
+    }
+
+    async fn body_json(response: Response<Body>) -> serde_json::Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_disputes_auth() {
+        let (server, _dir) = test_server(None).await;
+        let response = server.route(&get("/disputes", None)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let (server, _dir) = test_server(Some("secret")).await;
+        let response = server.route(&get("/disputes", None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = server.route(&get("/disputes", Some("wrong"))).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = server.route(&get("/disputes", Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_auth_token_protects_all_but_live() {
+        let (server, _dir) = test_server(Some("secret")).await;
+        for path in [
+            "/health",
+            "/health/details",
+            "/ready",
+            "/events/recent",
+            "/",
+            "/dashboard",
+            "/dashboard?token=wrong",
+        ] {
+            let response = server.route(&get(path, None)).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{path}");
+            assert_eq!(response.headers()["WWW-Authenticate"], "Bearer");
+        }
+        let response = server.route(&get("/health", Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = server.route(&get("/dashboard", Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        // Only the dashboard page takes the token from the query
+        let response = server.route(&get("/health?token=secret", None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = server.route(&get("/live", None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Without a token everything but the dispute and event APIs is open
+        let (server, _dir) = test_server(None).await;
+        let response = server.route(&get("/health", None)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_test_alert() {
+        let (server, _dir) = test_server(None).await;
+        let response = server.route(&post("/test-alert", None)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let (server, _dir) = test_server(Some("secret")).await;
+        let response = server.route(&post("/test-alert", None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = server.route(&get("/test-alert", Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        // No alert pipeline attached in tests
+        let response = server.route(&post("/test-alert", Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_silence() {
+        let (server, _dir) = test_server(None).await;
+        let response = server.route(&post("/silence?duration=1h", None)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let (server, _dir) = test_server(Some("secret")).await;
+        for uri in [
+            "/silence",
+            "/silence?duration=forever",
+            "/silence?duration=1h&categories=relays",
+        ] {
+            let response = server.route(&post(uri, Some("secret"))).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+
+        let response = server
+            .route(&post(
+                "/silence?duration=1h&categories=disputes",
+                Some("secret"),
+            ))
+            .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["silenced"]["disputes"].is_u64());
+
+        let request = request(
+            Method::DELETE,
+            "/silence?categories=disputes",
+            Some("secret"),
+        );
+        let response = server.route(&request).await;
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(body["silenced"].get("disputes").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_config() {
+        let (server, _dir) = test_server(None).await;
+        let response = server.route(&get("/config", None)).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let (server, _dir) = test_server(Some("secret")).await;
+        server.health_monitor.state().config =
+            serde_json::json!({ "telegram": { "bot_token": "***" } });
+        let response = server.route(&get("/config", Some("secret"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["telegram"]["bot_token"], "***");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_socket() {
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("http.sock");
+        let config = HealthConfig {
+            http_socket: Some(path.clone()),
+            ..HealthConfig::default()
+        };
+        tokio::spawn(async move {
+            let _ = serve(
+                Arc::new(HealthMonitor::new()),
+                &config,
+                shutdown::Tracker::new(),
+            )
+            .await;
+        });
+
+        let mut stream = loop {
+            match tokio::net::UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        stream
+            .write_all(b"GET /live HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(r#"{"status":"alive"}"#));
+
+        // Moved into place with its final mode, leaving nothing else behind
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o660);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_connection_limit() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("http.sock");
+        let config = HealthConfig {
+            http_socket: Some(path.clone()),
+            http_max_connections: 1,
+            ..HealthConfig::default()
+        };
+        tokio::spawn(async move {
+            let _ = serve(
+                Arc::new(HealthMonitor::new()),
+                &config,
+                shutdown::Tracker::new(),
+            )
+            .await;
+        });
+
+        // An idle client takes the only slot
+        let idle = loop {
+            match tokio::net::UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        let mut waiting = tokio::net::UnixStream::connect(&path).await.unwrap();
+        waiting
+            .write_all(b"GET /live HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        let read = tokio::time::timeout(
+            Duration::from_millis(200),
+            waiting.read_to_string(&mut response),
+        );
+        assert!(read.await.is_err(), "served beyond the connection limit");
+
+        // Served once the slot frees up
+        drop(idle);
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            waiting.read_to_string(&mut response),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+    }
+
+    #[tokio::test]
+    async fn test_dashboard() {
+        // A browser opens the page with the token in the query
+        let (server, _dir) = test_server(Some("secret")).await;
+        for path in ["/?token=secret", "/dashboard?token=secret"] {
+            let response = server.route(&get(path, None)).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(
+                response.headers()["Content-Type"],
+                "text/html; charset=utf-8"
+            );
+        }
+
+        events::publish_dispute(
+            "dashboard-test",
+            "initiated",
+            crate::config::AlertSeverity::Critical,
+            "buyer",
+        );
+        let response = server.route(&get("/events/recent", Some("secret"))).await;
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let recent: Vec<serde_json::Value> = serde_json::from_slice(&body).unwrap();
+        assert!(recent.iter().any(|e| e["dispute_id"] == "dashboard-test"));
+    }
+
+    #[tokio::test]
+    async fn test_event_stream() {
+        // Dispute ids and statuses are only streamed to token holders
+        let (server, _dir) = test_server(None).await;
+        for path in ["/events", "/events/recent"] {
+            let response = server.route(&get(path, None)).await;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{path}");
+        }
+
+        let (server, _dir) = test_server(Some("secret")).await;
+        let response = server.route(&get("/events", None)).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = server.route(&get("/events", Some("secret"))).await;
+        assert_eq!(response.headers()["Content-Type"], "text/event-stream");
+
+        let mut body = response.into_body();
+        // Give the forwarder a moment to subscribe
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        events::publish_dispute(
+            "stream-test",
+            "initiated",
+            crate::config::AlertSeverity::Critical,
+            "buyer",
+        );
+        loop {
+            let frame = body.frame().await.unwrap().unwrap();
+            let chunk = String::from_utf8(frame.into_data().unwrap().to_vec()).unwrap();
+            if chunk.contains("stream-test") {
+                assert!(chunk.starts_with("event: dispute\ndata: {"));
+                assert!(chunk.ends_with("}\n\n"));
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_loopback() {
+        assert!(is_loopback("127.0.0.1"));
+        assert!(is_loopback("::1"));
+        assert!(is_loopback("localhost"));
+        assert!(!is_loopback("0.0.0.0"));
+    }
+
+    #[tokio::test]
+    async fn test_disputes_filter_and_pagination() {
+        let (server, _dir) = test_server(Some("secret")).await;
+
+        let response = server
+            .route(&get("/disputes?status=initiated&limit=1", Some("secret")))
+            .await;
+        let body = body_json(response).await;
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["disputes"].as_array().unwrap().len(), 1);
+        assert_eq!(body["disputes"][0]["status"], "initiated");
+        assert!(body["disputes"][0]["age_seconds"].is_number());
+
+        let response = server
+            .route(&get(
+                "/disputes?status=initiated&limit=1&offset=1",
+                Some("secret"),
+            ))
+            .await;
+        let second = body_json(response).await;
+        assert_ne!(
+            second["disputes"][0]["dispute_id"],
+            body["disputes"][0]["dispute_id"]
+        );
+
+        let response = server
+            .route(&get("/disputes?limit=-1", Some("secret")))
+            .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_page() {
+        let query = |q: &str| -> HashMap<String, String> {
+            url::form_urlencoded::parse(q.as_bytes())
+                .into_owned()
+                .collect()
+        };
+        assert_eq!(
+            Page::from_query(&query("")).unwrap(),
+            Page {
+                limit: DEFAULT_PAGE_SIZE,
+                offset: 0
+            }
+        );
+        assert_eq!(
+            Page::from_query(&query("limit=10000&offset=20")).unwrap(),
+            Page {
+                limit: MAX_PAGE_SIZE,
+                offset: 20
+            }
+        );
+    }
+}
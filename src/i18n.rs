@@ -0,0 +1,1492 @@
+//! Bundled translations of the built-in message templates.
+//!
+//! Every language provides the same set of templates with the same variables;
+//! see the `templates` module for how they are rendered.
+
+use crate::config::Language;
+
+/// Built-in templates by name for the given language
+pub fn builtin_templates(language: Language) -> &'static [(&'static str, &'static str)] {
+    match language {
+        Language::En => en::TEMPLATES,
+        Language::Es => es::TEMPLATES,
+        Language::Pt => pt::TEMPLATES,
+        Language::It => it::TEMPLATES,
+        Language::De => de::TEMPLATES,
+    }
+}
+
+/// English
+mod en {
+    pub const INITIATED: &str = r"🚨 *NEW DISPUTE*
+
+📋 *Dispute ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+👤 *Initiated by:* {{ initiator }}
+⏰ *Time:* {{ time }}{% if oncall %}
+📟 *On call:* {{ oncall | safe }}{% endif %}
+
+⚡ Please take this dispute in Mostrix or your admin client\.";
+
+    pub const IN_PROGRESS: &str = r"🔄 *DISPUTE IN PROGRESS*
+
+📋 *Dispute ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Taken by:* `{{ solver | code }}`{% endif %}
+⏰ *Time:* {{ time }}
+
+ℹ️ Dispute is now being handled\.";
+
+    pub const SELLER_REFUNDED: &str = r"💰 *DISPUTE RESOLVED \- SELLER REFUNDED*
+
+📋 *Dispute ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Resolved by:* `{{ solver | code }}`{% endif %}
+⏰ *Time:* {{ time }}
+
+✔️ Dispute closed: funds returned to seller\.";
+
+    pub const SETTLED: &str = r"✅ *DISPUTE RESOLVED \- SETTLED*
+
+📋 *Dispute ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Resolved by:* `{{ solver | code }}`{% endif %}
+⏰ *Time:* {{ time }}
+
+✔️ Dispute closed: buyer receives payment\.";
+
+    pub const RELEASED: &str = r"🔓 *DISPUTE RESOLVED \- RELEASED*
+
+📋 *Dispute ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+🤝 *Resolution:* Released by seller
+⏰ *Time:* {{ time }}
+
+✔️ Dispute closed: trade completed\.";
+
+    pub const OTHER: &str = r"📡 *DISPUTE STATUS UPDATE*
+
+📋 *Dispute ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+📊 *Status:* {{ status }}
+⏰ *Time:* {{ time }}
+
+ℹ️ Status changed\.";
+
+    pub const HEARTBEAT: &str = r"💓 *Health Check*
+
+{% if healthy %}✅ System: Online{% else %}⚠️ System: Deliveries failing{% endif %}
+⏰ Uptime: {{ uptime_hours }} hours {{ uptime_minutes }} minutes
+📊 Events processed: {{ events_processed }}
+📨 Deliveries: {{ delivered }} sent, {{ failed }} failed
+🔌 Relays: {{ relays_connected }}/{{ relays_total }} connected
+⚖️ Open disputes: {% if open_disputes is number %}{{ open_disputes }}{% else %}unknown{% endif %}
+🕐 Last dispute: {% if last_dispute_hours is number %}{{ last_dispute_hours }}h {{ last_dispute_minutes }}m ago{% else %}none yet{% endif %}
+🔔 Status: Monitoring active";
+
+    pub const EVENT_SILENCE: &str = r"⚠️ *Event Silence Alert*
+
+🔕 No dispute events received for {{ threshold_hours }} hours
+⏰ System uptime: {{ uptime_hours }} hours {{ uptime_minutes }} minutes
+🔍 Please check:
+• Mostro daemon status
+• Nostr relay connections
+• Network connectivity";
+
+    pub const RELAY_ALERT: &str = r"🔌 *Relay Connection Alert*
+
+⚠️ Disconnected relays: {{ failed_count }}
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}✅ Connected relays: {{ connected_count }}
+🔄 Attempting reconnection\.\.\.";
+
+    pub const STARTUP: &str = r"🐕 *mostro\-watchdog* is now online and monitoring for disputes\.
+
+📊 Heartbeat: {% if heartbeat_interval %}every {{ heartbeat_interval }} seconds{% else %}disabled{% endif %}
+🔔 Event silence alert: {% if event_alert_threshold %}{{ event_alert_threshold }} seconds{% else %}disabled{% endif %}{% if self_test_failure %}
+❌ Self\-test failed at {{ self_test_failure }}{% elif self_test %}
+🧪 Self\-test passed{% endif %}";
+
+    pub const DIGEST: &str = r"📦 *{{ count }} new dispute alerts*";
+
+    pub const ESCALATION: &str = r"⏰ *UNACKNOWLEDGED DISPUTE*
+
+Nobody has taken this dispute for {{ minutes }} minutes\.
+
+{{ alert | safe }}";
+
+    pub const ONCALL: &str = r"{% if name %}📟 *On call:* {{ name }}
+🔁 *Until:* {{ until }}
+⏭️ *Next:* {{ next }}{% else %}📟 No on\-call rotation is configured\.{% endif %}";
+
+    pub const UNAUTHORIZED: &str = r"⛔ You are not authorized to use this command\.";
+
+    pub const UNAUTHORIZED_REPORT: &str = r"⚠️ *Unauthorized command*
+
+👤 *User:* {{ user }} \(`{{ user_id | code }}`\)
+💬 *Command:* `{{ command | code }}`";
+
+    pub const HEALTH: &str = r"{% if healthy %}✅{% else %}⚠️{% endif %} *mostro\-watchdog {{ version }}* \- {% if healthy %}healthy{% else %}unhealthy{% endif %}
+
+⏰ Uptime: {{ uptime_hours }} hours {{ uptime_minutes }} minutes
+📊 Events processed: {{ events_processed }}
+📥 Last event: {% if last_event %}{{ last_event }}{% else %}never{% endif %}
+💓 Last heartbeat: {% if last_heartbeat %}{{ last_heartbeat }}{% else %}never{% endif %}";
+
+    pub const HEARTBEAT_FAILED: &str = r"❌ Failed to send heartbeat: {{ error }}";
+
+    pub const UPDATE_AVAILABLE: &str = r"🆕 *Update available*
+
+mostro\-watchdog {{ latest }} has been released \(running {{ version }}\)\.
+🔗 {{ url }}";
+
+    pub const VERSION: &str = r"🐕 *mostro\-watchdog {{ version }}*
+
+📝 Commit: `{{ commit | code }}`
+🖥️ Target: {{ target }}
+🔧 Profile: {{ profile }}";
+
+    pub const STATS: &str = r"📈 *Stats* \(uptime {{ uptime_hours }}h {{ uptime_minutes }}m\)
+```
+{{ table | code }}
+```";
+
+    pub const TASK_RESTARTED: &str = r"🔁 *Task restarted*
+
+⚙️ Task: {{ task }}
+❗ Reason: {{ reason }}
+🔢 Restarts so far: {{ restarts }}";
+
+    pub const CLOCK_DRIFT: &str = r"🕰️ *Clock drift detected*
+
+⏱️ Local clock {% if behind %}behind{% else %}ahead{% endif %} by {{ drift }}s
+📡 Measured against: {% if from_relays %}relay clocks{% else %}event timestamps{% endif %}
+📏 Threshold: {{ threshold }}s
+
+Events may be missed\. Check the host's time synchronization \(NTP\)\.";
+
+    pub const MEMORY_ALERT: &str = r"🧠 *High memory usage*
+
+💾 Memory: {{ rss_mb }} MB \(limit {{ limit_mb }} MB\)
+📂 File descriptors: {% if open_fds %}{{ open_fds }}{% else %}unknown{% endif %}
+⚙️ Tasks: {{ tasks }}";
+
+    pub const DEGRADED: &str = r"⚠️ *Watchdog degraded*
+
+{% if relays_down %}🔌 All relays disconnected
+{% endif %}{% if queue_backlog %}📬 Notification queue backed up: {{ queue_backlog }} messages waiting
+{% endif %}{% if store_error %}💾 Dispute store not writable: {{ store_error }}
+{% endif %}
+Alerts may be delayed or lost until this clears\.";
+
+    pub const DEGRADED_RECOVERED: &str = r"✅ *Watchdog recovered*
+
+All self\-checks pass again after {{ minutes }} minutes degraded\.";
+
+    pub const RELAY_LATENCY: &str = r"🐢 *Slow relay*
+
+📡 Relay: `{{ relay | code }}`
+⏱️ Propagation latency: p50 {{ p50 }}s, p90 {{ p90 }}s, p99 {{ p99 }}s
+📏 Threshold: p90 {{ threshold }}s
+
+Events from this relay arrive late; it may be overloaded\.";
+
+    pub const RELAY_BLACKLISTED: &str = r"🚫 *Relay blacklisted*
+
+📡 Relay: `{{ relay | code }}`
+📉 Score: {{ score }}/100 \(threshold {{ threshold }}\)
+
+The relay keeps disconnecting or missing dispute events, so it was taken out of the pool\. It will be tried again in {{ retry_minutes }} minutes\.";
+
+    pub const RELAY_PING: &str = r"{% if rtt_ms %}🐌 *Slow relay replies*{% else %}⏳ *Relay not replying*{% endif %}
+
+📡 Relay: `{{ relay | code }}`
+{% if rtt_ms %}⏱️ Round trip: {{ rtt_ms }} ms
+📏 Threshold: {{ threshold_ms }} ms{% else %}❌ {{ failures }} pings in a row failed: `{{ error | code }}`{% endif %}
+
+The relay is connected but slow to answer queries\.";
+
+    pub const FALLBACK_RELAYS: &str = r"{% if engaged %}⚠️ *Degraded mode: fallback relays in use*
+
+All {{ primary_count }} primary relays have been down for {{ minutes }} minutes\. Watching for disputes through:
+{% for url in relays %}  • {{ url }}
+{% endfor %}
+Alerts may be late or incomplete until a primary relay is back\.{% else %}✅ *Primary relays back*
+
+A primary relay reconnected and the fallback relays were released\.{% endif %}";
+
+    pub const RELAY_QUORUM: &str = r"{% if lost %}🚨 *Relay quorum lost*
+
+Only {{ connected_count }} of {{ relay_count }} relays connected, below the minimum of {{ min_connected }}\. Disputes may be missed\.
+Disconnected:
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}{% else %}✅ *Relay quorum restored*
+
+{{ connected_count }} of {{ relay_count }} relays connected \(minimum {{ min_connected }}\)\.{% endif %}";
+
+    pub const RELAY_RECONNECTED: &str = r"🔌 *Relay reconnected*
+
+✅ {{ relay }} is back after {% if minutes %}{{ minutes }} minutes{% else %}less than a minute{% endif %}\.
+Connected relays: {{ connected_count }}";
+
+    pub const DEAD_SUBSCRIPTION: &str = r"🔕 *Dispute subscription re\-created*
+
+{{ relay }} {% if closed %}closed the dispute subscription: {{ closed }}{% else %}stopped delivering disputes while connected \({{ missed }} missed that other relays delivered\){% endif %}\.
+{% if error %}❌ Re\-creating it failed: {{ error }}{% else %}✅ Subscribed again since the last dispute it delivered\.{% endif %}";
+
+    pub const CONFIG_RELOADED: &str = r"{% if error %}❌ *Configuration reload failed*
+
+`{{ error | code }}`
+
+The previous configuration stays in effect\.{% else %}🔄 *Configuration reloaded*
+
+✅ Applied: {% if applied %}{{ applied | join(sep=', ') }}{% else %}nothing{% endif %}{% if restart_required %}
+♻️ Restart required for: {{ restart_required | join(sep=', ') }}{% endif %}{% endif %}";
+
+    pub const ANNOUNCEMENT: &str = r"📢 *Announcement*
+
+{{ message }}";
+
+    pub const SHUTDOWN: &str = r"🔌 *mostro\-watchdog* is going offline\. Disputes are no longer monitored until it is restarted\.
+
+⏱️ Uptime: {{ uptime_hours }}h {{ uptime_minutes }}m
+📨 Events processed: {{ events_processed }}";
+
+    pub const CRASHED: &str = r"💥 *mostro\-watchdog crashed*
+
+❗ {{ reason }}
+
+Disputes are no longer monitored until it is restarted\.";
+
+    pub const FLOOD: &str = r"🌊 *Alert flood*
+
+{{ count }} dispute updates in the last minute were not sent one by one\. Individual alerts resume when the rate drops; open disputes are listed by the `/disputes` endpoint\.";
+
+    pub const EVENT_LOOP_RESTARTED: &str = r"🧊 *Nostr event loop restarted*
+
+No relay message was handled for {{ idle_seconds }}s while {{ relays | length }} relay\(s\) reported being connected\. A new event loop was started and these relays reconnected:
+{% for url in relays %}  • {{ url }}
+{% endfor %}{% if error %}
+❌ Reconnecting failed for {{ error }}{% endif %}";
+
+    pub const TEMPLATES: &[(&str, &str)] = &[
+        ("initiated", INITIATED),
+        ("in_progress", IN_PROGRESS),
+        ("seller_refunded", SELLER_REFUNDED),
+        ("settled", SETTLED),
+        ("released", RELEASED),
+        ("other", OTHER),
+        ("heartbeat", HEARTBEAT),
+        ("event_silence", EVENT_SILENCE),
+        ("relay_alert", RELAY_ALERT),
+        ("startup", STARTUP),
+        ("digest", DIGEST),
+        ("escalation", ESCALATION),
+        ("oncall", ONCALL),
+        ("unauthorized", UNAUTHORIZED),
+        ("unauthorized_report", UNAUTHORIZED_REPORT),
+        ("health", HEALTH),
+        ("heartbeat_failed", HEARTBEAT_FAILED),
+        ("update_available", UPDATE_AVAILABLE),
+        ("version", VERSION),
+        ("stats", STATS),
+        ("task_restarted", TASK_RESTARTED),
+        ("clock_drift", CLOCK_DRIFT),
+        ("memory_alert", MEMORY_ALERT),
+        ("degraded", DEGRADED),
+        ("degraded_recovered", DEGRADED_RECOVERED),
+        ("relay_latency", RELAY_LATENCY),
+        ("relay_blacklisted", RELAY_BLACKLISTED),
+        ("relay_ping", RELAY_PING),
+        ("fallback_relays", FALLBACK_RELAYS),
+        ("relay_quorum", RELAY_QUORUM),
+        ("relay_reconnected", RELAY_RECONNECTED),
+        ("dead_subscription", DEAD_SUBSCRIPTION),
+        ("config_reloaded", CONFIG_RELOADED),
+        ("announcement", ANNOUNCEMENT),
+        ("shutdown", SHUTDOWN),
+        ("crashed", CRASHED),
+        ("flood", FLOOD),
+        ("event_loop_restarted", EVENT_LOOP_RESTARTED),
+    ];
+}
+
+/// Spanish
+mod es {
+    pub const INITIATED: &str = r"🚨 *NUEVA DISPUTA*
+
+📋 *ID de disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+👤 *Iniciada por:* {{ initiator }}
+⏰ *Hora:* {{ time }}{% if oncall %}
+📟 *De guardia:* {{ oncall | safe }}{% endif %}
+
+⚡ Toma esta disputa en Mostrix o en tu cliente de administración\.";
+
+    pub const IN_PROGRESS: &str = r"🔄 *DISPUTA EN CURSO*
+
+📋 *ID de disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Tomada por:* `{{ solver | code }}`{% endif %}
+⏰ *Hora:* {{ time }}
+
+ℹ️ La disputa está siendo atendida\.";
+
+    pub const SELLER_REFUNDED: &str = r"💰 *DISPUTA RESUELTA \- REEMBOLSO AL VENDEDOR*
+
+📋 *ID de disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Resuelta por:* `{{ solver | code }}`{% endif %}
+⏰ *Hora:* {{ time }}
+
+✔️ Disputa cerrada: fondos devueltos al vendedor\.";
+
+    pub const SETTLED: &str = r"✅ *DISPUTA RESUELTA \- LIQUIDADA*
+
+📋 *ID de disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Resuelta por:* `{{ solver | code }}`{% endif %}
+⏰ *Hora:* {{ time }}
+
+✔️ Disputa cerrada: el comprador recibe el pago\.";
+
+    pub const RELEASED: &str = r"🔓 *DISPUTA RESUELTA \- LIBERADA*
+
+📋 *ID de disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+🤝 *Resolución:* Liberada por el vendedor
+⏰ *Hora:* {{ time }}
+
+✔️ Disputa cerrada: intercambio completado\.";
+
+    pub const OTHER: &str = r"📡 *ACTUALIZACIÓN DE DISPUTA*
+
+📋 *ID de disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+📊 *Estado:* {{ status }}
+⏰ *Hora:* {{ time }}
+
+ℹ️ El estado cambió\.";
+
+    pub const HEARTBEAT: &str = r"💓 *Control de salud*
+
+{% if healthy %}✅ Sistema: En línea{% else %}⚠️ Sistema: Fallan las entregas{% endif %}
+⏰ Tiempo activo: {{ uptime_hours }} horas {{ uptime_minutes }} minutos
+📊 Eventos procesados: {{ events_processed }}
+📨 Entregas: {{ delivered }} enviadas, {{ failed }} fallidas
+🔌 Relays: {{ relays_connected }}/{{ relays_total }} conectados
+⚖️ Disputas abiertas: {% if open_disputes is number %}{{ open_disputes }}{% else %}desconocido{% endif %}
+🕐 Última disputa: {% if last_dispute_hours is number %}hace {{ last_dispute_hours }}h {{ last_dispute_minutes }}m{% else %}ninguna aún{% endif %}
+🔔 Estado: Monitoreo activo";
+
+    pub const EVENT_SILENCE: &str = r"⚠️ *Alerta de silencio*
+
+🔕 No se recibieron eventos de disputa en {{ threshold_hours }} horas
+⏰ Tiempo activo del sistema: {{ uptime_hours }} horas {{ uptime_minutes }} minutos
+🔍 Revisa:
+• Estado del daemon de Mostro
+• Conexiones a relays de Nostr
+• Conectividad de red";
+
+    pub const RELAY_ALERT: &str = r"🔌 *Alerta de conexión a relays*
+
+⚠️ Relays desconectados: {{ failed_count }}
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}✅ Relays conectados: {{ connected_count }}
+🔄 Intentando reconectar\.\.\.";
+
+    pub const STARTUP: &str = r"🐕 *mostro\-watchdog* está en línea y vigilando disputas\.
+
+📊 Heartbeat: {% if heartbeat_interval %}cada {{ heartbeat_interval }} segundos{% else %}desactivado{% endif %}
+🔔 Alerta de silencio: {% if event_alert_threshold %}{{ event_alert_threshold }} segundos{% else %}desactivada{% endif %}{% if self_test_failure %}
+❌ Autoprueba fallida en {{ self_test_failure }}{% elif self_test %}
+🧪 Autoprueba superada{% endif %}";
+
+    pub const DIGEST: &str = r"📦 *{{ count }} nuevas alertas de disputa*";
+
+    pub const ESCALATION: &str = r"⏰ *DISPUTA SIN ATENDER*
+
+Nadie ha tomado esta disputa en {{ minutes }} minutos\.
+
+{{ alert | safe }}";
+
+    pub const ONCALL: &str = r"{% if name %}📟 *De guardia:* {{ name }}
+🔁 *Hasta:* {{ until }}
+⏭️ *Siguiente:* {{ next }}{% else %}📟 No hay rotación de guardias configurada\.{% endif %}";
+
+    pub const UNAUTHORIZED: &str = r"⛔ No tienes autorización para usar este comando\.";
+
+    pub const UNAUTHORIZED_REPORT: &str = r"⚠️ *Comando no autorizado*
+
+👤 *Usuario:* {{ user }} \(`{{ user_id | code }}`\)
+💬 *Comando:* `{{ command | code }}`";
+
+    pub const HEALTH: &str = r"{% if healthy %}✅{% else %}⚠️{% endif %} *mostro\-watchdog {{ version }}* \- {% if healthy %}saludable{% else %}con problemas{% endif %}
+
+⏰ Tiempo activo: {{ uptime_hours }} horas {{ uptime_minutes }} minutos
+📊 Eventos procesados: {{ events_processed }}
+📥 Último evento: {% if last_event %}{{ last_event }}{% else %}nunca{% endif %}
+💓 Último latido: {% if last_heartbeat %}{{ last_heartbeat }}{% else %}nunca{% endif %}";
+
+    pub const HEARTBEAT_FAILED: &str = r"❌ No se pudo enviar el latido: {{ error }}";
+
+    pub const UPDATE_AVAILABLE: &str = r"🆕 *Actualización disponible*
+
+Se publicó mostro\-watchdog {{ latest }} \(versión actual: {{ version }}\)\.
+🔗 {{ url }}";
+
+    pub const VERSION: &str = r"🐕 *mostro\-watchdog {{ version }}*
+
+📝 Commit: `{{ commit | code }}`
+🖥️ Plataforma: {{ target }}
+🔧 Perfil: {{ profile }}";
+
+    pub const STATS: &str = r"📈 *Estadísticas* \(activo {{ uptime_hours }}h {{ uptime_minutes }}m\)
+```
+{{ table | code }}
+```";
+
+    pub const TASK_RESTARTED: &str = r"🔁 *Tarea reiniciada*
+
+⚙️ Tarea: {{ task }}
+❗ Motivo: {{ reason }}
+🔢 Reinicios hasta ahora: {{ restarts }}";
+
+    pub const CLOCK_DRIFT: &str = r"🕰️ *Desfase de reloj detectado*
+
+⏱️ Reloj local {% if behind %}atrasado{% else %}adelantado{% endif %} {{ drift }}s
+📡 Medido contra: {% if from_relays %}relojes de los relays{% else %}marcas de tiempo de eventos{% endif %}
+📏 Umbral: {{ threshold }}s
+
+Se pueden perder eventos\. Revisa la sincronización de hora del servidor \(NTP\)\.";
+
+    pub const MEMORY_ALERT: &str = r"🧠 *Uso de memoria elevado*
+
+💾 Memoria: {{ rss_mb }} MB \(límite {{ limit_mb }} MB\)
+📂 Descriptores de archivo: {% if open_fds %}{{ open_fds }}{% else %}desconocido{% endif %}
+⚙️ Tareas: {{ tasks }}";
+
+    pub const DEGRADED: &str = r"⚠️ *Watchdog degradado*
+
+{% if relays_down %}🔌 Todos los relays desconectados
+{% endif %}{% if queue_backlog %}📬 Cola de notificaciones atascada: {{ queue_backlog }} mensajes en espera
+{% endif %}{% if store_error %}💾 Almacén de disputas sin escritura: {{ store_error }}
+{% endif %}
+Las alertas pueden retrasarse o perderse hasta que se resuelva\.";
+
+    pub const DEGRADED_RECOVERED: &str = r"✅ *Watchdog recuperado*
+
+Todas las autocomprobaciones pasan de nuevo tras {{ minutes }} minutos degradado\.";
+
+    pub const RELAY_LATENCY: &str = r"🐢 *Relay lento*
+
+📡 Relay: `{{ relay | code }}`
+⏱️ Latencia de propagación: p50 {{ p50 }}s, p90 {{ p90 }}s, p99 {{ p99 }}s
+📏 Umbral: p90 {{ threshold }}s
+
+Los eventos de este relay llegan tarde; puede estar sobrecargado\.";
+
+    pub const RELAY_BLACKLISTED: &str = r"🚫 *Relay en lista negra*
+
+📡 Relay: `{{ relay | code }}`
+📉 Puntuación: {{ score }}/100 \(umbral {{ threshold }}\)
+
+El relay se desconecta a menudo o no entrega eventos de disputa, así que se retiró del pool\. Se volverá a probar en {{ retry_minutes }} minutos\.";
+
+    pub const RELAY_PING: &str = r"{% if rtt_ms %}🐌 *Relay lento en responder*{% else %}⏳ *El relay no responde*{% endif %}
+
+📡 Relay: `{{ relay | code }}`
+{% if rtt_ms %}⏱️ Ida y vuelta: {{ rtt_ms }} ms
+📏 Umbral: {{ threshold_ms }} ms{% else %}❌ {{ failures }} pings seguidos fallaron: `{{ error | code }}`{% endif %}
+
+El relay está conectado pero tarda en responder a las consultas\.";
+
+    pub const FALLBACK_RELAYS: &str = r"{% if engaged %}⚠️ *Modo degradado: usando relays de respaldo*
+
+Los {{ primary_count }} relays principales llevan {{ minutes }} minutos caídos\. Vigilando disputas a través de:
+{% for url in relays %}  • {{ url }}
+{% endfor %}
+Las alertas pueden llegar tarde o incompletas hasta que vuelva un relay principal\.{% else %}✅ *Relays principales de vuelta*
+
+Un relay principal se reconectó y se liberaron los relays de respaldo\.{% endif %}";
+
+    pub const RELAY_QUORUM: &str = r"{% if lost %}🚨 *Quórum de relays perdido*
+
+Solo {{ connected_count }} de {{ relay_count }} relays conectados, por debajo del mínimo de {{ min_connected }}\. Pueden perderse disputas\.
+Desconectados:
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}{% else %}✅ *Quórum de relays restablecido*
+
+{{ connected_count }} de {{ relay_count }} relays conectados \(mínimo {{ min_connected }}\)\.{% endif %}";
+
+    pub const RELAY_RECONNECTED: &str = r"🔌 *Relay reconectado*
+
+✅ {{ relay }} volvió tras {% if minutes %}{{ minutes }} minutos{% else %}menos de un minuto{% endif %}\.
+Relays conectados: {{ connected_count }}";
+
+    pub const DEAD_SUBSCRIPTION: &str = r"🔕 *Suscripción a disputas recreada*
+
+{{ relay }} {% if closed %}cerró la suscripción a disputas: {{ closed }}{% else %}dejó de entregar disputas estando conectado \({{ missed }} perdidas que otros relays sí entregaron\){% endif %}\.
+{% if error %}❌ No se pudo recrear: {{ error }}{% else %}✅ Suscrito de nuevo desde la última disputa que entregó\.{% endif %}";
+
+    pub const CONFIG_RELOADED: &str = r"{% if error %}❌ *Error al recargar la configuración*
+
+`{{ error | code }}`
+
+La configuración anterior sigue vigente\.{% else %}🔄 *Configuración recargada*
+
+✅ Aplicado: {% if applied %}{{ applied | join(sep=', ') }}{% else %}nada{% endif %}{% if restart_required %}
+♻️ Requiere reinicio: {{ restart_required | join(sep=', ') }}{% endif %}{% endif %}";
+
+    pub const ANNOUNCEMENT: &str = r"📢 *Anuncio*
+
+{{ message }}";
+
+    pub const SHUTDOWN: &str = r"🔌 *mostro\-watchdog* se está desconectando\. Las disputas no se vigilan hasta que se reinicie\.
+
+⏱️ Tiempo activo: {{ uptime_hours }}h {{ uptime_minutes }}m
+📨 Eventos procesados: {{ events_processed }}";
+
+    pub const CRASHED: &str = r"💥 *mostro\-watchdog se cayó*
+
+❗ {{ reason }}
+
+Las disputas no se vigilan hasta que se reinicie\.";
+
+    pub const FLOOD: &str = r"🌊 *Avalancha de alertas*
+
+{{ count }} actualizaciones de disputas del último minuto no se enviaron una por una\. Las alertas individuales se reanudan cuando baje el ritmo; el endpoint `/disputes` lista las disputas abiertas\.";
+
+    pub const EVENT_LOOP_RESTARTED: &str = r"🧊 *Bucle de eventos de Nostr reiniciado*
+
+No se procesó ningún mensaje de los relays durante {{ idle_seconds }}s mientras {{ relays | length }} relay\(s\) figuraban como conectados\. Se inició un nuevo bucle de eventos y estos relays se reconectaron:
+{% for url in relays %}  • {{ url }}
+{% endfor %}{% if error %}
+❌ Falló la reconexión de {{ error }}{% endif %}";
+
+    pub const TEMPLATES: &[(&str, &str)] = &[
+        ("initiated", INITIATED),
+        ("in_progress", IN_PROGRESS),
+        ("seller_refunded", SELLER_REFUNDED),
+        ("settled", SETTLED),
+        ("released", RELEASED),
+        ("other", OTHER),
+        ("heartbeat", HEARTBEAT),
+        ("event_silence", EVENT_SILENCE),
+        ("relay_alert", RELAY_ALERT),
+        ("startup", STARTUP),
+        ("digest", DIGEST),
+        ("escalation", ESCALATION),
+        ("oncall", ONCALL),
+        ("unauthorized", UNAUTHORIZED),
+        ("unauthorized_report", UNAUTHORIZED_REPORT),
+        ("health", HEALTH),
+        ("heartbeat_failed", HEARTBEAT_FAILED),
+        ("update_available", UPDATE_AVAILABLE),
+        ("version", VERSION),
+        ("stats", STATS),
+        ("task_restarted", TASK_RESTARTED),
+        ("clock_drift", CLOCK_DRIFT),
+        ("memory_alert", MEMORY_ALERT),
+        ("degraded", DEGRADED),
+        ("degraded_recovered", DEGRADED_RECOVERED),
+        ("relay_latency", RELAY_LATENCY),
+        ("relay_blacklisted", RELAY_BLACKLISTED),
+        ("relay_ping", RELAY_PING),
+        ("fallback_relays", FALLBACK_RELAYS),
+        ("relay_quorum", RELAY_QUORUM),
+        ("relay_reconnected", RELAY_RECONNECTED),
+        ("dead_subscription", DEAD_SUBSCRIPTION),
+        ("config_reloaded", CONFIG_RELOADED),
+        ("announcement", ANNOUNCEMENT),
+        ("shutdown", SHUTDOWN),
+        ("crashed", CRASHED),
+        ("flood", FLOOD),
+        ("event_loop_restarted", EVENT_LOOP_RESTARTED),
+    ];
+}
+
+/// Portuguese
+mod pt {
+    pub const INITIATED: &str = r"🚨 *NOVA DISPUTA*
+
+📋 *ID da disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+👤 *Iniciada por:* {{ initiator }}
+⏰ *Hora:* {{ time }}{% if oncall %}
+📟 *De plantão:* {{ oncall | safe }}{% endif %}
+
+⚡ Assuma esta disputa no Mostrix ou no seu cliente de administração\.";
+
+    pub const IN_PROGRESS: &str = r"🔄 *DISPUTA EM ANDAMENTO*
+
+📋 *ID da disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Assumida por:* `{{ solver | code }}`{% endif %}
+⏰ *Hora:* {{ time }}
+
+ℹ️ A disputa está sendo tratada\.";
+
+    pub const SELLER_REFUNDED: &str = r"💰 *DISPUTA RESOLVIDA \- VENDEDOR REEMBOLSADO*
+
+📋 *ID da disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Resolvida por:* `{{ solver | code }}`{% endif %}
+⏰ *Hora:* {{ time }}
+
+✔️ Disputa encerrada: fundos devolvidos ao vendedor\.";
+
+    pub const SETTLED: &str = r"✅ *DISPUTA RESOLVIDA \- LIQUIDADA*
+
+📋 *ID da disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Resolvida por:* `{{ solver | code }}`{% endif %}
+⏰ *Hora:* {{ time }}
+
+✔️ Disputa encerrada: o comprador recebe o pagamento\.";
+
+    pub const RELEASED: &str = r"🔓 *DISPUTA RESOLVIDA \- LIBERADA*
+
+📋 *ID da disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+🤝 *Resolução:* Liberada pelo vendedor
+⏰ *Hora:* {{ time }}
+
+✔️ Disputa encerrada: negociação concluída\.";
+
+    pub const OTHER: &str = r"📡 *ATUALIZAÇÃO DE DISPUTA*
+
+📋 *ID da disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+📊 *Status:* {{ status }}
+⏰ *Hora:* {{ time }}
+
+ℹ️ O status mudou\.";
+
+    pub const HEARTBEAT: &str = r"💓 *Verificação de saúde*
+
+{% if healthy %}✅ Sistema: Online{% else %}⚠️ Sistema: Entregas falhando{% endif %}
+⏰ Tempo ativo: {{ uptime_hours }} horas {{ uptime_minutes }} minutos
+📊 Eventos processados: {{ events_processed }}
+📨 Entregas: {{ delivered }} enviadas, {{ failed }} com falha
+🔌 Relays: {{ relays_connected }}/{{ relays_total }} conectados
+⚖️ Disputas abertas: {% if open_disputes is number %}{{ open_disputes }}{% else %}desconhecido{% endif %}
+🕐 Última disputa: {% if last_dispute_hours is number %}há {{ last_dispute_hours }}h {{ last_dispute_minutes }}m{% else %}nenhuma ainda{% endif %}
+🔔 Status: Monitoramento ativo";
+
+    pub const EVENT_SILENCE: &str = r"⚠️ *Alerta de silêncio*
+
+🔕 Nenhum evento de disputa recebido há {{ threshold_hours }} horas
+⏰ Tempo ativo do sistema: {{ uptime_hours }} horas {{ uptime_minutes }} minutos
+🔍 Verifique:
+• Status do daemon Mostro
+• Conexões com relays Nostr
+• Conectividade de rede";
+
+    pub const RELAY_ALERT: &str = r"🔌 *Alerta de conexão com relays*
+
+⚠️ Relays desconectados: {{ failed_count }}
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}✅ Relays conectados: {{ connected_count }}
+🔄 Tentando reconectar\.\.\.";
+
+    pub const STARTUP: &str = r"🐕 *mostro\-watchdog* está online e monitorando disputas\.
+
+📊 Heartbeat: {% if heartbeat_interval %}a cada {{ heartbeat_interval }} segundos{% else %}desativado{% endif %}
+🔔 Alerta de silêncio: {% if event_alert_threshold %}{{ event_alert_threshold }} segundos{% else %}desativado{% endif %}{% if self_test_failure %}
+❌ Autoteste falhou em {{ self_test_failure }}{% elif self_test %}
+🧪 Autoteste aprovado{% endif %}";
+
+    pub const DIGEST: &str = r"📦 *{{ count }} novos alertas de disputa*";
+
+    pub const ESCALATION: &str = r"⏰ *DISPUTA SEM ATENDIMENTO*
+
+Ninguém assumiu esta disputa há {{ minutes }} minutos\.
+
+{{ alert | safe }}";
+
+    pub const ONCALL: &str = r"{% if name %}📟 *De plantão:* {{ name }}
+🔁 *Até:* {{ until }}
+⏭️ *Próximo:* {{ next }}{% else %}📟 Nenhuma escala de plantão configurada\.{% endif %}";
+
+    pub const UNAUTHORIZED: &str = r"⛔ Você não tem autorização para usar este comando\.";
+
+    pub const UNAUTHORIZED_REPORT: &str = r"⚠️ *Comando não autorizado*
+
+👤 *Usuário:* {{ user }} \(`{{ user_id | code }}`\)
+💬 *Comando:* `{{ command | code }}`";
+
+    pub const HEALTH: &str = r"{% if healthy %}✅{% else %}⚠️{% endif %} *mostro\-watchdog {{ version }}* \- {% if healthy %}saudável{% else %}com problemas{% endif %}
+
+⏰ Tempo ativo: {{ uptime_hours }} horas {{ uptime_minutes }} minutos
+📊 Eventos processados: {{ events_processed }}
+📥 Último evento: {% if last_event %}{{ last_event }}{% else %}nunca{% endif %}
+💓 Último heartbeat: {% if last_heartbeat %}{{ last_heartbeat }}{% else %}nunca{% endif %}";
+
+    pub const HEARTBEAT_FAILED: &str = r"❌ Falha ao enviar heartbeat: {{ error }}";
+
+    pub const UPDATE_AVAILABLE: &str = r"🆕 *Atualização disponível*
+
+mostro\-watchdog {{ latest }} foi lançado \(versão atual: {{ version }}\)\.
+🔗 {{ url }}";
+
+    pub const VERSION: &str = r"🐕 *mostro\-watchdog {{ version }}*
+
+📝 Commit: `{{ commit | code }}`
+🖥️ Plataforma: {{ target }}
+🔧 Perfil: {{ profile }}";
+
+    pub const STATS: &str = r"📈 *Estatísticas* \(ativo {{ uptime_hours }}h {{ uptime_minutes }}m\)
+```
+{{ table | code }}
+```";
+
+    pub const TASK_RESTARTED: &str = r"🔁 *Tarefa reiniciada*
+
+⚙️ Tarefa: {{ task }}
+❗ Motivo: {{ reason }}
+🔢 Reinícios até agora: {{ restarts }}";
+
+    pub const CLOCK_DRIFT: &str = r"🕰️ *Desvio de relógio detectado*
+
+⏱️ Relógio local {% if behind %}atrasado{% else %}adiantado{% endif %} {{ drift }}s
+📡 Medido contra: {% if from_relays %}relógios dos relays{% else %}carimbos de data dos eventos{% endif %}
+📏 Limite: {{ threshold }}s
+
+Eventos podem ser perdidos\. Verifique a sincronização de hora do servidor \(NTP\)\.";
+
+    pub const MEMORY_ALERT: &str = r"🧠 *Uso de memória elevado*
+
+💾 Memória: {{ rss_mb }} MB \(limite {{ limit_mb }} MB\)
+📂 Descritores de arquivo: {% if open_fds %}{{ open_fds }}{% else %}desconhecido{% endif %}
+⚙️ Tarefas: {{ tasks }}";
+
+    pub const DEGRADED: &str = r"⚠️ *Watchdog degradado*
+
+{% if relays_down %}🔌 Todos os relays desconectados
+{% endif %}{% if queue_backlog %}📬 Fila de notificações congestionada: {{ queue_backlog }} mensagens aguardando
+{% endif %}{% if store_error %}💾 Armazenamento de disputas sem escrita: {{ store_error }}
+{% endif %}
+Alertas podem atrasar ou se perder até que isso se resolva\.";
+
+    pub const DEGRADED_RECOVERED: &str = r"✅ *Watchdog recuperado*
+
+Todas as autoverificações passam novamente após {{ minutes }} minutos degradado\.";
+
+    pub const RELAY_LATENCY: &str = r"🐢 *Relay lento*
+
+📡 Relay: `{{ relay | code }}`
+⏱️ Latência de propagação: p50 {{ p50 }}s, p90 {{ p90 }}s, p99 {{ p99 }}s
+📏 Limite: p90 {{ threshold }}s
+
+Os eventos deste relay chegam atrasados; ele pode estar sobrecarregado\.";
+
+    pub const RELAY_BLACKLISTED: &str = r"🚫 *Relay na lista negra*
+
+📡 Relay: `{{ relay | code }}`
+📉 Pontuação: {{ score }}/100 \(limite {{ threshold }}\)
+
+O relay desconecta com frequência ou não entrega eventos de disputa, então foi retirado do pool\. Será testado novamente em {{ retry_minutes }} minutos\.";
+
+    pub const RELAY_PING: &str = r"{% if rtt_ms %}🐌 *Relay lento para responder*{% else %}⏳ *Relay não responde*{% endif %}
+
+📡 Relay: `{{ relay | code }}`
+{% if rtt_ms %}⏱️ Ida e volta: {{ rtt_ms }} ms
+📏 Limite: {{ threshold_ms }} ms{% else %}❌ {{ failures }} pings seguidos falharam: `{{ error | code }}`{% endif %}
+
+O relay está conectado, mas demora a responder às consultas\.";
+
+    pub const FALLBACK_RELAYS: &str = r"{% if engaged %}⚠️ *Modo degradado: usando relays de reserva*
+
+Os {{ primary_count }} relays principais estão fora há {{ minutes }} minutos\. Monitorando disputas através de:
+{% for url in relays %}  • {{ url }}
+{% endfor %}
+Os alertas podem atrasar ou ficar incompletos até um relay principal voltar\.{% else %}✅ *Relays principais de volta*
+
+Um relay principal reconectou e os relays de reserva foram liberados\.{% endif %}";
+
+    pub const RELAY_QUORUM: &str = r"{% if lost %}🚨 *Quórum de relays perdido*
+
+Apenas {{ connected_count }} de {{ relay_count }} relays conectados, abaixo do mínimo de {{ min_connected }}\. Disputas podem ser perdidas\.
+Desconectados:
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}{% else %}✅ *Quórum de relays restabelecido*
+
+{{ connected_count }} de {{ relay_count }} relays conectados \(mínimo {{ min_connected }}\)\.{% endif %}";
+
+    pub const RELAY_RECONNECTED: &str = r"🔌 *Relay reconectado*
+
+✅ {{ relay }} voltou após {% if minutes %}{{ minutes }} minutos{% else %}menos de um minuto{% endif %}\.
+Relays conectados: {{ connected_count }}";
+
+    pub const DEAD_SUBSCRIPTION: &str = r"🔕 *Assinatura de disputas recriada*
+
+{{ relay }} {% if closed %}encerrou a assinatura de disputas: {{ closed }}{% else %}parou de entregar disputas mesmo conectado \({{ missed }} perdidas que outros relays entregaram\){% endif %}\.
+{% if error %}❌ Falha ao recriá\-la: {{ error }}{% else %}✅ Assinada novamente desde a última disputa que entregou\.{% endif %}";
+
+    pub const CONFIG_RELOADED: &str = r"{% if error %}❌ *Falha ao recarregar a configuração*
+
+`{{ error | code }}`
+
+A configuração anterior continua em vigor\.{% else %}🔄 *Configuração recarregada*
+
+✅ Aplicado: {% if applied %}{{ applied | join(sep=', ') }}{% else %}nada{% endif %}{% if restart_required %}
+♻️ Requer reinício: {{ restart_required | join(sep=', ') }}{% endif %}{% endif %}";
+
+    pub const ANNOUNCEMENT: &str = r"📢 *Comunicado*
+
+{{ message }}";
+
+    pub const SHUTDOWN: &str = r"🔌 *mostro\-watchdog* está ficando offline\. As disputas não são monitoradas até que seja reiniciado\.
+
+⏱️ Tempo ativo: {{ uptime_hours }}h {{ uptime_minutes }}m
+📨 Eventos processados: {{ events_processed }}";
+
+    pub const CRASHED: &str = r"💥 *mostro\-watchdog falhou*
+
+❗ {{ reason }}
+
+As disputas não são monitoradas até que seja reiniciado\.";
+
+    pub const FLOOD: &str = r"🌊 *Avalanche de alertas*
+
+{{ count }} atualizações de disputas do último minuto não foram enviadas uma a uma\. Os alertas individuais voltam quando o ritmo cair; o endpoint `/disputes` lista as disputas abertas\.";
+
+    pub const EVENT_LOOP_RESTARTED: &str = r"🧊 *Loop de eventos do Nostr reiniciado*
+
+Nenhuma mensagem dos relays foi processada por {{ idle_seconds }}s enquanto {{ relays | length }} relay\(s\) constavam como conectados\. Um novo loop de eventos foi iniciado e estes relays foram reconectados:
+{% for url in relays %}  • {{ url }}
+{% endfor %}{% if error %}
+❌ A reconexão falhou para {{ error }}{% endif %}";
+
+    pub const TEMPLATES: &[(&str, &str)] = &[
+        ("initiated", INITIATED),
+        ("in_progress", IN_PROGRESS),
+        ("seller_refunded", SELLER_REFUNDED),
+        ("settled", SETTLED),
+        ("released", RELEASED),
+        ("other", OTHER),
+        ("heartbeat", HEARTBEAT),
+        ("event_silence", EVENT_SILENCE),
+        ("relay_alert", RELAY_ALERT),
+        ("startup", STARTUP),
+        ("digest", DIGEST),
+        ("escalation", ESCALATION),
+        ("oncall", ONCALL),
+        ("unauthorized", UNAUTHORIZED),
+        ("unauthorized_report", UNAUTHORIZED_REPORT),
+        ("health", HEALTH),
+        ("heartbeat_failed", HEARTBEAT_FAILED),
+        ("update_available", UPDATE_AVAILABLE),
+        ("version", VERSION),
+        ("stats", STATS),
+        ("task_restarted", TASK_RESTARTED),
+        ("clock_drift", CLOCK_DRIFT),
+        ("memory_alert", MEMORY_ALERT),
+        ("degraded", DEGRADED),
+        ("degraded_recovered", DEGRADED_RECOVERED),
+        ("relay_latency", RELAY_LATENCY),
+        ("relay_blacklisted", RELAY_BLACKLISTED),
+        ("relay_ping", RELAY_PING),
+        ("fallback_relays", FALLBACK_RELAYS),
+        ("relay_quorum", RELAY_QUORUM),
+        ("relay_reconnected", RELAY_RECONNECTED),
+        ("dead_subscription", DEAD_SUBSCRIPTION),
+        ("config_reloaded", CONFIG_RELOADED),
+        ("announcement", ANNOUNCEMENT),
+        ("shutdown", SHUTDOWN),
+        ("crashed", CRASHED),
+        ("flood", FLOOD),
+        ("event_loop_restarted", EVENT_LOOP_RESTARTED),
+    ];
+}
+
+/// Italian
+mod it {
+    pub const INITIATED: &str = r"🚨 *NUOVA DISPUTA*
+
+📋 *ID disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+👤 *Aperta da:* {{ initiator }}
+⏰ *Ora:* {{ time }}{% if oncall %}
+📟 *Reperibile:* {{ oncall | safe }}{% endif %}
+
+⚡ Prendi in carico questa disputa in Mostrix o nel tuo client di amministrazione\.";
+
+    pub const IN_PROGRESS: &str = r"🔄 *DISPUTA IN CORSO*
+
+📋 *ID disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Presa in carico da:* `{{ solver | code }}`{% endif %}
+⏰ *Ora:* {{ time }}
+
+ℹ️ La disputa è ora in gestione\.";
+
+    pub const SELLER_REFUNDED: &str = r"💰 *DISPUTA RISOLTA \- VENDITORE RIMBORSATO*
+
+📋 *ID disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Risolta da:* `{{ solver | code }}`{% endif %}
+⏰ *Ora:* {{ time }}
+
+✔️ Disputa chiusa: fondi restituiti al venditore\.";
+
+    pub const SETTLED: &str = r"✅ *DISPUTA RISOLTA \- REGOLATA*
+
+📋 *ID disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Risolta da:* `{{ solver | code }}`{% endif %}
+⏰ *Ora:* {{ time }}
+
+✔️ Disputa chiusa: l'acquirente riceve il pagamento\.";
+
+    pub const RELEASED: &str = r"🔓 *DISPUTA RISOLTA \- RILASCIATA*
+
+📋 *ID disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+🤝 *Risoluzione:* Rilasciata dal venditore
+⏰ *Ora:* {{ time }}
+
+✔️ Disputa chiusa: scambio completato\.";
+
+    pub const OTHER: &str = r"📡 *AGGIORNAMENTO DISPUTA*
+
+📋 *ID disputa:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+📊 *Stato:* {{ status }}
+⏰ *Ora:* {{ time }}
+
+ℹ️ Stato cambiato\.";
+
+    pub const HEARTBEAT: &str = r"💓 *Controllo di stato*
+
+{% if healthy %}✅ Sistema: Online{% else %}⚠️ Sistema: Consegne non riuscite{% endif %}
+⏰ Uptime: {{ uptime_hours }} ore {{ uptime_minutes }} minuti
+📊 Eventi elaborati: {{ events_processed }}
+📨 Consegne: {{ delivered }} inviate, {{ failed }} fallite
+🔌 Relay: {{ relays_connected }}/{{ relays_total }} connessi
+⚖️ Dispute aperte: {% if open_disputes is number %}{{ open_disputes }}{% else %}sconosciuto{% endif %}
+🕐 Ultima disputa: {% if last_dispute_hours is number %}{{ last_dispute_hours }}h {{ last_dispute_minutes }}m fa{% else %}nessuna finora{% endif %}
+🔔 Stato: Monitoraggio attivo";
+
+    pub const EVENT_SILENCE: &str = r"⚠️ *Avviso di silenzio*
+
+🔕 Nessun evento di disputa ricevuto da {{ threshold_hours }} ore
+⏰ Uptime del sistema: {{ uptime_hours }} ore {{ uptime_minutes }} minuti
+🔍 Verifica:
+• Stato del daemon Mostro
+• Connessioni ai relay Nostr
+• Connettività di rete";
+
+    pub const RELAY_ALERT: &str = r"🔌 *Avviso connessione relay*
+
+⚠️ Relay disconnessi: {{ failed_count }}
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}✅ Relay connessi: {{ connected_count }}
+🔄 Tentativo di riconnessione\.\.\.";
+
+    pub const STARTUP: &str = r"🐕 *mostro\-watchdog* è online e monitora le dispute\.
+
+📊 Heartbeat: {% if heartbeat_interval %}ogni {{ heartbeat_interval }} secondi{% else %}disattivato{% endif %}
+🔔 Avviso di silenzio: {% if event_alert_threshold %}{{ event_alert_threshold }} secondi{% else %}disattivato{% endif %}{% if self_test_failure %}
+❌ Autotest fallito in {{ self_test_failure }}{% elif self_test %}
+🧪 Autotest superato{% endif %}";
+
+    pub const DIGEST: &str = r"📦 *{{ count }} nuovi avvisi di disputa*";
+
+    pub const ESCALATION: &str = r"⏰ *DISPUTA NON GESTITA*
+
+Nessuno ha preso in carico questa disputa da {{ minutes }} minuti\.
+
+{{ alert | safe }}";
+
+    pub const ONCALL: &str = r"{% if name %}📟 *Reperibile:* {{ name }}
+🔁 *Fino a:* {{ until }}
+⏭️ *Prossimo:* {{ next }}{% else %}📟 Nessuna rotazione di reperibilità configurata\.{% endif %}";
+
+    pub const UNAUTHORIZED: &str = r"⛔ Non sei autorizzato a usare questo comando\.";
+
+    pub const UNAUTHORIZED_REPORT: &str = r"⚠️ *Comando non autorizzato*
+
+👤 *Utente:* {{ user }} \(`{{ user_id | code }}`\)
+💬 *Comando:* `{{ command | code }}`";
+
+    pub const HEALTH: &str = r"{% if healthy %}✅{% else %}⚠️{% endif %} *mostro\-watchdog {{ version }}* \- {% if healthy %}sano{% else %}non sano{% endif %}
+
+⏰ Uptime: {{ uptime_hours }} ore {{ uptime_minutes }} minuti
+📊 Eventi elaborati: {{ events_processed }}
+📥 Ultimo evento: {% if last_event %}{{ last_event }}{% else %}mai{% endif %}
+💓 Ultimo heartbeat: {% if last_heartbeat %}{{ last_heartbeat }}{% else %}mai{% endif %}";
+
+    pub const HEARTBEAT_FAILED: &str = r"❌ Invio dell'heartbeat non riuscito: {{ error }}";
+
+    pub const UPDATE_AVAILABLE: &str = r"🆕 *Aggiornamento disponibile*
+
+È stato rilasciato mostro\-watchdog {{ latest }} \(versione attuale: {{ version }}\)\.
+🔗 {{ url }}";
+
+    pub const VERSION: &str = r"🐕 *mostro\-watchdog {{ version }}*
+
+📝 Commit: `{{ commit | code }}`
+🖥️ Piattaforma: {{ target }}
+🔧 Profilo: {{ profile }}";
+
+    pub const STATS: &str = r"📈 *Statistiche* \(attivo da {{ uptime_hours }}h {{ uptime_minutes }}m\)
+```
+{{ table | code }}
+```";
+
+    pub const TASK_RESTARTED: &str = r"🔁 *Attività riavviata*
+
+⚙️ Attività: {{ task }}
+❗ Motivo: {{ reason }}
+🔢 Riavvii finora: {{ restarts }}";
+
+    pub const CLOCK_DRIFT: &str = r"🕰️ *Deriva dell'orologio rilevata*
+
+⏱️ Orologio locale {% if behind %}indietro{% else %}avanti{% endif %} di {{ drift }}s
+📡 Misurato rispetto a: {% if from_relays %}orologi dei relay{% else %}timestamp degli eventi{% endif %}
+📏 Soglia: {{ threshold }}s
+
+Alcuni eventi potrebbero andare persi\. Controlla la sincronizzazione dell'ora del server \(NTP\)\.";
+
+    pub const MEMORY_ALERT: &str = r"🧠 *Utilizzo di memoria elevato*
+
+💾 Memoria: {{ rss_mb }} MB \(limite {{ limit_mb }} MB\)
+📂 Descrittori di file: {% if open_fds %}{{ open_fds }}{% else %}sconosciuto{% endif %}
+⚙️ Attività: {{ tasks }}";
+
+    pub const DEGRADED: &str = r"⚠️ *Watchdog degradato*
+
+{% if relays_down %}🔌 Tutti i relay disconnessi
+{% endif %}{% if queue_backlog %}📬 Coda delle notifiche congestionata: {{ queue_backlog }} messaggi in attesa
+{% endif %}{% if store_error %}💾 Archivio delle dispute non scrivibile: {{ store_error }}
+{% endif %}
+Gli avvisi potrebbero subire ritardi o andare persi finché non si risolve\.";
+
+    pub const DEGRADED_RECOVERED: &str = r"✅ *Watchdog ripristinato*
+
+Tutti i controlli interni sono di nuovo superati dopo {{ minutes }} minuti di degrado\.";
+
+    pub const RELAY_LATENCY: &str = r"🐢 *Relay lento*
+
+📡 Relay: `{{ relay | code }}`
+⏱️ Latenza di propagazione: p50 {{ p50 }}s, p90 {{ p90 }}s, p99 {{ p99 }}s
+📏 Soglia: p90 {{ threshold }}s
+
+Gli eventi da questo relay arrivano in ritardo; potrebbe essere sovraccarico\.";
+
+    pub const RELAY_BLACKLISTED: &str = r"🚫 *Relay in lista nera*
+
+📡 Relay: `{{ relay | code }}`
+📉 Punteggio: {{ score }}/100 \(soglia {{ threshold }}\)
+
+Il relay si disconnette spesso o non consegna gli eventi di disputa, quindi è stato rimosso dal pool\. Verrà riprovato tra {{ retry_minutes }} minuti\.";
+
+    pub const RELAY_PING: &str = r"{% if rtt_ms %}🐌 *Relay lento a rispondere*{% else %}⏳ *Il relay non risponde*{% endif %}
+
+📡 Relay: `{{ relay | code }}`
+{% if rtt_ms %}⏱️ Andata e ritorno: {{ rtt_ms }} ms
+📏 Soglia: {{ threshold_ms }} ms{% else %}❌ {{ failures }} ping consecutivi falliti: `{{ error | code }}`{% endif %}
+
+Il relay è connesso ma lento a rispondere alle richieste\.";
+
+    pub const FALLBACK_RELAYS: &str = r"{% if engaged %}⚠️ *Modalità degradata: relay di riserva in uso*
+
+Tutti i {{ primary_count }} relay principali sono giù da {{ minutes }} minuti\. Monitoraggio delle dispute tramite:
+{% for url in relays %}  • {{ url }}
+{% endfor %}
+Gli avvisi potrebbero arrivare in ritardo o incompleti finché un relay principale non torna\.{% else %}✅ *Relay principali di nuovo attivi*
+
+Un relay principale si è riconnesso e i relay di riserva sono stati rilasciati\.{% endif %}";
+
+    pub const RELAY_QUORUM: &str = r"{% if lost %}🚨 *Quorum dei relay perso*
+
+Solo {{ connected_count }} relay su {{ relay_count }} connessi, sotto il minimo di {{ min_connected }}\. Alcune dispute potrebbero sfuggire\.
+Disconnessi:
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}{% else %}✅ *Quorum dei relay ripristinato*
+
+{{ connected_count }} relay su {{ relay_count }} connessi \(minimo {{ min_connected }}\)\.{% endif %}";
+
+    pub const RELAY_RECONNECTED: &str = r"🔌 *Relay riconnesso*
+
+✅ {{ relay }} è tornato dopo {% if minutes %}{{ minutes }} minuti{% else %}meno di un minuto{% endif %}\.
+Relay connessi: {{ connected_count }}";
+
+    pub const DEAD_SUBSCRIPTION: &str = r"🔕 *Sottoscrizione alle dispute ricreata*
+
+{{ relay }} {% if closed %}ha chiuso la sottoscrizione alle dispute: {{ closed }}{% else %}ha smesso di consegnare dispute pur essendo connesso \({{ missed }} perse che altri relay hanno consegnato\){% endif %}\.
+{% if error %}❌ Impossibile ricrearla: {{ error }}{% else %}✅ Sottoscritta di nuovo dall'ultima disputa consegnata\.{% endif %}";
+
+    pub const CONFIG_RELOADED: &str = r"{% if error %}❌ *Ricaricamento della configurazione non riuscito*
+
+`{{ error | code }}`
+
+La configurazione precedente resta in vigore\.{% else %}🔄 *Configurazione ricaricata*
+
+✅ Applicato: {% if applied %}{{ applied | join(sep=', ') }}{% else %}nulla{% endif %}{% if restart_required %}
+♻️ Richiede un riavvio: {{ restart_required | join(sep=', ') }}{% endif %}{% endif %}";
+
+    pub const ANNOUNCEMENT: &str = r"📢 *Annuncio*
+
+{{ message }}";
+
+    pub const SHUTDOWN: &str = r"🔌 *mostro\-watchdog* sta andando offline\. Le dispute non sono monitorate finché non viene riavviato\.
+
+⏱️ Attivo da: {{ uptime_hours }}h {{ uptime_minutes }}m
+📨 Eventi elaborati: {{ events_processed }}";
+
+    pub const CRASHED: &str = r"💥 *mostro\-watchdog si è arrestato*
+
+❗ {{ reason }}
+
+Le dispute non sono monitorate finché non viene riavviato\.";
+
+    pub const FLOOD: &str = r"🌊 *Valanga di avvisi*
+
+{{ count }} aggiornamenti di dispute dell'ultimo minuto non sono stati inviati singolarmente\. Gli avvisi singoli riprendono quando il ritmo cala; l'endpoint `/disputes` elenca le dispute aperte\.";
+
+    pub const EVENT_LOOP_RESTARTED: &str = r"🧊 *Ciclo di eventi Nostr riavviato*
+
+Nessun messaggio dei relay è stato gestito per {{ idle_seconds }}s mentre {{ relays | length }} relay risultavano connessi\. È stato avviato un nuovo ciclo di eventi e questi relay sono stati riconnessi:
+{% for url in relays %}  • {{ url }}
+{% endfor %}{% if error %}
+❌ Riconnessione fallita per {{ error }}{% endif %}";
+
+    pub const TEMPLATES: &[(&str, &str)] = &[
+        ("initiated", INITIATED),
+        ("in_progress", IN_PROGRESS),
+        ("seller_refunded", SELLER_REFUNDED),
+        ("settled", SETTLED),
+        ("released", RELEASED),
+        ("other", OTHER),
+        ("heartbeat", HEARTBEAT),
+        ("event_silence", EVENT_SILENCE),
+        ("relay_alert", RELAY_ALERT),
+        ("startup", STARTUP),
+        ("digest", DIGEST),
+        ("escalation", ESCALATION),
+        ("oncall", ONCALL),
+        ("unauthorized", UNAUTHORIZED),
+        ("unauthorized_report", UNAUTHORIZED_REPORT),
+        ("health", HEALTH),
+        ("heartbeat_failed", HEARTBEAT_FAILED),
+        ("update_available", UPDATE_AVAILABLE),
+        ("version", VERSION),
+        ("stats", STATS),
+        ("task_restarted", TASK_RESTARTED),
+        ("clock_drift", CLOCK_DRIFT),
+        ("memory_alert", MEMORY_ALERT),
+        ("degraded", DEGRADED),
+        ("degraded_recovered", DEGRADED_RECOVERED),
+        ("relay_latency", RELAY_LATENCY),
+        ("relay_blacklisted", RELAY_BLACKLISTED),
+        ("relay_ping", RELAY_PING),
+        ("fallback_relays", FALLBACK_RELAYS),
+        ("relay_quorum", RELAY_QUORUM),
+        ("relay_reconnected", RELAY_RECONNECTED),
+        ("dead_subscription", DEAD_SUBSCRIPTION),
+        ("config_reloaded", CONFIG_RELOADED),
+        ("announcement", ANNOUNCEMENT),
+        ("shutdown", SHUTDOWN),
+        ("crashed", CRASHED),
+        ("flood", FLOOD),
+        ("event_loop_restarted", EVENT_LOOP_RESTARTED),
+    ];
+}
+
+/// German
+mod de {
+    pub const INITIATED: &str = r"🚨 *NEUER DISPUT*
+
+📋 *Disput\-ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+👤 *Eröffnet von:* {{ initiator }}
+⏰ *Zeit:* {{ time }}{% if oncall %}
+📟 *Bereitschaft:* {{ oncall | safe }}{% endif %}
+
+⚡ Bitte übernimm diesen Disput in Mostrix oder deinem Admin\-Client\.";
+
+    pub const IN_PROGRESS: &str = r"🔄 *DISPUT IN BEARBEITUNG*
+
+📋 *Disput\-ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Übernommen von:* `{{ solver | code }}`{% endif %}
+⏰ *Zeit:* {{ time }}
+
+ℹ️ Der Disput wird jetzt bearbeitet\.";
+
+    pub const SELLER_REFUNDED: &str = r"💰 *DISPUT GELÖST \- VERKÄUFER ERSTATTET*
+
+📋 *Disput\-ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Gelöst von:* `{{ solver | code }}`{% endif %}
+⏰ *Zeit:* {{ time }}
+
+✔️ Disput geschlossen: Gelder an den Verkäufer zurückerstattet\.";
+
+    pub const SETTLED: &str = r"✅ *DISPUT GELÖST \- ABGEWICKELT*
+
+📋 *Disput\-ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}{% if solver %}
+👨‍⚖️ *Gelöst von:* `{{ solver | code }}`{% endif %}
+⏰ *Zeit:* {{ time }}
+
+✔️ Disput geschlossen: Käufer erhält die Zahlung\.";
+
+    pub const RELEASED: &str = r"🔓 *DISPUT GELÖST \- FREIGEGEBEN*
+
+📋 *Disput\-ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+🤝 *Lösung:* Vom Verkäufer freigegeben
+⏰ *Zeit:* {{ time }}
+
+✔️ Disput geschlossen: Handel abgeschlossen\.";
+
+    pub const OTHER: &str = r"📡 *DISPUT\-STATUSÄNDERUNG*
+
+📋 *Disput\-ID:* `{{ dispute_id | code }}`{% if mostro %}
+🏷️ *Mostro:* `{{ mostro | code }}`{% endif %}
+📊 *Status:* {{ status }}
+⏰ *Zeit:* {{ time }}
+
+ℹ️ Status geändert\.";
+
+    pub const HEARTBEAT: &str = r"💓 *Statusprüfung*
+
+{% if healthy %}✅ System: Online{% else %}⚠️ System: Zustellungen schlagen fehl{% endif %}
+⏰ Laufzeit: {{ uptime_hours }} Stunden {{ uptime_minutes }} Minuten
+📊 Verarbeitete Ereignisse: {{ events_processed }}
+📨 Zustellungen: {{ delivered }} gesendet, {{ failed }} fehlgeschlagen
+🔌 Relays: {{ relays_connected }}/{{ relays_total }} verbunden
+⚖️ Offene Dispute: {% if open_disputes is number %}{{ open_disputes }}{% else %}unbekannt{% endif %}
+🕐 Letzter Disput: {% if last_dispute_hours is number %}vor {{ last_dispute_hours }}h {{ last_dispute_minutes }}m{% else %}noch keiner{% endif %}
+🔔 Status: Überwachung aktiv";
+
+    pub const EVENT_SILENCE: &str = r"⚠️ *Warnung: Keine Ereignisse*
+
+🔕 Seit {{ threshold_hours }} Stunden keine Disput\-Ereignisse empfangen
+⏰ Systemlaufzeit: {{ uptime_hours }} Stunden {{ uptime_minutes }} Minuten
+🔍 Bitte prüfen:
+• Status des Mostro\-Daemons
+• Nostr\-Relay\-Verbindungen
+• Netzwerkverbindung";
+
+    pub const RELAY_ALERT: &str = r"🔌 *Relay\-Verbindungswarnung*
+
+⚠️ Getrennte Relays: {{ failed_count }}
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}✅ Verbundene Relays: {{ connected_count }}
+🔄 Verbindung wird wiederhergestellt\.\.\.";
+
+    pub const STARTUP: &str = r"🐕 *mostro\-watchdog* ist online und überwacht Dispute\.
+
+📊 Heartbeat: {% if heartbeat_interval %}alle {{ heartbeat_interval }} Sekunden{% else %}deaktiviert{% endif %}
+🔔 Warnung bei Stille: {% if event_alert_threshold %}{{ event_alert_threshold }} Sekunden{% else %}deaktiviert{% endif %}{% if self_test_failure %}
+❌ Selbsttest fehlgeschlagen bei {{ self_test_failure }}{% elif self_test %}
+🧪 Selbsttest bestanden{% endif %}";
+
+    pub const DIGEST: &str = r"📦 *{{ count }} neue Disput\-Warnungen*";
+
+    pub const ESCALATION: &str = r"⏰ *UNBEARBEITETER DISPUT*
+
+Niemand hat diesen Disput seit {{ minutes }} Minuten übernommen\.
+
+{{ alert | safe }}";
+
+    pub const ONCALL: &str = r"{% if name %}📟 *Bereitschaft:* {{ name }}
+🔁 *Bis:* {{ until }}
+⏭️ *Als Nächstes:* {{ next }}{% else %}📟 Keine Bereitschaftsrotation konfiguriert\.{% endif %}";
+
+    pub const UNAUTHORIZED: &str = r"⛔ Du bist nicht berechtigt, diesen Befehl zu verwenden\.";
+
+    pub const UNAUTHORIZED_REPORT: &str = r"⚠️ *Nicht autorisierter Befehl*
+
+👤 *Benutzer:* {{ user }} \(`{{ user_id | code }}`\)
+💬 *Befehl:* `{{ command | code }}`";
+
+    pub const HEALTH: &str = r"{% if healthy %}✅{% else %}⚠️{% endif %} *mostro\-watchdog {{ version }}* \- {% if healthy %}gesund{% else %}gestört{% endif %}
+
+⏰ Laufzeit: {{ uptime_hours }} Stunden {{ uptime_minutes }} Minuten
+📊 Verarbeitete Ereignisse: {{ events_processed }}
+📥 Letztes Ereignis: {% if last_event %}{{ last_event }}{% else %}nie{% endif %}
+💓 Letzter Heartbeat: {% if last_heartbeat %}{{ last_heartbeat }}{% else %}nie{% endif %}";
+
+    pub const HEARTBEAT_FAILED: &str = r"❌ Heartbeat konnte nicht gesendet werden: {{ error }}";
+
+    pub const UPDATE_AVAILABLE: &str = r"🆕 *Update verfügbar*
+
+mostro\-watchdog {{ latest }} ist erschienen \(laufende Version: {{ version }}\)\.
+🔗 {{ url }}";
+
+    pub const VERSION: &str = r"🐕 *mostro\-watchdog {{ version }}*
+
+📝 Commit: `{{ commit | code }}`
+🖥️ Zielplattform: {{ target }}
+🔧 Profil: {{ profile }}";
+
+    pub const STATS: &str = r"📈 *Statistik* \(Laufzeit {{ uptime_hours }}h {{ uptime_minutes }}m\)
+```
+{{ table | code }}
+```";
+
+    pub const TASK_RESTARTED: &str = r"🔁 *Task neu gestartet*
+
+⚙️ Task: {{ task }}
+❗ Grund: {{ reason }}
+🔢 Bisherige Neustarts: {{ restarts }}";
+
+    pub const CLOCK_DRIFT: &str = r"🕰️ *Uhrabweichung erkannt*
+
+⏱️ Lokale Uhr geht {{ drift }}s {% if behind %}nach{% else %}vor{% endif %}
+📡 Gemessen an: {% if from_relays %}Relay\-Uhren{% else %}Event\-Zeitstempeln{% endif %}
+📏 Schwellenwert: {{ threshold }}s
+
+Events können verpasst werden\. Prüfe die Zeitsynchronisation des Servers \(NTP\)\.";
+
+    pub const MEMORY_ALERT: &str = r"🧠 *Hoher Speicherverbrauch*
+
+💾 Speicher: {{ rss_mb }} MB \(Limit {{ limit_mb }} MB\)
+📂 Dateideskriptoren: {% if open_fds %}{{ open_fds }}{% else %}unbekannt{% endif %}
+⚙️ Tasks: {{ tasks }}";
+
+    pub const DEGRADED: &str = r"⚠️ *Watchdog beeinträchtigt*
+
+{% if relays_down %}🔌 Alle Relays getrennt
+{% endif %}{% if queue_backlog %}📬 Benachrichtigungswarteschlange überlastet: {{ queue_backlog }} Nachrichten wartend
+{% endif %}{% if store_error %}💾 Disput\-Speicher nicht beschreibbar: {{ store_error }}
+{% endif %}
+Warnungen können verzögert werden oder verloren gehen, bis dies behoben ist\.";
+
+    pub const DEGRADED_RECOVERED: &str = r"✅ *Watchdog wiederhergestellt*
+
+Alle Selbsttests sind nach {{ minutes }} Minuten Beeinträchtigung wieder erfolgreich\.";
+
+    pub const RELAY_LATENCY: &str = r"🐢 *Langsames Relay*
+
+📡 Relay: `{{ relay | code }}`
+⏱️ Übertragungslatenz: p50 {{ p50 }}s, p90 {{ p90 }}s, p99 {{ p99 }}s
+📏 Schwellenwert: p90 {{ threshold }}s
+
+Events von diesem Relay kommen verspätet an; es ist möglicherweise überlastet\.";
+
+    pub const RELAY_BLACKLISTED: &str = r"🚫 *Relay gesperrt*
+
+📡 Relay: `{{ relay | code }}`
+📉 Bewertung: {{ score }}/100 \(Schwellenwert {{ threshold }}\)
+
+Das Relay trennt sich häufig oder liefert keine Dispute\-Events, daher wurde es aus dem Pool genommen\. Es wird in {{ retry_minutes }} Minuten erneut versucht\.";
+
+    pub const RELAY_PING: &str = r"{% if rtt_ms %}🐌 *Relay antwortet langsam*{% else %}⏳ *Relay antwortet nicht*{% endif %}
+
+📡 Relay: `{{ relay | code }}`
+{% if rtt_ms %}⏱️ Umlaufzeit: {{ rtt_ms }} ms
+📏 Schwellenwert: {{ threshold_ms }} ms{% else %}❌ {{ failures }} Pings in Folge fehlgeschlagen: `{{ error | code }}`{% endif %}
+
+Das Relay ist verbunden, beantwortet Anfragen aber nur langsam\.";
+
+    pub const FALLBACK_RELAYS: &str = r"{% if engaged %}⚠️ *Eingeschränkter Modus: Ersatz\-Relays aktiv*
+
+Alle {{ primary_count }} primären Relays sind seit {{ minutes }} Minuten nicht erreichbar\. Dispute werden überwacht über:
+{% for url in relays %}  • {{ url }}
+{% endfor %}
+Benachrichtigungen können sich verzögern oder unvollständig sein, bis ein primäres Relay zurück ist\.{% else %}✅ *Primäre Relays wieder da*
+
+Ein primäres Relay hat sich wieder verbunden, die Ersatz\-Relays wurden freigegeben\.{% endif %}";
+
+    pub const RELAY_QUORUM: &str = r"{% if lost %}🚨 *Relay\-Quorum verloren*
+
+Nur {{ connected_count }} von {{ relay_count }} Relays verbunden, weniger als das Minimum von {{ min_connected }}\. Dispute können verpasst werden\.
+Getrennt:
+{% for url in failed_relays %}  • {{ url }}
+{% endfor %}{% else %}✅ *Relay\-Quorum wiederhergestellt*
+
+{{ connected_count }} von {{ relay_count }} Relays verbunden \(Minimum {{ min_connected }}\)\.{% endif %}";
+
+    pub const RELAY_RECONNECTED: &str = r"🔌 *Relay wieder verbunden*
+
+✅ {{ relay }} ist nach {% if minutes %}{{ minutes }} Minuten{% else %}weniger als einer Minute{% endif %} zurück\.
+Verbundene Relays: {{ connected_count }}";
+
+    pub const DEAD_SUBSCRIPTION: &str = r"🔕 *Dispute\-Abonnement neu erstellt*
+
+{{ relay }} {% if closed %}hat das Dispute\-Abonnement geschlossen: {{ closed }}{% else %}liefert trotz Verbindung keine Dispute mehr \({{ missed }} verpasst, die andere Relays geliefert haben\){% endif %}\.
+{% if error %}❌ Neu erstellen fehlgeschlagen: {{ error }}{% else %}✅ Erneut abonniert ab dem letzten gelieferten Dispute\.{% endif %}";
+
+    pub const CONFIG_RELOADED: &str = r"{% if error %}❌ *Neuladen der Konfiguration fehlgeschlagen*
+
+`{{ error | code }}`
+
+Die bisherige Konfiguration bleibt aktiv\.{% else %}🔄 *Konfiguration neu geladen*
+
+✅ Übernommen: {% if applied %}{{ applied | join(sep=', ') }}{% else %}nichts{% endif %}{% if restart_required %}
+♻️ Neustart erforderlich für: {{ restart_required | join(sep=', ') }}{% endif %}{% endif %}";
+
+    pub const ANNOUNCEMENT: &str = r"📢 *Ankündigung*
+
+{{ message }}";
+
+    pub const SHUTDOWN: &str = r"🔌 *mostro\-watchdog* geht offline\. Dispute werden bis zum Neustart nicht überwacht\.
+
+⏱️ Laufzeit: {{ uptime_hours }}h {{ uptime_minutes }}m
+📨 Verarbeitete Ereignisse: {{ events_processed }}";
+
+    pub const CRASHED: &str = r"💥 *mostro\-watchdog ist abgestürzt*
+
+❗ {{ reason }}
+
+Dispute werden bis zum Neustart nicht überwacht\.";
+
+    pub const FLOOD: &str = r"🌊 *Alarmflut*
+
+{{ count }} Streitfall\-Aktualisierungen der letzten Minute wurden nicht einzeln gesendet\. Einzelne Alarme werden fortgesetzt, sobald die Rate sinkt; offene Streitfälle listet der Endpunkt `/disputes` auf\.";
+
+    pub const EVENT_LOOP_RESTARTED: &str = r"🧊 *Nostr\-Ereignisschleife neu gestartet*
+
+{{ idle_seconds }}s lang wurde keine Relay\-Nachricht verarbeitet, obwohl {{ relays | length }} Relay\(s\) als verbunden galten\. Eine neue Ereignisschleife wurde gestartet und diese Relays neu verbunden:
+{% for url in relays %}  • {{ url }}
+{% endfor %}{% if error %}
+❌ Neuverbindung fehlgeschlagen für {{ error }}{% endif %}";
+
+    pub const TEMPLATES: &[(&str, &str)] = &[
+        ("initiated", INITIATED),
+        ("in_progress", IN_PROGRESS),
+        ("seller_refunded", SELLER_REFUNDED),
+        ("settled", SETTLED),
+        ("released", RELEASED),
+        ("other", OTHER),
+        ("heartbeat", HEARTBEAT),
+        ("event_silence", EVENT_SILENCE),
+        ("relay_alert", RELAY_ALERT),
+        ("startup", STARTUP),
+        ("digest", DIGEST),
+        ("escalation", ESCALATION),
+        ("oncall", ONCALL),
+        ("unauthorized", UNAUTHORIZED),
+        ("unauthorized_report", UNAUTHORIZED_REPORT),
+        ("health", HEALTH),
+        ("heartbeat_failed", HEARTBEAT_FAILED),
+        ("update_available", UPDATE_AVAILABLE),
+        ("version", VERSION),
+        ("stats", STATS),
+        ("task_restarted", TASK_RESTARTED),
+        ("clock_drift", CLOCK_DRIFT),
+        ("memory_alert", MEMORY_ALERT),
+        ("degraded", DEGRADED),
+        ("degraded_recovered", DEGRADED_RECOVERED),
+        ("relay_latency", RELAY_LATENCY),
+        ("relay_blacklisted", RELAY_BLACKLISTED),
+        ("relay_ping", RELAY_PING),
+        ("fallback_relays", FALLBACK_RELAYS),
+        ("relay_quorum", RELAY_QUORUM),
+        ("relay_reconnected", RELAY_RECONNECTED),
+        ("dead_subscription", DEAD_SUBSCRIPTION),
+        ("config_reloaded", CONFIG_RELOADED),
+        ("announcement", ANNOUNCEMENT),
+        ("shutdown", SHUTDOWN),
+        ("crashed", CRASHED),
+        ("flood", FLOOD),
+        ("event_loop_restarted", EVENT_LOOP_RESTARTED),
+    ];
+}
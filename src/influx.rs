@@ -0,0 +1,266 @@
+//! Optional InfluxDB export in line protocol.
+//!
+//! Dispute, delivery and health measurements are written periodically so Grafana
+//! can chart long-term trends. Counters are written as running totals since
+//! startup; use `difference()` or `non_negative_derivative()` for rates. Both the
+//! v2 API (org, bucket and token) and v1 (database, optional credentials) are
+//! supported.
+
+use crate::config::InfluxConfig;
+use crate::pipeline;
+use crate::stats::{stats, Counters};
+use crate::HealthMonitor;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// One line-protocol point
+struct Point {
+    measurement: &'static str,
+    tags: Vec<(&'static str, String)>,
+    fields: Vec<(&'static str, Field)>,
+}
+
+enum Field {
+    Int(u64),
+    Bool(bool),
+}
+
+impl Point {
+    fn new(measurement: &'static str) -> Self {
+        Self {
+            measurement,
+            tags: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    fn tag(mut self, key: &'static str, value: &str) -> Self {
+        self.tags.push((key, value.to_string()));
+        self
+    }
+
+    fn int(mut self, key: &'static str, value: u64) -> Self {
+        self.fields.push((key, Field::Int(value)));
+        self
+    }
+
+    fn bool(mut self, key: &'static str, value: bool) -> Self {
+        self.fields.push((key, Field::Bool(value)));
+        self
+    }
+
+    fn line(&self, timestamp: u64) -> String {
+        let mut line = escape(self.measurement, &[',', ' ']);
+        for (key, value) in &self.tags {
+            line.push(',');
+            line.push_str(key);
+            line.push('=');
+            line.push_str(&escape(value, &[',', '=', ' ']));
+        }
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(key, value)| match value {
+                Field::Int(value) => format!("{key}={value}i"),
+                Field::Bool(value) => format!("{key}={value}"),
+            })
+            .collect();
+        format!("{} {} {}", line, fields.join(","), timestamp)
+    }
+}
+
+/// Backslash-escape the characters line protocol treats as separators
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Points for the dispute and delivery counters
+fn counter_points(counters: &Counters) -> Vec<Point> {
+    let mut points = Vec::new();
+    for (status, count) in &counters.events {
+        points.push(
+            Point::new("mostro_watchdog_events")
+                .tag("status", status)
+                .int("count", *count),
+        );
+    }
+    for (status, count) in &counters.resolutions {
+        let seconds = counters
+            .resolution_seconds
+            .get(status)
+            .copied()
+            .unwrap_or_default();
+        points.push(
+            Point::new("mostro_watchdog_resolutions")
+                .tag("status", status)
+                .int("count", *count)
+                .int("total_seconds", seconds),
+        );
+    }
+    for (cache, count) in &counters.evictions {
+        points.push(
+            Point::new("mostro_watchdog_cache_evictions")
+                .tag("cache", cache)
+                .int("count", *count),
+        );
+    }
+    for (channel, deliveries) in counters.deliveries() {
+        points.push(
+            Point::new("mostro_watchdog_deliveries")
+                .tag("channel", &channel)
+                .int("sent", deliveries.sent)
+                .int("failed", deliveries.failed),
+        );
+    }
+    points
+}
+
+/// Points for the watchdog's own health and each relay
+async fn health_points(health_monitor: &HealthMonitor, counters: &Counters) -> Vec<Point> {
+    let status = health_monitor.status();
+    let mut health = Point::new("mostro_watchdog_health")
+        .bool("healthy", status.healthy)
+        .int("uptime", status.uptime_secs)
+        .int("events_processed", status.events_processed)
+        .int("relay_reconnects", counters.relay_reconnects)
+        .int("event_queue", pipeline::depth() as u64)
+        .int("events_dropped", counters.events_dropped)
+        .int("events_future", counters.events_future);
+    if let Some(open) = health_monitor.open_disputes().await {
+        health = health.int("open_disputes", open);
+    }
+    if let Some(rss) = status.resources.rss_bytes {
+        health = health.int("memory", rss);
+    }
+
+    let mut points = vec![health];
+    for relay in &status.relays {
+        let mut point = Point::new("mostro_watchdog_relay")
+            .tag("relay", &relay.url)
+            .bool("connected", relay.status == "connected")
+            .int("messages", relay.messages_received);
+        if let Some(latency) = relay.latency {
+            point = point.int("latency_p90", latency.p90);
+        }
+        if let Some(rtt) = relay.rtt_ms {
+            point = point.int("rtt_ms", rtt);
+        }
+        points.push(point);
+    }
+    points
+}
+
+/// Write URL and authentication for the configured API version
+fn write_request(http: &reqwest::Client, config: &InfluxConfig) -> reqwest::RequestBuilder {
+    let base = config.url.trim_end_matches('/');
+    match config.bucket {
+        Some(ref bucket) => {
+            let request = http.post(format!("{base}/api/v2/write")).query(&[
+                ("org", config.org.as_deref().unwrap_or_default()),
+                ("bucket", bucket.as_str()),
+                ("precision", "s"),
+            ]);
+            match config.token {
+                Some(ref token) => request.header("Authorization", format!("Token {token}")),
+                None => request,
+            }
+        }
+        None => {
+            let mut query = vec![
+                ("db", config.database.clone().unwrap_or_default()),
+                ("precision", "s".to_string()),
+            ];
+            if let Some(ref rp) = config.retention_policy {
+                query.push(("rp", rp.clone()));
+            }
+            if let Some(ref username) = config.username {
+                query.push(("u", username.clone()));
+            }
+            if let Some(ref password) = config.password {
+                query.push(("p", password.clone()));
+            }
+            http.post(format!("{base}/write")).query(&query)
+        }
+    }
+}
+
+/// Write measurements every `interval` seconds until shutdown
+pub async fn run(config: InfluxConfig, health_monitor: Arc<HealthMonitor>) {
+    info!("📈 Writing InfluxDB metrics to {}", config.url);
+    let http = reqwest::Client::new();
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        let counters = stats().snapshot();
+        let mut points = counter_points(&counters);
+        points.extend(health_points(&health_monitor, &counters).await);
+        let body: Vec<String> = points.iter().map(|p| p.line(timestamp)).collect();
+
+        let result = write_request(&http, &config)
+            .body(body.join("\n"))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            warn!("Failed to write InfluxDB metrics: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AlertSeverity;
+    use crate::stats::Stats;
+
+    #[test]
+    fn test_line() {
+        let point = Point::new("mostro_watchdog_relay")
+            .tag("relay", "wss://relay one,x=y")
+            .bool("connected", true)
+            .int("messages", 42);
+        assert_eq!(
+            point.line(1700000000),
+            r"mostro_watchdog_relay,relay=wss://relay\ one\,x\=y connected=true,messages=42i 1700000000"
+        );
+    }
+
+    #[test]
+    fn test_counter_points() {
+        let stats = Stats::default();
+        stats.record_event("initiated", AlertSeverity::Critical);
+        stats.record_event("initiated", AlertSeverity::Critical);
+        stats.record_resolution("settled", 600);
+        stats.record_sent("-100123");
+        stats.record_failure("pushover");
+
+        let lines: Vec<String> = counter_points(&stats.snapshot())
+            .iter()
+            .map(|p| p.line(1))
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "mostro_watchdog_events,status=initiated count=2i 1",
+                "mostro_watchdog_resolutions,status=settled count=1i,total_seconds=600i 1",
+                "mostro_watchdog_deliveries,channel=-100123 sent=1i,failed=0i 1",
+                "mostro_watchdog_deliveries,channel=pushover sent=0i,failed=1i 1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! `mostro-watchdog init [config]`: interactively write a minimal config.
+//!
+//! Prompts for the Mostro pubkey, relays, bot token and chat id, checking each
+//! answer as it goes. The chat id, which new operators most often get wrong, can be
+//! detected from a message sent to the bot.
+
+use crate::config::{self, check_relay_url, Config};
+use nostr_sdk::prelude::*;
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+
+/// Relay suggested when none is entered
+const DEFAULT_RELAY: &str = "wss://relay.mostro.network";
+/// Seconds to wait for a message when detecting the chat id
+const DETECT_TIMEOUT: u32 = 120;
+/// Seconds each `getUpdates` call waits
+const POLL_TIMEOUT: u32 = 10;
+
+/// Prompt for the settings and write them to `path`. Returns whether a config was written.
+pub async fn run(path: &Path) -> bool {
+    println!("🐕 mostro-watchdog setup\n");
+    if path.exists()
+        && !confirm(
+            &format!("{} already exists. Overwrite it?", path.display()),
+            false,
+        )
+    {
+        println!("Nothing written.");
+        return false;
+    }
+
+    let pubkey = prompt_until("Mostro pubkey (npub, nprofile or hex)", None, |answer| {
+        config::parse_pubkey(answer)
+            .map(|_| answer.to_string())
+            .map_err(|_| "not an npub, nprofile or hex public key".to_string())
+    });
+
+    let relays = prompt_until("Relays, comma-separated", Some(DEFAULT_RELAY), |answer| {
+        answer
+            .split(',')
+            .map(str::trim)
+            .filter(|relay| !relay.is_empty())
+            .map(|relay| {
+                check_relay_url(relay)
+                    .map(|()| relay.to_string())
+                    .map_err(|problem| format!("{relay} {problem}"))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|relays| match relays.is_empty() {
+                true => Err("at least one relay is needed".to_string()),
+                false => Ok(relays),
+            })
+    });
+
+    let (bot, bot_token) = loop {
+        let token = prompt_until("Telegram bot token (from @BotFather)", None, |answer| {
+            Ok(answer.to_string())
+        });
+        let bot = Bot::new(&token);
+        match bot.get_me().await {
+            Ok(me) => {
+                println!("  ✔ Connected as @{}", me.username());
+                break (bot, token);
+            }
+            Err(e) => println!("  ✘ Telegram rejected the token: {e}"),
+        }
+    };
+
+    let mut chat_id = None;
+    if confirm("Detect the chat id from a message sent to the bot?", true) {
+        chat_id = detect_chat_id(&bot).await;
+    }
+    let chat_id = match chat_id {
+        Some(chat_id) => chat_id,
+        None => prompt_until("Chat id (groups start with -100)", None, |answer| {
+            answer
+                .parse::<i64>()
+                .map_err(|_| "not a number".to_string())
+        }),
+    };
+
+    if let Err(e) = write_private(path, &render(&pubkey, &relays, &bot_token, chat_id)) {
+        eprintln!("❌ Failed to write {}: {}", path.display(), e);
+        return false;
+    }
+    // Catch anything the prompts missed before the operator first starts the watchdog
+    if let Err(e) = Config::load(path) {
+        eprintln!("❌ Wrote {} but it doesn't load: {}", path.display(), e);
+        return false;
+    }
+    println!(
+        "\n✅ Wrote {}. See config.example.toml for every other option, then run:\n    mostro-watchdog --config {}",
+        path.display(),
+        path.display()
+    );
+    true
+}
+
+/// Wait for the first message the bot receives and offer its chat
+async fn detect_chat_id(bot: &Bot) -> Option<i64> {
+    println!(
+        "  Add the bot to your alert group (or open a chat with it) and send any message there.\n  Waiting up to {DETECT_TIMEOUT}s..."
+    );
+    // Skip anything sent before the prompt
+    let mut offset = match bot.get_updates().await {
+        Ok(updates) => updates.last().map(|update| update.id.0 as i32 + 1),
+        Err(e) => {
+            println!("  ✘ Failed to read updates: {e}");
+            return None;
+        }
+    };
+
+    // Short long-polls, as the bot's HTTP client times out long before DETECT_TIMEOUT
+    let deadline = Instant::now() + Duration::from_secs(DETECT_TIMEOUT.into());
+    let chat = loop {
+        if Instant::now() >= deadline {
+            println!("  ✘ No message arrived");
+            return None;
+        }
+        let mut request = bot.get_updates().timeout(POLL_TIMEOUT);
+        if let Some(offset) = offset {
+            request = request.offset(offset);
+        }
+        let updates = match request.await {
+            Ok(updates) => updates,
+            Err(e) => {
+                println!("  ✘ Failed to read updates: {e}");
+                return None;
+            }
+        };
+        if let Some(chat) = updates.iter().find_map(|update| update.chat()) {
+            break chat.clone();
+        }
+        if let Some(update) = updates.last() {
+            offset = Some(update.id.0 as i32 + 1);
+        }
+    };
+
+    let name = chat
+        .title()
+        .or(chat.username())
+        .unwrap_or("private chat")
+        .to_string();
+    if confirm(&format!("Use \"{}\" ({})?", name, chat.id.0), true) {
+        Some(chat.id.0)
+    } else {
+        None
+    }
+}
+
+/// A minimal config with the answers filled in
+fn render(pubkey: &str, relays: &[String], bot_token: &str, chat_id: i64) -> String {
+    let quote = |value: &str| toml::Value::String(value.to_string()).to_string();
+    let relays = relays
+        .iter()
+        .map(|relay| format!("    {},\n", quote(relay)))
+        .collect::<String>();
+    format!(
+        "# mostro-watchdog configuration, written by `mostro-watchdog init`\n\
+         # See config.example.toml for every option.\n\n\
+         [mostro]\n\
+         pubkey = {}\n\n\
+         [nostr]\n\
+         relays = [\n{}]\n\n\
+         [telegram]\n\
+         bot_token = {}\n\
+         chat_id = {}\n",
+        quote(pubkey),
+        relays,
+        quote(bot_token),
+        chat_id
+    )
+}
+
+/// Ask until `parse` accepts the answer
+fn prompt_until<T>(
+    question: &str,
+    default: Option<&str>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> T {
+    loop {
+        let answer = prompt(question, default);
+        match parse(&answer) {
+            Ok(value) => return value,
+            Err(problem) => println!("  ✘ {problem}"),
+        }
+    }
+}
+
+fn confirm(question: &str, default: bool) -> bool {
+    let hint = if default { "Y/n" } else { "y/N" };
+    let answer = prompt(&format!("{question} [{hint}]"), Some(""));
+    match answer.to_lowercase().as_str() {
+        "" => default,
+        answer => answer.starts_with('y'),
+    }
+}
+
+/// Read one trimmed line from stdin; exits if stdin is closed
+fn prompt(question: &str, default: Option<&str>) -> String {
+    loop {
+        match default {
+            Some(default) if !default.is_empty() => print!("{question} [{default}]: "),
+            _ => print!("{question}: "),
+        }
+        let _ = std::io::stdout().flush();
+
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                eprintln!("\nAborted, nothing written.");
+                std::process::exit(1);
+            }
+            Ok(_) => {}
+        }
+        let answer = line.trim();
+        match (answer.is_empty(), default) {
+            (true, Some(default)) => return default.to_string(),
+            (true, None) => continue,
+            (false, _) => return answer.to_string(),
+        }
+    }
+}
+
+/// Write `contents` readable by the owner only, as the config holds the bot token
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files; tighten a config being replaced too
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+    }
+    options.open(path)?.write_all(contents.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let pubkey = Keys::generate().public_key().to_bech32().unwrap();
+        let relays = vec![DEFAULT_RELAY.to_string(), "wss://nos.lol".to_string()];
+        std::fs::write(&path, render(&pubkey, &relays, "123:A\"BC", -100123)).unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.mostro.pubkey, vec![pubkey]);
+        assert_eq!(config.nostr.relays, relays);
+        assert_eq!(config.telegram.bot_token, "123:A\"BC");
+        assert_eq!(config.telegram.chat_id, -100123);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "old").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, "bot_token = \"123:ABC\"").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "bot_token = \"123:ABC\""
+        );
+    }
+}
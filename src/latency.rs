@@ -0,0 +1,81 @@
+//! Event propagation latency: the time between an event's `created_at` and its
+//! arrival from a relay. Rising latency is an early sign of an overloaded relay.
+
+use std::collections::VecDeque;
+
+/// Samples kept per relay
+const WINDOW: usize = 100;
+
+/// Latency percentiles in seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct Percentiles {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+}
+
+/// Latencies of the most recent events
+#[derive(Debug, Clone, Default)]
+pub struct LatencyWindow {
+    samples: VecDeque<u64>,
+}
+
+impl LatencyWindow {
+    /// Record an event received now; events stamped in the future count as zero latency
+    pub fn record(&mut self, created_at: u64, received_at: u64) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples
+            .push_back(received_at.saturating_sub(created_at));
+    }
+
+    pub fn percentiles(&self) -> Option<Percentiles> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<u64> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        // Nearest-rank percentile
+        let rank = |p: usize| sorted[(p * sorted.len()).div_ceil(100).max(1) - 1];
+        Some(Percentiles {
+            p50: rank(50),
+            p90: rank(90),
+            p99: rank(99),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let mut window = LatencyWindow::default();
+        assert_eq!(window.percentiles(), None);
+
+        for latency in 1..=10 {
+            window.record(1000, 1000 + latency);
+        }
+        assert_eq!(
+            window.percentiles(),
+            Some(Percentiles {
+                p50: 5,
+                p90: 9,
+                p99: 10
+            })
+        );
+
+        // Old samples fall out of the window
+        for _ in 0..WINDOW {
+            window.record(1000, 1001);
+        }
+        assert_eq!(window.percentiles().unwrap().p99, 1);
+
+        // Clock skew can put events in the future
+        let mut window = LatencyWindow::default();
+        window.record(2000, 1000);
+        assert_eq!(window.percentiles().unwrap().p50, 0);
+    }
+}
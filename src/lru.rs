@@ -0,0 +1,152 @@
+//! Memory-bounded LRU map for in-memory dispute and event state.
+//!
+//! State keyed by event or dispute id (deduplication, debouncing, escalation)
+//! lives in one of these, capped at `alerts.cache_capacity` entries, so a long
+//! run or a flood of made-up disputes can't grow memory without limit. When full,
+//! the least recently used entry is evicted and counted per cache in the
+//! `cache_evictions` metric.
+
+use crate::stats;
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// Entries kept per cache unless configured otherwise
+pub const DEFAULT_CAPACITY: usize = 4096;
+
+pub struct Lru<K, V> {
+    /// Cache name in the eviction metric
+    name: &'static str,
+    capacity: usize,
+    entries: HashMap<K, (V, u64)>,
+    /// Keys by last use, oldest first
+    order: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Hash + Eq + Clone, V> Lru<K, V> {
+    pub fn new(name: &'static str, capacity: usize) -> Self {
+        Self {
+            name,
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// The entry for `key`, created with `default` if needed, marked as most
+    /// recently used
+    pub fn get_or_insert_with(&mut self, key: K, default: impl FnOnce() -> V) -> &mut V {
+        self.tick += 1;
+        let tick = self.tick;
+        match self.entries.get_mut(&key) {
+            Some((_, last_used)) => {
+                self.order.remove(last_used);
+                *last_used = tick;
+            }
+            None => {
+                if self.entries.len() >= self.capacity {
+                    self.evict();
+                }
+                self.entries.insert(key.clone(), (default(), tick));
+            }
+        }
+        self.order.insert(tick, key.clone());
+        &mut self.entries.get_mut(&key).expect("entry just inserted").0
+    }
+
+    /// The entry for `key`, marked as most recently used
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.tick += 1;
+        let tick = self.tick;
+        let (value, last_used) = self.entries.get_mut(key)?;
+        let owned = self
+            .order
+            .remove(last_used)
+            .expect("entries and order agree");
+        self.order.insert(tick, owned);
+        *last_used = tick;
+        Some(value)
+    }
+
+    /// The entry for `key`, without marking it as used
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.entries.get(key).map(|(value, _)| value)
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let (value, last_used) = self.entries.remove(key)?;
+        self.order.remove(&last_used);
+        Some(value)
+    }
+
+    /// Keep only the entries for which `keep` returns true
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        let order = &mut self.order;
+        self.entries.retain(|key, (value, last_used)| {
+            let kept = keep(key, value);
+            if !kept {
+                order.remove(last_used);
+            }
+            kept
+        });
+    }
+
+    fn evict(&mut self) {
+        if let Some((_, oldest)) = self.order.pop_first() {
+            self.entries.remove(&oldest);
+            stats::stats().record_eviction(self.name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut lru = Lru::new("test", 2);
+        *lru.get_or_insert_with("a", || 0) += 1;
+        lru.get_or_insert_with("b", || 0);
+        // Using "a" again makes "b" the oldest
+        *lru.get_mut("a").unwrap() += 1;
+        lru.get_or_insert_with("c", || 0);
+
+        assert_eq!(lru.entries.len(), 2);
+        assert_eq!(lru.get(&"a"), Some(&2));
+        assert_eq!(lru.get(&"b"), None);
+        assert_eq!(lru.get(&"c"), Some(&0));
+        assert!(stats::stats().snapshot().evictions.get("test") >= Some(&1));
+    }
+
+    #[test]
+    fn test_remove_and_retain() {
+        let mut lru = Lru::new("test", 3);
+        for key in 1..=3 {
+            lru.get_or_insert_with(key, || key * 10);
+        }
+        assert_eq!(lru.remove(&2), Some(20));
+        lru.retain(|key, _| *key != 3);
+        assert_eq!(lru.entries.len(), 1);
+
+        // Removed entries don't count toward the capacity
+        lru.get_or_insert_with(4, || 40);
+        lru.get_or_insert_with(5, || 50);
+        assert_eq!(lru.get(&1), Some(&10));
+        assert_eq!(lru.entries.len(), 3);
+    }
+}
@@ -1,887 +1,4107 @@
 use nostr_sdk::prelude::*;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use teloxide::prelude::*;
-use teloxide::types::MessageId;
-use tokio::sync::RwLock;
-use tracing::{error, info, warn};
-
+use teloxide::requests::{HasPayload, Request};
+use teloxide::types::{
+    InlineKeyboardButton, InlineKeyboardMarkup, InputFile, MessageId, ReplyParameters, ThreadId,
+};
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::prelude::*;
+
+mod alertmanager;
+mod announce;
+mod backfill;
+mod backoff;
+mod branding;
+mod channels;
+mod cli;
+mod clock;
+mod commands;
+mod components;
 mod config;
+mod crash;
 mod db;
-
-use config::Config;
+mod debounce;
+mod dedup;
+mod degradation;
+mod doctor;
+mod dry_run;
+mod escalation;
+mod event_loop;
+mod events;
+mod failover;
+mod fallback;
+mod flood;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod http;
+mod i18n;
+mod influx;
+mod init;
+mod latency;
+mod lru;
+mod oncall;
+mod ping;
+mod pipeline;
+mod proxy;
+mod quiet_hours;
+mod redact;
+mod relay_score;
+mod relay_status;
+mod reload;
+#[cfg(feature = "sentry")]
+mod reporting;
+mod resources;
+mod retry;
+mod secrets;
+mod self_test;
+mod send_queue;
+mod shutdown;
+mod silence;
+mod stats;
+mod statsd;
+mod subscriptions;
+mod supervisor;
+#[cfg(feature = "otel")]
+mod telemetry;
+mod templates;
+mod timestamp;
+#[cfg(feature = "tls")]
+mod tls;
+mod updates;
+mod validate;
+
+use alertmanager::Alertmanager;
+use branding::Branding;
+use commands::CommandContext;
+use config::{AlertSeverity, Config};
 use db::DisputeMessageStore;
+use escalation::Escalation;
+use oncall::OnCall;
+use quiet_hours::{QuietHours, QuietHoursQueue};
+use supervisor::{Liveness, Supervisor};
+use templates::Templates;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Build info embedded by build.rs
+const BUILD_COMMIT: &str = env!("BUILD_COMMIT");
+const BUILD_TARGET: &str = env!("BUILD_TARGET");
+const BUILD_PROFILE: &str = env!("BUILD_PROFILE");
 
-/// Health monitor to track system status and send periodic heartbeats
-#[derive(Debug, Clone)]
+/// Consecutive failed deliveries after which the watchdog reports itself unhealthy
+const MAX_CONSECUTIVE_DELIVERY_FAILURES: u64 = 5;
+
+/// Seconds between relay status checks; reconnections back off on top of this
+const RELAY_POLL_INTERVAL: u64 = 5;
+
+/// Status of a secondary relay that is not connected until promoted
+const STANDBY: &str = "standby";
+
+/// How long in-flight work (e.g. HTTP requests) gets to finish on SIGINT/SIGTERM
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How far back `--once` looks when there is no last-seen event yet
+const ONCE_FIRST_LOOKBACK: Duration = Duration::from_secs(24 * 3600);
+
+/// How long `--once` and `replay` wait for the relays to return stored events
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Health monitor told about delivery outcomes by the send helpers, which have no other context
+static DELIVERY_HEALTH: OnceLock<Arc<HealthMonitor>> = OnceLock::new();
+
+/// Health monitor to track system status and send periodic heartbeats.
+///
+/// Shared behind one `Arc`: plain counters are atomics, the relay, component and
+/// other status state sits behind one lock that is never held across an await, and
+/// handles attached once at startup are `OnceLock`s.
+#[derive(Debug)]
 struct HealthMonitor {
-    /// Last time we received a dispute event
-    last_event_time: Arc<RwLock<Option<SystemTime>>>,
-    /// Last time we sent a heartbeat  
-    last_heartbeat: Arc<RwLock<Option<SystemTime>>>,
+    /// Unix timestamp of the last dispute event, 0 before the first
+    last_event_time: AtomicU64,
+    /// Unix timestamp of the last heartbeat sent, 0 before the first
+    last_heartbeat: AtomicU64,
     /// Start time of the application
     start_time: SystemTime,
     /// Number of events processed
-    events_processed: Arc<RwLock<u64>>,
+    events_processed: AtomicU64,
     /// Health status
-    is_healthy: Arc<RwLock<bool>>,
+    is_healthy: AtomicBool,
+    /// Failed deliveries since the last successful one
+    consecutive_delivery_failures: AtomicU64,
+    /// Connected relays needed to be ready (`health.min_connected_relays`)
+    min_connected_relays: AtomicUsize,
+    /// `created_at` offsets of recent dispute events, for clock drift detection
+    event_offsets: clock::EventOffsets,
+    /// Relay and component statistics and other status values, read together by snapshots
+    state: std::sync::Mutex<MonitorState>,
+    /// Dispute store, attached once opened, for the open dispute count
+    dispute_store: OnceLock<Arc<DisputeMessageStore>>,
+    /// Nostr client, attached once subscribed, for readiness checks
+    nostr_client: OnceLock<Client>,
+    /// Dispute alert pipeline, attached once configured, for `POST /test-alert`
+    alert_ctx: OnceLock<AlertContext>,
+}
+
+#[derive(Debug, Default)]
+struct MonitorState {
+    /// Per-relay statistics, keyed by URL without trailing slash
+    relays: BTreeMap<String, RelayHealth>,
+    /// Last outcomes per component, for the verbose health endpoint
+    components: BTreeMap<&'static str, components::ComponentHealth>,
+    /// Last estimated offset of the local clock, positive when it is behind
+    clock_offset: Option<i64>,
+    /// Failing self-checks (`relays`, `queue`, `store`)
+    degraded: Vec<&'static str>,
+    /// Effective configuration with secrets redacted, for `GET /config`
+    config: serde_json::Value,
+}
+
+/// Connection statistics for one relay
+#[derive(Debug, Clone, serde::Serialize)]
+struct RelayHealth {
+    url: String,
+    /// Connection status as last observed by the relay check
+    status: String,
+    /// Events received from this relay
+    messages_received: u64,
+    /// Unix timestamp of the last event received
+    last_message_timestamp: Option<u64>,
+    /// Reconnect attempts after the relay was found disconnected
+    reconnects: u64,
+    /// Propagation latency of recent events, in seconds
+    latency: Option<latency::Percentiles>,
+    #[serde(skip)]
+    latency_window: latency::LatencyWindow,
+    /// Last time the relay was seen not connected, and its status then
+    last_error: Option<String>,
+    last_error_timestamp: Option<u64>,
+    /// Dispute events this relay delivered, including those other relays delivered first
+    dispute_events: u64,
+    /// Dispute events seen while this relay was connected
+    dispute_events_expected: u64,
+    /// `created_at` of the newest dispute event this relay delivered
+    last_dispute_created_at: Option<u64>,
+    /// CLOSED message for the dispute subscription, until the subscription check
+    /// re-creates it
+    #[serde(skip)]
+    subscription_closed: Option<String>,
+    /// Dispute subscriptions re-created after the relay closed or stopped serving them
+    subscriptions_renewed: u64,
+    /// Last NOTICE the relay sent
+    last_notice: Option<String>,
+    /// Reliability score from 0 to 100, once the relay check has run
+    score: Option<u8>,
+    /// Unix timestamp until which the relay is blacklisted
+    blacklisted_until: Option<u64>,
+    /// Round-trip time of the last successful ping, in milliseconds
+    rtt_ms: Option<u64>,
+    /// Failed or timed out pings in a row
+    ping_failures: u32,
 }
 
 impl HealthMonitor {
     fn new() -> Self {
         Self {
-            last_event_time: Arc::new(RwLock::new(None)),
-            last_heartbeat: Arc::new(RwLock::new(None)),
+            last_event_time: AtomicU64::new(0),
+            last_heartbeat: AtomicU64::new(0),
             start_time: SystemTime::now(),
-            events_processed: Arc::new(RwLock::new(0)),
-            is_healthy: Arc::new(RwLock::new(true)),
+            events_processed: AtomicU64::new(0),
+            is_healthy: AtomicBool::new(true),
+            consecutive_delivery_failures: AtomicU64::new(0),
+            min_connected_relays: AtomicUsize::new(1),
+            event_offsets: clock::EventOffsets::default(),
+            state: std::sync::Mutex::new(MonitorState::default()),
+            dispute_store: OnceLock::new(),
+            nostr_client: OnceLock::new(),
+            alert_ctx: OnceLock::new(),
         }
     }
 
-    /// Record that we received an event
-    async fn record_event(&self) {
-        *self.last_event_time.write().await = Some(SystemTime::now());
-        *self.events_processed.write().await += 1;
+    fn state(&self) -> std::sync::MutexGuard<'_, MonitorState> {
+        self.state.lock().expect("health monitor lock poisoned")
     }
 
-    /// Record that we sent a heartbeat
-    async fn record_heartbeat(&self) {
-        *self.last_heartbeat.write().await = Some(SystemTime::now());
+    /// Attach the dispute store once it is opened
+    fn attach_dispute_store(&self, store: Arc<DisputeMessageStore>) {
+        let _ = self.dispute_store.set(store);
     }
 
-    /// Check if we should be concerned about lack of events
-    async fn should_alert_no_events(&self, threshold_seconds: u64) -> bool {
-        if threshold_seconds == 0 {
-            return false; // Disabled
-        }
+    /// Record the configuration in effect
+    fn set_config(&self, config: &Config) {
+        self.state().config = config.redacted();
+        self.min_connected_relays.store(
+            config
+                .health
+                .as_ref()
+                .map_or(1, |health| health.min_connected_relays),
+            Ordering::Relaxed,
+        );
+    }
 
-        let last_event = *self.last_event_time.read().await;
-        match last_event {
-            None => {
-                // No events yet - check if we've been running long enough to be concerned
-                let uptime = self.start_time.elapsed().unwrap_or(Duration::ZERO);
-                uptime.as_secs() > threshold_seconds
-            }
-            Some(last) => {
-                let elapsed = last.elapsed().unwrap_or(Duration::MAX);
-                elapsed.as_secs() > threshold_seconds
-            }
-        }
+    /// The configuration in effect, as JSON with secrets redacted
+    fn config_json(&self) -> String {
+        self.state().config.to_string()
+    }
+
+    /// Record the latest clock offset estimate
+    fn set_clock_offset(&self, offset: i64) {
+        self.state().clock_offset = Some(offset);
     }
 
-    /// Get health status as JSON
-    async fn get_status_json(&self) -> String {
-        let last_event = *self.last_event_time.read().await;
-        let last_heartbeat = *self.last_heartbeat.read().await;
-        let events_count = *self.events_processed.read().await;
-        let is_healthy = *self.is_healthy.read().await;
+    /// Record the failing self-checks
+    fn set_degraded(&self, degraded: Vec<&'static str>) {
+        self.state().degraded = degraded;
+    }
 
-        let uptime_secs = self
-            .start_time
-            .elapsed()
-            .unwrap_or(Duration::ZERO)
-            .as_secs();
+    /// Attach the dispute alert pipeline once it is configured
+    fn attach_alert_context(&self, ctx: AlertContext) {
+        let _ = self.alert_ctx.set(ctx);
+    }
 
-        let last_event_ts = last_event
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
+    /// Attach the Nostr client once the dispute subscription is active
+    fn attach_client(&self, client: Client) {
+        let _ = self.nostr_client.set(client);
+    }
 
-        let last_heartbeat_ts = last_heartbeat
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| d.as_secs());
+    /// Whether the watchdog can currently receive and deliver alerts
+    async fn readiness(&self) -> Readiness {
+        let telegram = self.consecutive_delivery_failures.load(Ordering::Relaxed)
+            < MAX_CONSECUTIVE_DELIVERY_FAILURES;
+        let relays_required = self.min_connected_relays.load(Ordering::Relaxed);
+        let Some(client) = self.nostr_client.get() else {
+            return Readiness {
+                ready: false,
+                relays_connected: 0,
+                relays_required,
+                telegram,
+                subscribed: false,
+            };
+        };
 
-        serde_json::json!({
-            "status": if is_healthy { "healthy" } else { "unhealthy" },
-            "uptime_seconds": uptime_secs,
-            "events_processed": events_count,
-            "last_event_timestamp": last_event_ts,
-            "last_heartbeat_timestamp": last_heartbeat_ts,
-            "version": VERSION
-        })
-        .to_string()
+        let urls: Vec<String> = self.state().relays.keys().cloned().collect();
+        let failed = self.refresh_relay_statuses(client, &urls).await;
+        let relays_connected = urls.len() - failed.len();
+        let subscribed = !client.subscriptions().await.is_empty();
+
+        Readiness {
+            ready: relays_connected > 0
+                && relays_connected >= relays_required
+                && telegram
+                && subscribed,
+            relays_connected,
+            relays_required,
+            telegram,
+            subscribed,
+        }
     }
-}
 
-/// Parse command-line arguments for config path.
-///
-/// Supported forms:
-///   mostro-watchdog                          → config.toml (cwd)
-///   mostro-watchdog /path/to/config.toml     → positional arg
-///   mostro-watchdog --config /path/to/config  → named flag
-///   mostro-watchdog -c /path/to/config        → short flag
-///   mostro-watchdog --help | -h              → print usage
-///   mostro-watchdog --version | -V           → print version
-fn parse_config_path() -> PathBuf {
-    let args: Vec<String> = std::env::args().skip(1).collect();
-
-    if args.is_empty() {
-        return default_config_path();
-    }
-
-    match args[0].as_str() {
-        "--help" | "-h" => {
-            print_usage();
-            std::process::exit(0);
-        }
-        "--version" | "-V" => {
-            println!("mostro-watchdog {VERSION}");
-            std::process::exit(0);
-        }
-        "--config" | "-c" => {
-            if let Some(path) = args.get(1) {
-                PathBuf::from(path)
-            } else {
-                eprintln!("Error: --config requires a path argument\n");
-                print_usage();
-                std::process::exit(1);
+    /// Disputes not yet resolved, if the store is attached and readable
+    async fn open_disputes(&self) -> Option<u64> {
+        match self.dispute_store.get()?.count_open().await {
+            Ok(count) => Some(count),
+            Err(e) => {
+                warn!("Failed to count open disputes: {}", e);
+                None
             }
         }
-        arg if arg.starts_with('-') => {
-            eprintln!("Error: unknown option '{arg}'\n");
-            print_usage();
-            std::process::exit(1);
-        }
-        path => PathBuf::from(path),
     }
-}
 
-/// Resolve the default config path with fallback:
-/// 1. ./config.toml (current directory)
-/// 2. ~/.config/mostro-watchdog/config.toml
-fn default_config_path() -> PathBuf {
-    let local = PathBuf::from("config.toml");
-    if local.exists() {
-        return local;
+    /// Record a delivery outcome; too many failures in a row mark the watchdog unhealthy
+    fn record_delivery(&self, ok: bool) {
+        if ok {
+            let failures = self
+                .consecutive_delivery_failures
+                .swap(0, Ordering::Relaxed);
+            if failures >= MAX_CONSECUTIVE_DELIVERY_FAILURES {
+                info!("✅ Deliveries recovered after {} failures", failures);
+            }
+            self.is_healthy.store(true, Ordering::Relaxed);
+        } else {
+            let failures = self
+                .consecutive_delivery_failures
+                .fetch_add(1, Ordering::Relaxed)
+                + 1;
+            if failures == MAX_CONSECUTIVE_DELIVERY_FAILURES {
+                error!(
+                    "❌ {} consecutive delivery failures, marking unhealthy",
+                    failures
+                );
+                self.is_healthy.store(false, Ordering::Relaxed);
+            }
+        }
     }
 
-    if let Some(home) = std::env::var_os("HOME") {
-        let xdg = PathBuf::from(home).join(".config/mostro-watchdog/config.toml");
-        if xdg.exists() {
-            return xdg;
+    /// Start tracking the configured relays
+    fn register_relays(&self, urls: &[String]) {
+        let mut state = self.state();
+        for url in urls {
+            state
+                .relays
+                .entry(relay_key(url))
+                .or_insert_with(|| RelayHealth {
+                    url: relay_key(url),
+                    status: "unknown".to_string(),
+                    messages_received: 0,
+                    last_message_timestamp: None,
+                    reconnects: 0,
+                    latency: None,
+                    latency_window: latency::LatencyWindow::default(),
+                    last_error: None,
+                    last_error_timestamp: None,
+                    dispute_events: 0,
+                    dispute_events_expected: 0,
+                    last_dispute_created_at: None,
+                    subscription_closed: None,
+                    subscriptions_renewed: 0,
+                    last_notice: None,
+                    score: None,
+                    blacklisted_until: None,
+                    rtt_ms: None,
+                    ping_failures: 0,
+                });
         }
     }
 
-    // Return local path anyway — Config::load will produce a helpful error
-    local
-}
+    /// Apply an update to a tracked relay
+    fn update_relay(&self, url: &str, f: impl FnOnce(&mut RelayHealth)) {
+        if let Some(relay) = self.state().relays.get_mut(&relay_key(url)) {
+            f(relay);
+        }
+    }
 
-fn print_usage() {
-    println!(
-        "🐕 mostro-watchdog {VERSION} — Dispute notification bot for Mostro admins\n\n\
-         USAGE:\n\
-         \x20   mostro-watchdog [OPTIONS] [CONFIG_PATH]\n\n\
-         ARGS:\n\
-         \x20   [CONFIG_PATH]  Path to config.toml (default: ./config.toml)\n\n\
-         OPTIONS:\n\
-         \x20   -c, --config <PATH>  Path to config file\n\
-         \x20   -h, --help           Print this help message\n\
-         \x20   -V, --version        Print version\n\n\
-         CONFIG SEARCH ORDER:\n\
-         \x20   1. ./config.toml (current directory)\n\
-         \x20   2. ~/.config/mostro-watchdog/config.toml\n\n\
-         EXAMPLES:\n\
-         \x20   mostro-watchdog\n\
-         \x20   mostro-watchdog /etc/mostro-watchdog/config.toml\n\
-         \x20   mostro-watchdog --config ~/my-config.toml\n\
-         \x20   RUST_LOG=debug mostro-watchdog"
-    );
-}
+    /// Record an event received from a relay
+    fn record_relay_message(&self, url: &str) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        self.update_relay(url, |relay| {
+            relay.messages_received += 1;
+            relay.last_message_timestamp = now;
+        });
+    }
 
-/// Start health monitoring background tasks
-fn start_health_tasks(
-    health_monitor: Arc<HealthMonitor>,
-    bot: Bot,
-    chat_id: i64,
-    health_config: &config::HealthConfig,
-    client: Client,
-    relays: &[String],
-) {
-    // Heartbeat task
-    if health_config.heartbeat_enabled {
-        let health_monitor_hb = health_monitor.clone();
-        let bot_hb = bot.clone();
-        let heartbeat_interval = health_config.heartbeat_interval;
+    /// Record a dispute event delivered by a relay, whether or not another relay
+    /// delivered it first
+    fn record_relay_dispute_event(&self, url: &str, created_at: u64) {
+        self.update_relay(url, |relay| {
+            relay.dispute_events += 1;
+            relay.last_dispute_created_at = relay.last_dispute_created_at.max(Some(created_at));
+        });
+    }
 
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(heartbeat_interval));
-            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-            interval.tick().await; // skip the immediate first tick
-
-            loop {
-                interval.tick().await;
-
-                let uptime = health_monitor_hb
-                    .start_time
-                    .elapsed()
-                    .unwrap_or(Duration::ZERO)
-                    .as_secs();
-
-                let events_count = *health_monitor_hb.events_processed.read().await;
-
-                let heartbeat_msg = format!(
-                    "💓 *Health Check*\n\n\
-                     ✅ System: Online\n\
-                     ⏰ Uptime: {} hours {} minutes\n\
-                     📊 Events processed: {}\n\
-                     🔔 Status: Monitoring active",
-                    escape_markdown(&(uptime / 3600).to_string()),
-                    escape_markdown(&((uptime % 3600) / 60).to_string()),
-                    escape_markdown(&events_count.to_string())
-                );
+    /// Where a relay's dispute subscription should resume after a reconnection: the
+    /// newest dispute event it delivered, or else the newest seen from any relay
+    async fn relay_resume_point(&self, url: &str) -> Option<u64> {
+        let own = self
+            .state()
+            .relays
+            .get(&relay_key(url))
+            .and_then(|relay| relay.last_dispute_created_at);
+        if own.is_some() {
+            return own;
+        }
+        match self.dispute_store.get()?.last_seen().await {
+            Ok(last_seen) => last_seen,
+            Err(e) => {
+                warn!("Failed to read last seen event: {}", e);
+                None
+            }
+        }
+    }
 
-                if let Err(e) = bot_hb
-                    .send_message(ChatId(chat_id), &heartbeat_msg)
-                    .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                    .await
-                {
-                    error!("Failed to send heartbeat: {}", e);
-                } else {
-                    health_monitor_hb.record_heartbeat().await;
-                    info!(
-                        "💓 Heartbeat sent (uptime: {}h {}m, events: {})",
-                        uptime / 3600,
-                        (uptime % 3600) / 60,
-                        events_count
-                    );
-                }
+    /// Count a new dispute event against every connected relay
+    fn expect_dispute_event(&self) {
+        for relay in self.state().relays.values_mut() {
+            if relay.status == "connected" {
+                relay.dispute_events_expected += 1;
             }
-        });
+        }
     }
 
-    // Event silence monitoring task
-    if health_config.event_alert_threshold > 0 {
-        let health_monitor_es = health_monitor.clone();
-        let bot_es = bot.clone();
-        let threshold = health_config.event_alert_threshold;
+    /// Dispute events a relay delivered, of those it was expected to
+    fn relay_deliveries(&self, url: &str) -> relay_score::Deliveries {
+        match self.state().relays.get(&relay_key(url)) {
+            Some(relay) => relay_score::Deliveries {
+                delivered: relay.dispute_events,
+                expected: relay.dispute_events_expected,
+            },
+            None => Default::default(),
+        }
+    }
 
-        tokio::spawn(async move {
-            let check_period = std::cmp::max(threshold / 2, 1);
-            let mut interval = tokio::time::interval(Duration::from_secs(check_period));
-            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-            interval.tick().await; // skip the immediate first tick
-
-            let mut last_alert = SystemTime::UNIX_EPOCH;
-
-            loop {
-                interval.tick().await;
-
-                if health_monitor_es.should_alert_no_events(threshold).await {
-                    // Avoid spam - only alert once every threshold period
-                    let now = SystemTime::now();
-                    if now
-                        .duration_since(last_alert)
-                        .unwrap_or(Duration::MAX)
-                        .as_secs()
-                        >= threshold
-                    {
-                        let uptime = health_monitor_es
-                            .start_time
-                            .elapsed()
-                            .unwrap_or(Duration::ZERO)
-                            .as_secs();
-
-                        let alert_msg = format!(
-                            "⚠️ *Event Silence Alert*\n\n\
-                             🔕 No dispute events received for {} hours\n\
-                             ⏰ System uptime: {} hours {} minutes\n\
-                             🔍 Please check:\n\
-                             • Mostro daemon status\n\
-                             • Nostr relay connections\n\
-                             • Network connectivity",
-                            escape_markdown(&(threshold / 3600).to_string()),
-                            escape_markdown(&(uptime / 3600).to_string()),
-                            escape_markdown(&((uptime % 3600) / 60).to_string())
-                        );
+    /// Whether `url` is blacklisted by its score
+    fn is_relay_blacklisted(&self, url: &str) -> bool {
+        self.state()
+            .relays
+            .get(&relay_key(url))
+            .is_some_and(|relay| relay.blacklisted_until.is_some())
+    }
 
-                        if let Err(e) = bot_es
-                            .send_message(ChatId(chat_id), &alert_msg)
-                            .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                            .await
-                        {
-                            error!("Failed to send event silence alert: {}", e);
-                        } else {
-                            warn!(
-                                "⚠️ Event silence alert sent ({}h threshold)",
-                                threshold / 3600
-                            );
-                            last_alert = now;
-                        }
-                    }
-                }
-            }
+    /// Put a secondary relay on standby, or take it off
+    fn set_relay_standby(&self, url: &str, standby: bool) {
+        let status = if standby { STANDBY } else { "unknown" };
+        self.update_relay(url, |relay| relay.status = status.to_string());
+    }
+
+    /// Whether `url` is a secondary relay on standby
+    fn is_relay_on_standby(&self, url: &str) -> bool {
+        self.state()
+            .relays
+            .get(&relay_key(url))
+            .is_some_and(|relay| relay.status == STANDBY)
+    }
+
+    /// Record that a relay closed the dispute subscription
+    fn record_subscription_closed(&self, url: &str, message: &str) {
+        self.update_relay(url, |relay| {
+            relay.subscription_closed = Some(message.to_string())
         });
     }
 
-    // Relay connectivity check task
-    if health_config.check_relays {
-        let client_rc = client.clone();
-        let bot_rc = bot.clone();
-        let relays_rc = relays.to_vec();
-        // Derive relay check cadence from relay_timeout (check every 10x the timeout, min 10s)
-        let relay_timeout = health_config.relay_timeout;
+    /// The CLOSED message a relay sent since the last call, if any
+    fn take_subscription_closed(&self, url: &str) -> Option<String> {
+        self.state()
+            .relays
+            .get_mut(&relay_key(url))
+            .and_then(|relay| relay.subscription_closed.take())
+    }
 
-        tokio::spawn(async move {
-            let check_secs = std::cmp::max(relay_timeout, 1) * 10;
-            let mut interval = tokio::time::interval(Duration::from_secs(check_secs));
-            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-            interval.tick().await; // skip first immediate tick to allow connections to establish
-
-            loop {
-                interval.tick().await;
-
-                let mut failed_relays = Vec::new();
-
-                for relay_url_str in &relays_rc {
-                    match client_rc.pool().relay(relay_url_str).await {
-                        Ok(relay) => {
-                            if relay.status() != RelayStatus::Connected {
-                                failed_relays.push(relay_url_str.clone());
-                            }
-                        }
-                        Err(_) => {
-                            failed_relays.push(relay_url_str.clone());
-                        }
-                    }
-                }
+    /// Record that a relay's dispute subscription was re-created
+    fn record_subscription_renewed(&self, url: &str) {
+        self.update_relay(url, |relay| relay.subscriptions_renewed += 1);
+    }
 
-                if !failed_relays.is_empty() {
-                    let failed_list: String = failed_relays
-                        .iter()
-                        .map(|url| format!("  • {}", escape_markdown(url)))
-                        .collect::<Vec<_>>()
-                        .join("\n");
-
-                    let alert_msg = format!(
-                        "🔌 *Relay Connection Alert*\n\n\
-                         ⚠️ Disconnected relays: {}\n{}\n\
-                         ✅ Connected relays: {}\n\
-                         🔄 Attempting reconnection\\.\\.\\.",
-                        escape_markdown(&failed_relays.len().to_string()),
-                        failed_list,
-                        escape_markdown(&(relays_rc.len() - failed_relays.len()).to_string())
-                    );
+    /// Record a NOTICE from a relay
+    fn record_relay_notice(&self, url: &str, message: &str) {
+        self.update_relay(url, |relay| relay.last_notice = Some(message.to_string()));
+    }
 
-                    if let Err(e) = bot_rc
-                        .send_message(ChatId(chat_id), &alert_msg)
-                        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                        .await
-                    {
-                        error!("Failed to send relay alert: {}", e);
-                    } else {
-                        warn!(
-                            "🔌 Relay connectivity alert sent ({} failed)",
-                            failed_relays.len()
-                        );
-                    }
+    /// Record a relay's score, and until when it is blacklisted
+    fn set_relay_score(&self, url: &str, score: Option<u8>, blacklisted_until: Option<u64>) {
+        self.update_relay(url, |relay| {
+            relay.score = score;
+            relay.blacklisted_until = blacklisted_until;
+        });
+    }
 
-                    // Attempt to reconnect all failed/terminated relays
-                    client_rc.connect().await;
-                }
+    /// Record a ping's round-trip time, or `None` if it failed
+    fn record_relay_ping(&self, url: &str, rtt: Option<Duration>) {
+        self.update_relay(url, |relay| match rtt {
+            Some(rtt) => {
+                relay.rtt_ms = Some(rtt.as_millis() as u64);
+                relay.ping_failures = 0;
             }
+            None => relay.ping_failures += 1,
         });
     }
 
-    // HTTP health endpoint task
-    if health_config.enable_http_endpoint {
-        let health_monitor_http = health_monitor.clone();
-        let http_port = health_config.http_port;
-        let http_bind = health_config.http_bind.clone();
+    /// Record how long an event took to arrive from a relay
+    fn record_relay_latency(&self, url: &str, created_at: u64) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs();
+        self.update_relay(url, |relay| {
+            relay.latency_window.record(created_at, now);
+            relay.latency = relay.latency_window.percentiles();
+        });
+    }
 
-        tokio::spawn(async move {
-            if let Err(e) = start_health_server(health_monitor_http, &http_bind, http_port).await {
-                error!("Health HTTP server failed: {}", e);
+    /// Record the connection status seen by the relay check
+    fn set_relay_status(&self, url: &str, status: &str) {
+        let status = status.to_lowercase();
+        self.update_relay(url, |relay| {
+            if status != "connected" {
+                relay.last_error = Some(status.clone());
+                relay.last_error_timestamp = Some(components::now());
             }
+            relay.status = status;
         });
     }
-}
 
-/// Start HTTP health status endpoint
-async fn start_health_server(
-    health_monitor: Arc<HealthMonitor>,
-    bind: &str,
-    port: u16,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use http_body_util::Full;
-    use hyper::body::Bytes;
-    use hyper::service::service_fn;
-    use hyper::{Request, Response, StatusCode};
-    use hyper_util::rt::TokioIo;
-    use hyper_util::server::conn::auto::Builder;
-    use std::convert::Infallible;
-    use tokio::net::TcpListener;
-
-    let addr = format!("{}:{}", bind, port);
-    let listener = TcpListener::bind(&addr).await?;
-    info!(
-        "🌐 Health HTTP endpoint listening on http://{}/health",
-        addr
-    );
+    /// Record the outcome of an operation by a component
+    fn record_component(&self, name: &'static str, result: Result<(), String>) {
+        self.state()
+            .components
+            .entry(name)
+            .or_default()
+            .record(result, components::now());
+    }
 
-    loop {
-        let (stream, _) = match listener.accept().await {
-            Ok(conn) => conn,
-            Err(e) => {
-                error!("Failed to accept HTTP connection: {}", e);
-                tokio::time::sleep(Duration::from_millis(100)).await;
-                continue;
-            }
+    /// Check the Nostr subscription and the dispute store now, then return every
+    /// component's state
+    async fn check_components(&self) -> BTreeMap<&'static str, components::ComponentHealth> {
+        let readiness = self.readiness().await;
+        let nostr = if self.nostr_client.get().is_none() {
+            Err("not connected yet".to_string())
+        } else if readiness.relays_connected == 0 {
+            Err("no relay connected".to_string())
+        } else if readiness.relays_connected < readiness.relays_required {
+            Err(format!(
+                "{} of the {} required relays connected",
+                readiness.relays_connected, readiness.relays_required
+            ))
+        } else if !readiness.subscribed {
+            Err("dispute subscription not active".to_string())
+        } else {
+            Ok(())
         };
-        let health_monitor = health_monitor.clone();
+        self.record_component("nostr", nostr);
 
-        tokio::spawn(async move {
-            let service = service_fn(move |req: Request<hyper::body::Incoming>| {
-                let health_monitor = health_monitor.clone();
-                async move {
-                    match req.uri().path() {
-                        "/health" => {
-                            let status_json = health_monitor.get_status_json().await;
-                            Ok::<Response<Full<Bytes>>, Infallible>(
-                                Response::builder()
-                                    .status(StatusCode::OK)
-                                    .header("Content-Type", "application/json")
-                                    .body(Full::from(Bytes::from(status_json)))
-                                    .expect("valid response"),
-                            )
-                        }
-                        _ => Ok(Response::builder()
-                            .status(StatusCode::NOT_FOUND)
-                            .body(Full::from(Bytes::from("Not Found")))
-                            .expect("valid response")),
+        if let Some(store) = self.dispute_store.get() {
+            let result = store.probe_write().await.map_err(|e| e.to_string());
+            self.record_component("store", result);
+        }
+
+        self.state().components.clone()
+    }
+
+    /// Record a reconnect attempt for a disconnected relay
+    fn record_relay_reconnect(&self, url: &str) {
+        self.update_relay(url, |relay| relay.reconnects += 1);
+    }
+
+    /// Read every relay's connection status from the client, returning those not
+    /// connected; relays on standby are left out
+    async fn refresh_relay_statuses(&self, client: &Client, relays: &[String]) -> Vec<String> {
+        let mut failed_relays = Vec::new();
+
+        for relay_url_str in relays {
+            if self.is_relay_on_standby(relay_url_str) {
+                continue;
+            }
+            match client.pool().relay(relay_url_str).await {
+                Ok(relay) => {
+                    let status = relay.status();
+                    self.set_relay_status(relay_url_str, &status.to_string());
+                    if status != RelayStatus::Connected {
+                        failed_relays.push(relay_url_str.clone());
                     }
                 }
-            });
-
-            if let Err(err) = Builder::new(hyper_util::rt::TokioExecutor::new())
-                .serve_connection(TokioIo::new(stream), service)
-                .await
-            {
-                error!("Error serving HTTP connection: {:?}", err);
+                Err(_) => {
+                    self.set_relay_status(relay_url_str, "missing");
+                    failed_relays.push(relay_url_str.clone());
+                }
             }
-        });
+        }
+
+        failed_relays
     }
-}
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive("mostro_watchdog=info".parse()?),
-        )
-        .init();
+    /// Record that we received an event
+    fn record_event(&self) {
+        self.last_event_time
+            .store(components::now(), Ordering::Relaxed);
+        self.events_processed.fetch_add(1, Ordering::Relaxed);
+    }
 
-    let config_path = parse_config_path();
+    /// Record that we sent a heartbeat
+    fn record_heartbeat(&self) {
+        self.last_heartbeat
+            .store(components::now(), Ordering::Relaxed);
+    }
 
-    let config = Config::load(&config_path)?;
+    /// Unix timestamp of the last dispute event
+    fn last_event(&self) -> Option<u64> {
+        Some(self.last_event_time.load(Ordering::Relaxed)).filter(|&t| t > 0)
+    }
 
-    info!("🐕 mostro-watchdog starting...");
-    info!("Monitoring Mostro pubkey: {}", config.mostro.pubkey);
-    info!(
-        "Sending alerts to Telegram chat: {}",
-        config.telegram.chat_id
-    );
+    /// Unix timestamp of the last heartbeat sent
+    fn last_heartbeat(&self) -> Option<u64> {
+        Some(self.last_heartbeat.load(Ordering::Relaxed)).filter(|&t| t > 0)
+    }
 
-    // Initialize Telegram bot
-    let bot = Bot::new(&config.telegram.bot_token);
+    fn events_processed(&self) -> u64 {
+        self.events_processed.load(Ordering::Relaxed)
+    }
 
-    // Verify Telegram bot connection
-    match bot.get_me().await {
-        Ok(me) => info!("Telegram bot connected: @{}", me.username()),
-        Err(e) => {
-            error!("Failed to connect Telegram bot: {}", e);
-            return Err(e.into());
+    fn is_healthy(&self) -> bool {
+        self.is_healthy.load(Ordering::Relaxed)
+    }
+
+    /// Check if we should be concerned about lack of events
+    fn should_alert_no_events(&self, threshold_seconds: u64) -> bool {
+        if threshold_seconds == 0 {
+            return false; // Disabled
+        }
+
+        match self.last_event() {
+            None => {
+                // No events yet - check if we've been running long enough to be concerned
+                let uptime = self.start_time.elapsed().unwrap_or(Duration::ZERO);
+                uptime.as_secs() > threshold_seconds
+            }
+            Some(last) => components::now().saturating_sub(last) > threshold_seconds,
         }
     }
 
-    // Initialize Nostr client
-    let client = Client::default();
+    /// Snapshot of the current health status
+    fn status(&self) -> HealthStatus {
+        let (relays, clock_offset, degraded) = {
+            let state = self.state();
+            (
+                state.relays.values().cloned().collect(),
+                state.clock_offset,
+                state.degraded.clone(),
+            )
+        };
 
-    for relay in &config.nostr.relays {
-        info!("Adding relay: {}", relay);
-        client.add_relay(relay).await?;
+        HealthStatus {
+            healthy: self.is_healthy(),
+            uptime_secs: self
+                .start_time
+                .elapsed()
+                .unwrap_or(Duration::ZERO)
+                .as_secs(),
+            events_processed: self.events_processed(),
+            last_event: self.last_event(),
+            last_heartbeat: self.last_heartbeat(),
+            last_event_time: self.last_event().map(timestamp::iso8601),
+            last_heartbeat_time: self.last_heartbeat().map(timestamp::iso8601),
+            relays,
+            deliveries: stats::stats().snapshot().deliveries(),
+            consecutive_delivery_failures: self
+                .consecutive_delivery_failures
+                .load(Ordering::Relaxed),
+            clock_offset,
+            resources: resources::sample(),
+            degraded,
+            silenced: silence::active(),
+        }
     }
 
-    client.connect().await;
-    info!("Connected to {} relay(s)", config.nostr.relays.len());
+    /// Get health status as JSON, with the component breakdown when `verbose`
+    async fn get_status_json(&self, verbose: bool) -> String {
+        let components = match verbose {
+            true => Some(self.check_components().await),
+            false => None,
+        };
+        let report = HealthReport {
+            status: self.status(),
+            components,
+            version: VERSION,
+        };
+        serde_json::to_string(&report).expect("health report is serializable")
+    }
+}
 
-    // Subscribe to dispute events (kind 38386) from the configured Mostro pubkey
-    let mostro_pubkey = PublicKey::from_bech32(&config.mostro.pubkey)
-        .or_else(|_| PublicKey::from_hex(&config.mostro.pubkey))?;
+/// Point-in-time health data shared by the HTTP endpoint and the /health command
+#[derive(Debug, Clone, serde::Serialize)]
+struct HealthStatus {
+    #[serde(rename = "status", serialize_with = "serialize_healthy")]
+    healthy: bool,
+    #[serde(rename = "uptime_seconds")]
+    uptime_secs: u64,
+    events_processed: u64,
+    /// Unix timestamp of the last dispute event
+    #[serde(rename = "last_event_timestamp")]
+    last_event: Option<u64>,
+    /// Unix timestamp of the last heartbeat sent
+    #[serde(rename = "last_heartbeat_timestamp")]
+    last_heartbeat: Option<u64>,
+    /// `last_event` in ISO 8601
+    last_event_time: Option<String>,
+    /// `last_heartbeat` in ISO 8601
+    last_heartbeat_time: Option<String>,
+    relays: Vec<RelayHealth>,
+    /// Succeeded and failed deliveries per channel
+    deliveries: BTreeMap<String, stats::Deliveries>,
+    consecutive_delivery_failures: u64,
+    /// Estimated offset of the local clock in seconds, positive when it is behind
+    #[serde(rename = "clock_offset_seconds")]
+    clock_offset: Option<i64>,
+    /// Memory, file descriptors and tasks of this process
+    resources: resources::ResourceUsage,
+    /// Failing self-checks
+    degraded: Vec<&'static str>,
+    /// Silenced alert categories and when each silence ends
+    silenced: BTreeMap<silence::Category, u64>,
+}
 
-    let dispute_filter = Filter::new()
-        .kind(Kind::Custom(38386))
-        .author(mostro_pubkey)
-        .since(Timestamp::now());
+fn serialize_healthy<S: serde::Serializer>(
+    healthy: &bool,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(if *healthy { "healthy" } else { "unhealthy" })
+}
 
-    client.subscribe(vec![dispute_filter], None).await?;
+/// Body of the HTTP health endpoint
+#[derive(serde::Serialize)]
+struct HealthReport {
+    #[serde(flatten)]
+    status: HealthStatus,
+    /// Per-component breakdown, only in the verbose report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    components: Option<BTreeMap<&'static str, components::ComponentHealth>>,
+    version: &'static str,
+}
 
-    info!("🔍 Subscribed to dispute events. Watching...");
+/// Result of the `/ready` check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+struct Readiness {
+    ready: bool,
+    relays_connected: usize,
+    /// `health.min_connected_relays`
+    relays_required: usize,
+    /// Fewer than `MAX_CONSECUTIVE_DELIVERY_FAILURES` failed deliveries in a row
+    telegram: bool,
+    /// The dispute subscription is registered with the relay pool
+    subscribed: bool,
+}
 
-    // Initialize health monitor
-    let health_monitor = Arc::new(HealthMonitor::new());
-    let health_config = config.health.unwrap_or_default();
+/// Relay URLs may or may not carry a trailing slash depending on where they come from
+fn relay_key(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
 
-    // Start health check background tasks
-    start_health_tasks(
-        health_monitor.clone(),
-        bot.clone(),
-        config.telegram.chat_id,
-        &health_config,
-        client.clone(),
-        &config.nostr.relays,
-    );
+/// Health alerts, rendered from their templates and sent to the health chat and topic
+#[derive(Clone)]
+struct HealthAlerts {
+    bot: Bot,
+    destination: Destination,
+    templates: Arc<Templates>,
+}
 
-    // Initialize dispute message store
-    let db_path = config_path
-        .parent()
-        .unwrap_or(std::path::Path::new("."))
-        .join("disputes.db");
-    let dispute_store = Arc::new(
-        DisputeMessageStore::new(&db_path)
-            .await
-            .expect("Failed to initialize dispute message store"),
-    );
+impl HealthAlerts {
+    /// Publish the `kind` alert to the event stream and send it unless health alerts
+    /// are silenced; info alerts are delivered without a notification sound. Returns
+    /// false when sending failed.
+    async fn send(&self, kind: &str, severity: AlertSeverity, mut context: tera::Context) -> bool {
+        context.insert("severity", severity.as_str());
+        events::publish_health(kind, &context);
+        if silence::suppressed(silence::Category::Health, kind) {
+            // Logged by the silence check
+            return true;
+        }
 
-    // Send startup notification
-    let startup_msg = format!(
-        "🐕 *mostro\\-watchdog* is now online and monitoring for disputes\\.\n\n\
-         📊 Heartbeat: {}\n\
-         🔔 Event silence alert: {}",
-        if health_config.heartbeat_enabled {
-            format!("every {} seconds", health_config.heartbeat_interval)
-        } else {
-            "disabled".to_string()
-        },
-        if health_config.event_alert_threshold > 0 {
-            format!("{} seconds", health_config.event_alert_threshold)
-        } else {
-            "disabled".to_string()
+        let message = self.templates.render(kind, &context);
+        let destination = Destination {
+            silent: severity == AlertSeverity::Info,
+            ..self.destination
+        };
+        match send_markdown(&self.bot, destination, &message).await {
+            Ok(_) => true,
+            Err(e) => {
+                error!("Failed to send {} alert: {}", kind, e);
+                false
+            }
         }
-    );
+    }
+}
 
-    if let Err(e) = bot
-        .send_message(ChatId(config.telegram.chat_id), &startup_msg)
-        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-        .await
-    {
-        warn!("Failed to send startup message: {}", e);
+/// What the health check tasks share. Each check is a method run under the
+/// supervisor, which calls it again on a clone when the task is restarted.
+#[derive(Clone)]
+struct HealthTasks {
+    health_monitor: Arc<HealthMonitor>,
+    client: Client,
+    nostr: Arc<config::NostrConfig>,
+    /// Thresholds are re-read on every check so config reloads apply to running tasks
+    live_health: Arc<reload::Live<config::HealthConfig>>,
+    alerts: HealthAlerts,
+}
+
+/// Start health monitoring background tasks
+#[allow(clippy::too_many_arguments)]
+fn start_health_tasks(
+    health_monitor: Arc<HealthMonitor>,
+    bot: Bot,
+    telegram_config: &config::TelegramConfig,
+    live_health: &Arc<reload::Live<config::HealthConfig>>,
+    client: Client,
+    nostr: &config::NostrConfig,
+    dispute_filter: Filter,
+    dispute_subscriptions: Vec<subscriptions::DisputeSubscription>,
+    templates: Arc<Templates>,
+    severity: &config::SeverityConfig,
+    in_flight: shutdown::Tracker,
+) {
+    let chat_id = telegram_config.health_chat();
+    let health_config = live_health.get();
+    let alerts = HealthAlerts {
+        bot: bot.clone(),
+        destination: Destination {
+            chat_id,
+            topic_id: telegram_config.health_topic(),
+            silent: false,
+        },
+        templates: templates.clone(),
+    };
+
+    // Restarted tasks are reported to the health topic
+    let supervisor = Supervisor::new(
+        health_config
+            .notify_task_restarts
+            .then(|| (bot.clone(), alerts.destination, templates.clone())),
+    );
+    let tasks = HealthTasks {
+        health_monitor: health_monitor.clone(),
+        client: client.clone(),
+        nostr: Arc::new(nostr.clone()),
+        live_health: live_health.clone(),
+        alerts,
+    };
+
+    // Heartbeat task, also pinging the dead man's switch
+    if health_config.heartbeat_enabled || health_config.ping_url.is_some() {
+        let tasks = tasks.clone();
+        let heartbeat_interval = health_config.heartbeat_interval;
+        let heartbeat_dest = health_config.heartbeat_enabled.then_some(Destination {
+            chat_id,
+            topic_id: telegram_config.heartbeat_topic(),
+            silent: health_config.heartbeat_silent,
+        });
+        let ping_url = health_config.ping_url.clone();
+        supervisor.spawn(
+            "heartbeat",
+            Some(stall_timeout(heartbeat_interval)),
+            move |liveness| {
+                tasks.clone().heartbeat(
+                    heartbeat_interval,
+                    heartbeat_dest,
+                    ping_url.clone(),
+                    liveness,
+                )
+            },
+        );
+    }
+
+    // Event silence monitoring task
+    if health_config.event_alert_threshold > 0 {
+        let tasks = tasks.clone();
+        let severity = severity.event_silence;
+        let check_period = std::cmp::max(health_config.event_alert_threshold / 2, 1);
+        supervisor.spawn(
+            "event silence monitor",
+            Some(stall_timeout(check_period)),
+            move |liveness| {
+                tasks
+                    .clone()
+                    .event_silence(check_period, severity, liveness)
+            },
+        );
+    }
+
+    // Relay connectivity check task
+    if health_config.check_relays {
+        let tasks = tasks.clone();
+        let health_config = health_config.clone();
+        let subscriptions = dispute_subscriptions.clone();
+        let severity = severity.clone();
+        let check_secs = RELAY_POLL_INTERVAL.min(health_config.relay_timeout);
+        supervisor.spawn(
+            "relay check",
+            Some(stall_timeout(health_config.relay_timeout + check_secs)),
+            move |liveness| {
+                tasks.clone().relay_check(
+                    health_config.clone(),
+                    dispute_filter.clone(),
+                    subscriptions.clone(),
+                    severity.clone(),
+                    liveness,
+                )
+            },
+        );
+    }
+
+    // Relay status notifications: alerts on each change, and resubscribing after a
+    // reconnection so nothing published meanwhile is skipped
+    {
+        let tasks = tasks.clone();
+        let alert_relays = health_config.check_relays;
+        let severity = severity.relay_alert;
+        supervisor.spawn("relay status", None, move |_liveness| {
+            tasks.clone().relay_status(alert_relays, severity)
+        });
+    }
+
+    // Dead dispute subscription check task
+    {
+        let tasks = tasks.clone();
+        supervisor.spawn(
+            "subscription check",
+            Some(stall_timeout(SUBSCRIPTION_CHECK_INTERVAL)),
+            move |liveness| {
+                tasks
+                    .clone()
+                    .subscription_check(dispute_subscriptions.clone(), liveness)
+            },
+        );
+    }
+
+    // Clock drift check task
+    if health_config.max_clock_drift > 0 {
+        let tasks = tasks.clone();
+        supervisor.spawn(
+            "clock drift check",
+            Some(stall_timeout(CLOCK_CHECK_INTERVAL)),
+            move |liveness| tasks.clone().clock_drift_check(liveness),
+        );
+    }
+
+    // Relay latency check task
+    if health_config.max_relay_latency > 0 {
+        let tasks = tasks.clone();
+        supervisor.spawn(
+            "relay latency check",
+            Some(stall_timeout(LATENCY_CHECK_INTERVAL)),
+            move |liveness| tasks.clone().relay_latency_check(liveness),
+        );
+    }
+
+    // Relay ping task
+    if health_config.relay_ping_interval > 0 {
+        let tasks = tasks.clone();
+        let interval_secs = health_config.relay_ping_interval;
+        // Pings are queries, which relays without the disputes role aren't read from
+        let relay_count = nostr.dispute_relays().len() as u64;
+        supervisor.spawn(
+            "relay ping",
+            Some(stall_timeout(
+                interval_secs + ping::TIMEOUT.as_secs() * relay_count,
+            )),
+            move |liveness| tasks.clone().relay_ping(interval_secs, liveness),
+        );
+    }
+
+    // Event loop watchdog task
+    if health_config.event_loop_stall_timeout > 0 {
+        let tasks = tasks.clone();
+        let timeout = Duration::from_secs(health_config.event_loop_stall_timeout);
+        supervisor.spawn(
+            "event loop watchdog",
+            Some(stall_timeout(
+                event_loop::CHECK_INTERVAL.as_secs() + ping::TIMEOUT.as_secs(),
+            )),
+            move |liveness| tasks.clone().event_loop_watchdog(timeout, liveness),
+        );
+    }
+
+    // Memory limit check task
+    if health_config.max_memory_mb > 0 {
+        let tasks = tasks.clone();
+        supervisor.spawn(
+            "memory check",
+            Some(stall_timeout(MEMORY_CHECK_INTERVAL)),
+            move |liveness| tasks.clone().memory_check(liveness),
+        );
+    }
+
+    // Self-health degradation check task
+    if health_config.degraded_alerts {
+        let monitor = Arc::new(degradation::DegradationMonitor {
+            health_monitor: health_monitor.clone(),
+            client: client.clone(),
+            relays: nostr.relays.to_vec(),
+            max_queue_backlog: health_config.max_queue_backlog,
+            bot: bot.clone(),
+            destination: tasks.alerts.destination,
+            templates: templates.clone(),
+            fallback: health_config.degraded_fallback.clone(),
+            http: reqwest::Client::new(),
+        });
+
+        supervisor.spawn(
+            "degradation check",
+            Some(stall_timeout(degradation::CHECK_INTERVAL)),
+            move |liveness| monitor.clone().run(liveness),
+        );
+    }
+
+    // HTTP health endpoint task
+    if health_config.enable_http_endpoint {
+        let health_monitor_http = health_monitor.clone();
+        let health_config_http = health_config.clone();
+
+        // The server has no loop to report progress from, so only exits are supervised
+        supervisor.spawn("health HTTP server", None, move |_| {
+            let health_monitor_http = health_monitor_http.clone();
+            let health_config_http = health_config_http.clone();
+            let in_flight = in_flight.clone();
+            async move {
+                if let Err(e) =
+                    http::serve(health_monitor_http, &health_config_http, in_flight).await
+                {
+                    error!("Health HTTP server failed: {}", e);
+                }
+            }
+        });
+    }
+
+    // gRPC health service task
+    #[cfg(feature = "grpc")]
+    if let Some(port) = health_config.grpc_port {
+        let health_monitor_grpc = health_monitor.clone();
+        let health_config_grpc = health_config.clone();
+
+        supervisor.spawn("gRPC health server", None, move |_| {
+            let health_monitor_grpc = health_monitor_grpc.clone();
+            let health_config_grpc = health_config_grpc.clone();
+            async move {
+                if let Err(e) = grpc::serve(health_monitor_grpc, &health_config_grpc, port).await {
+                    error!("gRPC health server failed: {}", e);
+                }
+            }
+        });
+    }
+}
+
+impl HealthTasks {
+    /// Send a heartbeat to `destination`, if heartbeats are enabled, and ping the dead
+    /// man's switch every `interval_secs`
+    async fn heartbeat(
+        self,
+        interval_secs: u64,
+        destination: Option<Destination>,
+        ping_url: Option<String>,
+        liveness: Liveness,
+    ) {
+        let http = reqwest::Client::new();
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        interval.tick().await; // skip the immediate first tick
+
+        loop {
+            interval.tick().await;
+
+            let mut ok = self.health_monitor.is_healthy();
+            if let Some(destination) = destination {
+                // Report current relay connections, not the last relay check's
+                self.health_monitor
+                    .refresh_relay_statuses(&self.client, &self.nostr.relays)
+                    .await;
+                if let Err(e) = send_heartbeat(
+                    &self.health_monitor,
+                    &self.alerts.bot,
+                    destination,
+                    &self.alerts.templates,
+                )
+                .await
+                {
+                    error!("Failed to send heartbeat: {}", e);
+                    ok = false;
+                }
+            }
+
+            if let Some(ref ping_url) = ping_url {
+                ping_dead_mans_switch(&http, ping_url, ok).await;
+            }
+            liveness.beat();
+        }
+    }
+
+    /// Alert when no dispute event arrived within `health.event_alert_threshold`, at
+    /// most once per threshold period
+    async fn event_silence(self, check_period: u64, severity: AlertSeverity, liveness: Liveness) {
+        let mut interval = tokio::time::interval(Duration::from_secs(check_period));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        interval.tick().await; // skip the immediate first tick
+
+        let mut last_alert = SystemTime::UNIX_EPOCH;
+
+        loop {
+            interval.tick().await;
+            liveness.beat();
+
+            let threshold = self.live_health.get().event_alert_threshold;
+            if threshold == 0 || !self.health_monitor.should_alert_no_events(threshold) {
+                continue;
+            }
+            // Avoid spam - only alert once every threshold period
+            let now = SystemTime::now();
+            if now
+                .duration_since(last_alert)
+                .unwrap_or(Duration::MAX)
+                .as_secs()
+                < threshold
+            {
+                continue;
+            }
+
+            let uptime = self
+                .health_monitor
+                .start_time
+                .elapsed()
+                .unwrap_or(Duration::ZERO)
+                .as_secs();
+            warn!(
+                "⚠️ No dispute events for {}h, sending an event silence alert",
+                threshold / 3600
+            );
+            let mut context = tera::Context::new();
+            context.insert("threshold_hours", &(threshold / 3600));
+            context.insert("uptime_hours", &(uptime / 3600));
+            context.insert("uptime_minutes", &((uptime % 3600) / 60));
+            if self.alerts.send("event_silence", severity, context).await {
+                last_alert = now;
+            }
+        }
+    }
+
+    /// Score, blacklist, reconnect and fail over relays, engage the fallback relays and
+    /// alert on the connected relay quorum
+    async fn relay_check(
+        self,
+        health_config: Arc<config::HealthConfig>,
+        dispute_filter: Filter,
+        subscriptions: Vec<subscriptions::DisputeSubscription>,
+        severity: config::SeverityConfig,
+        liveness: Liveness,
+    ) {
+        let Self {
+            health_monitor,
+            client,
+            nostr,
+            live_health,
+            alerts,
+        } = self;
+        let relays = &nostr.relays;
+        let backoff = backoff::Backoff {
+            base: Duration::from_secs(health_config.relay_timeout),
+            max: Duration::from_secs(health_config.relay_backoff_max),
+        };
+        let blacklist_score = health_config.relay_blacklist_score;
+        let blacklist_retry = health_config.relay_blacklist_retry;
+        let check_secs = RELAY_POLL_INTERVAL.min(health_config.relay_timeout);
+
+        // Give the initial connections relay_timeout to establish
+        tokio::time::sleep(backoff.base).await;
+        let mut interval = tokio::time::interval(Duration::from_secs(check_secs));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut reconnects = backoff::RelayReconnects::new(backoff);
+        let mut scores =
+            relay_score::RelayScores::new(blacklist_score, Duration::from_secs(blacklist_retry));
+        let mut fallback = fallback::Fallback::new(&nostr, dispute_filter);
+        let mut failover = failover::Failover::new(&nostr, backoff.base);
+        // Promoted relays not yet subscribed to disputes
+        let mut unsubscribed: Vec<String> = Vec::new();
+        let mut below_quorum = false;
+
+        loop {
+            interval.tick().await;
+
+            let failed_relays = health_monitor.refresh_relay_statuses(&client, relays).await;
+            let now = tokio::time::Instant::now();
+            let standby = failover
+                .as_ref()
+                .map(|failover| failover.standby())
+                .unwrap_or_default();
+            let mut blacklisted = Vec::new();
+            for relay_url in relays {
+                if standby.contains(relay_url) {
+                    continue;
+                }
+                let connected = !failed_relays.contains(relay_url);
+                let disputes = nostr.has_role(relay_url, config::RelayRole::Disputes);
+                // Relays with a narrower filter aren't sent every dispute
+                let deliveries = if disputes && !nostr.relay_filters.contains_key(relay_url) {
+                    health_monitor.relay_deliveries(relay_url)
+                } else {
+                    Default::default()
+                };
+                // Keep at least one other relay for disputes, or at all
+                let may_blacklist = relays.iter().any(|other| {
+                    other != relay_url
+                        && !scores.is_blacklisted(other)
+                        && (!disputes || nostr.has_role(other, config::RelayRole::Disputes))
+                });
+                match scores.check(relay_url, connected, deliveries, may_blacklist, now) {
+                    relay_score::Check::Scored(score) => {
+                        health_monitor.set_relay_score(relay_url, Some(score), None);
+                    }
+                    relay_score::Check::Blacklisted => continue,
+                    relay_score::Check::Blacklist(score) => {
+                        warn!("🚫 Blacklisting relay {} (score {})", relay_url, score);
+                        let until = components::now() + blacklist_retry;
+                        health_monitor.set_relay_score(relay_url, Some(score), Some(until));
+                        if let Err(e) = client.disconnect_relay(relay_url).await {
+                            warn!("Failed to disconnect {}: {}", relay_url, e);
+                        }
+                        reconnects.connected(relay_url);
+                        blacklisted.push((relay_url.clone(), score));
+                        continue;
+                    }
+                    relay_score::Check::Retry => {
+                        info!("Retrying blacklisted relay {}", relay_url);
+                        health_monitor.set_relay_score(relay_url, None, None);
+                        if let Err(e) = reconnect_relay(&client, &nostr, relay_url).await {
+                            warn!("Failed to reconnect to {}: {}", relay_url, e);
+                        }
+                        continue;
+                    }
+                }
+
+                if connected {
+                    reconnects.connected(relay_url);
+                    continue;
+                }
+                if reconnects.disconnected(relay_url, now).is_none() {
+                    continue; // Backing off
+                }
+                stats::stats().record_relay_reconnects(1);
+                health_monitor.record_relay_reconnect(relay_url);
+                if let Err(e) = reconnect_relay(&client, &nostr, relay_url).await {
+                    warn!("Failed to reconnect to {}: {}", relay_url, e);
+                }
+            }
+
+            if let Some(step) = failover
+                .as_mut()
+                .and_then(|failover| failover.step(&failed_relays, now))
+            {
+                match &step {
+                    failover::Step::Promote(relay) => {
+                        warn!("⬆️ Promoting secondary relay {}", relay);
+                        health_monitor.set_relay_standby(relay, false);
+                        if let Err(e) = reconnect_relay(&client, &nostr, relay).await {
+                            warn!("Failed to connect to {}: {}", relay, e);
+                        }
+                        unsubscribed.push(relay.clone());
+                    }
+                    failover::Step::Demote(relay) => {
+                        info!("⬇️ Primary relays back, demoting {}", relay);
+                        health_monitor.set_relay_standby(relay, true);
+                        if let Err(e) = client.disconnect_relay(relay).await {
+                            warn!("Failed to disconnect {}: {}", relay, e);
+                        }
+                        reconnects.connected(relay);
+                        unsubscribed.retain(|other| other != relay);
+                    }
+                }
+            }
+            // Subscribe promoted relays once they are connected
+            let mut pending = Vec::new();
+            for relay in unsubscribed.drain(..) {
+                if failed_relays.contains(&relay) {
+                    pending.push(relay);
+                    continue;
+                }
+                if let Err(e) = subscribe_promoted(&client, &subscriptions, &relay).await {
+                    warn!("Failed to subscribe to disputes on {}: {}", relay, e);
+                    pending.push(relay);
+                }
+            }
+            unsubscribed = pending;
+
+            if let Some(fallback) = fallback.as_mut() {
+                let primaries: Vec<String> = nostr
+                    .dispute_relays()
+                    .into_iter()
+                    .filter(|relay| !standby.contains(relay))
+                    .collect();
+                let primaries_down = primaries.iter().all(|relay| failed_relays.contains(relay));
+                let change = fallback.update(&client, &nostr, primaries_down, now).await;
+                if let Some(change) = change {
+                    let mut context = tera::Context::new();
+                    match change {
+                        fallback::Change::Engaged { down_for } => {
+                            warn!("⚠️ Every primary relay is down, using fallback relays");
+                            context.insert("engaged", &true);
+                            context.insert("minutes", &(down_for.as_secs() / 60));
+                        }
+                        fallback::Change::Released => {
+                            info!("✅ Primary relay back, released fallback relays");
+                            context.insert("engaged", &false);
+                        }
+                    }
+                    context.insert("relays", fallback.relays());
+                    context.insert("primary_count", &primaries.len());
+                    alerts
+                        .send("fallback_relays", severity.relay_alert, context)
+                        .await;
+                }
+            }
+
+            for (relay, score) in blacklisted {
+                let mut context = tera::Context::new();
+                context.insert("relay", &relay);
+                context.insert("score", &score);
+                context.insert("threshold", &blacklist_score);
+                context.insert("retry_minutes", &(blacklist_retry / 60));
+                alerts
+                    .send("relay_blacklisted", severity.relay_alert, context)
+                    .await;
+            }
+
+            // Fewer relays than the quorum is alerted on, and again when it is restored
+            let connected_count = relays.len() - failed_relays.len() - standby.len();
+            let min_connected = live_health.get().min_connected_relays;
+            if (connected_count < min_connected) != below_quorum {
+                below_quorum = !below_quorum;
+                let mut context = tera::Context::new();
+                context.insert("lost", &below_quorum);
+                context.insert("connected_count", &connected_count);
+                context.insert("min_connected", &min_connected);
+                context.insert("relay_count", &relays.len());
+                context.insert("failed_relays", &failed_relays);
+                if below_quorum {
+                    error!(
+                        "🚨 Only {} of the {} required relays connected",
+                        connected_count, min_connected
+                    );
+                } else {
+                    info!("✅ Relay quorum restored ({} connected)", connected_count);
+                }
+                alerts
+                    .send("relay_quorum", severity.relay_quorum, context)
+                    .await;
+            }
+            liveness.beat();
+        }
+    }
+
+    /// Follow relay status changes: resubscribe reconnected relays from their resume
+    /// point, and alert on each disconnection and reconnection when `alert_relays`
+    async fn relay_status(self, alert_relays: bool, severity: AlertSeverity) {
+        let relays = &self.nostr.relays;
+        let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+        let _watchers = relay_status::watch(&self.client, relays, tx).await;
+        let mut transitions = relay_status::Transitions::default();
+
+        while let Some((relay, status)) = rx.recv().await {
+            // Blacklisting and demotion disconnect a relay on purpose
+            if self.health_monitor.is_relay_blacklisted(&relay)
+                || self.health_monitor.is_relay_on_standby(&relay)
+            {
+                continue;
+            }
+            let now = tokio::time::Instant::now();
+            let Some(transition) = transitions.update(&relay, status, now) else {
+                continue;
+            };
+            if let relay_status::Transition::Reconnected { .. } = transition {
+                match self.health_monitor.relay_resume_point(&relay).await {
+                    Some(since) => {
+                        match resubscribe_since(&self.client, &relay, Timestamp::from(since)).await
+                        {
+                            Ok(renewed) => info!(
+                                "Resubscribed {} subscription(s) on {} since {}",
+                                renewed,
+                                relay,
+                                timestamp::utc(since)
+                            ),
+                            Err(e) => warn!("Failed to resubscribe on {}: {}", relay, e),
+                        }
+                    }
+                    None => debug!("No dispute event seen yet, {} resumes as is", relay),
+                }
+            }
+            if !alert_relays {
+                continue;
+            }
+
+            let down = transitions.down();
+            let mut context = tera::Context::new();
+            context.insert("relay", &relay);
+            let (name, severity) = match transition {
+                relay_status::Transition::Disconnected => {
+                    warn!("🔌 Relay {} disconnected ({})", relay, status);
+                    context.insert("failed_relays", &down);
+                    context.insert("failed_count", &down.len());
+                    context.insert("connected_count", &(relays.len() - down.len()));
+                    ("relay_alert", severity)
+                }
+                relay_status::Transition::Reconnected { down_for } => {
+                    info!(
+                        "✅ Relay {} reconnected after {}s",
+                        relay,
+                        down_for.as_secs()
+                    );
+                    context.insert("minutes", &(down_for.as_secs() / 60));
+                    context.insert("connected_count", &(relays.len() - down.len()));
+                    // Good news doesn't need a notification sound
+                    ("relay_reconnected", AlertSeverity::Info)
+                }
+            };
+            self.alerts.send(name, severity, context).await;
+        }
+    }
+
+    /// Re-create dispute subscriptions that relays closed or stopped serving
+    async fn subscription_check(
+        self,
+        subscriptions: Vec<subscriptions::DisputeSubscription>,
+        liveness: Liveness,
+    ) {
+        let mut interval = tokio::time::interval(Duration::from_secs(SUBSCRIPTION_CHECK_INTERVAL));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut watch = subscriptions::Watch::default();
+
+        loop {
+            interval.tick().await;
+            for subscription in &subscriptions {
+                for url in &subscription.relays {
+                    let Ok(relay) = self.client.pool().relay(url).await else {
+                        continue;
+                    };
+                    if relay.status() != RelayStatus::Connected
+                        || self.health_monitor.is_relay_blacklisted(url)
+                    {
+                        continue;
+                    }
+                    let closed = self.health_monitor.take_subscription_closed(url);
+                    let deliveries = self.health_monitor.relay_deliveries(url);
+                    // Relays with a narrower filter aren't sent every dispute
+                    let silent = !self.nostr.relay_filters.contains_key(url);
+                    let Some(death) = watch.check(url, closed, deliveries, silent) else {
+                        continue;
+                    };
+
+                    let mut filter = subscription.filter.clone();
+                    if let Some(since) = self.health_monitor.relay_resume_point(url).await {
+                        filter = filter.since(Timestamp::from(since));
+                    }
+                    let renewed = relay
+                        .subscribe_with_id(
+                            subscription.id.clone(),
+                            vec![filter],
+                            SubscribeOptions::default(),
+                        )
+                        .await
+                        .map_err(|e| e.to_string());
+                    let mut context = tera::Context::new();
+                    context.insert("relay", url);
+                    let (closed, missed) = match death {
+                        subscriptions::Death::Closed(message) => {
+                            warn!("🔕 {} closed the dispute subscription: {}", url, message);
+                            (Some(message), None)
+                        }
+                        subscriptions::Death::Silent { missed } => {
+                            warn!(
+                                "🔕 {} missed {} dispute events other relays delivered",
+                                url, missed
+                            );
+                            (None, Some(missed))
+                        }
+                    };
+                    context.insert("closed", &closed);
+                    context.insert("missed", &missed);
+                    match &renewed {
+                        Ok(()) => self.health_monitor.record_subscription_renewed(url),
+                        Err(e) => error!("Failed to re-create subscription on {}: {}", url, e),
+                    }
+                    context.insert("error", &renewed.err());
+                    self.alerts
+                        .send("dead_subscription", AlertSeverity::Warning, context)
+                        .await;
+                }
+            }
+            liveness.beat();
+        }
+    }
+
+    /// Estimate the local clock's offset and alert when it drifts past
+    /// `health.max_clock_drift`
+    async fn clock_drift_check(self, liveness: Liveness) {
+        let http = reqwest::Client::new();
+        let mut interval = tokio::time::interval(Duration::from_secs(CLOCK_CHECK_INTERVAL));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut drifting = false;
+
+        loop {
+            interval.tick().await;
+
+            // Relay clocks are precise; event timestamps include propagation delay
+            let estimate = match clock::relay_offset(&http, &self.nostr.relays).await {
+                Some(offset) => Some((offset, clock::OffsetSource::Relays)),
+                None => self
+                    .health_monitor
+                    .event_offsets
+                    .median()
+                    .map(|offset| (offset, clock::OffsetSource::Events)),
+            };
+            liveness.beat();
+            let Some((offset, source)) = estimate else {
+                continue;
+            };
+            self.health_monitor.set_clock_offset(offset);
+
+            let max_drift = self.live_health.get().max_clock_drift;
+            let was_drifting = drifting;
+            drifting = max_drift > 0 && offset.unsigned_abs() > max_drift;
+            if drifting && !was_drifting {
+                warn!(
+                    "🕰️ Local clock is {}s off ({}), threshold {}s",
+                    offset,
+                    source.as_str(),
+                    max_drift
+                );
+                let mut context = tera::Context::new();
+                context.insert("drift", &offset.unsigned_abs());
+                context.insert("behind", &(offset > 0));
+                context.insert("from_relays", &(source == clock::OffsetSource::Relays));
+                context.insert("threshold", &max_drift);
+                self.alerts
+                    .send("clock_drift", AlertSeverity::Warning, context)
+                    .await;
+            } else if was_drifting && !drifting {
+                info!("🕰️ Local clock back in sync ({}s off)", offset);
+            }
+        }
+    }
+
+    /// Alert once per relay whose p90 propagation latency exceeds
+    /// `health.max_relay_latency`, until it recovers
+    async fn relay_latency_check(self, liveness: Liveness) {
+        let mut interval = tokio::time::interval(Duration::from_secs(LATENCY_CHECK_INTERVAL));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        // Relays currently over the threshold, alerted once until they recover
+        let mut slow = HashSet::new();
+
+        loop {
+            interval.tick().await;
+            liveness.beat();
+
+            let max_latency = self.live_health.get().max_relay_latency;
+            if max_latency == 0 {
+                slow.clear();
+                continue;
+            }
+            let relays = self.health_monitor.state().relays.clone();
+            for relay in relays.values() {
+                let Some(latency) = relay.latency else {
+                    continue;
+                };
+
+                if latency.p90 <= max_latency {
+                    if slow.remove(&relay.url) {
+                        info!(
+                            "🐢 Relay {} latency back to normal (p90 {}s)",
+                            relay.url, latency.p90
+                        );
+                    }
+                    continue;
+                }
+                if !slow.insert(relay.url.clone()) {
+                    continue;
+                }
+
+                warn!(
+                    "🐢 Relay {} latency p90 {}s exceeds {}s",
+                    relay.url, latency.p90, max_latency
+                );
+                let mut context = tera::Context::new();
+                context.insert("relay", &relay.url);
+                context.insert("p50", &latency.p50);
+                context.insert("p90", &latency.p90);
+                context.insert("p99", &latency.p99);
+                context.insert("threshold", &max_latency);
+                self.alerts
+                    .send("relay_latency", AlertSeverity::Warning, context)
+                    .await;
+            }
+        }
+    }
+
+    /// Ping the connected dispute relays every `interval_secs` and alert on slow or
+    /// silent ones
+    async fn relay_ping(self, interval_secs: u64, liveness: Liveness) {
+        // Pings are queries, which relays without the disputes role aren't read from
+        let relays = self.nostr.dispute_relays();
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut states: HashMap<String, ping::PingState> = HashMap::new();
+
+        loop {
+            interval.tick().await;
+            liveness.beat();
+
+            let health = self.live_health.get();
+            let max_rtt = Duration::from_millis(health.max_relay_rtt);
+            for relay in &relays {
+                // Disconnected relays are the relay check's business
+                let connected = self
+                    .health_monitor
+                    .state()
+                    .relays
+                    .get(&relay_key(relay))
+                    .is_some_and(|relay| relay.status == "connected");
+                if !connected {
+                    continue;
+                }
+
+                let result = ping::ping(&self.client, relay).await;
+                liveness.beat();
+                self.health_monitor
+                    .record_relay_ping(relay, result.as_ref().ok().copied());
+                let state = states.entry(relay.clone()).or_default();
+                let verdict = state.record(
+                    result.as_ref().ok().copied(),
+                    max_rtt,
+                    health.relay_ping_failures,
+                );
+                match verdict {
+                    ping::Verdict::Ok => continue,
+                    ping::Verdict::Recovered => {
+                        info!("📶 Relay {} replies to pings normally again", relay);
+                        continue;
+                    }
+                    ping::Verdict::Alert => {}
+                }
+
+                let mut context = tera::Context::new();
+                context.insert("relay", relay);
+                context.insert("threshold_ms", &health.max_relay_rtt);
+                context.insert("failures", &state.failures);
+                match &result {
+                    Ok(rtt) => {
+                        warn!("📶 Relay {} ping took {} ms", relay, rtt.as_millis());
+                        context.insert("rtt_ms", &(rtt.as_millis() as u64));
+                    }
+                    Err(e) => {
+                        warn!(
+                            "📶 Relay {} failed {} pings in a row: {}",
+                            relay, state.failures, e
+                        );
+                        context.insert("rtt_ms", &None::<u64>);
+                        context.insert("error", e);
+                    }
+                }
+                self.alerts
+                    .send("relay_ping", AlertSeverity::Warning, context)
+                    .await;
+            }
+        }
+    }
+
+    /// Restart the Nostr event loop and reconnect its relays when it makes no progress
+    /// for `timeout` while relays are connected
+    async fn event_loop_watchdog(self, timeout: Duration, liveness: Liveness) {
+        let mut interval = tokio::time::interval(event_loop::CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            interval.tick().await;
+            liveness.beat();
+
+            let mut connected = Vec::new();
+            for relay in &self.nostr.relays {
+                if let Ok(r) = self.client.pool().relay(relay).await {
+                    if r.status() == RelayStatus::Connected {
+                        connected.push(relay.clone());
+                    }
+                }
+            }
+            // Nothing reaches the loop without a relay; the relay check alerts on that
+            let Some(first) = connected.first() else {
+                continue;
+            };
+            // The ping's reply passes through the loop like any relay message
+            let _ = ping::ping(&self.client, first).await;
+            liveness.beat();
+
+            let idle = event_loop::idle();
+            if !event_loop::is_stalled(idle, timeout) {
+                continue;
+            }
+            error!(
+                "🧊 Nostr event loop made no progress for {}s with {} relay(s) connected, restarting it",
+                idle.as_secs(),
+                connected.len()
+            );
+            event_loop::request_restart();
+            let mut failures = Vec::new();
+            for relay in &connected {
+                if let Err(e) = self.client.disconnect_relay(relay.as_str()).await {
+                    warn!("Failed to disconnect {}: {}", relay, e);
+                }
+                if let Err(e) = reconnect_relay(&self.client, &self.nostr, relay).await {
+                    error!("Failed to reconnect {}: {}", relay, e);
+                    failures.push(format!("{relay}: {e}"));
+                }
+            }
+
+            let mut context = tera::Context::new();
+            context.insert("idle_seconds", &idle.as_secs());
+            context.insert("relays", &connected);
+            context.insert(
+                "error",
+                &(!failures.is_empty()).then(|| failures.join(", ")),
+            );
+            self.alerts
+                .send("event_loop_restarted", AlertSeverity::Warning, context)
+                .await;
+        }
+    }
+
+    /// Alert when the process's memory use exceeds `health.max_memory_mb`, and log when
+    /// it is back under
+    async fn memory_check(self, liveness: Liveness) {
+        let mut interval = tokio::time::interval(Duration::from_secs(MEMORY_CHECK_INTERVAL));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut exceeded = false;
+
+        loop {
+            interval.tick().await;
+            liveness.beat();
+
+            let usage = resources::sample();
+            let Some(rss_mb) = usage.rss_mb() else {
+                continue;
+            };
+
+            let max_memory_mb = self.live_health.get().max_memory_mb;
+            let was_exceeded = exceeded;
+            exceeded = max_memory_mb > 0 && rss_mb > max_memory_mb;
+            if exceeded && !was_exceeded {
+                warn!(
+                    "🧠 Memory usage {} MB exceeds the {} MB limit",
+                    rss_mb, max_memory_mb
+                );
+                let mut context = tera::Context::new();
+                context.insert("rss_mb", &rss_mb);
+                context.insert("limit_mb", &max_memory_mb);
+                context.insert("open_fds", &usage.open_fds);
+                context.insert("tasks", &usage.tasks);
+                self.alerts
+                    .send("memory_alert", AlertSeverity::Warning, context)
+                    .await;
+            } else if was_exceeded && !exceeded {
+                info!("🧠 Memory usage back under the limit ({} MB)", rss_mb);
+            }
+        }
+    }
+}
+
+/// Check for clock drift every 10 minutes
+const CLOCK_CHECK_INTERVAL: u64 = 600;
+
+/// Check memory usage every minute
+const MEMORY_CHECK_INTERVAL: u64 = 60;
+
+/// Check relay latency every minute
+const LATENCY_CHECK_INTERVAL: u64 = 60;
+
+/// Check the dispute subscriptions every 30 seconds
+const SUBSCRIPTION_CHECK_INTERVAL: u64 = 30;
+
+/// How long a periodic task may go without completing an iteration before it is
+/// considered stalled: two periods plus a minute of slack for slow network calls
+fn stall_timeout(period_secs: u64) -> Duration {
+    Duration::from_secs(period_secs.saturating_mul(2).saturating_add(60))
+}
+
+/// Tell the external dead man's switch that the watchdog is alive, or that it is failing
+async fn ping_dead_mans_switch(http: &reqwest::Client, ping_url: &str, ok: bool) {
+    let url = if ok {
+        ping_url.to_string()
+    } else {
+        format!("{}/fail", ping_url.trim_end_matches('/'))
+    };
+    let url = &url;
+    let result = retry::POLICY
+        .run("Dead man's switch ping", || async move {
+            http.get(url)
+                .timeout(Duration::from_secs(10))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+        })
+        .await;
+    match result {
+        Ok(_) => debug!(
+            "Pinged dead man's switch ({})",
+            if ok { "ok" } else { "fail" }
+        ),
+        Err(e) => warn!("Failed to ping dead man's switch: {}", e),
+    }
+}
+
+/// Send a heartbeat message and record it
+async fn send_heartbeat(
+    health_monitor: &HealthMonitor,
+    bot: &Bot,
+    destination: Destination,
+    templates: &Templates,
+) -> Result<(), teloxide::RequestError> {
+    let uptime = health_monitor
+        .start_time
+        .elapsed()
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+
+    let events_count = health_monitor.events_processed();
+    let deliveries = stats::stats().snapshot().deliveries();
+    let status = health_monitor.status();
+    let last_dispute = health_monitor
+        .last_event()
+        .map(|last| components::now().saturating_sub(last));
+
+    let mut context = tera::Context::new();
+    context.insert("uptime_hours", &(uptime / 3600));
+    context.insert("uptime_minutes", &((uptime % 3600) / 60));
+    context.insert("events_processed", &events_count);
+    context.insert(
+        "delivered",
+        &deliveries.values().map(|d| d.sent).sum::<u64>(),
+    );
+    context.insert(
+        "failed",
+        &deliveries.values().map(|d| d.failed).sum::<u64>(),
+    );
+    context.insert("healthy", &status.healthy);
+    context.insert(
+        "relays_connected",
+        &status
+            .relays
+            .iter()
+            .filter(|relay| relay.status == "connected")
+            .count(),
+    );
+    context.insert("relays_total", &status.relays.len());
+    context.insert("open_disputes", &health_monitor.open_disputes().await);
+    context.insert("last_dispute_hours", &last_dispute.map(|secs| secs / 3600));
+    context.insert(
+        "last_dispute_minutes",
+        &last_dispute.map(|secs| (secs % 3600) / 60),
+    );
+    events::publish_health("heartbeat", &context);
+    let heartbeat_msg = templates.render("heartbeat", &context);
+
+    send_markdown(bot, destination, &heartbeat_msg).await?;
+    health_monitor.record_heartbeat();
+    info!(
+        "💓 Heartbeat sent (uptime: {}h {}m, events: {})",
+        uptime / 3600,
+        (uptime % 3600) / 60,
+        events_count
+    );
+    Ok(())
+}
+
+/// Run a one-shot subcommand. `None` for the long-running `run`, otherwise whether
+/// the command passed.
+async fn run_command(
+    cli: &cli::Cli,
+    command: cli::Command,
+    config_path: PathBuf,
+) -> Result<Option<bool>, Box<dyn std::error::Error>> {
+    let passed = match command {
+        cli::Command::Run { once: false, .. } => None,
+        cli::Command::Run { once: true, .. } => Some(run_once(&config_path, &cli.overrides).await?),
+        cli::Command::Validate(_) => Some(validate::run(&config_path, &cli.overrides).await),
+        cli::Command::Doctor(_) => Some(doctor::run(&config_path, &cli.overrides).await),
+        cli::Command::Init(_) => Some(init::run(&config_path).await),
+        cli::Command::Export(_) => Some(export_config(&config_path, &cli.overrides)?),
+        cli::Command::Disputes { status, limit, .. } => {
+            Some(list_disputes(&config_path, status.as_deref(), limit).await?)
+        }
+        cli::Command::Replay { limit, notify, .. } => {
+            Some(replay(&config_path, &cli.overrides, limit, notify).await?)
+        }
+        cli::Command::Announce { message, .. } => {
+            let config = cli.overrides.load(&config_path)?;
+            Some(announce::run(&config, &message).await)
+        }
+        cli::Command::TestAlert(_) => Some(test_alert_command(&config_path, &cli.overrides).await?),
+    };
+    Ok(passed)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = <cli::Cli as clap::Parser>::parse();
+    if cli.dry_run {
+        dry_run::enable();
+    }
+    let command = cli.command();
+    let config_path = command.config_path();
+    let passed = run_command(&cli, command, config_path.clone()).await?;
+    if let Some(passed) = passed {
+        if !passed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let config = cli.overrides.load(&config_path)?;
+    // Running config that reloads are compared against; `config` is taken apart below
+    let watched = match config.reload {
+        Some(ref reload_config) if reload_config.watch => Some(cli.overrides.load(&config_path)?),
+        _ => None,
+    };
+
+    #[cfg(feature = "sentry")]
+    let sentry_guard = config
+        .sentry
+        .as_ref()
+        .map(|sentry_config| reporting::init(sentry_config, &config.mostro.pubkey.join(",")));
+
+    #[cfg(feature = "otel")]
+    let telemetry = match config.telemetry {
+        Some(ref telemetry_config) => Some(telemetry::Telemetry::init(telemetry_config)?),
+        None => None,
+    };
+
+    let subscriber = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive("mostro_watchdog=info".parse()?),
+        )
+        .with(tracing_subscriber::fmt::layer());
+    #[cfg(feature = "otel")]
+    let subscriber = subscriber.with(telemetry.as_ref().map(|telemetry| telemetry.layer()));
+    #[cfg(feature = "sentry")]
+    let subscriber = subscriber.with(sentry_guard.as_ref().map(|_| reporting::layer()));
+    subscriber.init();
+
+    #[cfg(not(feature = "otel"))]
+    if config.telemetry.is_some() {
+        warn!("[telemetry] is configured but this build lacks the `otel` feature, not exporting");
+    }
+    #[cfg(not(feature = "sentry"))]
+    if config.sentry.is_some() {
+        warn!("[sentry] is configured but this build lacks the `sentry` feature, not reporting");
+    }
+    let templates = Arc::new(
+        Templates::load(config.templates.as_ref().unwrap_or(&Default::default()))?.with_branding(
+            Branding::from_config(config.branding.as_ref().unwrap_or(&Default::default())),
+        ),
+    );
+
+    info!("🐕 mostro-watchdog starting...");
+    if config.sources.is_empty() {
+        info!("No config file, configured from environment variables");
+    }
+    if let Some(ref profile) = cli.overrides.profile {
+        info!("Using config profile '{}'", profile);
+    }
+    if dry_run::is_enabled() {
+        warn!("🧪 Dry run: alerts are logged instead of sent");
+    }
+    info!(
+        "Monitoring Mostro pubkey(s): {}",
+        config.mostro.pubkey.join(", ")
+    );
+    info!(
+        "Sending alerts to Telegram chat: {}",
+        config.telegram.chat_id
+    );
+
+    // Initialize Telegram bot
+    let bot = proxy::bot(&config.telegram)?;
+
+    // Verify Telegram bot connection
+    match bot.get_me().await {
+        Ok(me) => info!("Telegram bot connected: @{}", me.username()),
+        Err(e) => {
+            error!("Failed to connect Telegram bot: {}", e);
+            return Err(e.into());
+        }
+    }
+
+    // Initialize Nostr client
+    let client = nostr_client(&config, &config_path)?;
+
+    let check_relays = config
+        .health
+        .as_ref()
+        .is_none_or(|health| health.check_relays);
+    // Secondary relays wait on standby for the relay check to promote them
+    let standby: Vec<String> = if check_relays {
+        config.nostr.secondary_relays.clone()
+    } else {
+        Vec::new()
+    };
+    for relay in &config.nostr.relays {
+        if standby.contains(relay) {
+            continue;
+        }
+        info!("Adding relay: {}", relay);
+        client
+            .pool()
+            .add_relay(relay, relay_options(&config.nostr, relay, check_relays)?)
+            .await?;
+    }
+
+    client.connect().await;
+    info!(
+        "Connected to {} relay(s)",
+        config.nostr.relays.len() - standby.len()
+    );
+    for relay in &standby {
+        info!("Adding secondary relay on standby: {}", relay);
+        client
+            .pool()
+            .add_relay(relay, relay_options(&config.nostr, relay, check_relays)?)
+            .await?;
+    }
+
+    // Subscribe to dispute events (kind 38386) from the configured Mostro pubkeys,
+    // on the relays with the disputes role, one subscription per relay filter
+    let since = Timestamp::now();
+    let mut dispute_subscriptions = Vec::new();
+    for (relay_filter, relays) in config.nostr.dispute_relays_by_filter() {
+        let filter = relay_dispute_filter(&config, &relay_filter)?.since(since);
+        let active: Vec<String> = relays
+            .iter()
+            .filter(|relay| !standby.contains(relay))
+            .cloned()
+            .collect();
+        // Relays on standby join the subscription when promoted
+        let id = if active.is_empty() {
+            SubscriptionId::generate()
+        } else {
+            client
+                .subscribe_to(active, vec![filter.clone()], None)
+                .await?
+                .val
+        };
+        dispute_subscriptions.push(subscriptions::DisputeSubscription { id, relays, filter });
+    }
+    let dispute_subscription_ids: Arc<HashSet<SubscriptionId>> = Arc::new(
+        dispute_subscriptions
+            .iter()
+            .map(|subscription| subscription.id.clone())
+            .collect(),
+    );
+
+    info!("🔍 Subscribed to dispute events. Watching...");
+
+    // Initialize health monitor
+    let health_monitor = Arc::new(HealthMonitor::new());
+    health_monitor.set_config(&config);
+    health_monitor.register_relays(&config.nostr.relays);
+    for relay in &standby {
+        health_monitor.set_relay_standby(relay, true);
+    }
+    health_monitor.attach_client(client.clone());
+    let _ = DELIVERY_HEALTH.set(health_monitor.clone());
+
+    #[cfg(feature = "otel")]
+    if let Some(ref telemetry) = telemetry {
+        telemetry.register_metrics(health_monitor.clone());
+        info!("📡 Exporting OpenTelemetry traces and metrics");
+    }
+    if let Some(ref statsd_config) = config.statsd {
+        tokio::spawn(statsd::run(
+            statsd_config.clone(),
+            health_monitor.start_time,
+        ));
+    }
+    if let Some(ref influx_config) = config.influxdb {
+        tokio::spawn(influx::run(influx_config.clone(), health_monitor.clone()));
+    }
+    // Built before `config` is taken apart below
+    let filter = dispute_filter(&config)?;
+    let health_config = config.health.unwrap_or_default();
+    let live_health = Arc::new(reload::Live::new(health_config.clone()));
+    crash::install(
+        &config.telegram,
+        Destination {
+            chat_id: config.telegram.health_chat(),
+            topic_id: config.telegram.health_topic(),
+            silent: false,
+        },
+        templates.clone(),
+        health_config.degraded_fallback.clone(),
+    );
+
+    let alerts_config = Arc::new(reload::Live::new(config.alerts.unwrap_or_default()));
+    let cache_capacity = alerts_config.get().cache_capacity;
+    // Work that shutdown waits for: queued alerts and open HTTP connections
+    let in_flight = shutdown::Tracker::new();
+
+    // Start health check background tasks
+    start_health_tasks(
+        health_monitor.clone(),
+        bot.clone(),
+        &config.telegram,
+        &live_health,
+        client.clone(),
+        &config.nostr,
+        filter.clone(),
+        dispute_subscriptions.clone(),
+        templates.clone(),
+        &alerts_config.get().severity,
+        in_flight.clone(),
+    );
+
+    // Initialize dispute message store
+    let dispute_store = Arc::new(
+        open_dispute_store(&config_path)
+            .await
+            .expect("Failed to initialize dispute message store"),
+    );
+    health_monitor.attach_dispute_store(dispute_store.clone());
+
+    // On-call rotation and bot commands
+    let oncall = match config.oncall {
+        Some(ref oncall_config) => Some(Arc::new(OnCall::from_config(oncall_config)?)),
+        None => None,
+    };
+    let commands_config = config.commands.unwrap_or_default();
+    let mut allowed_users: HashSet<i64> = commands_config.allowed_users.iter().copied().collect();
+    if let Some(ref oncall) = oncall {
+        allowed_users.extend(oncall.user_ids());
+    }
+    if allowed_users.is_empty() {
+        warn!("No users allowed to use bot commands (set commands.allowed_users)");
+    }
+    let command_ctx = Arc::new(CommandContext {
+        templates: templates.clone(),
+        oncall: oncall.clone(),
+        health_monitor: health_monitor.clone(),
+        heartbeat_dest: Destination {
+            chat_id: config.telegram.health_chat(),
+            topic_id: config.telegram.heartbeat_topic(),
+            silent: health_config.heartbeat_silent,
+        },
+        allowed_users,
+        report_to: commands_config.report_unauthorized.then_some(Destination {
+            chat_id: config.telegram.chat_id,
+            topic_id: config.telegram.topic_id,
+            silent: false,
+        }),
+    });
+    tokio::spawn(commands::run(bot.clone(), command_ctx));
+
+    // Optionally escalate new disputes that nobody takes
+    let escalation = match config.escalation {
+        Some(escalation_config) if !escalation_config.steps.is_empty() => {
+            let escalation = Arc::new(Escalation::new(escalation_config.steps, cache_capacity));
+            let alert_dest = Destination {
+                chat_id: config.telegram.chat_id,
+                topic_id: config.telegram.disputes_topic(),
+                silent: false,
+            };
+            tokio::spawn(
+                escalation
+                    .clone()
+                    .run(bot.clone(), alert_dest, templates.clone()),
+            );
+            Some(escalation)
+        }
+        _ => None,
+    };
+
+    // Summarize the alerts held back during floods
+    let flood = Arc::new(flood::Flood::default());
+    tokio::spawn(flood::run(
+        flood.clone(),
+        bot.clone(),
+        Destination {
+            chat_id: config.telegram.chat_id,
+            topic_id: config.telegram.disputes_topic(),
+            silent: false,
+        },
+        templates.clone(),
+    ));
+
+    // Process events
+    let alert_ctx = AlertContext {
+        bot: bot.clone(),
+        chat_id: config.telegram.chat_id,
+        topic_id: config.telegram.disputes_topic(),
+        alerts_config: alerts_config.clone(),
+        templates: templates.clone(),
+        dispute_store: dispute_store.clone(),
+        escalation,
+        oncall,
+        alertmanager: config
+            .alertmanager
+            .as_ref()
+            .map(|c| Arc::new(Alertmanager::new(c))),
+        tag_mostro: config.mostro.pubkey.len() > 1,
+        flood: Some(flood),
+    };
+    health_monitor.attach_alert_context(alert_ctx.clone());
+
+    // Send startup notification
+    let mut context = tera::Context::new();
+    context.insert(
+        "heartbeat_interval",
+        &health_config
+            .heartbeat_enabled
+            .then_some(health_config.heartbeat_interval),
+    );
+    context.insert(
+        "event_alert_threshold",
+        &(health_config.event_alert_threshold > 0).then_some(health_config.event_alert_threshold),
+    );
+    if health_config.self_test {
+        let failure = self_test::run(&alert_ctx).await.err();
+        context.insert("self_test", &failure.is_none());
+        context.insert("self_test_failure", &failure.map(|f| f.to_string()));
+    }
+    let startup_msg = templates.render("startup", &context);
+
+    let startup_dest = Destination {
+        chat_id: config.telegram.chat_id,
+        topic_id: config.telegram.topic_id,
+        silent: false,
+    };
+    if let Err(e) = send_markdown(&bot, startup_dest, &startup_msg).await {
+        warn!("Failed to send startup message: {}", e);
+    }
+
+    // Optionally announce new releases
+    if let Some(updates_config) = config.updates.as_ref().filter(|u| u.check) {
+        tokio::spawn(updates::run(
+            bot.clone(),
+            startup_dest,
+            templates.clone(),
+            Duration::from_secs(updates_config.interval_hours * 3600),
+        ));
+    }
+
+    // Optionally apply config file changes without a restart
+    if let Some(running) = watched {
+        tokio::spawn(reload::run(
+            config_path.clone(),
+            cli.overrides.clone(),
+            running,
+            reload::Targets {
+                alerts: alerts_config.clone(),
+                health: live_health.clone(),
+                templates: templates.clone(),
+                health_monitor: health_monitor.clone(),
+                bot: bot.clone(),
+                destination: Destination {
+                    chat_id: config.telegram.health_chat(),
+                    topic_id: config.telegram.health_topic(),
+                    silent: false,
+                },
+            },
+        ));
+    }
+
+    // Optionally coalesce alerts through the digest task
+    let digest_window = alert_ctx.alerts().digest_window;
+    let digest_tx = if digest_window > 0 {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let window = Duration::from_secs(digest_window);
+        tokio::spawn(run_digest(alert_ctx.clone(), rx, window));
+        Some(tx)
+    } else {
+        None
+    };
+
+    // Optionally hold back non-critical alerts during quiet hours
+    let quiet_hours = match config.quiet_hours {
+        Some(ref quiet_config) => {
+            let queue = Arc::new(QuietHoursQueue::new(QuietHours::from_config(quiet_config)?));
+            tokio::spawn(run_quiet_hours_flush(alert_ctx.clone(), queue.clone()));
+            Some(queue)
+        }
+        None => None,
+    };
+
+    let debounce_window = alert_ctx.alerts().debounce_window;
+    let router = EventRouter {
+        ctx: alert_ctx,
+        digest_tx,
+        quiet_hours,
+        in_flight: in_flight.clone(),
+        seen: Arc::new(dedup::SeenEvents::new(cache_capacity)),
+        debounce: (debounce_window > 0).then(|| {
+            Arc::new(debounce::Debouncer::new(
+                Duration::from_secs(debounce_window),
+                cache_capacity,
+            ))
+        }),
+    };
+
+    // Catch up on dispute events published while the watchdog wasn't running
+    if let Some(last_seen) = dispute_store.last_seen().await? {
+        let catch_up_client = dispute_client(&config.nostr).await?;
+        let filter = filter.clone();
+        let router = router.clone();
+        tokio::spawn(async move {
+            let since = Timestamp::from(last_seen);
+            let missed =
+                backfill::missed_events(&catch_up_client, &router.ctx.dispute_store, filter, since)
+                    .await
+                    .map_err(|e| e.to_string());
+            match missed {
+                Ok(events) => match route_missed(&router, events).await {
+                    Ok(handled) => info!(
+                        "✅ Caught up on {} dispute event(s) since {}",
+                        handled,
+                        timestamp::utc(last_seen)
+                    ),
+                    Err(e) => warn!("Failed to catch up on missed dispute events: {}", e),
+                },
+                Err(e) => warn!("Failed to fetch missed dispute events: {}", e),
+            }
+        });
+    }
+
+    // Delivery runs on workers, so a slow channel never holds up the relays
+    let pipeline = {
+        let alerts = router.ctx.alerts();
+        let router = router.clone();
+        pipeline::Pipeline::start(
+            alerts.workers,
+            alerts.queue_capacity,
+            in_flight.clone(),
+            move |event| {
+                let router = router.clone();
+                async move { router.route(event).await }
+            },
+        )
+    };
+
+    let handler = |notification: RelayPoolNotification| {
+        event_loop::beat();
+        let router = router.clone();
+        let pipeline = pipeline.clone();
+        let health_monitor = health_monitor.clone();
+        let dispute_subscription_ids = dispute_subscription_ids.clone();
+
+        async move {
+            if let RelayPoolNotification::Message { relay_url, message } = &notification {
+                match message {
+                    RelayMessage::Closed {
+                        subscription_id,
+                        message,
+                    } if dispute_subscription_ids.contains(subscription_id) => {
+                        health_monitor.record_subscription_closed(relay_url.as_str(), message);
+                    }
+                    RelayMessage::Notice { message } => {
+                        warn!("Notice from {}: {}", relay_url, message);
+                        health_monitor.record_relay_notice(relay_url.as_str(), message);
+                    }
+                    _ => {}
+                }
+            }
+            // Every relay's copy, for relay scores and deduplication; `Event` below
+            // only has the first
+            if let RelayPoolNotification::Message {
+                relay_url,
+                message: RelayMessage::Event { event, .. },
+            } = &notification
+            {
+                if event.kind == Kind::Custom(38386) {
+                    router.seen.record_delivery(event.id, relay_url.as_str());
+                    // The relay resubscribes since this, so a future one would skip events
+                    let created_at = event.created_at.as_u64();
+                    let max_skew = router.ctx.alerts().max_future_skew;
+                    if !is_future(created_at, Timestamp::now().as_u64(), max_skew) {
+                        health_monitor.record_relay_dispute_event(relay_url.as_str(), created_at);
+                    }
+                }
+            }
+            if let RelayPoolNotification::Event {
+                relay_url, event, ..
+            } = notification
+            {
+                health_monitor.record_relay_message(relay_url.as_str());
+                health_monitor.record_relay_latency(relay_url.as_str(), event.created_at.as_u64());
+                if event.kind == Kind::Custom(38386) {
+                    health_monitor.record_event();
+                    health_monitor.expect_dispute_event();
+                    health_monitor
+                        .event_offsets
+                        .record(event.created_at.as_u64());
+                    let id = event.id;
+                    if let Err(e) = pipeline.push(*event) {
+                        error!("Dropping dispute event {}: {}", id, e);
+                        stats::stats().record_dropped();
+                    }
+                }
+            }
+            Ok(false) // Keep listening
+        }
+    };
+
+    // Outside the loop, so a signal arriving while the event loop restarts isn't missed
+    let signal = shutdown::signal();
+    tokio::pin!(signal);
+    loop {
+        tokio::select! {
+            result = client.handle_notifications(&handler) => {
+                if let Err(e) = result {
+                    crash::report(&format!("event loop stopped: {e}")).await;
+                    return Err(e.into());
+                }
+                break;
+            }
+            _ = event_loop::restart_requested() => {
+                warn!("🔄 Starting a new Nostr event loop");
+            }
+            _ = &mut signal => {
+                info!("🛑 Shutting down");
+                shutdown::trigger();
+                // No new events; queued alerts and other work in flight get to finish
+                client.unsubscribe_all().await;
+                in_flight.drained(SHUTDOWN_TIMEOUT).await;
+                send_offline_notice(&health_monitor, &bot, startup_dest, &templates).await;
+                let _ = client.disconnect().await;
+                dispute_store.close().await;
+                info!("👋 Stopped");
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Tell admins monitoring has stopped, rather than leaving them to notice the silence
+async fn send_offline_notice(
+    health_monitor: &HealthMonitor,
+    bot: &Bot,
+    destination: Destination,
+    templates: &Templates,
+) {
+    let uptime = health_monitor
+        .start_time
+        .elapsed()
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    let mut context = tera::Context::new();
+    context.insert("uptime_hours", &(uptime / 3600));
+    context.insert("uptime_minutes", &((uptime % 3600) / 60));
+    context.insert("events_processed", &health_monitor.events_processed());
+    let message = templates.render("shutdown", &context);
+    if let Err(e) = channels::timed(send_markdown(bot, destination, &message)).await {
+        warn!("Failed to send offline notice: {}", e);
+    }
+}
+
+/// Reconnect one relay, adding it back if it was removed from the pool
+async fn reconnect_relay(
+    client: &Client,
+    nostr: &config::NostrConfig,
+    url: &str,
+) -> Result<(), String> {
+    if client.pool().relay(url).await.is_err() {
+        client
+            .pool()
+            .add_relay(url, relay_options(nostr, url, true)?)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    client.connect_relay(url).await.map_err(|e| e.to_string())
+}
+
+/// Subscribe a promoted secondary relay to the dispute subscriptions it belongs
+/// to, under their existing ids
+async fn subscribe_promoted(
+    client: &Client,
+    subscriptions: &[subscriptions::DisputeSubscription],
+    url: &str,
+) -> Result<(), String> {
+    let relay = client.pool().relay(url).await.map_err(|e| e.to_string())?;
+    for subscription in subscriptions {
+        if subscription.relays.iter().any(|relay| relay == url) {
+            relay
+                .subscribe_with_id(
+                    subscription.id.clone(),
+                    vec![subscription.filter.clone()],
+                    SubscribeOptions::default(),
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Re-issue the dispute subscriptions of relay `url` starting at `since`, so events
+/// published while it was disconnected are delivered rather than skipped. Returns
+/// how many subscriptions were renewed.
+async fn resubscribe_since(client: &Client, url: &str, since: Timestamp) -> Result<usize, String> {
+    let relay = client.pool().relay(url).await.map_err(|e| e.to_string())?;
+    let mut renewed = 0;
+    for (id, filters) in relay.subscriptions().await {
+        // Pings and fetches ask for a limited number of events; leave them be
+        let disputes = filters.iter().all(|filter| {
+            filter.limit.is_none()
+                && filter
+                    .kinds
+                    .as_ref()
+                    .is_some_and(|kinds| kinds.contains(&Kind::Custom(38386)))
+        });
+        if !disputes {
+            continue;
+        }
+        let filters = filters
+            .into_iter()
+            .map(|filter| filter.since(since))
+            .collect();
+        relay
+            .subscribe_with_id(id, filters, SubscribeOptions::default())
+            .await
+            .map_err(|e| e.to_string())?;
+        renewed += 1;
+    }
+    Ok(renewed)
+}
+
+/// Relay options: its proxy, reading for the disputes role and writing for the
+/// publish role. With `check_relays` the relay check reconnects with backoff,
+/// otherwise the client's own reconnection is kept.
+fn relay_options(
+    nostr: &config::NostrConfig,
+    url: &str,
+    check_relays: bool,
+) -> Result<RelayOptions, String> {
+    Ok(proxy::relay_options(nostr, url)?
+        .read(nostr.has_role(url, config::RelayRole::Disputes))
+        .write(nostr.has_role(url, config::RelayRole::Publish))
+        .reconnect(!check_relays))
+}
+
+/// Dispute events (kind 38386) from the configured Mostro pubkeys
+fn dispute_filter(config: &Config) -> Result<Filter, Box<dyn std::error::Error>> {
+    relay_dispute_filter(config, &config::RelayFilter::default())
+}
+
+/// Dispute filter narrowed by a relay's `nostr.relay_filters` entry
+fn relay_dispute_filter(
+    config: &Config,
+    relay_filter: &config::RelayFilter,
+) -> Result<Filter, Box<dyn std::error::Error>> {
+    let pubkeys = if relay_filter.pubkeys.is_empty() {
+        &config.mostro.pubkey
+    } else {
+        &relay_filter.pubkeys
+    };
+    let mostro_pubkeys = pubkeys
+        .iter()
+        .map(|pubkey| config::parse_pubkey(pubkey))
+        .collect::<Result<Vec<_>, _>>()?;
+    let filter = Filter::new()
+        .kind(Kind::Custom(38386))
+        .authors(mostro_pubkeys);
+    if relay_filter.statuses.is_empty() {
+        return Ok(filter);
+    }
+    Ok(filter.custom_tag(
+        SingleLetterTag::lowercase(Alphabet::S),
+        relay_filter.statuses.iter().cloned(),
+    ))
+}
+
+/// `--once`: alert on dispute events published since the last run, then exit.
+/// Events whose status the store already has were handled by an earlier run.
+async fn run_once(
+    config_path: &Path,
+    overrides: &cli::Overrides,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let config = overrides.load(config_path)?;
+    init_cli_logging()?;
+
+    let ctx = standalone_context(&config, config_path).await?;
+    let dispute_store = ctx.dispute_store.clone();
+    let since = match dispute_store.last_seen().await? {
+        Some(last_seen) => Timestamp::from(last_seen),
+        None => Timestamp::now() - ONCE_FIRST_LOOKBACK,
+    };
+    let client = dispute_client(&config.nostr).await?;
+    let events =
+        backfill::missed_events(&client, &dispute_store, dispute_filter(&config)?, since).await?;
+
+    let seen = Arc::new(dedup::SeenEvents::new(ctx.alerts().cache_capacity));
+    let router = EventRouter {
+        ctx,
+        digest_tx: None,
+        quiet_hours: None,
+        in_flight: shutdown::Tracker::new(),
+        seen,
+        debounce: None,
+    };
+    // Only delivered events move the last seen time
+    let handled = route_missed(&router, events).await?;
+    info!(
+        "✅ Handled {} new dispute event(s) since {}",
+        handled,
+        timestamp::utc(since.as_u64())
+    );
+    Ok(true)
+}
+
+/// `replay`: print the latest dispute events on the relays, oldest first, and with
+/// `notify` send their alerts again
+async fn replay(
+    config_path: &Path,
+    overrides: &cli::Overrides,
+    limit: usize,
+    notify: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let config = overrides.load(config_path)?;
+    let events = fetch_dispute_events(&config, dispute_filter(&config)?.limit(limit)).await?;
+    // Relays apply the limit per filter, so with several relays keep the newest overall
+    let events = &events[events.len().saturating_sub(limit)..];
+    if events.is_empty() {
+        println!("No dispute events found on the relays");
+        return Ok(true);
+    }
+
+    println!(
+        "{:<20} {:<40} {:<16} INITIATOR",
+        "CREATED", "DISPUTE", "STATUS"
+    );
+    for event in events {
+        let dispute = DisputeInfo::from_event(event);
+        println!(
+            "{:<20} {:<40} {:<16} {}",
+            timestamp::utc(event.created_at.as_u64()),
+            dispute.dispute_id,
+            dispute.status,
+            dispute.initiator
+        );
+    }
+
+    if notify {
+        init_cli_logging()?;
+        let ctx = standalone_context(&config, config_path).await?;
+        for event in events {
+            handle_dispute_event(&ctx, event).await;
+        }
+        println!("✅ Re-sent {} alert(s)", events.len());
+    }
+    Ok(true)
+}
+
+/// Log to stderr for one-shot commands that send alerts
+fn init_cli_logging() -> Result<(), Box<dyn std::error::Error>> {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::from_default_env()
+                .add_directive("mostro_watchdog=info".parse()?),
+        )
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .init();
+    Ok(())
+}
+
+/// Alert context for one-shot commands: no escalation, on-call or digests
+async fn standalone_context(
+    config: &Config,
+    config_path: &Path,
+) -> Result<AlertContext, Box<dyn std::error::Error>> {
+    let templates = Templates::load(config.templates.as_ref().unwrap_or(&Default::default()))?
+        .with_branding(Branding::from_config(
+            config.branding.as_ref().unwrap_or(&Default::default()),
+        ));
+    Ok(AlertContext {
+        bot: proxy::bot(&config.telegram)?,
+        chat_id: config.telegram.chat_id,
+        topic_id: config.telegram.disputes_topic(),
+        alerts_config: Arc::new(reload::Live::new(config.alerts.clone().unwrap_or_default())),
+        templates: Arc::new(templates),
+        dispute_store: Arc::new(open_dispute_store(config_path).await?),
+        escalation: None,
+        oncall: None,
+        alertmanager: config
+            .alertmanager
+            .as_ref()
+            .map(|c| Arc::new(Alertmanager::new(c))),
+        tag_mostro: config.mostro.pubkey.len() > 1,
+        flood: None,
+    })
+}
+
+/// Route the dispute events whose status the store doesn't have yet, which an
+/// earlier run already handled otherwise. Returns how many were routed.
+async fn route_missed(router: &EventRouter, events: Vec<Event>) -> Result<usize, sqlx::Error> {
+    let mut handled = 0;
+    for event in events {
+        let dispute = DisputeInfo::from_event(&event);
+        let known = router
+            .ctx
+            .dispute_store
+            .get_status(&dispute.dispute_id)
+            .await?;
+        if known.is_some_and(|(status, _)| status == dispute.status) {
+            continue;
+        }
+        router.route(event).await;
+        handled += 1;
+    }
+    Ok(handled)
+}
+
+/// A client connected to the dispute relays, separate from the live subscription
+async fn dispute_client(nostr: &config::NostrConfig) -> Result<Client, Box<dyn std::error::Error>> {
+    let client = Client::default();
+    for relay in nostr.dispute_relays() {
+        let options = proxy::relay_options(nostr, &relay)?;
+        client.pool().add_relay(relay, options).await?;
+    }
+    client.connect().await;
+    Ok(client)
+}
+
+/// Fetch stored events matching `filter` from the dispute relays, oldest first
+async fn fetch_dispute_events(
+    config: &Config,
+    filter: Filter,
+) -> Result<Vec<Event>, Box<dyn std::error::Error>> {
+    let client = dispute_client(&config.nostr).await?;
+    let mut events: Vec<Event> = client
+        .fetch_events(vec![filter], Some(FETCH_TIMEOUT))
+        .await?
+        .into_iter()
+        .collect();
+    events.sort_by_key(|event| event.created_at);
+    Ok(events)
+}
+
+/// Client for the live subscription, with the event cache as its database if enabled
+fn nostr_client(config: &Config, config_path: &Path) -> Result<Client, Box<dyn std::error::Error>> {
+    if !config.nostr.event_cache {
+        return Ok(Client::default());
+    }
+    #[cfg(feature = "lmdb")]
+    {
+        let path = config_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("nostr-events");
+        std::fs::create_dir_all(&path)?;
+        let database = nostr_lmdb::NostrLMDB::open(&path)?;
+        info!("Caching Nostr events in {}", path.display());
+        Ok(Client::builder().database(database).build())
+    }
+    #[cfg(not(feature = "lmdb"))]
+    {
+        // Rejected by `Config::validate`
+        let _ = config_path;
+        Err("nostr.event_cache requires the `lmdb` feature".into())
+    }
+}
+
+/// The dispute store lives next to the config file
+fn dispute_db_path(config_path: &Path) -> PathBuf {
+    config_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("disputes.db")
+}
+
+/// Open the dispute store for alerting. A dry run works on a fresh copy of it, so
+/// alerts it only logged aren't taken as handled by the next live run.
+async fn open_dispute_store(config_path: &Path) -> Result<DisputeMessageStore, sqlx::Error> {
+    let path = dispute_db_path(config_path);
+    if !dry_run::is_enabled() {
+        return DisputeMessageStore::new(&path).await;
+    }
+
+    let copy = path.with_file_name("disputes.dry-run.db");
+    let copied = match path.exists() {
+        true => std::fs::copy(&path, &copy).map(|_| ()),
+        false => match std::fs::remove_file(&copy) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    };
+    copied.map_err(sqlx::Error::Io)?;
+    info!(
+        "🧪 Dry run: using a copy of the dispute store at {}",
+        copy.display()
+    );
+    DisputeMessageStore::new(&copy).await
+}
+
+/// `export`: print the effective config with secrets redacted
+fn export_config(
+    config_path: &Path,
+    overrides: &cli::Overrides,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let config = overrides.load(config_path)?;
+    println!("{}", serde_json::to_string_pretty(&config.redacted())?);
+    Ok(true)
+}
+
+/// `disputes`: list tracked disputes from the local store
+async fn list_disputes(
+    config_path: &Path,
+    status: Option<&str>,
+    limit: u32,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let store = DisputeMessageStore::new(&dispute_db_path(config_path)).await?;
+    let disputes = store.list(status, limit, 0).await?;
+    if disputes.is_empty() {
+        println!("No disputes tracked");
+        return Ok(true);
+    }
+    println!("{:<40} {:<16} CREATED", "DISPUTE", "STATUS");
+    for dispute in disputes {
+        println!(
+            "{:<40} {:<16} {}",
+            dispute.dispute_id,
+            dispute.status,
+            timestamp::utc(dispute.created_at.max(0) as u64)
+        );
+    }
+    Ok(true)
+}
+
+/// `test-alert`: send one test dispute alert with the configured templates
+async fn test_alert_command(
+    config_path: &Path,
+    overrides: &cli::Overrides,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let config = overrides.load(config_path)?;
+    let templates = Templates::load(config.templates.as_ref().unwrap_or(&Default::default()))?
+        .with_branding(Branding::from_config(
+            config.branding.as_ref().unwrap_or(&Default::default()),
+        ));
+    let ctx = AlertContext {
+        bot: proxy::bot(&config.telegram)?,
+        chat_id: config.telegram.chat_id,
+        topic_id: config.telegram.disputes_topic(),
+        alerts_config: Arc::new(reload::Live::new(config.alerts.clone().unwrap_or_default())),
+        templates: Arc::new(templates),
+        dispute_store: Arc::new(open_dispute_store(config_path).await?),
+        escalation: None,
+        oncall: None,
+        alertmanager: None,
+        tag_mostro: config.mostro.pubkey.len() > 1,
+        flood: None,
+    };
+    match send_test_alert(&ctx).await {
+        Ok((dispute_id, message_id)) => {
+            println!("✅ Test alert {dispute_id} sent (message {message_id})");
+            Ok(true)
+        }
+        Err(e) => {
+            eprintln!("❌ Failed to send test alert: {e}");
+            Ok(false)
+        }
+    }
+}
+
+/// Everything needed to deliver dispute alerts, shared across event handlers
+#[derive(Clone)]
+struct AlertContext {
+    bot: Bot,
+    chat_id: i64,
+    /// Forum topic for dispute alerts
+    topic_id: Option<i32>,
+    /// Swapped by config reloads
+    alerts_config: Arc<reload::Live<config::AlertsConfig>>,
+    templates: Arc<Templates>,
+    dispute_store: Arc<DisputeMessageStore>,
+    escalation: Option<Arc<Escalation>>,
+    oncall: Option<Arc<OnCall>>,
+    alertmanager: Option<Arc<Alertmanager>>,
+    /// Name the Mostro pubkey in alerts, as several are watched
+    tag_mostro: bool,
+    /// Rate of alerts sent, for `alerts.flood_limit`
+    flood: Option<Arc<flood::Flood>>,
+}
+
+// Shown in the health monitor's `Debug` output; the handles it holds have none
+impl std::fmt::Debug for AlertContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlertContext")
+            .field("chat_id", &self.chat_id)
+            .field("topic_id", &self.topic_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AlertContext {
+    fn alerts(&self) -> Arc<config::AlertsConfig> {
+        self.alerts_config.get()
+    }
+
+    fn severity(&self, dispute: &DisputeInfo) -> AlertSeverity {
+        self.alerts().severity.for_status(&dispute.status)
+    }
+}
+
+/// Dispute fields extracted from a kind 38386 event's tags
+#[derive(Debug, Clone)]
+struct DisputeInfo {
+    dispute_id: String,
+    status: String,
+    initiator: String,
+    solver_pubkey: Option<String>,
+}
+
+impl DisputeInfo {
+    fn from_event(event: &Event) -> Self {
+        let mut dispute = Self {
+            dispute_id: String::from("unknown"),
+            status: String::from("unknown"),
+            initiator: String::from("unknown"),
+            solver_pubkey: None,
+        };
+
+        for tag in event.tags.iter() {
+            let tag_vec: Vec<String> = tag.as_slice().iter().map(|s| s.to_string()).collect();
+            if tag_vec.len() >= 2 {
+                match tag_vec[0].as_str() {
+                    "d" => dispute.dispute_id = tag_vec[1].clone(),
+                    "s" => dispute.status = tag_vec[1].clone(),
+                    "initiator" => dispute.initiator = tag_vec[1].clone(),
+                    "solver" => dispute.solver_pubkey = Some(tag_vec[1].clone()),
+                    _ => {}
+                }
+            }
+        }
+
+        dispute
+    }
+}
+
+/// Render the alert text for a dispute status change
+fn render_dispute_message(ctx: &AlertContext, dispute: &DisputeInfo, event: &Event) -> String {
+    let context = dispute_context(ctx, dispute, event);
+    let mut message = ctx
+        .templates
+        .render(templates::status_template(&dispute.status), &context);
+
+    if ctx.alerts().raw_event == config::RawEventMode::Quote {
+        message.push_str("\n\n");
+        message.push_str(&raw_event_quote(&event.as_pretty_json()));
+    }
+    message
+}
+
+/// Values a dispute status template is rendered from
+fn dispute_context(ctx: &AlertContext, dispute: &DisputeInfo, event: &Event) -> tera::Context {
+    let severity = ctx.severity(dispute);
+
+    // Critical alerts mention the admin on call
+    let oncall = ctx
+        .oncall
+        .as_ref()
+        .filter(|oncall| severity == AlertSeverity::Critical && oncall.notify.mention())
+        .map(|oncall| oncall::mention(oncall.current().admin));
+
+    let redact_mode = ctx.alerts().redact;
+    let redacted = |value: &str| redact::redact(value, redact_mode);
+
+    let mut context = tera::Context::new();
+    context.insert("dispute_id", &redacted(&dispute.dispute_id));
+    context.insert("status", &dispute.status);
+    context.insert("initiator", &dispute.initiator);
+    context.insert("time", &ctx.templates.timestamp(event.created_at.as_u64()));
+    context.insert("solver", &dispute.solver_pubkey.as_deref().map(redacted));
+    context.insert("oncall", &oncall);
+    context.insert("severity", severity.as_str());
+    context.insert(
+        "mostro",
+        &ctx.tag_mostro.then(|| {
+            redacted(
+                &event
+                    .pubkey
+                    .to_bech32()
+                    .unwrap_or_else(|_| event.pubkey.to_hex()),
+            )
+        }),
+    );
+    context
+}
+
+/// Nostr event JSON as a collapsed (expandable) MarkdownV2 blockquote
+fn raw_event_quote(json: &str) -> String {
+    let mut quote = String::new();
+    for (i, line) in json.lines().enumerate() {
+        quote.push_str(if i == 0 { "**>" } else { "\n>" });
+        quote.push_str(&escape_markdown(&line.replace('\\', "\\\\")));
+    }
+    quote.push_str("||");
+    quote
+}
+
+/// Attach the raw Nostr event JSON to an alert as a file, for reporting protocol issues upstream
+#[tracing::instrument(name = "telegram_send_document", skip_all)]
+async fn send_raw_event_document(
+    bot: &Bot,
+    destination: Destination,
+    reply_to: Option<MessageId>,
+    event: &Event,
+) {
+    let chat = format!("chat {}", destination.chat_id);
+    if dry_run::intercepted(&chat, "attach raw event", &event.id.to_hex()) {
+        return;
+    }
+    let file = InputFile::memory(event.as_pretty_json().into_bytes())
+        .file_name(format!("event-{}.json", event.id.to_hex()));
+    let mut request = bot
+        .send_document(ChatId(destination.chat_id), file)
+        .disable_notification(true);
+    if let Some(topic) = destination.topic_id {
+        request = request.message_thread_id(ThreadId(MessageId(topic)));
+    }
+    if let Some(message_id) = reply_to {
+        request = request.reply_parameters(ReplyParameters::new(message_id));
+    }
+
+    let result = send_queue::queue()
+        .send(destination.chat_id, || request.clone().send())
+        .await;
+    record_delivery(
+        &destination.chat_id.to_string(),
+        result.as_ref().err().map(ToString::to_string),
+    );
+    if let Err(e) = result {
+        warn!("Failed to attach raw event JSON: {}", e);
+    }
+}
+
+/// Look up the Telegram message previously sent for a dispute
+async fn existing_dispute_message(
+    dispute_store: &DisputeMessageStore,
+    dispute_id: &str,
+) -> Option<(i32, i64)> {
+    match dispute_store.get_message_id(dispute_id).await {
+        Ok(result) => result,
+        Err(e) => {
+            error!("Failed to query dispute store: {}", e);
+            None
+        }
+    }
+}
+
+#[tracing::instrument(skip_all, fields(event_id = %event.id))]
+async fn handle_dispute_event(ctx: &AlertContext, event: &Event) {
+    if alert_dispute_event(ctx, event).await {
+        record_handled(ctx, event).await;
+    }
+}
+
+/// Record a handled dispute event: its content hash and creation time, so
+/// unchanged or older copies of it aren't alerted on again, and the creation time
+/// also so catching up after a restart resumes from the last event actually handled
+async fn record_handled(ctx: &AlertContext, event: &Event) {
+    let dispute = DisputeInfo::from_event(event);
+    let hash = dedup::content_hash(event);
+    if let Err(e) = ctx
+        .dispute_store
+        .set_last_handled(&dispute.dispute_id, &hash, event.created_at.as_u64())
+        .await
+    {
+        warn!("Failed to record handled dispute event: {}", e);
+    }
+    if let Err(e) = ctx
+        .dispute_store
+        .record_seen(event.created_at.as_u64())
+        .await
+    {
+        warn!("Failed to record last seen event: {}", e);
+    }
+}
+
+/// Alert on a dispute event. Returns false if the dispute chat couldn't be reached,
+/// true once the event is delivered or deliberately not alerted on.
+async fn alert_dispute_event(ctx: &AlertContext, event: &Event) -> bool {
+    let bot = &ctx.bot;
+    let alerts_config = ctx.alerts();
+    let dispute_store = ctx.dispute_store.as_ref();
+    let dispute = DisputeInfo::from_event(event);
+    let DisputeInfo {
+        dispute_id, status, ..
+    } = &dispute;
+
+    info!(
+        "Dispute event received: id={}, status={}, initiator={}",
+        dispute_id, status, dispute.initiator
+    );
+    events::publish_dispute(
+        dispute_id,
+        status,
+        ctx.severity(&dispute),
+        &dispute.initiator,
+    );
+
+    if let Some(ref alertmanager) = ctx.alertmanager {
+        notify_alertmanager(ctx, alertmanager.clone(), &dispute, event).await;
+    }
+    if is_terminal_status(status) {
+        record_resolution_time(dispute_store, dispute_id, status).await;
+    }
+
+    // Check if this alert type is enabled
+    if !alerts_config.is_enabled(status) {
+        info!(
+            "Alert for status '{}' is disabled, skipping notification",
+            status
+        );
+        return true;
+    }
+    if silence::suppressed(silence::Category::Disputes, status) {
+        return true;
+    }
+
+    // Check if we have an existing message for this dispute
+    let existing_message = existing_dispute_message(dispute_store, dispute_id).await;
+
+    // Handle cooperative cancellation: delete the message
+    if status == "canceled" {
+        if let Some((message_id, stored_chat_id)) = existing_message {
+            let chat = format!("chat {stored_chat_id}");
+            if !dry_run::intercepted(&chat, "delete the alert for", dispute_id) {
+                match bot
+                    .delete_message(ChatId(stored_chat_id), MessageId(message_id))
+                    .await
+                {
+                    Ok(_) => info!(
+                        "🗑️ Deleted dispute message for {} (cooperative cancel)",
+                        dispute_id
+                    ),
+                    Err(e) => warn!("Failed to delete dispute message: {}", e),
+                }
+            }
+            if let Err(e) = dispute_store.delete(dispute_id).await {
+                error!("Failed to remove dispute from store: {}", e);
+            }
+        }
+        return true;
+    }
+
+    // Hold the alert back during a flood; it's counted in the next summary
+    if let Some(ref flood) = ctx.flood {
+        if !flood.admit(alerts_config.flood_limit, tokio::time::Instant::now()) {
+            info!(
+                "🌊 Alert flood, holding back alert for dispute {}",
+                dispute_id
+            );
+            return true;
+        }
+    }
+
+    // Generate appropriate message based on status
+    let mut message = render_dispute_message(ctx, &dispute, event);
+
+    // Action links for new disputes
+    let mut keyboard = None;
+    if status == "initiated" && !alerts_config.links.is_empty() {
+        let event_id = event.id.to_hex();
+        let note_id = event.id.to_bech32().unwrap_or_default();
+        let (buttons, text_links) = dispute_links(
+            &alerts_config.links,
+            &[
+                ("dispute_id", dispute_id),
+                ("event_id", &event_id),
+                ("note_id", &note_id),
+            ],
+        );
+        if !text_links.is_empty() {
+            message.push_str("\n\n");
+            message.push_str(&text_links.join("\n"));
+        }
+        if !buttons.is_empty() {
+            keyboard = Some(InlineKeyboardMarkup::new(
+                buttons.into_iter().map(|button| vec![button]),
+            ));
+        }
+    }
+
+    // The other channels run alongside the dispute chat, so a slow one doesn't
+    // delay the alert
+    let critical = async {
+        if ctx.severity(&dispute) == AlertSeverity::Critical {
+            on_critical_alert(ctx, dispute_id, &message).await;
+        }
+    };
+    let telegram = send_dispute_alert(ctx, &dispute, event, existing_message, &message, keyboard);
+    let (delivered, ()) = tokio::join!(telegram, critical);
+    delivered
+}
+
+/// Send, update or reply to the dispute chat's message for `dispute`. Returns
+/// whether it was delivered.
+async fn send_dispute_alert(
+    ctx: &AlertContext,
+    dispute: &DisputeInfo,
+    event: &Event,
+    existing_message: Option<(i32, i64)>,
+    message: &str,
+    keyboard: Option<InlineKeyboardMarkup>,
+) -> bool {
+    let bot = &ctx.bot;
+    let alerts_config = ctx.alerts();
+    let dispute_store = ctx.dispute_store.as_ref();
+    let DisputeInfo {
+        dispute_id, status, ..
+    } = dispute;
+
+    let destination = Destination {
+        chat_id: ctx.chat_id,
+        topic_id: ctx.topic_id,
+        silent: alerts_config.silent.is_silent(status),
+    };
+    // If we have an existing message, update it; otherwise send a new one
+    let delivered = if let Some((message_id, stored_chat_id)) = existing_message {
+        if alerts_config.pin && is_terminal_status(status) {
+            unpin_dispute_message(bot, stored_chat_id, message_id, dispute_id).await;
+        }
+
+        if alerts_config.update_mode == config::UpdateMode::Reply {
+            // Reply to the original alert so the dispute history stays threaded
+            let reply_dest = Destination {
+                chat_id: stored_chat_id,
+                ..destination
+            };
+            match send_with_fallback(
+                markdown_message(bot, reply_dest, message)
+                    .reply_parameters(ReplyParameters::new(MessageId(message_id))),
+            )
+            .await
+            {
+                Ok(sent_message) => {
+                    info!(
+                        "↩️ Replied to dispute message for {} (status: {})",
+                        dispute_id, status
+                    );
+                    if let Err(e) = dispute_store.update_status(dispute_id, status).await {
+                        error!("Failed to update dispute status in store: {}", e);
+                    }
+                    Some((stored_chat_id, sent_message.id))
+                }
+                Err(e) => {
+                    // If reply fails (e.g., original deleted), start a new thread
+                    warn!("Failed to reply to message, sending new one: {}", e);
+                    send_new_dispute_message(
+                        bot,
+                        destination,
+                        dispute_id,
+                        status,
+                        message,
+                        keyboard.clone(),
+                        dispute_store,
+                    )
+                    .await
+                    .map(|sent| (destination.chat_id, sent))
+                }
+            }
+        } else {
+            // Update existing message
+            let mut request = bot
+                .edit_message_text(ChatId(stored_chat_id), MessageId(message_id), message)
+                .parse_mode(teloxide::types::ParseMode::MarkdownV2);
+            if let Some(ref keyboard) = keyboard {
+                request = request.reply_markup(keyboard.clone());
+            }
+            match edit_with_fallback(request).await {
+                Ok(_) => {
+                    info!(
+                        "✏️ Updated dispute message for {} (status: {})",
+                        dispute_id, status
+                    );
+                    if let Err(e) = dispute_store.update_status(dispute_id, status).await {
+                        error!("Failed to update dispute status in store: {}", e);
+                    }
+                    Some((stored_chat_id, MessageId(message_id)))
+                }
+                Err(e) => {
+                    // If edit fails (e.g., message deleted), send a new one
+                    warn!("Failed to edit message, sending new one: {}", e);
+                    send_new_dispute_message(
+                        bot,
+                        destination,
+                        dispute_id,
+                        status,
+                        message,
+                        keyboard.clone(),
+                        dispute_store,
+                    )
+                    .await
+                    .map(|sent| (destination.chat_id, sent))
+                }
+            }
+        }
+    } else {
+        // Send new message
+        let sent = send_new_dispute_message(
+            bot,
+            destination,
+            dispute_id,
+            status,
+            message,
+            keyboard.clone(),
+            dispute_store,
+        )
+        .await;
+
+        if let Some(message_id) = sent.filter(|_| alerts_config.pin && status == "initiated") {
+            pin_dispute_message(bot, destination.chat_id, message_id, dispute_id).await;
+        }
+        sent.map(|sent| (destination.chat_id, sent))
+    };
+
+    if let Some((chat_id, message_id)) = delivered {
+        if alerts_config.raw_event == config::RawEventMode::Document {
+            let attach_dest = Destination {
+                chat_id,
+                ..destination
+            };
+            send_raw_event_document(bot, attach_dest, Some(message_id), event).await;
+        }
+    }
+    delivered.is_some()
+}
+
+/// Send a synthetic `initiated` dispute alert through the dispute templates to the
+/// dispute chat, so operators can check the pipeline after config changes. The
+/// dispute store, escalation and other channels are left alone.
+/// Returns the test dispute ID and the Telegram message ID.
+async fn send_test_alert(ctx: &AlertContext) -> Result<(String, i32), String> {
+    let status = "initiated";
+    let (dispute_id, event) = test_dispute_event(status)?;
+    let dispute = DisputeInfo::from_event(&event);
+
+    let message = format!(
+        "🧪 *TEST*\n\n{}",
+        render_dispute_message(ctx, &dispute, &event)
+    );
+    let destination = Destination {
+        chat_id: ctx.chat_id,
+        topic_id: ctx.topic_id,
+        silent: ctx.alerts().silent.is_silent(status),
+    };
+    match send_markdown(&ctx.bot, destination, &message).await {
+        Ok(sent) => {
+            info!("🧪 Test alert sent for {}", dispute_id);
+            Ok((dispute_id, sent.id.0))
+        }
+        Err(e) => {
+            error!("Failed to send test alert: {}", e);
+            Err(e.to_string())
+        }
+    }
+}
+
+/// A dispute event with a made-up `test-` dispute ID, signed by throwaway keys
+fn test_dispute_event(status: &str) -> Result<(String, Event), String> {
+    let keys = Keys::generate();
+    let dispute_id = format!("test-{}", &keys.public_key().to_hex()[..16]);
+    let event = EventBuilder::new(Kind::Custom(38386), "")
+        .tags([
+            Tag::identifier(&dispute_id),
+            Tag::custom(TagKind::custom("s"), [status]),
+            Tag::custom(TagKind::custom("initiator"), ["buyer"]),
+        ])
+        .sign_with_keys(&keys)
+        .map_err(|e| e.to_string())?;
+    Ok((dispute_id, event))
+}
+
+/// Statuses after which a dispute needs no further attention
+fn is_terminal_status(status: &str) -> bool {
+    matches!(status, "seller-refunded" | "settled" | "released")
+}
+
+/// Post the event to the Alertmanager receiver in the background, resolving the
+/// alert once the dispute ends
+async fn notify_alertmanager(
+    ctx: &AlertContext,
+    alertmanager: Arc<Alertmanager>,
+    dispute: &DisputeInfo,
+    event: &Event,
+) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs();
+    let starts_at = match ctx.dispute_store.get_status(&dispute.dispute_id).await {
+        Ok(Some((_, created_at))) => created_at.max(0) as u64,
+        _ => event.created_at.as_u64(),
+    };
+    let ended = is_terminal_status(&dispute.status) || dispute.status == "canceled";
+    let alert = alertmanager::DisputeAlert {
+        dispute_id: dispute.dispute_id.clone(),
+        status: dispute.status.clone(),
+        severity: ctx.severity(dispute),
+        initiator: dispute.initiator.clone(),
+        starts_at,
+        ends_at: ended.then_some(now),
+        generator_url: format!("nostr:{}", event.id.to_bech32().unwrap_or_default()),
+    };
+    tokio::spawn(async move { alertmanager.send(&alert).await });
+}
+
+/// Count how long a dispute took from its first alert to resolution, once per dispute
+async fn record_resolution_time(
+    dispute_store: &DisputeMessageStore,
+    dispute_id: &str,
+    status: &str,
+) {
+    match dispute_store.get_status(dispute_id).await {
+        Ok(Some((stored_status, created_at))) if !is_terminal_status(&stored_status) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs() as i64;
+            stats::stats().record_resolution(status, now.saturating_sub(created_at).max(0) as u64);
+        }
+        Ok(_) => {}
+        Err(e) => warn!(
+            "Failed to look up dispute {} for resolution time: {}",
+            dispute_id, e
+        ),
+    }
+}
+
+/// Pin an open dispute's alert without notifying the chat again
+async fn pin_dispute_message(bot: &Bot, chat_id: i64, message_id: MessageId, dispute_id: &str) {
+    if dry_run::intercepted(&format!("chat {chat_id}"), "pin the alert for", dispute_id) {
+        return;
+    }
+    match bot
+        .pin_chat_message(ChatId(chat_id), message_id)
+        .disable_notification(true)
+        .await
+    {
+        Ok(_) => info!("📌 Pinned dispute message for {}", dispute_id),
+        Err(e) => warn!(
+            "Failed to pin dispute message (is the bot an admin?): {}",
+            e
+        ),
+    }
+}
+
+async fn unpin_dispute_message(bot: &Bot, chat_id: i64, message_id: i32, dispute_id: &str) {
+    if dry_run::intercepted(
+        &format!("chat {chat_id}"),
+        "unpin the alert for",
+        dispute_id,
+    ) {
+        return;
+    }
+    match bot
+        .unpin_chat_message(ChatId(chat_id))
+        .message_id(MessageId(message_id))
+        .await
+    {
+        Ok(_) => info!("📍 Unpinned dispute message for {}", dispute_id),
+        Err(e) => warn!("Failed to unpin dispute message: {}", e),
+    }
+}
+
+/// Start escalation tracking and direct-message the admin on call for a critical alert
+async fn on_critical_alert(ctx: &AlertContext, dispute_id: &str, message: &str) {
+    if let Some(ref escalation) = ctx.escalation {
+        escalation
+            .track(dispute_id, message, ctx.alerts().redact)
+            .await;
+    }
+
+    if let Some(ref oncall) = ctx.oncall {
+        if oncall.notify.dm() {
+            let admin = oncall.current().admin;
+            let dm = Destination {
+                chat_id: admin.user_id,
+                topic_id: None,
+                silent: false,
+            };
+            if let Err(e) = channels::timed(send_markdown(&ctx.bot, dm, message)).await {
+                warn!("Failed to message on-call admin {}: {}", admin.name, e);
+            }
+        }
+    }
+}
+
+async fn send_new_dispute_message(
+    bot: &Bot,
+    destination: Destination,
+    dispute_id: &str,
+    status: &str,
+    message: &str,
+    keyboard: Option<InlineKeyboardMarkup>,
+    dispute_store: &DisputeMessageStore,
+) -> Option<MessageId> {
+    let mut request = markdown_message(bot, destination, message);
+    if let Some(keyboard) = keyboard {
+        request = request.reply_markup(keyboard);
+    }
+    match send_with_fallback(request).await {
+        Ok(sent_message) => {
+            info!(
+                "✅ Telegram alert sent for dispute {} (status: {})",
+                dispute_id, status
+            );
+            // Store the message ID for future updates
+            if let Err(e) = dispute_store
+                .insert(dispute_id, sent_message.id.0, destination.chat_id, status)
+                .await
+            {
+                error!("Failed to store dispute message ID: {}", e);
+            }
+            Some(sent_message.id)
+        }
+        Err(e) => {
+            error!("Failed to send Telegram alert: {}", e);
+            None
+        }
+    }
+}
+
+/// Whether an event created at `created_at` is more than `max_age` seconds old
+/// at `now` (0 = no limit)
+fn is_stale(created_at: u64, now: u64, max_age: u64) -> bool {
+    max_age > 0 && now.saturating_sub(created_at) > max_age
+}
+
+/// Whether an event created at `created_at` is more than `max_skew` seconds ahead
+/// of `now` (0 = no limit)
+fn is_future(created_at: u64, now: u64, max_skew: u64) -> bool {
+    max_skew > 0 && created_at.saturating_sub(now) > max_skew
+}
+
+/// Routes dispute events to immediate delivery, the digest window or the quiet hours queue
+#[derive(Clone)]
+struct EventRouter {
+    ctx: AlertContext,
+    /// Events travel with a shutdown guard, so shutdown waits for their delivery
+    digest_tx: Option<tokio::sync::mpsc::UnboundedSender<(Event, shutdown::Guard)>>,
+    quiet_hours: Option<Arc<QuietHoursQueue>>,
+    /// Guards alerts held back by the digest, quiet hours or debouncing
+    in_flight: shutdown::Tracker,
+    /// Recent events, so copies from other relays are routed only once
+    seen: Arc<dedup::SeenEvents>,
+    /// Rapid updates to one dispute, coalesced into the newest
+    debounce: Option<Arc<debounce::Debouncer>>,
+}
+
+impl EventRouter {
+    #[tracing::instrument(name = "route_event", skip_all, fields(event_id = %event.id))]
+    async fn route(&self, event: Event) {
+        if !self.seen.first_pass(event.id) {
+            debug!(
+                "Dispute event already handled, delivered by {}",
+                self.seen.relays(&event.id).join(", ")
+            );
+            return;
+        }
+        // A future timestamp would become the last seen one, skipping real events
+        // in the next catch-up, and would skew SLA timers and digest statistics
+        let now = Timestamp::now().as_u64();
+        if is_future(
+            event.created_at.as_u64(),
+            now,
+            self.ctx.alerts().max_future_skew,
+        ) {
+            warn!(
+                "Dispute event {} is dated {}s ahead of the local clock, dropping it",
+                event.id,
+                event.created_at.as_u64() - now
+            );
+            stats::stats().record_future();
+            return;
+        }
+        if let Err(e) = self
+            .ctx
+            .dispute_store
+            .store_event(
+                &event.id.to_hex(),
+                event.created_at.as_u64(),
+                &event.as_json(),
+            )
+            .await
+        {
+            warn!("Failed to store dispute event: {}", e);
+        }
+        let dispute = DisputeInfo::from_event(&event);
+        // Recorded once the event is handled, so a failed delivery can be retried
+        let hash = dedup::content_hash(&event);
+        match self
+            .ctx
+            .dispute_store
+            .last_handled(&dispute.dispute_id)
+            .await
+        {
+            Ok(Some((previous, _))) if previous == hash => {
+                debug!(
+                    "Dispute {} unchanged by event {}, not alerting again",
+                    dispute.dispute_id, event.id
+                );
+                return;
+            }
+            // An older copy re-broadcast after a newer status was handled
+            Ok(Some((_, handled_at))) if event.created_at.as_u64() < handled_at => {
+                debug!(
+                    "Dispute {} event {} is older than the last one handled, not alerting",
+                    dispute.dispute_id, event.id
+                );
+                return;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to check the last handled dispute event: {}", e),
+        }
+        let severity = self.ctx.severity(&dispute);
+        stats::stats().record_event(&dispute.status, severity);
+
+        let max_age = self.ctx.alerts().max_event_age;
+        if is_stale(
+            event.created_at.as_u64(),
+            Timestamp::now().as_u64(),
+            max_age,
+        ) {
+            info!(
+                "Dispute {} event {} is older than {}s, not alerting",
+                dispute.dispute_id, event.id, max_age
+            );
+            return;
+        }
+
+        if let Some(ref debounce) = self.debounce {
+            if debounce.hold(&dispute.dispute_id, event) {
+                let router = self.clone();
+                let debounce = debounce.clone();
+                let guard = self.in_flight.guard();
+                tokio::spawn(async move {
+                    let _guard = guard;
+                    tokio::time::sleep(debounce.window()).await;
+                    if let Some(event) = debounce.take(&dispute.dispute_id) {
+                        router.dispatch(event).await;
+                    }
+                });
+            } else {
+                debug!("Coalescing update to dispute {}", dispute.dispute_id);
+            }
+            return;
+        }
+        self.dispatch(event).await;
+    }
+
+    /// Alert on a dispute event, or hold it for quiet hours or the digest
+    async fn dispatch(&self, event: Event) {
+        let dispute = DisputeInfo::from_event(&event);
+        let severity = self.ctx.severity(&dispute);
+
+        // A less severe status (dispute taken, resolved) means someone is handling it
+        if let Some(ref escalation) = self.ctx.escalation {
+            if severity < AlertSeverity::Critical {
+                escalation.acknowledge(&dispute.dispute_id).await;
+            }
+        }
+
+        if let Some(ref quiet_hours) = self.quiet_hours {
+            if self.should_hold(&event, quiet_hours).await {
+                let mut pending = quiet_hours.pending.lock().await;
+                // Held alerts are flushed on shutdown, so none may be added after it
+                if !shutdown::is_requested() {
+                    pending.push((event, self.in_flight.guard()));
+                    return;
+                }
+            }
+        }
+
+        match self.digest_tx {
+            Some(ref tx) => {
+                if tx.send((event, self.in_flight.guard())).is_err() {
+                    error!("Digest task stopped, dropping dispute event");
+                }
+            }
+            None => handle_dispute_event(&self.ctx, &event).await,
+        }
+    }
+
+    /// Critical alerts always go through; other updates are held during quiet hours
+    /// unless they only edit or delete an existing message, which is silent anyway.
+    async fn should_hold(&self, event: &Event, quiet_hours: &QuietHoursQueue) -> bool {
+        let dispute = DisputeInfo::from_event(event);
+        if self.ctx.severity(&dispute) == AlertSeverity::Critical
+            || dispute.status == "canceled"
+            || !self.ctx.alerts().is_enabled(&dispute.status)
+            || !quiet_hours.schedule.is_quiet_now()
+        {
+            return false;
+        }
+
+        let has_message = existing_dispute_message(&self.ctx.dispute_store, &dispute.dispute_id)
+            .await
+            .is_some();
+        if has_message && self.ctx.alerts().update_mode == config::UpdateMode::Edit {
+            return false;
+        }
+
+        info!(
+            "🌙 Quiet hours: holding {} update for dispute {}",
+            dispute.status, dispute.dispute_id
+        );
+        true
+    }
+}
+
+/// Deliver alerts held during quiet hours as a digest once the quiet window ends,
+/// or right away on shutdown rather than dropping them
+async fn run_quiet_hours_flush(ctx: AlertContext, quiet_hours: Arc<QuietHoursQueue>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(60));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        let shutting_down = tokio::select! {
+            _ = interval.tick() => false,
+            _ = shutdown::requested() => true,
+        };
+        if !shutting_down && quiet_hours.schedule.is_quiet_now() {
+            continue;
+        }
+
+        let pending = std::mem::take(&mut *quiet_hours.pending.lock().await);
+        if !pending.is_empty() {
+            if shutting_down {
+                info!(
+                    "🛑 Shutting down, delivering {} alert(s) held for quiet hours",
+                    pending.len()
+                );
+            } else {
+                info!(
+                    "☀️ Quiet hours over, delivering {} held alert(s)",
+                    pending.len()
+                );
+            }
+            // The guards are released once the alerts are delivered
+            let (events, _guards): (Vec<Event>, Vec<shutdown::Guard>) = pending.into_iter().unzip();
+            deliver_digest(&ctx, events).await;
+        }
+        if shutting_down {
+            return;
+        }
     }
+}
 
-    // Process events
-    let alerts_config = config.alerts.unwrap_or_default();
-    client
-        .handle_notifications(|notification| {
-            let bot = bot.clone();
-            let chat_id = config.telegram.chat_id;
-            let alerts_config = alerts_config.clone();
-            let health_monitor = health_monitor.clone();
-            let dispute_store = dispute_store.clone();
-
-            async move {
-                if let RelayPoolNotification::Event { event, .. } = notification {
-                    if event.kind == Kind::Custom(38386) {
-                        health_monitor.record_event().await;
-                        handle_dispute_event(&bot, chat_id, &event, &alerts_config, &dispute_store)
-                            .await;
+/// Collect dispute events for `window` after the first one arrives, then deliver them together
+async fn run_digest(
+    ctx: AlertContext,
+    mut rx: tokio::sync::mpsc::UnboundedReceiver<(Event, shutdown::Guard)>,
+    window: Duration,
+) {
+    while let Some((first, guard)) = rx.recv().await {
+        let mut batch = vec![first];
+        // Released once the batch is delivered
+        let mut guards = vec![guard];
+        let deadline = tokio::time::sleep(window);
+        tokio::pin!(deadline);
+
+        loop {
+            tokio::select! {
+                _ = &mut deadline => break,
+                // Deliver what was collected rather than waiting out the window
+                _ = shutdown::requested() => break,
+                event = rx.recv() => match event {
+                    Some((event, guard)) => {
+                        batch.push(event);
+                        guards.push(guard);
                     }
-                }
-                Ok(false) // Keep listening
+                    None => break,
+                },
             }
-        })
-        .await?;
+        }
 
-    Ok(())
+        deliver_digest(&ctx, batch).await;
+        drop(guards);
+    }
 }
 
-async fn handle_dispute_event(
-    bot: &Bot,
-    chat_id: i64,
-    event: &Event,
-    alerts_config: &config::AlertsConfig,
-    dispute_store: &DisputeMessageStore,
-) {
-    let mut dispute_id = String::from("unknown");
-    let mut status = String::from("unknown");
-    let mut initiator = String::from("unknown");
-    let mut solver_pubkey: Option<String> = None;
-
-    for tag in event.tags.iter() {
-        let tag_vec: Vec<String> = tag.as_slice().iter().map(|s| s.to_string()).collect();
-        if tag_vec.len() >= 2 {
-            match tag_vec[0].as_str() {
-                "d" => dispute_id = tag_vec[1].clone(),
-                "s" => status = tag_vec[1].clone(),
-                "initiator" => initiator = tag_vec[1].clone(),
-                "solver" => solver_pubkey = Some(tag_vec[1].clone()),
-                _ => {}
-            }
+/// Deliver a batch of dispute events.
+///
+/// Updates to disputes that already have a message (edits, deletions) are handled
+/// individually since they don't add messages to the chat. Remaining new alerts are
+/// combined into as few messages as possible.
+#[tracing::instrument(skip_all, fields(events = batch.len()))]
+async fn deliver_digest(ctx: &AlertContext, batch: Vec<Event>) {
+    // Keep only the latest event per dispute
+    let mut latest: Vec<(DisputeInfo, Event)> = Vec::new();
+    for event in batch {
+        let dispute = DisputeInfo::from_event(&event);
+        match latest
+            .iter_mut()
+            .find(|(d, _)| d.dispute_id == dispute.dispute_id)
+        {
+            Some(entry) if entry.1.created_at <= event.created_at => *entry = (dispute, event),
+            Some(_) => {}
+            None => latest.push((dispute, event)),
         }
     }
 
-    info!(
-        "Dispute event received: id={}, status={}, initiator={}",
-        dispute_id, status, initiator
-    );
-
-    // Check if this alert type is enabled
-    let alert_enabled = match status.as_str() {
-        "initiated" => alerts_config.initiated,
-        "in-progress" => alerts_config.in_progress,
-        "seller-refunded" => alerts_config.seller_refunded,
-        "settled" => alerts_config.settled,
-        "released" => alerts_config.released,
-        _ => alerts_config.other,
-    };
-
-    if !alert_enabled {
-        info!(
-            "Alert for status '{}' is disabled, skipping notification",
-            status
-        );
-        return;
+    let mut new_alerts = Vec::new();
+    for (dispute, event) in latest {
+        let is_update = dispute.status == "canceled"
+            || existing_dispute_message(&ctx.dispute_store, &dispute.dispute_id)
+                .await
+                .is_some();
+        if is_update || !ctx.alerts().is_enabled(&dispute.status) {
+            handle_dispute_event(ctx, &event).await;
+        } else {
+            new_alerts.push((dispute, event));
+        }
     }
 
-    // Check if we have an existing message for this dispute
-    let existing_message = match dispute_store.get_message_id(&dispute_id).await {
-        Ok(result) => result,
-        Err(e) => {
-            error!("Failed to query dispute store: {}", e);
-            None
+    if new_alerts.len() <= 1 {
+        for (_, event) in new_alerts {
+            handle_dispute_event(ctx, &event).await;
         }
-    };
+        return;
+    }
 
-    // Handle cooperative cancellation: delete the message
-    if status == "canceled" {
-        if let Some((message_id, stored_chat_id)) = existing_message {
-            if let Err(e) = bot
-                .delete_message(ChatId(stored_chat_id), MessageId(message_id))
-                .await
-            {
-                warn!("Failed to delete dispute message: {}", e);
-            } else {
-                info!(
-                    "🗑️ Deleted dispute message for {} (cooperative cancel)",
-                    dispute_id
-                );
-            }
-            if let Err(e) = dispute_store.delete(&dispute_id).await {
-                error!("Failed to remove dispute from store: {}", e);
-            }
+    if silence::suppressed(silence::Category::Disputes, "digest") {
+        for (_, event) in &new_alerts {
+            record_handled(ctx, event).await;
         }
         return;
     }
+    info!("📦 Sending digest of {} dispute alerts", new_alerts.len());
+
+    let mut context = tera::Context::new();
+    context.insert("count", &new_alerts.len());
+    let header = ctx.templates.render("digest", &context);
+    let parts: Vec<String> = new_alerts
+        .iter()
+        .map(|(dispute, event)| render_dispute_message(ctx, dispute, event))
+        .collect();
+
+    for ((dispute, _), part) in new_alerts.iter().zip(&parts) {
+        if ctx.severity(dispute) == AlertSeverity::Critical {
+            on_critical_alert(ctx, &dispute.dispute_id, part).await;
+        }
+    }
 
-    // Generate appropriate message based on status
-    let message = match status.as_str() {
-        "initiated" => {
-            format!(
-                "🚨 *NEW DISPUTE*\n\n\
-                 📋 *Dispute ID:* `{}`\n\
-                 👤 *Initiated by:* {}\n\
-                 ⏰ *Time:* {}\n\n\
-                 ⚡ Please take this dispute in Mostrix or your admin client\\.",
-                escape_markdown_code(&dispute_id),
-                escape_markdown(&initiator),
-                escape_markdown(&chrono_timestamp(event.created_at.as_u64())),
-            )
+    let destination = Destination {
+        chat_id: ctx.chat_id,
+        topic_id: ctx.topic_id,
+        silent: new_alerts
+            .iter()
+            .all(|(dispute, _)| ctx.alerts().silent.is_silent(&dispute.status)),
+    };
+
+    // Only the digest header carries the branding prefix
+    let parts: Vec<String> = parts
+        .iter()
+        .map(|part| ctx.templates.embeddable(part))
+        .collect();
+    let mut delivered = true;
+    for chunk in digest_chunks(&header, &parts) {
+        if let Err(e) = send_markdown(&ctx.bot, destination, &chunk).await {
+            error!("Failed to send dispute digest: {}", e);
+            delivered = false;
         }
-        "in-progress" => {
-            let solver_info = solver_pubkey
-                .as_ref()
-                .map(|pk| format!("\n👨‍⚖️ *Taken by:* `{}`", escape_markdown_code(pk)))
-                .unwrap_or_default();
-            format!(
-                "🔄 *DISPUTE IN PROGRESS*\n\n\
-                 📋 *Dispute ID:* `{}`{}\n\
-                 ⏰ *Time:* {}\n\n\
-                 ℹ️ Dispute is now being handled\\.",
-                escape_markdown_code(&dispute_id),
-                solver_info,
-                escape_markdown(&chrono_timestamp(event.created_at.as_u64())),
-            )
+    }
+    if delivered {
+        for (_, event) in &new_alerts {
+            record_handled(ctx, event).await;
         }
-        "seller-refunded" => {
-            let solver_info = solver_pubkey
-                .as_ref()
-                .map(|pk| format!("\n👨‍⚖️ *Resolved by:* `{}`", escape_markdown_code(pk)))
-                .unwrap_or_default();
-            format!(
-                "💰 *DISPUTE RESOLVED \\- SELLER REFUNDED*\n\n\
-                 📋 *Dispute ID:* `{}`{}\n\
-                 ⏰ *Time:* {}\n\n\
-                 ✔️ Dispute closed: funds returned to seller\\.",
-                escape_markdown_code(&dispute_id),
-                solver_info,
-                escape_markdown(&chrono_timestamp(event.created_at.as_u64())),
-            )
+    }
+
+    if ctx.alerts().raw_event == config::RawEventMode::Document {
+        for (_, event) in &new_alerts {
+            send_raw_event_document(&ctx.bot, destination, None, event).await;
         }
-        "settled" => {
-            let solver_info = solver_pubkey
-                .as_ref()
-                .map(|pk| format!("\n👨‍⚖️ *Resolved by:* `{}`", escape_markdown_code(pk)))
-                .unwrap_or_default();
-            format!(
-                "✅ *DISPUTE RESOLVED \\- SETTLED*\n\n\
-                 📋 *Dispute ID:* `{}`{}\n\
-                 ⏰ *Time:* {}\n\n\
-                 ✔️ Dispute closed: buyer receives payment\\.",
-                escape_markdown_code(&dispute_id),
-                solver_info,
-                escape_markdown(&chrono_timestamp(event.created_at.as_u64())),
-            )
+    }
+}
+
+/// Telegram rejects messages longer than 4096 characters; stay safely below it
+const MAX_MESSAGE_LEN: usize = 4000;
+
+/// Join digest parts under a header, splitting into several messages when too long
+fn digest_chunks(header: &str, parts: &[String]) -> Vec<String> {
+    const SEPARATOR: &str = "\n\n➖➖➖\n\n";
+
+    let mut chunks = Vec::new();
+    let mut current = header.to_string();
+    let mut has_parts = false;
+
+    for part in parts {
+        if has_parts && current.len() + SEPARATOR.len() + part.len() > MAX_MESSAGE_LEN {
+            chunks.push(std::mem::take(&mut current));
         }
-        "released" => {
-            format!(
-                "🔓 *DISPUTE RESOLVED \\- RELEASED*\n\n\
-                 📋 *Dispute ID:* `{}`\n\
-                 🤝 *Resolution:* Released by seller\n\
-                 ⏰ *Time:* {}\n\n\
-                 ✔️ Dispute closed: trade completed\\.",
-                escape_markdown_code(&dispute_id),
-                escape_markdown(&chrono_timestamp(event.created_at.as_u64())),
-            )
+        if !current.is_empty() {
+            current.push_str(SEPARATOR);
         }
-        _ => {
-            format!(
-                "📡 *DISPUTE STATUS UPDATE*\n\n\
-                 📋 *Dispute ID:* `{}`\n\
-                 📊 *Status:* {}\n\
-                 ⏰ *Time:* {}\n\n\
-                 ℹ️ Status changed\\.",
-                escape_markdown_code(&dispute_id),
-                escape_markdown(&status),
-                escape_markdown(&chrono_timestamp(event.created_at.as_u64())),
-            )
+        current.push_str(part);
+        has_parts = true;
+    }
+
+    if has_parts {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Expand action link templates for a dispute alert. Values are percent-encoded,
+/// so one can't add path segments or query parameters to the link.
+///
+/// Telegram only accepts web URLs in inline buttons, so http(s) links become
+/// buttons and anything else (e.g. `nostr:` URIs) is returned as MarkdownV2 text lines.
+fn dispute_links(
+    links: &[config::LinkConfig],
+    vars: &[(&str, &str)],
+) -> (Vec<InlineKeyboardButton>, Vec<String>) {
+    let mut buttons = Vec::new();
+    let mut text_links = Vec::new();
+
+    for link in links {
+        let mut url = link.url.clone();
+        for (name, value) in vars {
+            url = url.replace(&format!("{{{name}}}"), &percent_encode(value));
         }
-    };
 
-    // If we have an existing message, update it; otherwise send a new one
-    if let Some((message_id, stored_chat_id)) = existing_message {
-        // Update existing message
-        match bot
-            .edit_message_text(ChatId(stored_chat_id), MessageId(message_id), &message)
-            .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-            .await
-        {
-            Ok(_) => {
-                info!(
-                    "✏️ Updated dispute message for {} (status: {})",
-                    dispute_id, status
-                );
-                if let Err(e) = dispute_store.update_status(&dispute_id, &status).await {
-                    error!("Failed to update dispute status in store: {}", e);
-                }
-            }
-            Err(e) => {
-                // If edit fails (e.g., message deleted), send a new one
-                warn!("Failed to edit message, sending new one: {}", e);
-                send_new_dispute_message(
-                    bot,
-                    chat_id,
-                    &dispute_id,
-                    &status,
-                    &message,
-                    dispute_store,
-                )
-                .await;
+        match url::Url::parse(&url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {
+                buttons.push(InlineKeyboardButton::url(link.label.clone(), parsed));
             }
+            _ => text_links.push(format!(
+                "🔗 {}: `{}`",
+                escape_markdown(&link.label),
+                escape_markdown_code(&url)
+            )),
         }
-    } else {
-        // Send new message
-        send_new_dispute_message(bot, chat_id, &dispute_id, &status, &message, dispute_store).await;
     }
+
+    (buttons, text_links)
 }
 
-async fn send_new_dispute_message(
-    bot: &Bot,
+/// Percent-encode everything but the unreserved URL characters (RFC 3986)
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
+
+/// Where and how an outgoing Telegram message is delivered
+#[derive(Debug, Clone, Copy)]
+struct Destination {
     chat_id: i64,
-    dispute_id: &str,
-    status: &str,
-    message: &str,
-    dispute_store: &DisputeMessageStore,
-) {
-    match bot
-        .send_message(ChatId(chat_id), message)
-        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-        .await
-    {
-        Ok(sent_message) => {
-            info!(
-                "✅ Telegram alert sent for dispute {} (status: {})",
-                dispute_id, status
+    /// Forum topic (message_thread_id), if any
+    topic_id: Option<i32>,
+    /// Deliver without a notification sound
+    silent: bool,
+}
+
+/// Send a MarkdownV2 message to the given destination
+async fn send_markdown(
+    bot: &Bot,
+    destination: Destination,
+    text: &str,
+) -> Result<teloxide::types::Message, teloxide::RequestError> {
+    send_with_fallback(markdown_message(bot, destination, text)).await
+}
+
+/// Send a MarkdownV2 request through the send queue, retrying as plain text if Telegram
+/// can't parse the entities (e.g. unexpected characters in dispute tags) so the alert is not lost
+#[tracing::instrument(name = "telegram_send", skip_all)]
+async fn send_with_fallback(
+    request: <Bot as Requester>::SendMessage,
+) -> Result<teloxide::types::Message, teloxide::RequestError> {
+    let chat_id = recipient_id(&request.payload_ref().chat_id);
+    let text = &request.payload_ref().text;
+    if dry_run::intercepted(&format!("chat {chat_id}"), "send", text) {
+        return Ok(dry_run::message(chat_id, text));
+    }
+    let queue = send_queue::queue();
+    let result = match queue.send(chat_id, || request.clone().send()).await {
+        Err(teloxide::RequestError::Api(teloxide::ApiError::CantParseEntities(reason))) => {
+            let mut request = request;
+            let payload = request.payload_mut();
+            warn!(
+                "Telegram rejected MarkdownV2 ({}), sending as plain text: {:?}",
+                reason, payload.text
             );
-            // Store the message ID for future updates
-            if let Err(e) = dispute_store
-                .insert(dispute_id, sent_message.id.0, chat_id, status)
-                .await
-            {
-                error!("Failed to store dispute message ID: {}", e);
-            }
+            payload.text = markdown_to_plain(&payload.text);
+            payload.parse_mode = None;
+            queue.send(chat_id, || request.clone().send()).await
         }
-        Err(e) => {
-            error!("Failed to send Telegram alert: {}", e);
+        result => result,
+    };
+    record_delivery(
+        &chat_id.to_string(),
+        result.as_ref().err().map(ToString::to_string),
+    );
+    result
+}
+
+/// Edit a message with MarkdownV2 through the send queue, retrying as plain text on
+/// entity parse errors
+#[tracing::instrument(name = "telegram_edit", skip_all)]
+async fn edit_with_fallback(
+    request: <Bot as Requester>::EditMessageText,
+) -> Result<teloxide::types::Message, teloxide::RequestError> {
+    let chat_id = recipient_id(&request.payload_ref().chat_id);
+    let text = &request.payload_ref().text;
+    if dry_run::intercepted(&format!("chat {chat_id}"), "edit a message", text) {
+        return Ok(dry_run::message(chat_id, text));
+    }
+    let queue = send_queue::queue();
+    let result = match queue.send(chat_id, || request.clone().send()).await {
+        Err(teloxide::RequestError::Api(teloxide::ApiError::CantParseEntities(reason))) => {
+            let mut request = request;
+            let payload = request.payload_mut();
+            warn!(
+                "Telegram rejected MarkdownV2 ({}), editing as plain text: {:?}",
+                reason, payload.text
+            );
+            payload.text = markdown_to_plain(&payload.text);
+            payload.parse_mode = None;
+            queue.send(chat_id, || request.clone().send()).await
         }
+        result => result,
+    };
+    record_delivery(
+        &chat_id.to_string(),
+        result.as_ref().err().map(ToString::to_string),
+    );
+    result
+}
+
+/// Count a delivery (Telegram chat ID, `pushover`, `webhook`) for /stats and health
+fn record_delivery(channel: &str, error: Option<String>) {
+    let ok = error.is_none();
+    if ok {
+        stats::stats().record_sent(channel);
+    } else {
+        stats::stats().record_failure(channel);
+    }
+    if let Some(health_monitor) = DELIVERY_HEALTH.get() {
+        health_monitor.record_delivery(ok);
+        let component = match channel {
+            "pushover" => "pushover",
+            "webhook" => "webhook",
+            "alertmanager" => "alertmanager",
+            _ => "telegram",
+        };
+        health_monitor.record_component(component, error.map_or(Ok(()), Err));
     }
 }
 
-fn chrono_timestamp(unix: u64) -> String {
-    let secs = unix as i64;
-    let days = secs / 86400;
-    let time_secs = secs % 86400;
-    let hours = time_secs / 3600;
-    let minutes = (time_secs % 3600) / 60;
-    let seconds = time_secs % 60;
+/// Numeric chat ID used to rate-limit a recipient (channel usernames share one slot)
+fn recipient_id(recipient: &teloxide::types::Recipient) -> i64 {
+    match recipient {
+        teloxide::types::Recipient::Id(ChatId(id)) => *id,
+        teloxide::types::Recipient::ChannelUsername(_) => 0,
+    }
+}
 
-    // Simple days-since-epoch to Y-M-D (good enough for 2020-2099)
-    let mut y = 1970i64;
-    let mut remaining = days;
-    loop {
-        let days_in_year = if y % 4 == 0 && (y % 100 != 0 || y % 400 == 0) {
-            366
-        } else {
-            365
-        };
-        if remaining < days_in_year {
-            break;
-        }
-        remaining -= days_in_year;
-        y += 1;
-    }
-    let leap = y % 4 == 0 && (y % 100 != 0 || y % 400 == 0);
-    let month_days = [
-        31,
-        if leap { 29 } else { 28 },
-        31,
-        30,
-        31,
-        30,
-        31,
-        31,
-        30,
-        31,
-        30,
-        31,
-    ];
-    let mut m = 0usize;
-    for md in &month_days {
-        if remaining < *md {
-            break;
+/// Best-effort conversion of MarkdownV2 to readable plain text:
+/// escapes are resolved and formatting markers dropped
+fn markdown_to_plain(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => plain.extend(chars.next()),
+            '*' | '_' | '~' | '`' => {}
+            _ => plain.push(c),
         }
-        remaining -= md;
-        m += 1;
     }
-    format!(
-        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
-        y,
-        m + 1,
-        remaining + 1,
-        hours,
-        minutes,
-        seconds
-    )
+    plain
+}
+
+/// Build a MarkdownV2 send request so callers can add extra options before sending
+fn markdown_message(
+    bot: &Bot,
+    destination: Destination,
+    text: &str,
+) -> <Bot as Requester>::SendMessage {
+    let mut request = bot
+        .send_message(ChatId(destination.chat_id), text)
+        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+        .disable_notification(destination.silent);
+    if let Some(topic) = destination.topic_id {
+        request = request.message_thread_id(ThreadId(MessageId(topic)));
+    }
+    request
 }
 
 fn escape_markdown(text: &str) -> String {
@@ -946,6 +4166,24 @@ mod tests {
         assert_eq!(escape_markdown("normal text"), "normal text");
     }
 
+    #[test]
+    fn test_raw_event_quote() {
+        let quote = raw_event_quote("{\n  \"kind\": 38386,\n  \"tags\": [\"a\\\\b\"]\n}");
+        assert_eq!(
+            quote,
+            "**>\\{\n>  \"kind\": 38386,\n>  \"tags\": \\[\"a\\\\\\\\b\"\\]\n>\\}||"
+        );
+    }
+
+    #[test]
+    fn test_markdown_to_plain() {
+        assert_eq!(
+            markdown_to_plain("🚨 *NEW DISPUTE*\n📋 *Dispute ID:* `abc\\_123`\nPlease take it\\."),
+            "🚨 NEW DISPUTE\n📋 Dispute ID: abc_123\nPlease take it."
+        );
+        assert_eq!(markdown_to_plain("trailing \\"), "trailing ");
+    }
+
     #[test]
     fn test_escape_markdown_code() {
         // Only backticks and backslashes should be escaped in code spans
@@ -972,18 +4210,67 @@ mod tests {
     }
 
     #[test]
-    fn test_chrono_timestamp() {
-        // Test known Unix timestamp: 1609459200 = 2021-01-01 00:00:00 UTC
-        assert_eq!(chrono_timestamp(1609459200), "2021-01-01 00:00:00 UTC");
+    fn test_dispute_links() {
+        let config: AlertsConfig = toml::from_str(
+            r#"
+            [[links]]
+            label = "Open in Mostrix"
+            url = "https://mostrix.example/disputes/{dispute_id}"
+
+            [[links]]
+            label = "Nostr event"
+            url = "nostr:{note_id}"
+            "#,
+        )
+        .unwrap();
+
+        let (buttons, text_links) = dispute_links(
+            &config.links,
+            &[("dispute_id", "abc-123"), ("note_id", "note1xyz")],
+        );
+
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(buttons[0].text, "Open in Mostrix");
+        assert_eq!(
+            text_links,
+            vec!["🔗 Nostr event: `nostr:note1xyz`".to_string()]
+        );
+
+        // A value can't change the link's path or query
+        let (buttons, _) = dispute_links(
+            &config.links,
+            &[
+                ("dispute_id", "../admin?x=1&y=ü z"),
+                ("note_id", "note1xyz"),
+            ],
+        );
+        let teloxide::types::InlineKeyboardButtonKind::Url(ref url) = buttons[0].kind else {
+            panic!("not a URL button");
+        };
+        assert_eq!(
+            url.as_str(),
+            "https://mostrix.example/disputes/..%2Fadmin%3Fx%3D1%26y%3D%C3%BC%20z"
+        );
+    }
 
-        // Test another known timestamp: 1640995200 = 2022-01-01 00:00:00 UTC
-        assert_eq!(chrono_timestamp(1640995200), "2022-01-01 00:00:00 UTC");
+    #[test]
+    fn test_digest_chunks() {
+        let parts = vec!["first".to_string(), "second".to_string()];
+        assert_eq!(
+            digest_chunks("📦 header", &parts),
+            vec!["📦 header\n\n➖➖➖\n\nfirst\n\n➖➖➖\n\nsecond".to_string()]
+        );
 
-        // Test with time: 1609459200 + 3661 = 2021-01-01 01:01:01 UTC
-        assert_eq!(chrono_timestamp(1609462861), "2021-01-01 01:01:01 UTC");
+        // Long digests are split, with the header only in the first message
+        let long_part = "x".repeat(MAX_MESSAGE_LEN / 3);
+        let parts = vec![long_part.clone(), long_part.clone(), long_part.clone()];
+        let chunks = digest_chunks("header", &parts);
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].starts_with("header"));
+        assert!(chunks.iter().all(|c| c.len() <= MAX_MESSAGE_LEN));
+        assert_eq!(chunks[1], long_part);
 
-        // Test leap year: 1582934400 = 2020-02-29 00:00:00 UTC (leap year)
-        assert_eq!(chrono_timestamp(1582934400), "2020-02-29 00:00:00 UTC");
+        assert!(digest_chunks("header", &[]).is_empty());
     }
 
     #[test]
@@ -995,6 +4282,115 @@ mod tests {
         assert!(config.settled);
         assert!(config.released);
         assert!(config.other);
+        assert_eq!(config.update_mode, config::UpdateMode::Edit);
+        assert!(config.links.is_empty());
+        assert_eq!(config.digest_window, 0);
+        assert_eq!(config.debounce_window, 0);
+        assert_eq!(config.max_event_age, 0);
+        assert_eq!(config.max_future_skew, 300);
+        assert_eq!(config.redact, config::RedactMode::Off);
+        assert_eq!(config.cache_capacity, 4096);
+        assert!(!config.pin);
+    }
+
+    #[test]
+    fn test_stale_events() {
+        assert!(!is_stale(1_000, 1_000 + 3600, 0));
+        assert!(!is_stale(1_000, 1_000 + 3600, 3600));
+        assert!(is_stale(1_000, 1_000 + 3601, 3600));
+        // Events from the future are never stale
+        assert!(!is_stale(2_000, 1_000, 3600));
+    }
+
+    #[test]
+    fn test_future_events() {
+        assert!(!is_future(1_000 + 300, 1_000, 300));
+        assert!(is_future(1_000 + 301, 1_000, 300));
+        assert!(!is_future(1_000 + 86400, 1_000, 0));
+        assert!(!is_future(500, 1_000, 300));
+    }
+
+    #[test]
+    fn test_terminal_statuses() {
+        assert!(is_terminal_status("seller-refunded"));
+        assert!(is_terminal_status("settled"));
+        assert!(is_terminal_status("released"));
+        assert!(!is_terminal_status("initiated"));
+        assert!(!is_terminal_status("in-progress"));
+    }
+
+    #[test]
+    fn test_alerts_update_mode_parsing() {
+        let config: AlertsConfig = toml::from_str("update_mode = \"reply\"").unwrap();
+        assert_eq!(config.update_mode, config::UpdateMode::Reply);
+
+        let config: AlertsConfig = toml::from_str("").unwrap();
+        assert_eq!(config.update_mode, config::UpdateMode::Edit);
+
+        assert!(toml::from_str::<AlertsConfig>("update_mode = \"bogus\"").is_err());
+    }
+
+    #[test]
+    fn test_silent_config() {
+        let config = AlertsConfig::default();
+
+        // Only "dispute taken" updates are silent by default
+        assert!(!config.silent.is_silent("initiated"));
+        assert!(config.silent.is_silent("in-progress"));
+        assert!(!config.silent.is_silent("settled"));
+        assert!(!config.silent.is_silent("unknown-status"));
+
+        let config: AlertsConfig = toml::from_str(
+            r#"
+            [silent]
+            in_progress = false
+            other = true
+            "#,
+        )
+        .unwrap();
+        assert!(!config.silent.is_silent("in-progress"));
+        assert!(config.silent.is_silent("unknown-status")); // maps to other
+        assert!(!config.silent.is_silent("initiated"));
+    }
+
+    #[test]
+    fn test_severity_config() {
+        let config = AlertsConfig::default();
+
+        assert_eq!(
+            config.severity.for_status("initiated"),
+            AlertSeverity::Critical
+        );
+        assert_eq!(
+            config.severity.for_status("in-progress"),
+            AlertSeverity::Info
+        );
+        assert_eq!(config.severity.for_status("settled"), AlertSeverity::Info);
+        assert_eq!(
+            config.severity.for_status("unknown-status"),
+            AlertSeverity::Warning
+        );
+        assert_eq!(config.severity.relay_alert, AlertSeverity::Warning);
+        assert_eq!(config.severity.relay_quorum, AlertSeverity::Critical);
+
+        let config: AlertsConfig = toml::from_str(
+            r#"
+            [severity]
+            other = "info"
+            relay_alert = "critical"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.severity.for_status("canceled"), AlertSeverity::Info);
+        assert_eq!(config.severity.relay_alert, AlertSeverity::Critical);
+        assert_eq!(
+            config.severity.for_status("initiated"),
+            AlertSeverity::Critical
+        );
+        assert!(AlertSeverity::Info < AlertSeverity::Warning);
+        assert!(AlertSeverity::Warning < AlertSeverity::Critical);
+
+        assert!(toml::from_str::<AlertsConfig>("[severity]\ninitiated = \"urgent\"").is_err());
     }
 
     #[test]
@@ -1041,7 +4437,7 @@ mod tests {
 
         // Test malformed events (simulated with empty strings)
         assert_eq!(escape_markdown_code(""), "");
-        assert_eq!(chrono_timestamp(0), "1970-01-01 00:00:00 UTC"); // Unix epoch
+        assert_eq!(timestamp::utc(0), "1970-01-01 00:00:00 UTC"); // Unix epoch
 
         // Test boundary conditions - backslash is NOT in escape_markdown special chars
         assert_eq!(escape_markdown("\\"), "\\"); // backslash not escaped by escape_markdown
@@ -1054,10 +4450,10 @@ mod tests {
         let health_monitor = HealthMonitor::new();
 
         // Initial state should be healthy with no events
-        assert!(*health_monitor.is_healthy.read().await);
-        assert_eq!(*health_monitor.events_processed.read().await, 0);
-        assert!(health_monitor.last_event_time.read().await.is_none());
-        assert!(health_monitor.last_heartbeat.read().await.is_none());
+        assert!(health_monitor.is_healthy());
+        assert_eq!(health_monitor.events_processed(), 0);
+        assert!(health_monitor.last_event().is_none());
+        assert!(health_monitor.last_heartbeat().is_none());
 
         // Start time should be recent
         let uptime = health_monitor
@@ -1072,15 +4468,15 @@ mod tests {
         let health_monitor = HealthMonitor::new();
 
         // Record an event
-        health_monitor.record_event().await;
+        health_monitor.record_event();
 
         // Check that event was recorded
-        assert_eq!(*health_monitor.events_processed.read().await, 1);
-        assert!(health_monitor.last_event_time.read().await.is_some());
+        assert_eq!(health_monitor.events_processed(), 1);
+        assert!(health_monitor.last_event().is_some());
 
         // Record another event
-        health_monitor.record_event().await;
-        assert_eq!(*health_monitor.events_processed.read().await, 2);
+        health_monitor.record_event();
+        assert_eq!(health_monitor.events_processed(), 2);
     }
 
     #[tokio::test]
@@ -1088,41 +4484,76 @@ mod tests {
         let health_monitor = HealthMonitor::new();
 
         // Initially no heartbeat
-        assert!(health_monitor.last_heartbeat.read().await.is_none());
+        assert!(health_monitor.last_heartbeat().is_none());
 
         // Record a heartbeat
-        health_monitor.record_heartbeat().await;
+        health_monitor.record_heartbeat();
 
         // Check that heartbeat was recorded
-        assert!(health_monitor.last_heartbeat.read().await.is_some());
+        assert!(health_monitor.last_heartbeat().is_some());
     }
 
-    #[tokio::test]
-    async fn test_should_alert_no_events() {
+    #[test]
+    fn test_should_alert_no_events() {
         let health_monitor = HealthMonitor::new();
 
         // With threshold 0 (disabled), should never alert
-        assert!(!health_monitor.should_alert_no_events(0).await);
+        assert!(!health_monitor.should_alert_no_events(0));
 
         // With threshold 10 and no events, should not alert immediately (just started)
-        assert!(!health_monitor.should_alert_no_events(10).await);
+        assert!(!health_monitor.should_alert_no_events(10));
 
         // Simulate system running for a while by manually setting start time
         let old_start = SystemTime::now() - Duration::from_secs(20);
         let health_monitor_old = HealthMonitor {
-            last_event_time: Arc::new(RwLock::new(None)),
-            last_heartbeat: Arc::new(RwLock::new(None)),
             start_time: old_start,
-            events_processed: Arc::new(RwLock::new(0)),
-            is_healthy: Arc::new(RwLock::new(true)),
+            ..HealthMonitor::new()
         };
 
         // Now with no events and system running for 20 seconds, should alert with 10s threshold
-        assert!(health_monitor_old.should_alert_no_events(10).await);
+        assert!(health_monitor_old.should_alert_no_events(10));
 
         // But if we record an event recently, should not alert
-        health_monitor_old.record_event().await;
-        assert!(!health_monitor_old.should_alert_no_events(10).await);
+        health_monitor_old.record_event();
+        assert!(!health_monitor_old.should_alert_no_events(10));
+    }
+
+    #[tokio::test]
+    async fn test_readiness() {
+        let health_monitor = HealthMonitor::new();
+
+        // Not ready before the client is attached
+        let readiness = health_monitor.readiness().await;
+        assert!(!readiness.ready);
+        assert!(readiness.telegram);
+
+        for _ in 0..MAX_CONSECUTIVE_DELIVERY_FAILURES {
+            health_monitor.record_delivery(false);
+        }
+        assert!(!health_monitor.readiness().await.telegram);
+    }
+
+    #[tokio::test]
+    async fn test_health_verbose() {
+        let health_monitor = HealthMonitor::new();
+        health_monitor.record_component("telegram", Err("Forbidden: bot was kicked".into()));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&health_monitor.get_status_json(false).await).unwrap();
+        assert!(json.get("components").is_none());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&health_monitor.get_status_json(true).await).unwrap();
+        assert_eq!(json["status"], "healthy");
+        assert_eq!(json["components"]["telegram"]["status"], "error");
+        assert_eq!(
+            json["components"]["telegram"]["last_error"],
+            "Forbidden: bot was kicked"
+        );
+        assert_eq!(
+            json["components"]["nostr"]["last_error"],
+            "not connected yet"
+        );
     }
 
     #[tokio::test]
@@ -1130,7 +4561,7 @@ mod tests {
         let health_monitor = HealthMonitor::new();
 
         // Get initial status
-        let status_json = health_monitor.get_status_json().await;
+        let status_json = health_monitor.get_status_json(false).await;
 
         // Should be valid JSON with expected fields
         assert!(status_json.contains("\"status\":\"healthy\""));
@@ -1139,26 +4570,97 @@ mod tests {
         assert!(status_json.contains("\"uptime_seconds\":"));
 
         // Record some events and check updated status
-        health_monitor.record_event().await;
-        health_monitor.record_event().await;
-        health_monitor.record_heartbeat().await;
+        health_monitor.record_event();
+        health_monitor.record_event();
+        health_monitor.record_heartbeat();
 
-        let updated_status = health_monitor.get_status_json().await;
+        let updated_status = health_monitor.get_status_json(false).await;
         assert!(updated_status.contains("\"events_processed\":2"));
+        assert!(updated_status.contains("\"relays\":[]"));
         assert!(updated_status.contains("\"last_event_timestamp\":"));
+        assert!(updated_status.contains("\"last_event_time\":\""));
         assert!(updated_status.contains("\"last_heartbeat_timestamp\":"));
     }
 
     #[test]
-    fn test_health_config_defaults() {
-        let config = config::HealthConfig::default();
-
-        assert!(!config.heartbeat_enabled); // Disabled by default to avoid flooding chat
-        assert_eq!(config.heartbeat_interval, 3600); // 1 hour
-        assert!(config.check_relays);
-        assert_eq!(config.relay_timeout, 30);
-        assert_eq!(config.event_alert_threshold, 7200); // 2 hours
-        assert!(!config.enable_http_endpoint); // Disabled by default
-        assert_eq!(config.http_port, 8080);
+    fn test_delivery_failures_mark_unhealthy() {
+        let health_monitor = HealthMonitor::new();
+
+        for _ in 0..MAX_CONSECUTIVE_DELIVERY_FAILURES - 1 {
+            health_monitor.record_delivery(false);
+        }
+        assert!(health_monitor.status().healthy);
+
+        health_monitor.record_delivery(false);
+        let status = health_monitor.status();
+        assert!(!status.healthy);
+        assert_eq!(
+            status.consecutive_delivery_failures,
+            MAX_CONSECUTIVE_DELIVERY_FAILURES
+        );
+
+        // A single success recovers
+        health_monitor.record_delivery(true);
+        let status = health_monitor.status();
+        assert!(status.healthy);
+        assert_eq!(status.consecutive_delivery_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_relay_health() {
+        let health_monitor = HealthMonitor::new();
+        health_monitor.register_relays(&["wss://relay.example".to_string()]);
+
+        // URLs from the relay pool may carry a trailing slash
+        health_monitor.record_relay_message("wss://relay.example/");
+        health_monitor.record_relay_message("wss://relay.example");
+        health_monitor.set_relay_status("wss://relay.example", "Disconnected");
+        health_monitor.record_relay_reconnect("wss://relay.example");
+        // Unknown relays are ignored
+        health_monitor.record_relay_message("wss://other.example");
+
+        let status = health_monitor.status();
+        assert_eq!(status.relays.len(), 1);
+        let relay = &status.relays[0];
+        assert_eq!(relay.url, "wss://relay.example");
+        assert_eq!(relay.status, "disconnected");
+        assert_eq!(relay.messages_received, 2);
+        assert!(relay.last_message_timestamp.is_some());
+        assert_eq!(relay.reconnects, 1);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&health_monitor.get_status_json(false).await).unwrap();
+        assert_eq!(json["relays"][0]["url"], "wss://relay.example");
+        assert_eq!(json["relays"][0]["reconnects"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_command_dispatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, "not [valid toml").unwrap();
+        let path = config_path.to_str().unwrap();
+        let parse = |args: &[&str]| {
+            <cli::Cli as clap::Parser>::try_parse_from(
+                std::iter::once("mostro-watchdog").chain(args.iter().copied()),
+            )
+            .unwrap()
+        };
+
+        // The long-running mode is left to main
+        let cli = parse(&[path]);
+        let passed = run_command(&cli, cli.command(), config_path.clone()).await;
+        assert!(matches!(passed, Ok(None)));
+
+        // Reaching replay or announce means it tries, and fails, to load the config
+        for args in [&["replay", path][..], &["announce", "Maintenance", path]] {
+            let cli = parse(args);
+            assert!(
+                run_command(&cli, cli.command(), config_path.clone())
+                    .await
+                    .is_err(),
+                "{args:?}"
+            );
+        }
     }
 }
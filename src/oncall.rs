@@ -0,0 +1,198 @@
+//! On-call rotation: admins take turns in fixed daily or weekly shifts starting at a
+//! configured date. New-dispute alerts mention (or DM) only the admin currently on call.
+
+use crate::config::{OnCallAdmin, OnCallConfig, OnCallNotify, Rotation};
+use crate::escape_markdown;
+use crate::quiet_hours::{parse_offset, parse_time};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Parsed on-call rotation
+#[derive(Debug, Clone)]
+pub struct OnCall {
+    admins: Vec<OnCallAdmin>,
+    /// Unix timestamp when the first admin's shift starts
+    start: i64,
+    /// Shift length in seconds
+    period: i64,
+    pub notify: OnCallNotify,
+}
+
+/// The admin on call at a given time
+#[derive(Debug, Clone, Copy)]
+pub struct Shift<'a> {
+    pub admin: &'a OnCallAdmin,
+    pub next: &'a OnCallAdmin,
+    /// Unix timestamp of the next handover
+    pub until: i64,
+}
+
+impl OnCall {
+    pub fn from_config(config: &OnCallConfig) -> Result<Self, String> {
+        if config.admins.is_empty() {
+            return Err("oncall.admins must list at least one admin".into());
+        }
+        let offset = parse_offset(&config.utc_offset)? * 60;
+        let start = parse_start(&config.start).ok_or_else(|| {
+            format!(
+                "Invalid oncall start '{}' (expected e.g. \"2026-01-05 09:00\")",
+                config.start
+            )
+        })?;
+        let period = match config.rotation {
+            Rotation::Daily => 86400,
+            Rotation::Weekly => 7 * 86400,
+        };
+
+        Ok(Self {
+            admins: config.admins.clone(),
+            start: start - offset,
+            period,
+            notify: config.notify,
+        })
+    }
+
+    /// Shift covering the given Unix timestamp
+    pub fn shift_at(&self, unix: i64) -> Shift<'_> {
+        let shift = (unix - self.start).div_euclid(self.period);
+        let index = shift.rem_euclid(self.admins.len() as i64) as usize;
+        Shift {
+            admin: &self.admins[index],
+            next: &self.admins[(index + 1) % self.admins.len()],
+            until: self.start + (shift + 1) * self.period,
+        }
+    }
+
+    /// Telegram user IDs of everyone in the rotation
+    pub fn user_ids(&self) -> impl Iterator<Item = i64> + '_ {
+        self.admins.iter().map(|admin| admin.user_id)
+    }
+
+    pub fn current(&self) -> Shift<'_> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.shift_at(now as i64)
+    }
+}
+
+/// MarkdownV2 mention that notifies the admin even without a username
+pub fn mention(admin: &OnCallAdmin) -> String {
+    format!(
+        "[{}](tg://user?id={})",
+        escape_markdown(&admin.name),
+        admin.user_id
+    )
+}
+
+/// Parse `YYYY-MM-DD` or `YYYY-MM-DD HH:MM` into a Unix timestamp (UTC)
+fn parse_start(start: &str) -> Option<i64> {
+    let start = start.trim();
+    let (date, time) = match start.split_once(' ') {
+        Some((date, time)) => (date, parse_time(time.trim())?),
+        None => (start, 0),
+    };
+
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day) * 86400 + time as i64 * 60)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12; // March = 0
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotation(toml: &str) -> OnCall {
+        let config: OnCallConfig = toml::from_str(toml).unwrap();
+        OnCall::from_config(&config).unwrap()
+    }
+
+    const ADMINS: &str = r#"
+        [[admins]]
+        name = "Alice"
+        user_id = 1
+
+        [[admins]]
+        name = "Bob"
+        user_id = 2
+    "#;
+
+    #[test]
+    fn test_parse_start() {
+        assert_eq!(parse_start("1970-01-01"), Some(0));
+        assert_eq!(parse_start("2021-01-01 00:00"), Some(1609459200));
+        assert_eq!(parse_start("2024-02-29 09:30"), Some(1709199000));
+        assert_eq!(parse_start("2024-13-01"), None);
+        assert_eq!(parse_start("2024-01-01 9am"), None);
+    }
+
+    #[test]
+    fn test_weekly_rotation() {
+        // Shifts start Mondays 09:00 UTC-3 (12:00 UTC), beginning 2021-01-04
+        let oncall = rotation(&format!(
+            "start = \"2021-01-04 09:00\"\nutc_offset = \"-03:00\"\n{ADMINS}"
+        ));
+        let first_handover = 1609761600;
+
+        let shift = oncall.shift_at(first_handover);
+        assert_eq!(shift.admin.name, "Alice");
+        assert_eq!(shift.next.name, "Bob");
+        assert_eq!(shift.until, first_handover + 7 * 86400);
+
+        assert_eq!(oncall.shift_at(first_handover - 1).admin.name, "Bob");
+        assert_eq!(
+            oncall.shift_at(first_handover + 7 * 86400).admin.name,
+            "Bob"
+        );
+        assert_eq!(
+            oncall.shift_at(first_handover + 14 * 86400).admin.name,
+            "Alice"
+        );
+    }
+
+    #[test]
+    fn test_daily_rotation() {
+        let oncall = rotation(&format!(
+            "rotation = \"daily\"\nstart = \"2021-01-01\"\n{ADMINS}"
+        ));
+        assert_eq!(oncall.shift_at(1609459200 + 3600).admin.user_id, 1);
+        assert_eq!(oncall.shift_at(1609459200 + 86400).admin.user_id, 2);
+        assert_eq!(oncall.shift_at(1609459200 + 2 * 86400).admin.user_id, 1);
+    }
+
+    #[test]
+    fn test_invalid_rotation() {
+        let config: OnCallConfig = toml::from_str("start = \"2021-01-01\"\nadmins = []").unwrap();
+        assert!(OnCall::from_config(&config).is_err());
+
+        let config: OnCallConfig =
+            toml::from_str(&format!("start = \"01/01/2021\"\n{ADMINS}")).unwrap();
+        assert!(OnCall::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_mention() {
+        let admin = OnCallAdmin {
+            name: "Ana M.".into(),
+            user_id: 42,
+        };
+        assert_eq!(mention(&admin), "[Ana M\\.](tg://user?id=42)");
+    }
+}
@@ -0,0 +1,119 @@
+//! Relay round-trip probing.
+//!
+//! A ping is a REQ for dispute events newer than now, which a healthy relay
+//! answers with an immediate EOSE; the time to that EOSE is the relay's round-trip
+//! time including its query overhead. Unlike propagation latency it doesn't need
+//! any dispute traffic, so a stalled relay shows up even on quiet days.
+
+use nostr_sdk::prelude::*;
+use std::time::{Duration, Instant};
+
+/// How long a ping may take before it counts as timed out
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Round-trip time of one ping to `relay`, or why there was none
+pub async fn ping(client: &Client, relay: &str) -> Result<Duration, String> {
+    let filter = Filter::new()
+        .kind(Kind::Custom(38386))
+        .since(Timestamp::now())
+        .limit(1);
+    let started = Instant::now();
+    // The client's own timeout ends the fetch without an error, so time it out here
+    match tokio::time::timeout(
+        TIMEOUT,
+        client.fetch_events_from(
+            vec![relay],
+            vec![filter],
+            Some(TIMEOUT + Duration::from_secs(1)),
+        ),
+    )
+    .await
+    {
+        Ok(Ok(_)) => Ok(started.elapsed()),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("no reply within {}s", TIMEOUT.as_secs())),
+    }
+}
+
+/// Ping outcomes of one relay, for alerting
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PingState {
+    /// Pings in a row that timed out or failed
+    pub failures: u32,
+    /// Alerted on round-trip time or failures, until it recovers
+    pub alerted: bool,
+}
+
+/// What a ping result means for alerting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Nothing to report
+    Ok,
+    /// The relay just crossed a threshold
+    Alert,
+    /// The relay is back under the thresholds after an alert
+    Recovered,
+}
+
+impl PingState {
+    /// Record a ping result against `max_rtt` (0 disables the RTT threshold) and
+    /// `max_failures` pings in a row
+    pub fn record(
+        &mut self,
+        rtt: Option<Duration>,
+        max_rtt: Duration,
+        max_failures: u32,
+    ) -> Verdict {
+        let bad = match rtt {
+            Some(rtt) => {
+                self.failures = 0;
+                !max_rtt.is_zero() && rtt > max_rtt
+            }
+            None => {
+                self.failures += 1;
+                self.failures >= max_failures
+            }
+        };
+        match (bad, self.alerted) {
+            (true, false) => {
+                self.alerted = true;
+                Verdict::Alert
+            }
+            (false, true) if rtt.is_some() => {
+                self.alerted = false;
+                Verdict::Recovered
+            }
+            _ => Verdict::Ok,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_RTT: Duration = Duration::from_millis(2000);
+
+    #[test]
+    fn test_record() {
+        let mut state = PingState::default();
+        let fast = Some(Duration::from_millis(150));
+        let slow = Some(Duration::from_millis(2500));
+
+        assert_eq!(state.record(fast, MAX_RTT, 3), Verdict::Ok);
+        assert_eq!(state.record(slow, MAX_RTT, 3), Verdict::Alert);
+        assert_eq!(state.record(slow, MAX_RTT, 3), Verdict::Ok);
+        assert_eq!(state.record(fast, MAX_RTT, 3), Verdict::Recovered);
+
+        // Failures alert once they reach the limit, and recover on the next reply
+        assert_eq!(state.record(None, MAX_RTT, 3), Verdict::Ok);
+        assert_eq!(state.record(None, MAX_RTT, 3), Verdict::Ok);
+        assert_eq!(state.record(None, MAX_RTT, 3), Verdict::Alert);
+        assert_eq!(state.failures, 3);
+        assert_eq!(state.record(None, MAX_RTT, 3), Verdict::Ok);
+        assert_eq!(state.record(fast, MAX_RTT, 3), Verdict::Recovered);
+
+        // Without an RTT threshold only failures alert
+        assert_eq!(state.record(slow, Duration::ZERO, 3), Verdict::Ok);
+    }
+}
@@ -0,0 +1,160 @@
+//! Bounded queue between Nostr event intake and alert delivery.
+//!
+//! The notification handler only queues dispute events, so a slow Telegram API
+//! can't stall the relay connections; a pool of workers routes them. Events are
+//! sharded by dispute id, so each dispute's updates are still handled in order.
+//! When the queue is full the event is dropped and counted rather than blocking
+//! intake. Like the send queue, the depth is process-wide for the metrics exporters.
+//! Each queued event holds a shutdown guard until handled, so a graceful shutdown
+//! waits for the queue to empty.
+
+use crate::shutdown;
+use nostr_sdk::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::mpsc;
+
+static DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Dispute events queued for the workers
+pub fn depth() -> usize {
+    DEPTH.load(Ordering::Relaxed)
+}
+
+/// Why an event couldn't be queued
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueError {
+    /// The worker's queue is full
+    Full,
+    /// The worker stopped
+    Closed,
+}
+
+impl std::fmt::Display for QueueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => write!(f, "delivery queue full"),
+            Self::Closed => write!(f, "delivery worker stopped"),
+        }
+    }
+}
+
+/// Intake side of the queue
+#[derive(Clone)]
+pub struct Pipeline {
+    workers: Vec<mpsc::Sender<(Event, shutdown::Guard)>>,
+    in_flight: shutdown::Tracker,
+}
+
+impl Pipeline {
+    /// Start `workers` workers running `handle` for each event, with room for
+    /// `capacity` queued events in total. Queued events are counted by `in_flight`.
+    pub fn start<F, Fut>(
+        workers: usize,
+        capacity: usize,
+        in_flight: shutdown::Tracker,
+        handle: F,
+    ) -> Self
+    where
+        F: Fn(Event) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let workers = workers.max(1);
+        let per_worker = capacity.div_ceil(workers).max(1);
+        let senders = (0..workers)
+            .map(|_| {
+                let (tx, mut rx) = mpsc::channel::<(Event, shutdown::Guard)>(per_worker);
+                let handle = handle.clone();
+                tokio::spawn(async move {
+                    while let Some((event, _guard)) = rx.recv().await {
+                        DEPTH.fetch_sub(1, Ordering::Relaxed);
+                        handle(event).await;
+                    }
+                });
+                tx
+            })
+            .collect();
+        Self {
+            workers: senders,
+            in_flight,
+        }
+    }
+
+    /// Queue `event` for the worker handling its dispute, without waiting
+    pub fn push(&self, event: Event) -> Result<(), QueueError> {
+        let worker = &self.workers[shard(&event, self.workers.len())];
+        DEPTH.fetch_add(1, Ordering::Relaxed);
+        worker
+            .try_send((event, self.in_flight.guard()))
+            .map_err(|e| {
+                DEPTH.fetch_sub(1, Ordering::Relaxed);
+                match e {
+                    mpsc::error::TrySendError::Full(_) => QueueError::Full,
+                    mpsc::error::TrySendError::Closed(_) => QueueError::Closed,
+                }
+            })
+    }
+}
+
+/// Worker for `event`: by its dispute id (`d` tag), else its id
+fn shard(event: &Event, workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    let dispute_id = event.tags.iter().find_map(|tag| match tag.as_slice() {
+        [name, value, ..] if name == "d" => Some(value),
+        _ => None,
+    });
+    match dispute_id {
+        Some(dispute_id) => dispute_id.hash(&mut hasher),
+        None => event.id.hash(&mut hasher),
+    }
+    (hasher.finish() % workers as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(keys: &Keys, dispute_id: &str, status: &str) -> Event {
+        EventBuilder::new(Kind::Custom(38386), "")
+            .tags([
+                Tag::identifier(dispute_id),
+                Tag::custom(TagKind::custom("s"), [status]),
+            ])
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_shard_by_dispute() {
+        let keys = Keys::generate();
+        let first = shard(&event(&keys, "dispute-1", "initiated"), 4);
+        assert_eq!(shard(&event(&keys, "dispute-1", "settled"), 4), first);
+        assert_eq!(shard(&event(&keys, "dispute-1", "initiated"), 1), 0);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops() {
+        let keys = Keys::generate();
+        let (release_tx, release_rx) = tokio::sync::watch::channel(false);
+        let in_flight = shutdown::Tracker::new();
+        let pipeline = Pipeline::start(1, 1, in_flight, move |_event| {
+            let mut release = release_rx.clone();
+            async move {
+                let _ = release.wait_for(|released| *released).await;
+            }
+        });
+        // The worker takes the first event and blocks; the second fills the queue
+        pipeline
+            .push(event(&keys, "dispute-1", "initiated"))
+            .unwrap();
+        tokio::task::yield_now().await;
+        let mut results = Vec::new();
+        for _ in 0..3 {
+            results.push(pipeline.push(event(&keys, "dispute-1", "settled")));
+        }
+        assert!(results.contains(&Err(QueueError::Full)));
+        release_tx.send(true).unwrap();
+    }
+}
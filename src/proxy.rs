@@ -0,0 +1,85 @@
+//! Proxies for relays (SOCKS5) and the Telegram API (SOCKS5 or HTTP(S)), e.g. Tor
+//! for `.onion` relays or a corporate egress proxy.
+//!
+//! Proxy URLs are checked by `Config::validate`; the host is resolved when a
+//! connection is set up, so a proxy addressed by a Docker service name works.
+
+use crate::config::{NostrConfig, TelegramConfig};
+use nostr_sdk::prelude::{ConnectionMode, RelayOptions};
+use std::net::{SocketAddr, ToSocketAddrs};
+use teloxide::Bot;
+
+/// Options for connecting to `relay`, through its proxy if it has one
+pub fn relay_options(nostr: &NostrConfig, relay: &str) -> Result<RelayOptions, String> {
+    let options = RelayOptions::new();
+    match nostr.relay_proxy(relay) {
+        Some(proxy) => Ok(options.connection_mode(ConnectionMode::Proxy(socket_addr(proxy)?))),
+        None => Ok(options),
+    }
+}
+
+/// Telegram bot, sending through `telegram.proxy` to `telegram.api_url` if set
+pub fn bot(telegram: &TelegramConfig) -> Result<Bot, String> {
+    let bot = match &telegram.proxy {
+        Some(proxy) => {
+            // Built with teloxide's own reqwest, which `Bot::with_client` takes
+            let client = teloxide_reqwest::Proxy::all(proxy)
+                .and_then(|proxy| {
+                    teloxide::net::default_reqwest_settings()
+                        .proxy(proxy)
+                        .build()
+                })
+                .map_err(|e| format!("telegram.proxy: {e}"))?;
+            Bot::with_client(&telegram.bot_token, client)
+        }
+        None => Bot::new(&telegram.bot_token),
+    };
+    match &telegram.api_url {
+        Some(api_url) => {
+            let url = reqwest::Url::parse(api_url).map_err(|e| format!("telegram.api_url: {e}"))?;
+            Ok(bot.set_api_url(url))
+        }
+        None => Ok(bot),
+    }
+}
+
+/// HTTP client from `builder`, through `proxy` if given
+pub fn http_client(
+    builder: reqwest::ClientBuilder,
+    proxy: Option<&str>,
+) -> Result<reqwest::Client, String> {
+    let builder = match proxy {
+        Some(proxy) => builder.proxy(reqwest::Proxy::all(proxy).map_err(|e| e.to_string())?),
+        None => builder,
+    };
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Address of the proxy at `url`
+fn socket_addr(url: &str) -> Result<SocketAddr, String> {
+    let parsed = url::Url::parse(url).map_err(|e| format!("proxy {url}: {e}"))?;
+    let (Some(host), Some(port)) = (parsed.host_str(), parsed.port()) else {
+        return Err(format!("proxy {url}: needs a host and port"));
+    };
+    // IPv6 hosts come bracketed
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    (host, port)
+        .to_socket_addrs()
+        .map_err(|e| format!("proxy {url}: {e}"))?
+        .next()
+        .ok_or_else(|| format!("proxy {url}: {host} did not resolve"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_addr() {
+        assert_eq!(
+            socket_addr("socks5h://127.0.0.1:9050").unwrap(),
+            "127.0.0.1:9050".parse().unwrap()
+        );
+        assert!(socket_addr("socks5://127.0.0.1").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+//! Quiet hours schedule: weekly windows during which non-critical alerts are held back.
+//!
+//! Times use a fixed UTC offset (no daylight saving adjustments). A window belongs to
+//! the day it starts on, so `22:00-07:00` on Friday covers Friday night until Saturday 07:00.
+
+use crate::config::QuietHoursConfig;
+use crate::shutdown;
+use nostr_sdk::prelude::Event;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+
+/// Parsed quiet hours schedule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuietHours {
+    /// Offset from UTC in minutes
+    offset_minutes: i64,
+    /// Quiet window per weekday (Monday = 0) as minutes of day `(start, end)`
+    windows: [Option<(u32, u32)>; 7],
+}
+
+impl QuietHours {
+    pub fn from_config(config: &QuietHoursConfig) -> Result<Self, String> {
+        let offset_minutes = parse_offset(&config.utc_offset)?;
+        let default = match config.default {
+            Some(ref window) => parse_window(window)?,
+            None => None,
+        };
+
+        let days = [
+            &config.mon,
+            &config.tue,
+            &config.wed,
+            &config.thu,
+            &config.fri,
+            &config.sat,
+            &config.sun,
+        ];
+        let mut windows = [None; 7];
+        for (window, day) in windows.iter_mut().zip(days) {
+            *window = match day {
+                Some(spec) => parse_window(spec)?,
+                None => default,
+            };
+        }
+
+        Ok(Self {
+            offset_minutes,
+            windows,
+        })
+    }
+
+    /// Whether the given Unix timestamp falls inside a quiet window
+    pub fn is_quiet_at(&self, unix: u64) -> bool {
+        let local = unix as i64 + self.offset_minutes * 60;
+        let minute = (local.rem_euclid(86400) / 60) as u32;
+        // 1970-01-01 was a Thursday
+        let weekday = (local.div_euclid(86400) + 3).rem_euclid(7) as usize;
+
+        if let Some((start, end)) = self.windows[weekday] {
+            let in_window = if start < end {
+                minute >= start && minute < end
+            } else {
+                minute >= start
+            };
+            if in_window {
+                return true;
+            }
+        }
+
+        // A window that started yesterday may run past midnight
+        if let Some((start, end)) = self.windows[(weekday + 6) % 7] {
+            if start > end && minute < end {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn is_quiet_now(&self) -> bool {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.is_quiet_at(now)
+    }
+}
+
+/// Quiet hours schedule together with the alerts held back until it ends
+pub struct QuietHoursQueue {
+    pub schedule: QuietHours,
+    /// Held alerts keep a shutdown guard, so shutdown waits for them to be flushed
+    pub pending: Mutex<Vec<(Event, shutdown::Guard)>>,
+}
+
+impl QuietHoursQueue {
+    pub fn new(schedule: QuietHours) -> Self {
+        Self {
+            schedule,
+            pending: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+/// Parse a UTC offset such as `+02:00` or `-03:30`
+pub fn parse_offset(offset: &str) -> Result<i64, String> {
+    let invalid = || format!("Invalid utc_offset '{offset}' (expected e.g. \"-03:00\")");
+    let (sign, rest) = if let Some(rest) = offset.strip_prefix('+') {
+        (1, rest)
+    } else if let Some(rest) = offset.strip_prefix('-') {
+        (-1, rest)
+    } else {
+        return Err(invalid());
+    };
+    let minutes = parse_time(rest).ok_or_else(invalid)?;
+    if minutes > 14 * 60 {
+        return Err(invalid());
+    }
+    Ok(sign * minutes as i64)
+}
+
+/// Parse a window such as `22:00-07:00`; `off` disables quiet hours for that day
+fn parse_window(window: &str) -> Result<Option<(u32, u32)>, String> {
+    let window = window.trim();
+    if window.eq_ignore_ascii_case("off") {
+        return Ok(None);
+    }
+
+    let invalid = || {
+        format!("Invalid quiet hours window '{window}' (expected e.g. \"22:00-07:00\" or \"off\")")
+    };
+    let (start, end) = window.split_once('-').ok_or_else(invalid)?;
+    let start = parse_time(start.trim()).ok_or_else(invalid)?;
+    let end = parse_time(end.trim()).ok_or_else(invalid)?;
+    if start == end {
+        return Err(invalid());
+    }
+    Ok(Some((start, end)))
+}
+
+/// Parse `HH:MM` into minutes of day
+pub fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Friday 2021-01-01 00:00:00 UTC
+    const FRIDAY: u64 = 1609459200;
+    const HOUR: u64 = 3600;
+
+    fn schedule(toml: &str) -> QuietHours {
+        let config: QuietHoursConfig = toml::from_str(toml).unwrap();
+        QuietHours::from_config(&config).unwrap()
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let quiet = schedule(r#"default = "22:00-07:00""#);
+
+        assert!(quiet.is_quiet_at(FRIDAY + 23 * HOUR)); // Fri 23:00
+        assert!(quiet.is_quiet_at(FRIDAY + 30 * HOUR + 59 * 60)); // Sat 06:59
+        assert!(!quiet.is_quiet_at(FRIDAY + 31 * HOUR)); // Sat 07:00
+        assert!(!quiet.is_quiet_at(FRIDAY + 12 * HOUR)); // Fri 12:00
+    }
+
+    #[test]
+    fn test_weekday_overrides() {
+        let quiet = schedule(
+            r#"
+            default = "22:00-07:00"
+            sat = "off"
+            sun = "01:00-09:00"
+            "#,
+        );
+
+        assert!(quiet.is_quiet_at(FRIDAY + 27 * HOUR)); // Sat 03:00, from Friday's window
+        assert!(!quiet.is_quiet_at(FRIDAY + 47 * HOUR)); // Sat 23:00, Saturday is off
+        assert!(!quiet.is_quiet_at(FRIDAY + 48 * HOUR + 30 * 60)); // Sun 00:30
+        assert!(quiet.is_quiet_at(FRIDAY + 56 * HOUR)); // Sun 08:00
+    }
+
+    #[test]
+    fn test_utc_offset() {
+        let quiet = schedule(
+            r#"
+            utc_offset = "-03:00"
+            default = "22:00-07:00"
+            "#,
+        );
+
+        // 01:00 UTC is 22:00 the previous evening at UTC-3
+        assert!(quiet.is_quiet_at(FRIDAY + HOUR));
+        // 09:00 UTC is 06:00 local
+        assert!(quiet.is_quiet_at(FRIDAY + 9 * HOUR));
+        assert!(!quiet.is_quiet_at(FRIDAY + 10 * HOUR));
+    }
+
+    #[test]
+    fn test_invalid_schedule() {
+        for toml in [
+            r#"default = "22:00""#,
+            r#"default = "25:00-07:00""#,
+            r#"default = "07:00-07:00""#,
+            r#"utc_offset = "03:00""#,
+            r#"utc_offset = "+15:00""#,
+        ] {
+            let config: QuietHoursConfig = toml::from_str(toml).unwrap();
+            assert!(QuietHours::from_config(&config).is_err(), "{toml}");
+        }
+
+        // No windows configured: never quiet
+        let quiet = schedule("");
+        assert!(!quiet.is_quiet_at(FRIDAY + 23 * HOUR));
+    }
+}
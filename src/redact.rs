@@ -0,0 +1,61 @@
+//! Redaction of dispute ids and public keys in outgoing messages.
+//!
+//! With `alerts.redact`, the `dispute_id`, `solver` and `mostro` template values
+//! are shortened or replaced by a hash before rendering, for chats that include
+//! members who aren't admins. Logs, the dispute store and the HTTP API keep the
+//! full values, so admins can still look a dispute up. `alerts.links` would carry
+//! them in their URLs, so config validation rejects links while redaction is on.
+
+use crate::config::RedactMode;
+use nostr_sdk::hashes::{sha256, Hash};
+
+/// Characters kept at each end of a truncated value
+const KEEP: usize = 6;
+
+/// Hex digits kept of a hashed value
+const HASH_LEN: usize = 12;
+
+/// `value` as it may appear in a message
+pub fn redact(value: &str, mode: RedactMode) -> String {
+    match mode {
+        RedactMode::Off => value.to_string(),
+        RedactMode::Truncate => truncate(value),
+        RedactMode::Hash => {
+            let mut hash = sha256::Hash::hash(value.as_bytes()).to_string();
+            hash.truncate(HASH_LEN);
+            hash
+        }
+    }
+}
+
+/// First and last characters of `value`, too few to identify it outright
+fn truncate(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 2 * KEEP {
+        return "…".to_string();
+    }
+    let start: String = chars[..KEEP].iter().collect();
+    let end: String = chars[chars.len() - KEEP..].iter().collect();
+    format!("{start}…{end}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISPUTE_ID: &str = "8c3e5f2a-1b4d-4e6f-9a7b-2c1d3e4f5a6b";
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(redact(DISPUTE_ID, RedactMode::Off), DISPUTE_ID);
+        assert_eq!(redact(DISPUTE_ID, RedactMode::Truncate), "8c3e5f…4f5a6b");
+        assert_eq!(redact("short", RedactMode::Truncate), "…");
+
+        let hashed = redact(DISPUTE_ID, RedactMode::Hash);
+        assert_eq!(hashed.len(), HASH_LEN);
+        assert!(hashed.chars().all(|c| c.is_ascii_hexdigit()));
+        // Stable, so messages about one dispute can be matched up
+        assert_eq!(hashed, redact(DISPUTE_ID, RedactMode::Hash));
+        assert_ne!(hashed, redact("another-dispute", RedactMode::Hash));
+    }
+}
@@ -0,0 +1,199 @@
+//! Relay scoring and temporary blacklisting.
+//!
+//! Each relay gets a score from 0 to 100: the share of recent relay checks that
+//! found it connected, multiplied, once enough dispute events have arrived, by the
+//! share of them it delivered. A relay scoring below the threshold is taken out of
+//! the pool and retried after a long wait with a clean record, instead of being
+//! reconnected with backoff forever.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Relay checks a score covers
+const WINDOW: usize = 360;
+
+/// Relay checks before a relay can be blacklisted
+const MIN_CHECKS: usize = 60;
+
+/// Dispute events seen while a relay was connected before its delivery counts
+const MIN_EXPECTED: u64 = 10;
+
+/// Dispute events a relay delivered, of those seen while it was connected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Deliveries {
+    pub delivered: u64,
+    pub expected: u64,
+}
+
+/// Outcome of one relay check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    /// The relay's current score
+    Scored(u8),
+    /// The relay scored this low and is now blacklisted
+    Blacklist(u8),
+    /// Still blacklisted; leave it alone
+    Blacklisted,
+    /// The blacklist expired: reconnect and score it afresh
+    Retry,
+}
+
+/// Scores of the configured relays
+#[derive(Debug)]
+pub struct RelayScores {
+    /// Scores below this blacklist the relay; 0 disables blacklisting
+    threshold: u8,
+    /// How long a blacklisted relay is left alone
+    retry: Duration,
+    relays: HashMap<String, Record>,
+}
+
+#[derive(Debug, Default)]
+struct Record {
+    /// Whether the relay was connected, most recent check last
+    checks: VecDeque<bool>,
+    /// Deliveries when the record was last reset
+    baseline: Deliveries,
+    blacklisted_until: Option<Instant>,
+}
+
+impl RelayScores {
+    pub fn new(threshold: u8, retry: Duration) -> Self {
+        Self {
+            threshold,
+            retry,
+            relays: HashMap::new(),
+        }
+    }
+
+    /// Record a relay check. `may_blacklist` is false when the relay is the last
+    /// usable one for its role.
+    pub fn check(
+        &mut self,
+        relay: &str,
+        connected: bool,
+        deliveries: Deliveries,
+        may_blacklist: bool,
+        now: Instant,
+    ) -> Check {
+        let record = self
+            .relays
+            .entry(relay.to_string())
+            .or_insert_with(|| Record {
+                baseline: deliveries,
+                ..Default::default()
+            });
+        if let Some(until) = record.blacklisted_until {
+            if now < until {
+                return Check::Blacklisted;
+            }
+            *record = Record {
+                baseline: deliveries,
+                ..Default::default()
+            };
+            return Check::Retry;
+        }
+
+        record.checks.push_back(connected);
+        if record.checks.len() > WINDOW {
+            record.checks.pop_front();
+        }
+        let score = record.score(deliveries);
+        if may_blacklist && record.checks.len() >= MIN_CHECKS && score < self.threshold {
+            record.blacklisted_until = Some(now + self.retry);
+            return Check::Blacklist(score);
+        }
+        Check::Scored(score)
+    }
+
+    /// Whether `relay` is blacklisted
+    pub fn is_blacklisted(&self, relay: &str) -> bool {
+        self.relays
+            .get(relay)
+            .is_some_and(|record| record.blacklisted_until.is_some())
+    }
+}
+
+impl Record {
+    fn score(&self, deliveries: Deliveries) -> u8 {
+        let connected = self.checks.iter().filter(|connected| **connected).count();
+        let mut score = connected as f64 / self.checks.len().max(1) as f64;
+        let expected = deliveries.expected.saturating_sub(self.baseline.expected);
+        if expected >= MIN_EXPECTED {
+            let delivered = deliveries.delivered.saturating_sub(self.baseline.delivered);
+            score *= delivered.min(expected) as f64 / expected as f64;
+        }
+        (score * 100.0).round() as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELAY: &str = "wss://relay.mostro.network";
+    const RETRY: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_score() {
+        let mut scores = RelayScores::new(25, RETRY);
+        let now = Instant::now();
+        let none = Deliveries::default();
+        for i in 0..10 {
+            scores.check(RELAY, i % 2 == 0, none, true, now);
+        }
+        assert_eq!(
+            scores.check(RELAY, true, none, true, now),
+            Check::Scored(55)
+        );
+
+        // Delivering half of the events halves the score
+        let half = Deliveries {
+            delivered: 10,
+            expected: 20,
+        };
+        assert_eq!(
+            scores.check(RELAY, true, half, true, now),
+            Check::Scored(29)
+        );
+    }
+
+    #[test]
+    fn test_blacklist() {
+        let mut scores = RelayScores::new(25, RETRY);
+        let now = Instant::now();
+        let none = Deliveries::default();
+        for _ in 1..MIN_CHECKS {
+            assert_eq!(
+                scores.check(RELAY, false, none, true, now),
+                Check::Scored(0)
+            );
+        }
+        // Never the last usable relay
+        assert_eq!(
+            scores.check(RELAY, false, none, false, now),
+            Check::Scored(0)
+        );
+        assert_eq!(
+            scores.check(RELAY, false, none, true, now),
+            Check::Blacklist(0)
+        );
+        assert!(scores.is_blacklisted(RELAY));
+        assert_eq!(
+            scores.check(RELAY, false, none, true, now + RETRY / 2),
+            Check::Blacklisted
+        );
+
+        // Retried with a clean record
+        assert_eq!(
+            scores.check(RELAY, false, none, true, now + RETRY),
+            Check::Retry
+        );
+        assert!(!scores.is_blacklisted(RELAY));
+        assert_eq!(
+            scores.check(RELAY, true, none, true, now + RETRY),
+            Check::Scored(100)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Relay connection transitions, from the relays' own status notifications.
+//!
+//! The relay check polls every few seconds to reconnect and score relays, but
+//! alerts follow the status changes each relay reports: a drop is alerted the
+//! moment it happens and only once, and the recovery says how long it lasted.
+
+use nostr_sdk::prelude::*;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+
+/// A relay connection change worth reporting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// The relay was connected, or was never connected, and is now down
+    Disconnected,
+    /// The relay is connected again after being down this long
+    Reconnected { down_for: Duration },
+}
+
+/// Which relays are down, and since when
+#[derive(Debug, Default)]
+pub struct Transitions {
+    down_since: HashMap<String, Instant>,
+}
+
+impl Transitions {
+    /// Record a status reported by `relay`. Connecting attempts while it is down
+    /// don't end the outage.
+    pub fn update(&mut self, relay: &str, status: RelayStatus, now: Instant) -> Option<Transition> {
+        match status {
+            RelayStatus::Connected => {
+                self.down_since
+                    .remove(relay)
+                    .map(|since| Transition::Reconnected {
+                        down_for: now.duration_since(since),
+                    })
+            }
+            RelayStatus::Disconnected | RelayStatus::Terminated => {
+                if self.down_since.contains_key(relay) {
+                    return None;
+                }
+                self.down_since.insert(relay.to_string(), now);
+                Some(Transition::Disconnected)
+            }
+            _ => None,
+        }
+    }
+
+    /// Relays currently down
+    pub fn down(&self) -> Vec<String> {
+        let mut relays: Vec<String> = self.down_since.keys().cloned().collect();
+        relays.sort();
+        relays
+    }
+}
+
+/// Forward the status notifications of `relays` to `tx`. The forwarding tasks
+/// stop when the returned set is dropped.
+pub async fn watch(
+    client: &Client,
+    relays: &[String],
+    tx: mpsc::Sender<(String, RelayStatus)>,
+) -> JoinSet<()> {
+    let mut tasks = JoinSet::new();
+    for url in relays {
+        let Ok(relay) = client.pool().relay(url).await else {
+            continue;
+        };
+        let url = url.clone();
+        let tx = tx.clone();
+        let mut notifications = relay.notifications();
+        tasks.spawn(async move {
+            loop {
+                match notifications.recv().await {
+                    Ok(RelayNotification::RelayStatus { status }) => {
+                        if tx.send((url.clone(), status)).await.is_err() {
+                            return;
+                        }
+                    }
+                    Ok(RelayNotification::Shutdown) => return,
+                    Ok(_) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+    tasks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELAY: &str = "wss://relay.mostro.network";
+
+    #[test]
+    fn test_transitions() {
+        let mut transitions = Transitions::default();
+        let now = Instant::now();
+
+        assert_eq!(transitions.update(RELAY, RelayStatus::Connected, now), None);
+        assert_eq!(
+            transitions.update(RELAY, RelayStatus::Disconnected, now),
+            Some(Transition::Disconnected)
+        );
+        assert_eq!(transitions.down(), [RELAY]);
+
+        // Reconnection attempts don't repeat the alert
+        let later = now + Duration::from_secs(720);
+        assert_eq!(
+            transitions.update(RELAY, RelayStatus::Connecting, later),
+            None
+        );
+        assert_eq!(
+            transitions.update(RELAY, RelayStatus::Disconnected, later),
+            None
+        );
+        assert_eq!(
+            transitions.update(RELAY, RelayStatus::Connected, later),
+            Some(Transition::Reconnected {
+                down_for: Duration::from_secs(720)
+            })
+        );
+        assert!(transitions.down().is_empty());
+    }
+}
@@ -0,0 +1,377 @@
+//! Live config reloads, enabled with `reload.watch`.
+//!
+//! The config file is watched for changes and re-read. Alert toggles, health
+//! thresholds, templates and branding take effect immediately; anything else is
+//! logged as needing a restart. Every reload is reported to the health chat.
+
+use crate::branding::Branding;
+use crate::cli::Overrides;
+use crate::config::{AlertsConfig, Config, HealthConfig};
+use crate::templates::Templates;
+use crate::{send_markdown, Destination, HealthMonitor};
+use notify::{RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use teloxide::Bot;
+use tracing::{error, info, warn};
+
+/// Quiet period after a change before reloading, as editors write files in several steps
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// A value that can be swapped while readers hold on to the previous one
+pub struct Live<T>(RwLock<Arc<T>>);
+
+impl<T> Live<T> {
+    pub fn new(value: T) -> Self {
+        Self(RwLock::new(Arc::new(value)))
+    }
+
+    pub fn get(&self) -> Arc<T> {
+        self.0.read().expect("live value lock").clone()
+    }
+
+    pub fn set(&self, value: T) {
+        *self.0.write().expect("live value lock") = Arc::new(value);
+    }
+}
+
+/// Everything a reload can update in place
+pub struct Targets {
+    pub alerts: Arc<Live<AlertsConfig>>,
+    pub health: Arc<Live<HealthConfig>>,
+    pub templates: Arc<Templates>,
+    pub health_monitor: Arc<HealthMonitor>,
+    pub bot: Bot,
+    /// Where reloads are reported
+    pub destination: Destination,
+}
+
+/// Sections changed between two configs
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Applied without a restart
+    pub applied: Vec<&'static str>,
+    /// Only take effect after a restart
+    pub restart_required: Vec<&'static str>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.restart_required.is_empty()
+    }
+}
+
+/// Compare the running config with a reloaded one
+pub fn diff(old: &Config, new: &Config) -> Changes {
+    let mut changes = Changes::default();
+
+    let old_alerts = old.alerts.clone().unwrap_or_default();
+    let new_alerts = new.alerts.clone().unwrap_or_default();
+    // Read by tasks started at launch
+    if old_alerts.digest_window != new_alerts.digest_window {
+        changes.restart_required.push("alerts.digest_window");
+    }
+    if old_alerts.debounce_window != new_alerts.debounce_window {
+        changes.restart_required.push("alerts.debounce_window");
+    }
+    if old_alerts.workers != new_alerts.workers {
+        changes.restart_required.push("alerts.workers");
+    }
+    if old_alerts.queue_capacity != new_alerts.queue_capacity {
+        changes.restart_required.push("alerts.queue_capacity");
+    }
+    if old_alerts.cache_capacity != new_alerts.cache_capacity {
+        changes.restart_required.push("alerts.cache_capacity");
+    }
+    if !same(&old_alerts.severity, &new_alerts.severity) {
+        changes.restart_required.push("alerts.severity");
+    }
+    let live_alerts = AlertsConfig {
+        digest_window: new_alerts.digest_window,
+        debounce_window: new_alerts.debounce_window,
+        workers: new_alerts.workers,
+        queue_capacity: new_alerts.queue_capacity,
+        cache_capacity: new_alerts.cache_capacity,
+        severity: new_alerts.severity.clone(),
+        ..old_alerts
+    };
+    if !same(&live_alerts, &new_alerts) {
+        changes.applied.push("alerts");
+    }
+
+    let old_health = old.health.clone().unwrap_or_default();
+    let new_health = new.health.clone().unwrap_or_default();
+    let live_health = live_thresholds(&old_health, &new_health);
+    if !same(&live_health, &old_health) {
+        changes.applied.push("health");
+    }
+    // Setting a threshold to 0 pauses its check, but checks disabled at launch were never started
+    let enabled = |health: &HealthConfig| {
+        [
+            health.event_alert_threshold > 0,
+            health.max_clock_drift > 0,
+            health.max_relay_latency > 0,
+            health.relay_ping_interval > 0,
+            health.max_memory_mb > 0,
+        ]
+    };
+    let newly_enabled = enabled(&new_health)
+        .into_iter()
+        .zip(enabled(&old_health))
+        .any(|(new, old)| new && !old);
+    if !same(&live_health, &new_health) || newly_enabled {
+        changes.restart_required.push("health");
+    }
+
+    if !same(&old.templates, &new.templates) {
+        changes.applied.push("templates");
+    }
+    if !same(&old.branding, &new.branding) {
+        changes.applied.push("branding");
+    }
+
+    let restart_only = [
+        ("mostro", same(&old.mostro, &new.mostro)),
+        ("nostr", same(&old.nostr, &new.nostr)),
+        ("telegram", same(&old.telegram, &new.telegram)),
+        ("quiet_hours", same(&old.quiet_hours, &new.quiet_hours)),
+        ("escalation", same(&old.escalation, &new.escalation)),
+        ("oncall", same(&old.oncall, &new.oncall)),
+        ("commands", same(&old.commands, &new.commands)),
+        ("updates", same(&old.updates, &new.updates)),
+        ("telemetry", same(&old.telemetry, &new.telemetry)),
+        ("sentry", same(&old.sentry, &new.sentry)),
+        ("statsd", same(&old.statsd, &new.statsd)),
+        ("influxdb", same(&old.influxdb, &new.influxdb)),
+        ("alertmanager", same(&old.alertmanager, &new.alertmanager)),
+        ("reload", same(&old.reload, &new.reload)),
+        ("secrets", same(&old.secrets, &new.secrets)),
+    ];
+    for (section, unchanged) in restart_only {
+        if !unchanged {
+            changes.restart_required.push(section);
+        }
+    }
+    changes
+}
+
+/// `running` with the thresholds that health checks read on every tick taken from `new`
+fn live_thresholds(running: &HealthConfig, new: &HealthConfig) -> HealthConfig {
+    HealthConfig {
+        event_alert_threshold: new.event_alert_threshold,
+        max_clock_drift: new.max_clock_drift,
+        max_relay_latency: new.max_relay_latency,
+        max_relay_rtt: new.max_relay_rtt,
+        relay_ping_failures: new.relay_ping_failures,
+        min_connected_relays: new.min_connected_relays,
+        max_memory_mb: new.max_memory_mb,
+        ..running.clone()
+    }
+}
+
+/// Config types have no `PartialEq`; their debug output covers every field
+fn same<T: std::fmt::Debug>(a: &T, b: &T) -> bool {
+    format!("{a:?}") == format!("{b:?}")
+}
+
+/// Watch `path`, and any files it includes, and apply changes until the process exits
+pub async fn run(path: PathBuf, overrides: Overrides, mut running: Config, targets: Targets) {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher =
+        match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if let Ok(event) = res {
+                if !event.kind.is_access() {
+                    let _ = tx.send(event.paths);
+                }
+            }
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                error!("Failed to watch config file: {}", e);
+                return;
+            }
+        };
+
+    // Watch the directories, as editors often replace a file rather than write to it
+    let mut file_names = Vec::new();
+    let mut dirs = Vec::new();
+    for source in &running.sources {
+        let Some(file_name) = source.file_name() else {
+            error!("Cannot watch config path {}", source.display());
+            return;
+        };
+        file_names.push(file_name.to_os_string());
+        let dir = match source.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if !dirs.contains(&dir) {
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                error!("Failed to watch {}: {}", dir.display(), e);
+                return;
+            }
+            dirs.push(dir);
+        }
+    }
+    info!("👀 Watching {} for changes", path.display());
+
+    while let Some(paths) = rx.recv().await {
+        if !paths.iter().any(|changed| {
+            changed
+                .file_name()
+                .is_some_and(|name| file_names.iter().any(|watched| watched == name))
+        }) {
+            continue;
+        }
+        tokio::time::sleep(DEBOUNCE).await;
+        while rx.try_recv().is_ok() {}
+
+        if let Some(config) = reload(&path, &overrides, &running, &targets).await {
+            running = config;
+        }
+    }
+}
+
+/// Re-read the config, apply what can be applied and report it.
+/// Returns the new config unless it was invalid or unchanged.
+async fn reload(
+    path: &Path,
+    overrides: &Overrides,
+    running: &Config,
+    targets: &Targets,
+) -> Option<Config> {
+    let mut context = tera::Context::new();
+    // Command-line overrides still win over the file
+    let loaded = overrides.load(path).map_err(|e| e.to_string());
+    let result = match loaded {
+        Ok(config) => {
+            let changes = diff(running, &config);
+            if changes.is_empty() {
+                return None;
+            }
+            match apply(&config, &changes, targets).await {
+                Ok(()) => {
+                    info!(
+                        "🔄 Config reloaded, applied {:?}, restart required for {:?}",
+                        changes.applied, changes.restart_required
+                    );
+                    context.insert("applied", &changes.applied);
+                    context.insert("restart_required", &changes.restart_required);
+                    context.insert("error", &None::<String>);
+                    Some(config)
+                }
+                Err(e) => {
+                    error!("Failed to apply reloaded config: {}", e);
+                    context.insert("error", &e);
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            error!("Reloaded config is invalid, keeping the current one: {}", e);
+            context.insert("error", &e);
+            None
+        }
+    };
+
+    let message = targets.templates.render("config_reloaded", &context);
+    if let Err(e) = send_markdown(&targets.bot, targets.destination, &message).await {
+        warn!("Failed to send config reload notice: {}", e);
+    }
+    result
+}
+
+async fn apply(config: &Config, changes: &Changes, targets: &Targets) -> Result<(), String> {
+    let applied = |section| changes.applied.contains(&section);
+    if applied("templates") || applied("branding") {
+        let templates = Templates::load(config.templates.as_ref().unwrap_or(&Default::default()))
+            .map_err(|e| e.to_string())?
+            .with_branding(Branding::from_config(
+                config.branding.as_ref().unwrap_or(&Default::default()),
+            ));
+        targets.templates.replace(templates);
+    }
+    if applied("alerts") {
+        targets
+            .alerts
+            .set(config.alerts.clone().unwrap_or_default());
+    }
+    if applied("health") {
+        let running = targets.health.get();
+        let new = config.health.clone().unwrap_or_default();
+        targets.health.set(live_thresholds(&running, &new));
+    }
+    targets.health_monitor.set_config(config);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+[mostro]
+pubkey = "npub1test"
+
+[nostr]
+relays = ["wss://relay.mostro.network"]
+
+[telegram]
+bot_token = "123:ABC"
+chat_id = -100
+"#;
+
+    fn config(extra: &str) -> Config {
+        toml::from_str(&format!("{BASE}\n{extra}")).unwrap()
+    }
+
+    #[test]
+    fn test_diff() {
+        let running = config("[alerts]\nsettled = true\n[health]\nmax_clock_drift = 60");
+        assert!(diff(&running, &running).is_empty());
+
+        let changed = config("[alerts]\nsettled = false\n[health]\nmax_clock_drift = 120");
+        assert_eq!(
+            diff(&running, &changed),
+            Changes {
+                applied: vec!["alerts", "health"],
+                restart_required: vec![],
+            }
+        );
+
+        let changed = config(
+            "[alerts]\nsettled = true\ndigest_window = 60\n[health]\nmax_clock_drift = 0\n[templates]\nlanguage = \"de\"",
+        );
+        assert_eq!(
+            diff(&running, &changed),
+            Changes {
+                applied: vec!["health", "templates"],
+                restart_required: vec!["alerts.digest_window"],
+            }
+        );
+
+        // Re-enabling a check needs it to be started
+        let disabled = config("[alerts]\nsettled = true\n[health]\nmax_clock_drift = 0");
+        assert_eq!(
+            diff(&disabled, &running),
+            Changes {
+                applied: vec!["health"],
+                restart_required: vec!["health"],
+            }
+        );
+
+        let mut changed = config("[alerts]\nsettled = true\n[health]\nmax_clock_drift = 60");
+        changed.nostr.relays.push("wss://nos.lol".into());
+        assert_eq!(diff(&running, &changed).restart_required, vec!["nostr"]);
+    }
+
+    #[test]
+    fn test_live() {
+        let live = Live::new(1);
+        let before = live.get();
+        live.set(2);
+        assert_eq!(*before, 1);
+        assert_eq!(*live.get(), 2);
+    }
+}
@@ -0,0 +1,37 @@
+//! Optional Sentry error reporting.
+//!
+//! Panics are captured, `error!` logs become Sentry events and warnings are kept as
+//! breadcrumbs for context. Requires the `sentry` cargo feature.
+
+use crate::config::SentryConfig;
+use tracing::Subscriber;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Start the Sentry client; reporting stops when the guard is dropped
+pub fn init(config: &SentryConfig, mostro_pubkey: &str) -> sentry::ClientInitGuard {
+    let guard = sentry::init((
+        config.dsn.as_str(),
+        sentry::ClientOptions {
+            release: Some(concat!("mostro-watchdog@", env!("CARGO_PKG_VERSION")).into()),
+            environment: config.environment.clone().map(Into::into),
+            sample_rate: config.sample_rate,
+            attach_stacktrace: true,
+            ..Default::default()
+        },
+    ));
+
+    sentry::configure_scope(|scope| {
+        scope.set_tag("mostro_pubkey", mostro_pubkey);
+        scope.set_tag("build_commit", crate::BUILD_COMMIT);
+    });
+
+    guard
+}
+
+/// Layer forwarding errors as events and warnings as breadcrumbs
+pub fn layer<S>() -> sentry::integrations::tracing::SentryLayer<S>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    sentry::integrations::tracing::layer()
+}
@@ -0,0 +1,68 @@
+//! The watchdog's own resource usage: memory, file descriptors and tokio tasks.
+//!
+//! Memory and descriptors are read from `/proc/self`, so they are only available on
+//! Linux; elsewhere they are reported as unknown.
+
+/// Point-in-time resource usage of this process
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ResourceUsage {
+    /// Resident set size in bytes
+    pub rss_bytes: Option<u64>,
+    pub open_fds: Option<u64>,
+    /// Tasks alive in the tokio runtime
+    pub tasks: usize,
+}
+
+impl ResourceUsage {
+    pub fn rss_mb(&self) -> Option<u64> {
+        self.rss_bytes.map(|bytes| bytes / (1024 * 1024))
+    }
+}
+
+/// Sample current usage; tasks are counted as zero outside the tokio runtime
+pub fn sample() -> ResourceUsage {
+    ResourceUsage {
+        rss_bytes: std::fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|status| parse_vm_rss(&status)),
+        open_fds: std::fs::read_dir("/proc/self/fd")
+            .ok()
+            .map(|entries| entries.count() as u64),
+        tasks: tokio::runtime::Handle::try_current()
+            .map(|runtime| runtime.metrics().num_alive_tasks())
+            .unwrap_or_default(),
+    }
+}
+
+/// Resident set size in bytes from the `VmRSS:` line of `/proc/self/status`
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let mut fields = line["VmRSS:".len()..].split_whitespace();
+    let value: u64 = fields.next()?.parse().ok()?;
+    match fields.next() {
+        Some("kB") => Some(value * 1024),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vm_rss() {
+        let status =
+            "Name:\tmostro-watchdog\nVmPeak:\t  123456 kB\nVmRSS:\t   20480 kB\nThreads:\t8\n";
+        assert_eq!(parse_vm_rss(status), Some(20480 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tx\n"), None);
+    }
+
+    #[tokio::test]
+    async fn test_sample() {
+        let usage = sample();
+        if cfg!(target_os = "linux") {
+            assert!(usage.rss_bytes.is_some_and(|rss| rss > 0));
+            assert!(usage.open_fds.is_some_and(|fds| fds > 0));
+        }
+    }
+}
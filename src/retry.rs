@@ -0,0 +1,166 @@
+//! Retries for outbound calls.
+//!
+//! Telegram sends, webhooks, Pushover, Alertmanager and dead man's switch pings are
+//! attempted up to `POLICY.attempts` times when they fail in a way that may pass on
+//! its own (a failed connection, a 5xx or 429 answer), backing off exponentially
+//! with jitter between attempts. Errors that would only fail again, such as a
+//! rejected token or an unknown chat, are returned at once, as are timeouts and other
+//! transport errors: the request may already have been received, and sending it
+//! again would duplicate the alert.
+
+use crate::backoff::Backoff;
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use teloxide::RequestError;
+use tracing::warn;
+
+/// How outbound calls are retried
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    /// Attempts in total, the first included
+    pub attempts: u32,
+    /// Wait between attempts
+    pub backoff: Backoff,
+}
+
+/// Shared by every outbound call
+pub const POLICY: Policy = Policy {
+    attempts: 3,
+    backoff: Backoff {
+        base: Duration::from_secs(1),
+        max: Duration::from_secs(8),
+    },
+};
+
+/// Errors that may succeed when the call is made again
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+impl Retryable for reqwest::Error {
+    fn is_retryable(&self) -> bool {
+        match self.status() {
+            Some(status) => {
+                status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            }
+            None => self.is_connect(),
+        }
+    }
+}
+
+impl Retryable for RequestError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            // teloxide's reqwest, another version than the one `Retryable` is implemented for
+            RequestError::Network(e) => e.is_connect(),
+            // Rate limits are waited out by the send queue, at the delay Telegram asks for,
+            // and API errors would only fail again
+            _ => false,
+        }
+    }
+}
+
+impl Policy {
+    /// Run `call` until it succeeds, fails for good or runs out of attempts.
+    /// `what` names the call in the logs.
+    pub async fn run<T, E, F, Fut>(&self, what: &str, mut call: F) -> Result<T, E>
+    where
+        E: Retryable + Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut failures = 0;
+        loop {
+            match call().await {
+                Err(e) if e.is_retryable() && failures + 1 < self.attempts => {
+                    failures += 1;
+                    let delay = self.backoff.delay(failures);
+                    warn!(
+                        "{} failed ({}), retrying in {:?} ({}/{})",
+                        what,
+                        e,
+                        delay,
+                        failures,
+                        self.attempts - 1
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Debug)]
+    struct Failure {
+        retryable: bool,
+    }
+
+    impl Display for Failure {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "failure")
+        }
+    }
+
+    impl Retryable for Failure {
+        fn is_retryable(&self) -> bool {
+            self.retryable
+        }
+    }
+
+    const FAST: Policy = Policy {
+        attempts: 3,
+        backoff: Backoff {
+            base: Duration::from_millis(1),
+            max: Duration::from_millis(2),
+        },
+    };
+
+    #[tokio::test]
+    async fn test_run() {
+        // Retryable failures until the attempts run out
+        let calls = Cell::new(0);
+        let result: Result<(), Failure> = FAST
+            .run("test", || {
+                calls.set(calls.get() + 1);
+                async { Err(Failure { retryable: true }) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 3);
+
+        // A permanent failure isn't retried
+        calls.set(0);
+        let result: Result<(), Failure> = FAST
+            .run("test", || {
+                calls.set(calls.get() + 1);
+                async { Err(Failure { retryable: false }) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+
+        // Succeeds on the second attempt
+        calls.set(0);
+        let result = FAST
+            .run("test", || {
+                calls.set(calls.get() + 1);
+                let attempt = calls.get();
+                async move {
+                    if attempt < 2 {
+                        Err(Failure { retryable: true })
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 2);
+    }
+}
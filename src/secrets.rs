@@ -0,0 +1,117 @@
+//! age-encrypted secrets in the config.
+//!
+//! Any secret value (`telegram.bot_token`, `health.http_auth_token`,
+//! `influxdb.token`, `influxdb.password`, `sentry.dsn`) or the file its `*_file`
+//! key points to may be age ciphertext, armored (`age -a`) or binary. It is
+//! decrypted when the config is loaded, with the identities in
+//! `secrets.age_identity_file` or the file named by `AGE_IDENTITY_FILE`, so the
+//! config can be committed without plaintext credentials. Requires the `age`
+//! feature.
+
+use crate::config::SecretsConfig;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the identity file when the config doesn't
+pub const IDENTITY_ENV: &str = "AGE_IDENTITY_FILE";
+
+const ARMOR_BEGIN: &[u8] = b"-----BEGIN AGE ENCRYPTED FILE-----";
+const BINARY_MAGIC: &[u8] = b"age-encryption.org/";
+
+/// Identity file to decrypt secrets with, if one is configured
+pub fn identity_file(config: Option<&SecretsConfig>) -> Option<PathBuf> {
+    config
+        .and_then(|secrets| secrets.age_identity_file.clone())
+        .or_else(|| std::env::var_os(IDENTITY_ENV).map(PathBuf::from))
+}
+
+/// Whether `data` is age ciphertext
+pub fn is_encrypted(data: &[u8]) -> bool {
+    let data = data.trim_ascii_start();
+    data.starts_with(ARMOR_BEGIN) || data.starts_with(BINARY_MAGIC)
+}
+
+/// The secret `key` decrypted from `data`, without the trailing newline
+pub fn decrypt(key: &str, data: &[u8], identity: Option<&Path>) -> Result<String, String> {
+    let Some(identity) = identity else {
+        return Err(format!(
+            "{key} is age-encrypted; set secrets.age_identity_file or {IDENTITY_ENV}"
+        ));
+    };
+    let plaintext =
+        decrypt_with(data, identity).map_err(|e| format!("Failed to decrypt {key}: {e}"))?;
+    let plaintext =
+        String::from_utf8(plaintext).map_err(|_| format!("{key} does not decrypt to text"))?;
+    Ok(plaintext.trim_end_matches(['\n', '\r']).to_string())
+}
+
+#[cfg(feature = "age")]
+fn decrypt_with(data: &[u8], identity: &Path) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let identities = age::IdentityFile::from_file(identity.display().to_string())
+        .map_err(|e| format!("reading {}: {e}", identity.display()))?
+        .into_identities()
+        .map_err(|e| format!("{}: {e}", identity.display()))?;
+    // Armored ciphertext pasted into a TOML string may be surrounded by blank lines
+    let decryptor = age::Decryptor::new(age::armor::ArmoredReader::new(data.trim_ascii()))
+        .map_err(|e| e.to_string())?;
+    let mut reader = decryptor
+        .decrypt(
+            identities
+                .iter()
+                .map(|identity| identity.as_ref() as &dyn age::Identity),
+        )
+        .map_err(|e| e.to_string())?;
+    let mut plaintext = Vec::new();
+    reader
+        .read_to_end(&mut plaintext)
+        .map_err(|e| e.to_string())?;
+    Ok(plaintext)
+}
+
+#[cfg(not(feature = "age"))]
+fn decrypt_with(_: &[u8], _: &Path) -> Result<Vec<u8>, String> {
+    Err("this build lacks the `age` feature".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_encrypted() {
+        assert!(is_encrypted(
+            b"\n-----BEGIN AGE ENCRYPTED FILE-----\nYWdl\n-----END AGE ENCRYPTED FILE-----\n"
+        ));
+        assert!(is_encrypted(b"age-encryption.org/v1\n-> X25519 abc\n"));
+        assert!(!is_encrypted(b"123456:ABC-DEF"));
+        assert!(!is_encrypted(b""));
+    }
+
+    #[test]
+    fn test_missing_identity() {
+        let err = decrypt("telegram.bot_token", ARMOR_BEGIN, None).unwrap_err();
+        assert!(err.contains("telegram.bot_token"), "{err}");
+        assert!(err.contains(IDENTITY_ENV), "{err}");
+    }
+
+    #[cfg(feature = "age")]
+    #[test]
+    fn test_decrypt() {
+        use age::secrecy::ExposeSecret;
+
+        let key = age::x25519::Identity::generate();
+        let dir = tempfile::tempdir().unwrap();
+        let identity = dir.path().join("key.txt");
+        std::fs::write(&identity, key.to_string().expose_secret()).unwrap();
+
+        let armored = age::encrypt_and_armor(&key.to_public(), b"123:secret\n").unwrap();
+        let token = decrypt("telegram.bot_token", armored.as_bytes(), Some(&identity));
+        assert_eq!(token.unwrap(), "123:secret");
+
+        let other = dir.path().join("other.txt");
+        let other_key = age::x25519::Identity::generate();
+        std::fs::write(&other, other_key.to_string().expose_secret()).unwrap();
+        assert!(decrypt("telegram.bot_token", armored.as_bytes(), Some(&other)).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Opt-in startup self-test (`health.self_test`).
+//!
+//! A made-up dispute goes through the steps a real one does: its event is parsed,
+//! the alert is rendered with the configured template (without the built-in
+//! fallback, so a broken template fails the test), routed to the alert chat and
+//! topic, and a "typing" chat action is sent there to confirm the bot may post.
+//! Nothing is stored or posted; the outcome is reported in the startup message.
+
+use crate::templates::status_template;
+use crate::{
+    channels, dispute_context, dry_run, send_queue, test_dispute_event, AlertContext, Destination,
+    DisputeInfo,
+};
+use std::fmt;
+use teloxide::prelude::*;
+use teloxide::types::{ChatAction, MessageId, ThreadId};
+use tracing::{info, warn};
+
+/// Status of the made-up dispute; new disputes are the alerts that must not be missed
+const STATUS: &str = "initiated";
+
+/// The step that failed and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub step: &'static str,
+    pub error: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.step, self.error)
+    }
+}
+
+fn fail(step: &'static str, error: impl ToString) -> Failure {
+    Failure {
+        step,
+        error: error.to_string(),
+    }
+}
+
+/// Run the self-test, logging the outcome
+pub async fn run(ctx: &AlertContext) -> Result<(), Failure> {
+    let result = check(ctx).await;
+    match result {
+        Ok(()) => info!("🧪 Self-test passed"),
+        Err(ref failure) => warn!("🧪 Self-test failed at {}", failure),
+    }
+    result
+}
+
+async fn check(ctx: &AlertContext) -> Result<(), Failure> {
+    let (dispute_id, event) = test_dispute_event(STATUS).map_err(|e| fail("parsing", e))?;
+    let dispute = DisputeInfo::from_event(&event);
+    if dispute.dispute_id != dispute_id || dispute.status != STATUS {
+        return Err(fail(
+            "parsing",
+            format!(
+                "read dispute {} with status {}",
+                dispute.dispute_id, dispute.status
+            ),
+        ));
+    }
+
+    let context = dispute_context(ctx, &dispute, &event);
+    ctx.templates
+        .try_render(status_template(STATUS), &context)
+        .map_err(|e| fail("templating", e))?;
+
+    if !ctx.alerts().is_enabled(STATUS) {
+        return Err(fail(
+            "routing",
+            format!("alerts for '{STATUS}' disputes are disabled"),
+        ));
+    }
+    let destination = Destination {
+        chat_id: ctx.chat_id,
+        topic_id: ctx.topic_id,
+        silent: true,
+    };
+
+    deliver(&ctx.bot, destination)
+        .await
+        .map_err(|e| fail("delivery", e))
+}
+
+/// Show "typing" in the destination chat, which fails like a send would if the
+/// bot can't post there
+async fn deliver(bot: &Bot, destination: Destination) -> Result<(), String> {
+    let chat = format!("chat {}", destination.chat_id);
+    if dry_run::intercepted(&chat, "send a chat action", "typing") {
+        return Ok(());
+    }
+    let mut request = bot.send_chat_action(ChatId(destination.chat_id), ChatAction::Typing);
+    if let Some(topic) = destination.topic_id {
+        request = request.message_thread_id(ThreadId(MessageId(topic)));
+    }
+    channels::timed(send_queue::queue().send(destination.chat_id, || request.clone().send()))
+        .await
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_display() {
+        let failure = fail("templating", "Variable `dispute_id` not found");
+        assert_eq!(
+            failure.to_string(),
+            "templating: Variable `dispute_id` not found"
+        );
+    }
+}
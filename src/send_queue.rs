@@ -0,0 +1,223 @@
+//! Rate limiting for outgoing Telegram messages.
+//!
+//! Telegram allows about one message per second in a chat, 20 per minute in a group
+//! and 30 per second overall for a bot. Sends wait their turn (FIFO per chat) instead
+//! of tripping these limits, and `429 Too Many Requests` answers are retried after the
+//! `retry_after` delay Telegram asks for instead of dropping the message. Network
+//! failures are retried under the shared [`retry::POLICY`](crate::retry::POLICY).
+//!
+//! The limits apply per bot token, so one queue is shared by the whole process.
+
+use crate::retry::{Retryable, POLICY};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use teloxide::RequestError;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use tracing::warn;
+
+const PER_CHAT_INTERVAL: Duration = Duration::from_secs(1);
+const GROUP_PER_MINUTE: usize = 20;
+const GLOBAL_INTERVAL: Duration = Duration::from_millis(1000 / 30);
+/// Give up after this many `RetryAfter` answers for the same message
+const MAX_RETRIES: u32 = 5;
+
+static QUEUE: LazyLock<SendQueue> = LazyLock::new(SendQueue::default);
+
+/// Process-wide Telegram send queue
+pub fn queue() -> &'static SendQueue {
+    &QUEUE
+}
+
+#[derive(Default)]
+pub struct SendQueue {
+    /// Send times within the last minute, per chat
+    chats: std::sync::Mutex<HashMap<i64, Arc<Mutex<VecDeque<Instant>>>>>,
+    /// Earliest time the next message may go out to any chat
+    next_global: Mutex<Option<Instant>>,
+    /// Messages waiting for their turn or in flight
+    backlog: AtomicUsize,
+}
+
+/// Counts a message in the backlog until dropped, even if the send is cancelled
+struct Queued<'a>(&'a AtomicUsize);
+
+impl<'a> Queued<'a> {
+    fn new(backlog: &'a AtomicUsize) -> Self {
+        backlog.fetch_add(1, Ordering::Relaxed);
+        Self(backlog)
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl SendQueue {
+    /// Run `request` once the chat's turn comes, retrying on `RetryAfter` and
+    /// retryable network errors
+    pub async fn send<T, F, Fut>(&self, chat_id: i64, mut request: F) -> Result<T, RequestError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, RequestError>>,
+    {
+        let _queued = Queued::new(&self.backlog);
+        let mut retries = 0;
+        let mut failures = 0;
+        loop {
+            self.acquire(chat_id).await;
+            match request().await {
+                Err(RequestError::RetryAfter(delay)) if retries < MAX_RETRIES => {
+                    retries += 1;
+                    warn!(
+                        "Telegram rate limit hit for chat {}, retrying in {:?} ({}/{})",
+                        chat_id,
+                        delay.duration(),
+                        retries,
+                        MAX_RETRIES
+                    );
+                    tokio::time::sleep(delay.duration()).await;
+                }
+                Err(e) if e.is_retryable() && failures + 1 < POLICY.attempts => {
+                    failures += 1;
+                    let delay = POLICY.backoff.delay(failures);
+                    warn!(
+                        "Telegram send to chat {} failed ({}), retrying in {:?} ({}/{})",
+                        chat_id,
+                        e,
+                        delay,
+                        failures,
+                        POLICY.attempts - 1
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Messages waiting for their turn or in flight
+    pub fn backlog(&self) -> usize {
+        self.backlog.load(Ordering::Relaxed)
+    }
+
+    /// Wait until a message may be sent to `chat_id` and record it
+    async fn acquire(&self, chat_id: i64) {
+        let chat = self
+            .chats
+            .lock()
+            .expect("send queue lock poisoned")
+            .entry(chat_id)
+            .or_default()
+            .clone();
+        // tokio's Mutex is fair, so senders to the same chat are served in order
+        let mut sent = chat.lock().await;
+
+        while let Some(wait) = chat_wait(&mut sent, Instant::now(), is_group(chat_id)) {
+            tokio::time::sleep(wait).await;
+        }
+
+        {
+            let mut next_global = self.next_global.lock().await;
+            let now = Instant::now();
+            if let Some(next) = *next_global {
+                if next > now {
+                    tokio::time::sleep(next - now).await;
+                }
+            }
+            *next_global = Some(Instant::now() + GLOBAL_INTERVAL);
+        }
+
+        sent.push_back(Instant::now());
+    }
+}
+
+/// Groups and channels have negative chat IDs
+fn is_group(chat_id: i64) -> bool {
+    chat_id < 0
+}
+
+/// How long to wait before the next message to a chat, given its recent send times.
+/// Entries older than a minute are dropped.
+fn chat_wait(sent: &mut VecDeque<Instant>, now: Instant, group: bool) -> Option<Duration> {
+    let minute = Duration::from_secs(60);
+    while sent
+        .front()
+        .is_some_and(|t| now.saturating_duration_since(*t) >= minute)
+    {
+        sent.pop_front();
+    }
+
+    let mut ready = now;
+    if let Some(last) = sent.back() {
+        ready = ready.max(*last + PER_CHAT_INTERVAL);
+    }
+    if group && sent.len() >= GROUP_PER_MINUTE {
+        ready = ready.max(sent[sent.len() - GROUP_PER_MINUTE] + minute);
+    }
+
+    let wait = ready.saturating_duration_since(now);
+    (!wait.is_zero()).then_some(wait)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_backlog() {
+        let queue = SendQueue::default();
+        let result = queue
+            .send(1, || async {
+                assert_eq!(queue.backlog(), 1);
+                Ok::<_, RequestError>(())
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(queue.backlog(), 0);
+    }
+
+    #[test]
+    fn test_per_chat_interval() {
+        let now = Instant::now();
+        let mut sent = VecDeque::from([now - Duration::from_millis(400)]);
+        assert_eq!(
+            chat_wait(&mut sent, now, false),
+            Some(Duration::from_millis(600))
+        );
+
+        let mut sent = VecDeque::from([now - Duration::from_secs(2)]);
+        assert_eq!(chat_wait(&mut sent, now, false), None);
+    }
+
+    #[test]
+    fn test_group_per_minute_limit() {
+        let now = Instant::now();
+        // 20 messages spread over the last 40 seconds
+        let mut sent: VecDeque<Instant> = (0..20)
+            .map(|i| now - Duration::from_secs(40) + Duration::from_secs(2 * i))
+            .collect();
+
+        // Private chats only wait for the per-chat interval
+        assert_eq!(chat_wait(&mut sent.clone(), now, false), None);
+        // Groups wait until the oldest of the last 20 is a minute old
+        assert_eq!(
+            chat_wait(&mut sent, now, true),
+            Some(Duration::from_secs(20))
+        );
+    }
+
+    #[test]
+    fn test_old_entries_dropped() {
+        let now = Instant::now();
+        let mut sent =
+            VecDeque::from([now - Duration::from_secs(90), now - Duration::from_secs(5)]);
+        assert_eq!(chat_wait(&mut sent, now, true), None);
+        assert_eq!(sent.len(), 1);
+    }
+}
@@ -0,0 +1,116 @@
+//! Process-wide graceful shutdown signal.
+//!
+//! `main` triggers it on SIGINT/SIGTERM; tasks with work in flight hold a
+//! [`Guard`] from `main`'s [`Tracker`] so it can wait for them to finish before
+//! exiting.
+
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+static REQUESTED: LazyLock<watch::Sender<bool>> = LazyLock::new(|| watch::channel(false).0);
+
+/// Ask every task to wind down
+pub fn trigger() {
+    REQUESTED.send_replace(true);
+}
+
+pub fn is_requested() -> bool {
+    *REQUESTED.borrow()
+}
+
+/// Resolves once shutdown has been triggered
+pub async fn requested() {
+    let mut rx = REQUESTED.subscribe();
+    // Only fails if the sender is dropped, which a static never is
+    let _ = rx.wait_for(|requested| *requested).await;
+}
+
+/// Wait for SIGINT (Ctrl-C) or, on Unix, SIGTERM
+pub async fn signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("Failed to listen for SIGTERM: {}", e),
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Work in flight, counted by live guards. Clones share the count.
+#[derive(Clone)]
+pub struct Tracker(Arc<watch::Sender<usize>>);
+
+impl Tracker {
+    pub fn new() -> Self {
+        Self(Arc::new(watch::channel(0).0))
+    }
+
+    /// Mark work that should finish before the process exits
+    pub fn guard(&self) -> Guard {
+        self.0.send_modify(|count| *count += 1);
+        Guard(self.0.clone())
+    }
+
+    /// Wait up to `timeout` for every [`Guard`] to be dropped
+    pub async fn drained(&self, timeout: Duration) {
+        let mut rx = self.0.subscribe();
+        let pending = *rx.borrow();
+        if pending == 0 {
+            return;
+        }
+        info!("⏳ Waiting for {} in-flight tasks to finish", pending);
+        if tokio::time::timeout(timeout, rx.wait_for(|count| *count == 0))
+            .await
+            .is_err()
+        {
+            warn!("Shutting down with {} tasks still in flight", *rx.borrow());
+        }
+    }
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Marks work that should finish before the process exits, until dropped
+pub struct Guard(Arc<watch::Sender<usize>>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.0.send_modify(|count| *count -= 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drained() {
+        let tracker = Tracker::new();
+        let guard = tracker.guard();
+        let waiter = tokio::spawn({
+            let tracker = tracker.clone();
+            async move { tracker.drained(Duration::from_secs(5)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        drop(guard);
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -0,0 +1,168 @@
+//! Temporary silences for planned maintenance, set through `POST /silence`.
+//!
+//! While a category is silenced its Telegram alerts are logged and dropped;
+//! everything else (the dispute store, stats, the event stream) keeps running.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+/// Longest silence accepted, so a typo can't mute the watchdog for good
+pub const MAX_DURATION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+static SILENCES: LazyLock<Mutex<BTreeMap<Category, u64>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// Alerts that can be silenced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    /// Dispute alerts and digests
+    Disputes,
+    /// Health alerts (event silence, relays, clock, latency, memory, degradation, restarts);
+    /// heartbeats are still sent
+    Health,
+}
+
+impl Category {
+    pub const ALL: [Category; 2] = [Category::Disputes, Category::Health];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "disputes" => Some(Self::Disputes),
+            "health" => Some(Self::Health),
+            _ => None,
+        }
+    }
+
+    /// Comma-separated categories, or all of them when empty
+    pub fn parse_list(list: Option<&str>) -> Result<Vec<Self>, String> {
+        let Some(list) = list.filter(|list| !list.trim().is_empty()) else {
+            return Ok(Self::ALL.to_vec());
+        };
+        list.split(',')
+            .map(|name| {
+                Self::parse(name.trim()).ok_or_else(|| format!("unknown category '{}'", name))
+            })
+            .collect()
+    }
+}
+
+/// Silence the categories for `duration`, returning when the silence ends.
+/// An existing longer silence is kept.
+pub fn silence(categories: &[Category], duration: Duration) -> u64 {
+    let until = now() + duration.as_secs();
+    let mut silences = SILENCES.lock().expect("silences lock");
+    for category in categories {
+        let entry = silences.entry(*category).or_insert(until);
+        *entry = (*entry).max(until);
+    }
+    info!("🔇 Silenced {:?} for {}s", categories, duration.as_secs());
+    until
+}
+
+/// Lift the silence on the categories
+pub fn unsilence(categories: &[Category]) {
+    let mut silences = SILENCES.lock().expect("silences lock");
+    for category in categories {
+        silences.remove(category);
+    }
+    info!("🔔 Unsilenced {:?}", categories);
+}
+
+/// Active silences and the Unix timestamp each ends at
+pub fn active() -> BTreeMap<Category, u64> {
+    let now = now();
+    let mut silences = SILENCES.lock().expect("silences lock");
+    silences.retain(|_, until| *until > now);
+    silences.clone()
+}
+
+/// Whether an alert in this category should be dropped, logging it if so
+pub fn suppressed(category: Category, alert: &str) -> bool {
+    match active().get(&category) {
+        Some(until) => {
+            info!("🔇 {} alert silenced until {}", alert, until);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Parse a duration like `90`, `45s`, `30m`, `2h` or `1d`
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let duration = duration.trim();
+    let (number, unit) = match duration.char_indices().last() {
+        Some((i, c)) if c.is_ascii_alphabetic() => (&duration[..i], c),
+        _ => (duration, 's'),
+    };
+    let multiplier = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        _ => return Err(format!("unknown unit '{}' in duration", unit)),
+    };
+    let seconds = number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .filter(|&seconds| seconds > 0)
+        .ok_or_else(|| format!("invalid duration '{}'", duration))?;
+    let duration = Duration::from_secs(seconds);
+    if duration > MAX_DURATION {
+        return Err("duration is longer than 7 days".into());
+    }
+    Ok(duration)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86400)));
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("2w").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("8d").is_err());
+    }
+
+    #[test]
+    fn test_parse_categories() {
+        assert_eq!(Category::parse_list(None).unwrap(), Category::ALL);
+        assert_eq!(
+            Category::parse_list(Some("health")).unwrap(),
+            vec![Category::Health]
+        );
+        assert!(Category::parse_list(Some("health,relays")).is_err());
+    }
+
+    #[test]
+    fn test_silence() {
+        // Only the health category, so parallel tests sending dispute alerts are unaffected
+        silence(&[Category::Health], Duration::from_secs(60));
+        assert!(suppressed(Category::Health, "test"));
+        // A shorter silence doesn't cut an existing one short
+        let until = active()[&Category::Health];
+        silence(&[Category::Health], Duration::from_secs(1));
+        assert_eq!(active()[&Category::Health], until);
+
+        unsilence(&[Category::Health]);
+        assert!(!suppressed(Category::Health, "test"));
+    }
+}
@@ -0,0 +1,239 @@
+//! Runtime counters reported by the `/stats` command.
+//!
+//! Like the send queue, counters are process-wide so delivery helpers can record
+//! outcomes without threading extra state through every call.
+
+use crate::config::AlertSeverity;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+
+static STATS: LazyLock<Stats> = LazyLock::new(Stats::default);
+
+/// Process-wide counters
+pub fn stats() -> &'static Stats {
+    &STATS
+}
+
+#[derive(Default)]
+pub struct Stats {
+    counters: Mutex<Counters>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counters {
+    /// Dispute events received, by status
+    pub events: BTreeMap<String, u64>,
+    /// Dispute events received, by severity
+    pub severities: BTreeMap<&'static str, u64>,
+    /// Messages delivered, by channel (Telegram chat ID, `pushover`, `webhook`)
+    pub sent: BTreeMap<String, u64>,
+    /// Failed deliveries, by channel
+    pub failed: BTreeMap<String, u64>,
+    /// Reconnect attempts for disconnected relays
+    pub relay_reconnects: u64,
+    /// Dispute events dropped because the delivery queue was full
+    pub events_dropped: u64,
+    /// Dispute events dropped because they were dated in the future
+    pub events_future: u64,
+    /// Resolved disputes, by final status
+    pub resolutions: BTreeMap<String, u64>,
+    /// Total seconds from first alert to resolution, by final status
+    pub resolution_seconds: BTreeMap<String, u64>,
+    /// Entries evicted from full in-memory caches, by cache
+    pub evictions: BTreeMap<&'static str, u64>,
+}
+
+impl Stats {
+    fn update(&self, f: impl FnOnce(&mut Counters)) {
+        f(&mut self.counters.lock().expect("stats lock poisoned"));
+    }
+
+    pub fn record_event(&self, status: &str, severity: AlertSeverity) {
+        self.update(|c| {
+            *c.events.entry(status.to_string()).or_default() += 1;
+            *c.severities.entry(severity.as_str()).or_default() += 1;
+        });
+    }
+
+    pub fn record_sent(&self, channel: &str) {
+        self.update(|c| *c.sent.entry(channel.to_string()).or_default() += 1);
+    }
+
+    pub fn record_failure(&self, channel: &str) {
+        self.update(|c| *c.failed.entry(channel.to_string()).or_default() += 1);
+    }
+
+    pub fn record_relay_reconnects(&self, count: u64) {
+        self.update(|c| c.relay_reconnects += count);
+    }
+
+    pub fn record_dropped(&self) {
+        self.update(|c| c.events_dropped += 1);
+    }
+
+    pub fn record_future(&self) {
+        self.update(|c| c.events_future += 1);
+    }
+
+    pub fn record_eviction(&self, cache: &'static str) {
+        self.update(|c| *c.evictions.entry(cache).or_default() += 1);
+    }
+
+    pub fn record_resolution(&self, status: &str, seconds: u64) {
+        self.update(|c| {
+            *c.resolutions.entry(status.to_string()).or_default() += 1;
+            *c.resolution_seconds.entry(status.to_string()).or_default() += seconds;
+        });
+    }
+
+    pub fn snapshot(&self) -> Counters {
+        self.counters.lock().expect("stats lock poisoned").clone()
+    }
+}
+
+/// Delivery outcomes for one channel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct Deliveries {
+    pub sent: u64,
+    pub failed: u64,
+}
+
+impl Counters {
+    /// Sent and failed deliveries per channel
+    pub fn deliveries(&self) -> BTreeMap<String, Deliveries> {
+        let mut deliveries: BTreeMap<String, Deliveries> = BTreeMap::new();
+        for (channel, count) in &self.sent {
+            deliveries.entry(channel.clone()).or_default().sent = *count;
+        }
+        for (channel, count) in &self.failed {
+            deliveries.entry(channel.clone()).or_default().failed = *count;
+        }
+        deliveries
+    }
+
+    /// Compact fixed-width table for a monospace block
+    pub fn table(&self) -> String {
+        let mut rows: Vec<(String, String)> = vec![(
+            "Events".into(),
+            self.events.values().sum::<u64>().to_string(),
+        )];
+        for (status, count) in &self.events {
+            rows.push((format!("  {status}"), count.to_string()));
+        }
+        rows.push(("By severity".into(), String::new()));
+        for (severity, count) in &self.severities {
+            rows.push((format!("  {severity}"), count.to_string()));
+        }
+
+        rows.push(("Sent / failed".into(), String::new()));
+        for (channel, deliveries) in self.deliveries() {
+            rows.push((
+                format!("  {channel}"),
+                format!("{} / {}", deliveries.sent, deliveries.failed),
+            ));
+        }
+
+        rows.push(("Relay reconnects".into(), self.relay_reconnects.to_string()));
+        if self.events_dropped > 0 {
+            rows.push(("Events dropped".into(), self.events_dropped.to_string()));
+        }
+        if self.events_future > 0 {
+            rows.push(("Future events".into(), self.events_future.to_string()));
+        }
+        if !self.evictions.is_empty() {
+            rows.push(("Cache evictions".into(), String::new()));
+            for (cache, count) in &self.evictions {
+                rows.push((format!("  {cache}"), count.to_string()));
+            }
+        }
+
+        if !self.resolutions.is_empty() {
+            rows.push(("Avg resolution (min)".into(), String::new()));
+            for (status, count) in &self.resolutions {
+                let seconds = self.resolution_seconds.get(status).copied().unwrap_or(0);
+                rows.push((
+                    format!("  {status}"),
+                    (seconds / count.max(&1) / 60).to_string(),
+                ));
+            }
+        }
+
+        let label_width = rows
+            .iter()
+            .map(|(l, _)| l.chars().count())
+            .max()
+            .unwrap_or(0);
+        let value_width = rows.iter().map(|(_, v)| v.len()).max().unwrap_or(0);
+        rows.iter()
+            .map(|(label, value)| {
+                format!("{label:<label_width$}  {value:>value_width$}")
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let stats = Stats::default();
+        stats.record_event("initiated", AlertSeverity::Critical);
+        stats.record_event("initiated", AlertSeverity::Critical);
+        stats.record_event("settled", AlertSeverity::Info);
+        stats.record_sent("-100123");
+        stats.record_failure("pushover");
+        stats.record_relay_reconnects(2);
+        stats.record_resolution("settled", 600);
+        stats.record_resolution("settled", 1800);
+
+        let counters = stats.snapshot();
+        assert_eq!(counters.events["initiated"], 2);
+        assert_eq!(counters.severities["critical"], 2);
+        assert_eq!(counters.severities["info"], 1);
+        assert_eq!(counters.sent["-100123"], 1);
+        assert_eq!(counters.failed["pushover"], 1);
+        assert_eq!(counters.relay_reconnects, 2);
+        assert_eq!(counters.resolutions["settled"], 2);
+        assert_eq!(counters.resolution_seconds["settled"], 2400);
+        // 40 minutes over 2 disputes
+        let table = counters.table();
+        let last = table.lines().last().unwrap();
+        assert!(
+            last.starts_with("  settled") && last.ends_with(" 20"),
+            "{last}"
+        );
+        assert_eq!(
+            counters.deliveries()["pushover"],
+            Deliveries { sent: 0, failed: 1 }
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let stats = Stats::default();
+        stats.record_event("initiated", AlertSeverity::Critical);
+        stats.record_event("in-progress", AlertSeverity::Info);
+        stats.record_sent("-100123");
+        stats.record_sent("-100123");
+        stats.record_failure("pushover");
+
+        assert_eq!(
+            stats.snapshot().table(),
+            "Events                2\n\
+             \x20 in-progress         1\n\
+             \x20 initiated           1\n\
+             By severity\n\
+             \x20 critical            1\n\
+             \x20 info                1\n\
+             Sent / failed\n\
+             \x20 -100123         2 / 0\n\
+             \x20 pushover        0 / 1\n\
+             Relay reconnects      0"
+        );
+    }
+}
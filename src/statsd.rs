@@ -0,0 +1,239 @@
+//! Optional StatsD/DogStatsD export over UDP.
+//!
+//! The `/stats` counters are pushed as counter deltas, and uptime and resource usage
+//! as gauges, for push-based stacks such as Datadog or Netdata. With `dogstatsd`,
+//! labels (status, channel, ...) are sent as tags; otherwise they become part of
+//! the metric name.
+
+use crate::config::StatsdConfig;
+use crate::pipeline;
+use crate::resources;
+use crate::stats::{stats, Counters};
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+use tokio::net::UdpSocket;
+use tracing::{info, warn};
+
+/// Keep datagrams under a typical Ethernet MTU
+const MAX_DATAGRAM: usize = 1432;
+
+/// Line formatting for one StatsD flavor
+struct Format {
+    prefix: String,
+    dogstatsd: bool,
+}
+
+impl Format {
+    fn line(&self, name: &str, label: Option<(&str, &str)>, value: u64, kind: &str) -> String {
+        match label {
+            Some((key, label)) if self.dogstatsd => format!(
+                "{}.{}:{}|{}|#{}:{}",
+                self.prefix,
+                name,
+                value,
+                kind,
+                key,
+                sanitize(label)
+            ),
+            Some((_, label)) => format!(
+                "{}.{}.{}:{}|{}",
+                self.prefix,
+                name,
+                sanitize(label),
+                value,
+                kind
+            ),
+            None => format!("{}.{}:{}|{}", self.prefix, name, value, kind),
+        }
+    }
+
+    /// Counter lines for everything that increased since `previous`
+    fn counters(&self, previous: &Counters, current: &Counters) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (status, delta) in deltas(&previous.events, &current.events) {
+            lines.push(self.line("events", Some(("status", status)), delta, "c"));
+        }
+        for (channel, delta) in deltas(&previous.sent, &current.sent) {
+            lines.push(self.line("messages_sent", Some(("channel", channel)), delta, "c"));
+        }
+        for (channel, delta) in deltas(&previous.failed, &current.failed) {
+            lines.push(self.line("messages_failed", Some(("channel", channel)), delta, "c"));
+        }
+        for (severity, delta) in deltas(&previous.severities, &current.severities) {
+            lines.push(self.line(
+                "events_by_severity",
+                Some(("severity", severity)),
+                delta,
+                "c",
+            ));
+        }
+        let reconnects = current.relay_reconnects - previous.relay_reconnects;
+        if reconnects > 0 {
+            lines.push(self.line("relay_reconnects", None, reconnects, "c"));
+        }
+        let dropped = current.events_dropped - previous.events_dropped;
+        if dropped > 0 {
+            lines.push(self.line("events_dropped", None, dropped, "c"));
+        }
+        let future = current.events_future - previous.events_future;
+        if future > 0 {
+            lines.push(self.line("events_future", None, future, "c"));
+        }
+        for (cache, delta) in deltas(&previous.evictions, &current.evictions) {
+            lines.push(self.line("cache_evictions", Some(("cache", cache)), delta, "c"));
+        }
+        lines
+    }
+}
+
+/// Labels whose count increased, with the increase
+fn deltas<'a, K: AsRef<str> + Ord>(
+    previous: &BTreeMap<K, u64>,
+    current: &'a BTreeMap<K, u64>,
+) -> Vec<(&'a str, u64)> {
+    current
+        .iter()
+        .map(|(label, count)| {
+            let delta = count - previous.get(label).copied().unwrap_or(0);
+            (label.as_ref(), delta)
+        })
+        .filter(|(_, delta)| *delta > 0)
+        .collect()
+}
+
+/// StatsD names can't contain the protocol's separators
+fn sanitize(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| match c {
+            ':' | '|' | '@' | '#' | ',' | '.' | ' ' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Join lines into newline-separated datagrams of at most `MAX_DATAGRAM` bytes
+fn datagrams(lines: &[String]) -> Vec<String> {
+    let mut datagrams: Vec<String> = Vec::new();
+    for line in lines {
+        match datagrams.last_mut() {
+            Some(datagram) if datagram.len() + 1 + line.len() <= MAX_DATAGRAM => {
+                datagram.push('\n');
+                datagram.push_str(line);
+            }
+            _ => datagrams.push(line.clone()),
+        }
+    }
+    datagrams
+}
+
+/// Push metrics every `interval` seconds until shutdown
+pub async fn run(config: StatsdConfig, start_time: SystemTime) {
+    let socket = match UdpSocket::bind("0.0.0.0:0").await {
+        Ok(socket) => socket,
+        Err(e) => {
+            warn!("Failed to open StatsD socket: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = socket.connect(&config.address).await {
+        warn!("Invalid StatsD address {}: {}", config.address, e);
+        return;
+    }
+    info!("📊 Sending StatsD metrics to {}", config.address);
+
+    let format = Format {
+        prefix: config.prefix,
+        dogstatsd: config.dogstatsd,
+    };
+    let mut previous = Counters::default();
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval));
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        interval.tick().await;
+
+        let current = stats().snapshot();
+        let mut lines = format.counters(&previous, &current);
+        previous = current;
+
+        let uptime = start_time.elapsed().unwrap_or(Duration::ZERO).as_secs();
+        lines.push(format.line("uptime", None, uptime, "g"));
+        let usage = resources::sample();
+        if let Some(rss) = usage.rss_bytes {
+            lines.push(format.line("memory", None, rss, "g"));
+        }
+        if let Some(fds) = usage.open_fds {
+            lines.push(format.line("open_fds", None, fds, "g"));
+        }
+        lines.push(format.line("tasks", None, usage.tasks as u64, "g"));
+        lines.push(format.line("event_queue", None, pipeline::depth() as u64, "g"));
+
+        for datagram in datagrams(&lines) {
+            // UDP is fire-and-forget; a missing agent only shows up as ICMP errors
+            if let Err(e) = socket.send(datagram.as_bytes()).await {
+                warn!("Failed to send StatsD metrics: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AlertSeverity;
+    use crate::stats::Stats;
+
+    fn format(dogstatsd: bool) -> Format {
+        Format {
+            prefix: "mostro_watchdog".into(),
+            dogstatsd,
+        }
+    }
+
+    #[test]
+    fn test_counter_deltas() {
+        let stats = Stats::default();
+        stats.record_event("initiated", AlertSeverity::Critical);
+        let previous = stats.snapshot();
+        stats.record_event("initiated", AlertSeverity::Critical);
+        stats.record_event("initiated", AlertSeverity::Critical);
+        stats.record_sent("-100123");
+        stats.record_relay_reconnects(1);
+        let current = stats.snapshot();
+
+        assert_eq!(
+            format(false).counters(&previous, &current),
+            [
+                "mostro_watchdog.events.initiated:2|c",
+                "mostro_watchdog.messages_sent.-100123:1|c",
+                "mostro_watchdog.events_by_severity.critical:2|c",
+                "mostro_watchdog.relay_reconnects:1|c",
+            ]
+        );
+        assert_eq!(
+            format(true).counters(&previous, &current)[0],
+            "mostro_watchdog.events:2|c|#status:initiated"
+        );
+        // Nothing changed, nothing sent
+        assert!(format(false).counters(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(
+            format(false).line("events", Some(("status", "a.b:c|d")), 1, "c"),
+            "mostro_watchdog.events.a_b_c_d:1|c"
+        );
+    }
+
+    #[test]
+    fn test_datagrams() {
+        let lines: Vec<String> = (0..100).map(|i| format!("m.metric_{i:03}:1|c")).collect();
+        let datagrams = datagrams(&lines);
+        assert!(datagrams.len() > 1);
+        assert!(datagrams.iter().all(|d| d.len() <= MAX_DATAGRAM));
+        assert_eq!(datagrams.join("\n").lines().count(), 100);
+    }
+}
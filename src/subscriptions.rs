@@ -0,0 +1,108 @@
+//! Dead dispute subscription detection.
+//!
+//! A relay can end a subscription with CLOSED (after a restart, a rate limit or an
+//! auth requirement) or silently stop serving it while the connection stays up,
+//! which looks exactly like a quiet day. A subscription is considered dead when
+//! the relay closed it, or when other relays delivered `SILENT_AFTER` dispute
+//! events in a row that it did not; it is then re-created on that relay.
+
+use crate::relay_score::Deliveries;
+use nostr_sdk::prelude::*;
+use std::collections::HashMap;
+
+/// Dispute events delivered by other relays, and not by this one, before its
+/// subscription counts as silently dead
+pub const SILENT_AFTER: u64 = 3;
+
+/// A dispute subscription as created at startup
+#[derive(Debug, Clone)]
+pub struct DisputeSubscription {
+    pub id: SubscriptionId,
+    pub relays: Vec<String>,
+    pub filter: Filter,
+}
+
+/// Why a subscription is considered dead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Death {
+    /// The relay sent CLOSED with this message
+    Closed(String),
+    /// The relay missed this many dispute events other relays delivered
+    Silent { missed: u64 },
+}
+
+/// Subscription liveness per relay
+#[derive(Debug, Default)]
+pub struct Watch {
+    relays: HashMap<String, Deliveries>,
+}
+
+impl Watch {
+    /// Check one relay given the CLOSED message it sent since the last check, if
+    /// any, and its dispute deliveries. `silent` is false for relays that aren't
+    /// sent every dispute event, whose deliveries prove nothing.
+    pub fn check(
+        &mut self,
+        relay: &str,
+        closed: Option<String>,
+        deliveries: Deliveries,
+        silent: bool,
+    ) -> Option<Death> {
+        let baseline = self.relays.entry(relay.to_string()).or_insert(deliveries);
+        if let Some(message) = closed {
+            *baseline = deliveries;
+            return Some(Death::Closed(message));
+        }
+        if deliveries.delivered > baseline.delivered {
+            *baseline = deliveries;
+            return None;
+        }
+        let missed = deliveries.expected.saturating_sub(baseline.expected);
+        if silent && missed >= SILENT_AFTER {
+            *baseline = deliveries;
+            return Some(Death::Silent { missed });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELAY: &str = "wss://relay.mostro.network";
+
+    fn deliveries(delivered: u64, expected: u64) -> Deliveries {
+        Deliveries {
+            delivered,
+            expected,
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let mut watch = Watch::default();
+        assert_eq!(watch.check(RELAY, None, deliveries(5, 5), true), None);
+        assert_eq!(watch.check(RELAY, None, deliveries(5, 7), true), None);
+        // A delivery proves the subscription alive
+        assert_eq!(watch.check(RELAY, None, deliveries(6, 8), true), None);
+        assert_eq!(watch.check(RELAY, None, deliveries(6, 10), true), None);
+        assert_eq!(
+            watch.check(RELAY, None, deliveries(6, 11), true),
+            Some(Death::Silent { missed: 3 })
+        );
+        // Counted afresh once re-created
+        assert_eq!(watch.check(RELAY, None, deliveries(6, 12), true), None);
+
+        assert_eq!(watch.check(RELAY, None, deliveries(6, 20), false), None);
+        assert_eq!(
+            watch.check(
+                RELAY,
+                Some("rate-limited: slow down".into()),
+                deliveries(6, 20),
+                false
+            ),
+            Some(Death::Closed("rate-limited: slow down".into()))
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Supervision of long-running background tasks.
+//!
+//! A task that panics, returns, or stops reporting progress is restarted with a
+//! backoff, and each restart is logged and optionally reported to the health chat.
+//! Without this, a panic in a spawned task silently disables that feature for the
+//! rest of the process lifetime.
+
+use crate::events;
+use crate::shutdown;
+use crate::silence;
+use crate::templates::Templates;
+use crate::{send_markdown, Destination};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use teloxide::Bot;
+use tokio::task::{JoinError, JoinHandle};
+use tracing::{error, warn};
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// A task running at least this long is considered recovered, resetting the backoff
+const HEALTHY_RUN: Duration = Duration::from_secs(300);
+
+/// Progress marker a supervised task updates on every loop iteration
+#[derive(Clone)]
+pub struct Liveness(Arc<Mutex<Instant>>);
+
+impl Liveness {
+    fn new() -> Self {
+        Self(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    /// Report progress
+    pub fn beat(&self) {
+        *self.0.lock().expect("liveness lock poisoned") = Instant::now();
+    }
+
+    fn idle(&self) -> Duration {
+        self.0.lock().expect("liveness lock poisoned").elapsed()
+    }
+}
+
+pub struct Supervisor {
+    /// Where restarts are reported, if enabled
+    alert: Option<(Bot, Destination, Arc<Templates>)>,
+}
+
+impl Supervisor {
+    pub fn new(alert: Option<(Bot, Destination, Arc<Templates>)>) -> Arc<Self> {
+        Arc::new(Self { alert })
+    }
+
+    /// Run `task` under supervision. Tasks with a `stall_timeout` are restarted when they
+    /// don't call [`Liveness::beat`] for that long.
+    pub fn spawn<F, Fut>(
+        self: &Arc<Self>,
+        name: &'static str,
+        stall_timeout: Option<Duration>,
+        task: F,
+    ) where
+        F: Fn(Liveness) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            let mut restarts = 0u32;
+            let mut backoff = MIN_BACKOFF;
+
+            loop {
+                let liveness = Liveness::new();
+                let started = Instant::now();
+                let handle = tokio::spawn(task(liveness.clone()));
+                let reason = watch(handle, &liveness, stall_timeout).await;
+                // Tasks wind down on their own during shutdown
+                if shutdown::is_requested() {
+                    return;
+                }
+
+                restarts += 1;
+                if started.elapsed() >= HEALTHY_RUN {
+                    backoff = MIN_BACKOFF;
+                }
+                error!(
+                    "🔁 Task {} {}, restarting in {:?} (restart #{})",
+                    name, reason, backoff, restarts
+                );
+                supervisor.report(name, &reason, restarts).await;
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        });
+    }
+
+    async fn report(&self, name: &str, reason: &str, restarts: u32) {
+        let mut context = tera::Context::new();
+        context.insert("task", name);
+        context.insert("reason", reason);
+        context.insert("restarts", &restarts);
+        events::publish_health("task_restarted", &context);
+
+        let Some((ref bot, destination, ref templates)) = self.alert else {
+            return;
+        };
+        if silence::suppressed(silence::Category::Health, "task_restarted") {
+            return;
+        }
+        let message = templates.render("task_restarted", &context);
+        if let Err(e) = send_markdown(bot, destination, &message).await {
+            warn!("Failed to report task restart: {}", e);
+        }
+    }
+}
+
+/// Wait for a task to end or stall, describing why it stopped
+async fn watch(
+    mut handle: JoinHandle<()>,
+    liveness: &Liveness,
+    stall_timeout: Option<Duration>,
+) -> String {
+    let Some(timeout) = stall_timeout else {
+        return outcome(handle.await);
+    };
+
+    let mut check = tokio::time::interval((timeout / 4).max(Duration::from_millis(10)));
+    loop {
+        tokio::select! {
+            result = &mut handle => return outcome(result),
+            _ = check.tick() => {
+                let idle = liveness.idle();
+                if idle > timeout {
+                    handle.abort();
+                    return format!("stalled (no progress for {}s)", idle.as_secs());
+                }
+            }
+        }
+    }
+}
+
+fn outcome(result: Result<(), JoinError>) -> String {
+    match result {
+        Ok(()) => "exited".to_string(),
+        Err(e) if e.is_panic() => {
+            let payload = e.into_panic();
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            format!("panicked: {message}")
+        }
+        Err(_) => "was cancelled".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_panic() {
+        let liveness = Liveness::new();
+        let handle = tokio::spawn(async { panic!("boom") });
+        assert_eq!(watch(handle, &liveness, None).await, "panicked: boom");
+
+        let handle = tokio::spawn(async {});
+        assert_eq!(watch(handle, &liveness, None).await, "exited");
+    }
+
+    #[tokio::test]
+    async fn test_watch_stall() {
+        let liveness = Liveness::new();
+        let handle = tokio::spawn(std::future::pending::<()>());
+        let reason = watch(handle, &liveness, Some(Duration::from_millis(100))).await;
+        assert!(reason.starts_with("stalled"), "{reason}");
+
+        // A task that keeps beating is left running until it ends
+        let liveness = Liveness::new();
+        let beating = liveness.clone();
+        let handle = tokio::spawn(async move {
+            for _ in 0..10 {
+                tokio::time::sleep(Duration::from_millis(30)).await;
+                beating.beat();
+            }
+        });
+        let reason = watch(handle, &liveness, Some(Duration::from_millis(100))).await;
+        assert_eq!(reason, "exited");
+    }
+}
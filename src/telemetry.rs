@@ -0,0 +1,218 @@
+//! Optional OpenTelemetry export over OTLP/HTTP.
+//!
+//! Tracing spans (event handling, Telegram delivery) are exported as traces, and the
+//! `/stats` counters are exported as metrics. Requires the `otel` cargo feature.
+
+use crate::config::TelemetryConfig;
+use crate::pipeline;
+use crate::resources;
+use crate::stats::stats;
+use crate::HealthMonitor;
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Keeps the exporters running; dropping it stops the export
+pub struct Telemetry {
+    tracer_provider: TracerProvider,
+    meter_provider: SdkMeterProvider,
+}
+
+impl Telemetry {
+    pub fn init(config: &TelemetryConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let endpoint = config.endpoint.trim_end_matches('/');
+        let resource = Resource::new(vec![
+            KeyValue::new("service.name", config.service_name.clone()),
+            KeyValue::new("service.version", crate::VERSION),
+        ]);
+
+        let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{endpoint}/v1/traces"))
+            .with_headers(config.headers.clone())
+            .build()?;
+        let tracer_provider = TracerProvider::builder()
+            .with_batch_exporter(span_exporter, runtime::Tokio)
+            .with_resource(resource.clone())
+            .build();
+
+        let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{endpoint}/v1/metrics"))
+            .with_headers(config.headers.clone())
+            .build()?;
+        let reader = PeriodicReader::builder(metric_exporter, runtime::Tokio)
+            .with_interval(Duration::from_secs(config.metrics_interval))
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_resource(resource)
+            .build();
+
+        Ok(Self {
+            tracer_provider,
+            meter_provider,
+        })
+    }
+
+    /// Layer exporting tracing spans
+    pub fn layer<S>(&self) -> OpenTelemetryLayer<S, Tracer>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span>,
+    {
+        tracing_opentelemetry::layer().with_tracer(self.tracer_provider.tracer("mostro-watchdog"))
+    }
+
+    /// Export the runtime counters, read from `stats` and the health monitor at each collection
+    pub fn register_metrics(&self, health_monitor: Arc<HealthMonitor>) {
+        let start_time = health_monitor.start_time;
+        let meter = self.meter_provider.meter("mostro-watchdog");
+
+        meter
+            .u64_observable_counter("mostro_watchdog.events")
+            .with_description("Dispute events received")
+            .with_callback(|observer| {
+                for (status, count) in &stats().snapshot().events {
+                    observer.observe(*count, &[KeyValue::new("status", status.clone())]);
+                }
+            })
+            .build();
+        meter
+            .u64_observable_counter("mostro_watchdog.events_by_severity")
+            .with_description("Dispute events received, by severity")
+            .with_callback(|observer| {
+                for (severity, count) in &stats().snapshot().severities {
+                    observer.observe(*count, &[KeyValue::new("severity", *severity)]);
+                }
+            })
+            .build();
+        meter
+            .u64_observable_counter("mostro_watchdog.messages_sent")
+            .with_description("Messages delivered, by channel")
+            .with_callback(|observer| {
+                for (channel, count) in &stats().snapshot().sent {
+                    observer.observe(*count, &[KeyValue::new("channel", channel.clone())]);
+                }
+            })
+            .build();
+        meter
+            .u64_observable_counter("mostro_watchdog.messages_failed")
+            .with_description("Failed deliveries, by channel")
+            .with_callback(|observer| {
+                for (channel, count) in &stats().snapshot().failed {
+                    observer.observe(*count, &[KeyValue::new("channel", channel.clone())]);
+                }
+            })
+            .build();
+        meter
+            .u64_observable_counter("mostro_watchdog.relay_reconnects")
+            .with_description("Reconnect attempts for disconnected relays")
+            .with_callback(|observer| observer.observe(stats().snapshot().relay_reconnects, &[]))
+            .build();
+        meter
+            .u64_observable_counter("mostro_watchdog.cache_evictions")
+            .with_description("Entries evicted from full in-memory caches, by cache")
+            .with_callback(|observer| {
+                for (cache, count) in &stats().snapshot().evictions {
+                    observer.observe(*count, &[KeyValue::new("cache", *cache)]);
+                }
+            })
+            .build();
+        let health_monitor_rtt = health_monitor.clone();
+        meter
+            .u64_observable_gauge("mostro_watchdog.relay_latency")
+            .with_description("Event propagation latency of recent events, by relay")
+            .with_unit("s")
+            .with_callback(move |observer| {
+                for relay in health_monitor.state().relays.values() {
+                    let Some(latency) = relay.latency else {
+                        continue;
+                    };
+                    for (quantile, value) in [
+                        ("0.5", latency.p50),
+                        ("0.9", latency.p90),
+                        ("0.99", latency.p99),
+                    ] {
+                        observer.observe(
+                            value,
+                            &[
+                                KeyValue::new("relay", relay.url.clone()),
+                                KeyValue::new("quantile", quantile),
+                            ],
+                        );
+                    }
+                }
+            })
+            .build();
+        meter
+            .u64_observable_gauge("mostro_watchdog.relay_rtt")
+            .with_description("Round-trip time of the last ping, by relay")
+            .with_unit("ms")
+            .with_callback(move |observer| {
+                for relay in health_monitor_rtt.state().relays.values() {
+                    if let Some(rtt) = relay.rtt_ms {
+                        observer.observe(rtt, &[KeyValue::new("relay", relay.url.clone())]);
+                    }
+                }
+            })
+            .build();
+        meter
+            .u64_observable_gauge("mostro_watchdog.memory")
+            .with_description("Resident set size")
+            .with_unit("By")
+            .with_callback(|observer| {
+                if let Some(rss) = resources::sample().rss_bytes {
+                    observer.observe(rss, &[]);
+                }
+            })
+            .build();
+        meter
+            .u64_observable_gauge("mostro_watchdog.open_fds")
+            .with_description("Open file descriptors")
+            .with_callback(|observer| {
+                if let Some(fds) = resources::sample().open_fds {
+                    observer.observe(fds, &[]);
+                }
+            })
+            .build();
+        meter
+            .u64_observable_gauge("mostro_watchdog.tasks")
+            .with_description("Tasks alive in the tokio runtime")
+            .with_callback(|observer| observer.observe(resources::sample().tasks as u64, &[]))
+            .build();
+        meter
+            .u64_observable_gauge("mostro_watchdog.event_queue")
+            .with_description("Dispute events waiting for a delivery worker")
+            .with_callback(|observer| observer.observe(pipeline::depth() as u64, &[]))
+            .build();
+        meter
+            .u64_observable_gauge("mostro_watchdog.uptime")
+            .with_description("Seconds since start")
+            .with_unit("s")
+            .with_callback(move |observer| {
+                let uptime = start_time.elapsed().unwrap_or(Duration::ZERO).as_secs();
+                observer.observe(uptime, &[]);
+            })
+            .build();
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Err(e) = self.tracer_provider.shutdown() {
+            eprintln!("Failed to flush traces: {e}");
+        }
+        if let Err(e) = self.meter_provider.shutdown() {
+            eprintln!("Failed to flush metrics: {e}");
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Tera-based rendering for all outgoing Telegram messages.
+//!
+//! Built-in templates come in several languages (see `i18n`). Operators can override any of
+//! them inline in `[templates]` or with `<name>.md` files in `templates.dir`.
+//! Substituted values are MarkdownV2-escaped automatically; use the `code` filter
+//! for values placed inside code spans.
+
+use crate::branding::Branding;
+use crate::config::{Language, TemplatesConfig, TimeFormat};
+use crate::i18n::builtin_templates;
+use crate::timestamp;
+use crate::{escape_markdown, escape_markdown_code};
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tera::{Context, Tera, Value};
+use tracing::{error, warn};
+
+/// Template name used for a dispute status
+pub fn status_template(status: &str) -> &'static str {
+    match status {
+        "initiated" => "initiated",
+        "in-progress" => "in_progress",
+        "seller-refunded" => "seller_refunded",
+        "settled" => "settled",
+        "released" => "released",
+        _ => "other",
+    }
+}
+
+/// Renders outgoing messages, falling back to the built-in template if an
+/// operator template fails to render.
+///
+/// Shared instances can be swapped for reloaded templates with [`Templates::replace`].
+pub struct Templates {
+    inner: RwLock<Inner>,
+}
+
+struct Inner {
+    tera: Tera,
+    builtin: Tera,
+    branding: Branding,
+    language: Language,
+    time_format: TimeFormat,
+}
+
+impl Templates {
+    /// Load built-in templates for the configured language and apply overrides from the templates directory
+    /// and inline config (inline config wins).
+    pub fn load(config: &TemplatesConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let builtins = builtin_templates(config.language);
+        let mut sources = Vec::with_capacity(builtins.len());
+        for (name, builtin) in builtins {
+            let mut source = builtin.to_string();
+            if let Some(ref dir) = config.dir {
+                let path = dir.join(format!("{name}.md"));
+                if path.exists() {
+                    source = std::fs::read_to_string(&path)
+                        .map_err(|e| format!("Failed to read template {}: {e}", path.display()))?;
+                }
+            }
+            if let Some(inline) = config.get(name) {
+                source = inline.to_string();
+            }
+            sources.push((format!("{name}.md"), source));
+        }
+
+        let tera = engine(sources).map_err(|e| format!("Invalid message template: {e}"))?;
+        let builtin = engine(builtins.iter().map(|(name, t)| (format!("{name}.md"), *t)))?;
+
+        Ok(Self {
+            inner: RwLock::new(Inner {
+                tera,
+                builtin,
+                branding: Branding::default(),
+                language: config.language,
+                time_format: config.time_format,
+            }),
+        })
+    }
+
+    /// Apply operator branding to every rendered message
+    pub fn with_branding(mut self, branding: Branding) -> Self {
+        self.inner.get_mut().expect("templates lock").branding = branding;
+        self
+    }
+
+    /// Use `other`'s templates and branding from now on
+    pub fn replace(&self, other: Templates) {
+        let other = other.inner.into_inner().expect("templates lock");
+        *self.inner.write().expect("templates lock") = other;
+    }
+
+    /// Render the named template with the given context
+    pub fn render(&self, name: &str, context: &Context) -> String {
+        let inner = self.inner.read().expect("templates lock");
+        let rendered = inner.render_unbranded(name, context);
+        inner.branding.apply(&rendered)
+    }
+
+    /// Render the named template without falling back to the built-in one, so a
+    /// broken operator template is reported instead of hidden
+    pub fn try_render(&self, name: &str, context: &Context) -> Result<String, tera::Error> {
+        let inner = self.inner.read().expect("templates lock");
+        let rendered = inner.tera.render(&format!("{name}.md"), context)?;
+        Ok(inner.branding.apply(&rendered))
+    }
+
+    /// `unix` as configured for messages (`templates.time_format`)
+    pub fn timestamp(&self, unix: u64) -> String {
+        let inner = self.inner.read().expect("templates lock");
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        timestamp::format(unix, inner.time_format, inner.language, now)
+    }
+
+    /// A rendered message without the branding prefix, for embedding in another message
+    pub fn embeddable(&self, message: &str) -> String {
+        let inner = self.inner.read().expect("templates lock");
+        inner.branding.strip_prefix(message).to_string()
+    }
+}
+
+impl Inner {
+    fn render_unbranded(&self, name: &str, context: &Context) -> String {
+        let template = format!("{name}.md");
+        match self.tera.render(&template, context) {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                warn!(
+                    "Failed to render template '{}', using built-in: {}",
+                    name, e
+                );
+                self.builtin.render(&template, context).unwrap_or_else(|e| {
+                    error!("Failed to render built-in template '{}': {}", name, e);
+                    format!("⚠️ Failed to render {} message", escape_markdown(name))
+                })
+            }
+        }
+    }
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::load(&TemplatesConfig::default()).expect("built-in templates are valid")
+    }
+}
+
+/// Create a Tera instance with MarkdownV2 auto-escaping and the `code` filter
+fn engine<I, N, C>(templates: I) -> tera::Result<Tera>
+where
+    I: IntoIterator<Item = (N, C)>,
+    N: AsRef<str>,
+    C: AsRef<str>,
+{
+    let mut tera = Tera::default();
+    tera.autoescape_on(vec![".md"]);
+    tera.set_escape_fn(escape_markdown);
+    tera.register_filter("code", CodeFilter);
+    tera.add_raw_templates(templates)?;
+    Ok(tera)
+}
+
+/// Escapes a value for use inside a MarkdownV2 code span.
+/// Marked safe so the regular auto-escaping is not applied on top.
+struct CodeFilter;
+
+impl tera::Filter for CodeFilter {
+    fn filter(&self, value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+        let text = match value.as_str() {
+            Some(s) => s.to_string(),
+            None => value.to_string(),
+        };
+        Ok(Value::String(escape_markdown_code(&text)))
+    }
+
+    fn is_safe(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dispute_context(status: &str, solver: Option<&str>) -> Context {
+        let mut context = Context::new();
+        context.insert("dispute_id", "abc_123");
+        context.insert("status", status);
+        context.insert("initiator", "buyer");
+        context.insert("time", "2021-01-01 00:00:00 UTC");
+        context.insert("solver", &solver);
+        context
+    }
+
+    #[test]
+    fn test_builtin_dispute_templates() {
+        let templates = Templates::default();
+
+        let msg = templates.render("initiated", &dispute_context("initiated", None));
+        assert_eq!(
+            msg,
+            "🚨 *NEW DISPUTE*\n\n\
+             📋 *Dispute ID:* `abc_123`\n\
+             👤 *Initiated by:* buyer\n\
+             ⏰ *Time:* 2021\\-01\\-01 00:00:00 UTC\n\n\
+             ⚡ Please take this dispute in Mostrix or your admin client\\."
+        );
+
+        // Solver line only appears when a solver is known
+        let msg = templates.render("in_progress", &dispute_context("in-progress", None));
+        assert!(!msg.contains("Taken by"));
+        let msg = templates.render("in_progress", &dispute_context("in-progress", Some("pk`1")));
+        assert!(msg.contains("📋 *Dispute ID:* `abc_123`\n👨‍⚖️ *Taken by:* `pk\\`1`\n⏰"));
+
+        let msg = templates.render(
+            status_template("weird_status"),
+            &dispute_context("weird_status", None),
+        );
+        assert!(msg.contains("📊 *Status:* weird\\_status"));
+
+        // The escalated alert is already MarkdownV2 and is not escaped again
+        let mut context = Context::new();
+        context.insert("minutes", &15);
+        context.insert("alert", "🚨 *NEW DISPUTE*");
+        context.insert("message", "Maintenance at 20:00 UTC (about 30 min.)");
+        context.insert("mostro", "npub1mostro");
+        context.insert("applied", &["alerts", "templates"]);
+        context.insert("restart_required", &["nostr"]);
+        let msg = templates.render("escalation", &context);
+        assert!(msg.contains("for 15 minutes\\.\n\n🚨 *NEW DISPUTE*"));
+    }
+
+    #[test]
+    fn test_builtin_health_templates() {
+        let templates = Templates::default();
+
+        let mut context = Context::new();
+        context.insert("failed_relays", &["wss://a.example", "wss://b.example"]);
+        context.insert("failed_count", &2);
+        context.insert("connected_count", &1);
+        let msg = templates.render("relay_alert", &context);
+        assert!(msg.contains(
+            "Disconnected relays: 2\n  • wss://a\\.example\n  • wss://b\\.example\n✅ Connected relays: 1\n"
+        ));
+
+        let mut context = Context::new();
+        context.insert("heartbeat_interval", &None::<u64>);
+        context.insert("event_alert_threshold", &Some(7200));
+        let msg = templates.render("startup", &context);
+        assert!(msg.contains("📊 Heartbeat: disabled\n"));
+        assert!(msg.ends_with("🔔 Event silence alert: 7200 seconds"));
+
+        context.insert("self_test", &false);
+        context.insert("self_test_failure", "delivery: chat not found");
+        let msg = templates.render("startup", &context);
+        assert!(msg.ends_with("\n❌ Self\\-test failed at delivery: chat not found"));
+    }
+
+    #[test]
+    fn test_template_overrides() {
+        let config: TemplatesConfig = toml::from_str(
+            r#"
+            initiated = "New {{ dispute_id }} by {{ initiator }}"
+            other = "{{ missing_variable }}"
+            "#,
+        )
+        .unwrap();
+        let templates = Templates::load(&config).unwrap();
+
+        // Values are escaped automatically
+        let msg = templates.render("initiated", &dispute_context("initiated", None));
+        assert_eq!(msg, "New abc\\_123 by buyer");
+
+        // Failed renders fall back to the built-in template
+        let msg = templates.render("other", &dispute_context("weird", None));
+        assert!(msg.starts_with("📡 *DISPUTE STATUS UPDATE*"));
+        // unless the failure is asked for
+        assert!(templates
+            .try_render("other", &dispute_context("weird", None))
+            .is_err());
+
+        // Syntax errors are reported at load time
+        let config: TemplatesConfig = toml::from_str(r#"settled = "{% if %}""#).unwrap();
+        assert!(Templates::load(&config).is_err());
+    }
+
+    /// Fail on MarkdownV2 special characters left unescaped outside code spans
+    fn assert_valid_markdown(text: &str) {
+        let mut chars = text.chars();
+        let mut in_code = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '`' => in_code = !in_code,
+                '.' | '-' | '!' | '(' | ')' | '+' | '=' | '|' | '{' | '}' | '>' | '#'
+                    if !in_code =>
+                {
+                    panic!("unescaped '{c}' in: {text}")
+                }
+                _ => {}
+            }
+        }
+        assert!(!in_code, "unterminated code span in: {text}");
+    }
+
+    #[test]
+    fn test_all_languages_render() {
+        use crate::config::Language;
+
+        let english: Vec<&str> = builtin_templates(Language::En)
+            .iter()
+            .map(|(name, _)| *name)
+            .collect();
+
+        let mut context = dispute_context("in-progress", Some("npub1solver"));
+        context.insert("uptime_hours", &5);
+        context.insert("uptime_minutes", &42);
+        context.insert("events_processed", &7);
+        context.insert("delivered", &12);
+        context.insert("failed", &1);
+        context.insert("threshold_hours", &2);
+        context.insert("failed_relays", &["wss://relay.example"]);
+        context.insert("failed_count", &1);
+        context.insert("connected_count", &1);
+        context.insert("heartbeat_interval", &Some(3600));
+        context.insert("event_alert_threshold", &None::<u64>);
+        context.insert("self_test", &true);
+        context.insert("self_test_failure", &None::<String>);
+        context.insert("count", &3);
+        context.insert("oncall", "Alice");
+        context.insert("name", "Alice");
+        context.insert("until", "2021-01-08 12:00:00 UTC");
+        context.insert("next", "Bob");
+        context.insert("user", "Mallory");
+        context.insert("user_id", &42);
+        context.insert("command", "/oncall");
+        context.insert("healthy", &true);
+        context.insert("version", "0.2.5");
+        context.insert("last_event", &None::<String>);
+        context.insert("last_heartbeat", "2021-01-01 00:00:00 UTC");
+        context.insert("error", "Network error: timed out");
+        context.insert("latest", "0.3.0");
+        context.insert(
+            "url",
+            "https://github.com/MostroP2P/mostro-watchdog/releases/tag/v0.3.0",
+        );
+        context.insert("commit", "0123456789");
+        context.insert("table", "Events  1");
+        context.insert("task", "relay check");
+        context.insert("reason", "panicked: boom");
+        context.insert("restarts", &1);
+        context.insert("drift", &95);
+        context.insert("behind", &true);
+        context.insert("from_relays", &true);
+        context.insert("rss_mb", &612);
+        context.insert("limit_mb", &512);
+        context.insert("open_fds", &Some(48));
+        context.insert("tasks", &17);
+        context.insert("relays_connected", &2);
+        context.insert("relays_total", &3);
+        context.insert("open_disputes", &Some(4));
+        context.insert("last_dispute_hours", &None::<u64>);
+        context.insert("last_dispute_minutes", &None::<u64>);
+        context.insert("relays_down", &true);
+        context.insert("queue_backlog", &Some(73));
+        context.insert("relay", "wss://relay.example");
+        context.insert("p50", &2);
+        context.insert("p90", &75);
+        context.insert("p99", &140);
+        context.insert("store_error", "attempt to write a readonly database");
+        context.insert("threshold", &30);
+        context.insert("score", &12);
+        context.insert("retry_minutes", &60);
+        context.insert("rtt_ms", &None::<u64>);
+        context.insert("threshold_ms", &2000);
+        context.insert("failures", &3);
+        context.insert("engaged", &true);
+        context.insert("primary_count", &2);
+        context.insert("lost", &true);
+        context.insert("min_connected", &2);
+        context.insert("relay_count", &3);
+        context.insert("closed", "rate-limited: slow down");
+        context.insert("missed", &3);
+        context.insert("relays", &["wss://relay.damus.io"]);
+        context.insert("idle_seconds", &240);
+        context.insert("target", "x86_64-unknown-linux-gnu");
+        context.insert("profile", "release");
+        context.insert("minutes", &15);
+        context.insert("alert", "🚨 *NEW DISPUTE*");
+        context.insert("message", "Maintenance at 20:00 UTC.");
+
+        for language in [
+            Language::En,
+            Language::Es,
+            Language::Pt,
+            Language::It,
+            Language::De,
+        ] {
+            let names: Vec<&str> = builtin_templates(language)
+                .iter()
+                .map(|(name, _)| *name)
+                .collect();
+            assert_eq!(names, english, "{language:?} is missing templates");
+
+            let config = TemplatesConfig {
+                language,
+                ..Default::default()
+            };
+            let templates = Templates::load(&config).unwrap();
+            for name in &names {
+                let rendered = templates
+                    .inner
+                    .read()
+                    .unwrap()
+                    .tera
+                    .render(&format!("{name}.md"), &context)
+                    .unwrap_or_else(|e| panic!("{language:?}/{name}: {e}"));
+                assert_valid_markdown(&rendered);
+            }
+        }
+    }
+
+    #[test]
+    fn test_replace() {
+        let templates = Templates::default();
+        let config: TemplatesConfig = toml::from_str("language = \"es\"").unwrap();
+        templates.replace(Templates::load(&config).unwrap());
+        let msg = templates.render("initiated", &dispute_context("initiated", None));
+        assert!(msg.starts_with("🚨 *NUEVA DISPUTA*"));
+
+        let mut context = Context::new();
+        context.insert("applied", &["alerts.initiated"]);
+        context.insert("restart_required", &Vec::<&str>::new());
+        context.insert("error", &None::<String>);
+        let msg = templates.render("config_reloaded", &context);
+        assert!(msg.contains("alerts\\.initiated"), "{msg}");
+    }
+
+    #[test]
+    fn test_language_config() {
+        let config: TemplatesConfig = toml::from_str("language = \"es\"").unwrap();
+        let templates = Templates::load(&config).unwrap();
+        let msg = templates.render("initiated", &dispute_context("initiated", None));
+        assert!(msg.starts_with("🚨 *NUEVA DISPUTA*"));
+
+        assert!(toml::from_str::<TemplatesConfig>("language = \"xx\"").is_err());
+    }
+
+    #[test]
+    fn test_templates_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("heartbeat.md"),
+            "alive {{ events_processed }}",
+        )
+        .unwrap();
+
+        let config = TemplatesConfig {
+            dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let templates = Templates::load(&config).unwrap();
+
+        let mut context = Context::new();
+        context.insert("events_processed", &3);
+        assert_eq!(templates.render("heartbeat", &context), "alive 3");
+    }
+}
@@ -0,0 +1,209 @@
+//! Timestamp formatting.
+//!
+//! Logs, CLI output, the health JSON and Alertmanager use fixed formats; messages use
+//! `templates.time_format`, in the language of the built-in messages:
+//!
+//! - `utc`: `2024-02-20 14:05:09 UTC` (default)
+//! - `iso8601`: `2024-02-20T14:05:09Z`
+//! - `locale`: `Feb 20, 2024, 14:05 UTC`, `20 feb 2024, 14:05 UTC`, ...
+//! - `relative`: `5 minutes ago`, `in 3 hours`, ..., as of when the message is rendered
+
+use crate::config::{Language, TimeFormat};
+use chrono::{DateTime, Datelike, NaiveDateTime, SecondsFormat, Utc};
+
+/// `2024-02-20 14:05:09 UTC`, for logs and CLI output
+pub fn utc(unix: u64) -> String {
+    datetime(unix).format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// `2024-02-20T14:05:09Z` (RFC 3339), for machine-readable output
+pub fn iso8601(unix: u64) -> String {
+    datetime(unix).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// `unix` in `format` and `language`, relative times counted from `now`
+pub fn format(unix: u64, format: TimeFormat, language: Language, now: u64) -> String {
+    match format {
+        TimeFormat::Utc => utc(unix),
+        TimeFormat::Iso8601 => iso8601(unix),
+        TimeFormat::Locale => locale(unix, language),
+        TimeFormat::Relative => relative(unix as i64 - now as i64, language),
+    }
+}
+
+/// Parse an RFC 9110 IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`) to a Unix timestamp
+pub fn parse_http_date(date: &str) -> Option<u64> {
+    let parsed = NaiveDateTime::parse_from_str(date, "%a, %d %b %Y %H:%M:%S GMT").ok()?;
+    u64::try_from(parsed.and_utc().timestamp()).ok()
+}
+
+fn datetime(unix: u64) -> DateTime<Utc> {
+    i64::try_from(unix)
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or_default()
+}
+
+fn locale(unix: u64, language: Language) -> String {
+    let datetime = datetime(unix);
+    let (day, year) = (datetime.day(), datetime.year());
+    let time = datetime.format("%H:%M UTC");
+    let month = datetime.month0() as usize;
+    match language {
+        Language::En => format!("{} {day}, {year}, {time}", MONTHS_EN[month]),
+        Language::Es => format!("{day} {} {year}, {time}", MONTHS_ES[month]),
+        Language::Pt => format!("{day} {} {year}, {time}", MONTHS_PT[month]),
+        Language::It => format!("{day} {} {year}, {time}", MONTHS_IT[month]),
+        Language::De => format!("{day}. {} {year}, {time}", MONTHS_DE[month]),
+    }
+}
+
+const MONTHS_EN: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const MONTHS_ES: [&str; 12] = [
+    "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+];
+const MONTHS_PT: [&str; 12] = [
+    "jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+];
+const MONTHS_IT: [&str; 12] = [
+    "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+];
+const MONTHS_DE: [&str; 12] = [
+    "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.", "Dez.",
+];
+
+/// `offset` seconds from now (negative in the past) in words
+fn relative(offset: i64, language: Language) -> String {
+    let secs = offset.unsigned_abs();
+    let (count, unit) = match secs {
+        0..=59 => return just_now(language).to_string(),
+        60..=3599 => (secs / 60, Unit::Minute),
+        3600..=86399 => (secs / 3600, Unit::Hour),
+        _ => (secs / 86400, Unit::Day),
+    };
+    let unit = unit.name(language, count == 1);
+    let past = offset < 0;
+    match (language, past) {
+        (Language::En, true) => format!("{count} {unit} ago"),
+        (Language::En, false) => format!("in {count} {unit}"),
+        (Language::Es, true) => format!("hace {count} {unit}"),
+        (Language::Es, false) => format!("en {count} {unit}"),
+        (Language::Pt, true) => format!("há {count} {unit}"),
+        (Language::Pt, false) => format!("em {count} {unit}"),
+        (Language::It, true) => format!("{count} {unit} fa"),
+        (Language::It, false) => format!("tra {count} {unit}"),
+        (Language::De, true) => format!("vor {count} {unit}"),
+        (Language::De, false) => format!("in {count} {unit}"),
+    }
+}
+
+fn just_now(language: Language) -> &'static str {
+    match language {
+        Language::En => "just now",
+        Language::Es => "justo ahora",
+        Language::Pt => "agora mesmo",
+        Language::It => "proprio ora",
+        Language::De => "gerade eben",
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Unit {
+    fn name(self, language: Language, one: bool) -> &'static str {
+        let (singular, plural) = match (language, self) {
+            (Language::En, Unit::Minute) => ("minute", "minutes"),
+            (Language::En, Unit::Hour) => ("hour", "hours"),
+            (Language::En, Unit::Day) => ("day", "days"),
+            (Language::Es, Unit::Minute) => ("minuto", "minutos"),
+            (Language::Es, Unit::Hour) => ("hora", "horas"),
+            (Language::Es, Unit::Day) => ("día", "días"),
+            (Language::Pt, Unit::Minute) => ("minuto", "minutos"),
+            (Language::Pt, Unit::Hour) => ("hora", "horas"),
+            (Language::Pt, Unit::Day) => ("dia", "dias"),
+            (Language::It, Unit::Minute) => ("minuto", "minuti"),
+            (Language::It, Unit::Hour) => ("ora", "ore"),
+            (Language::It, Unit::Day) => ("giorno", "giorni"),
+            (Language::De, Unit::Minute) => ("Minute", "Minuten"),
+            (Language::De, Unit::Hour) => ("Stunde", "Stunden"),
+            // Dative plural, as in "vor 2 Tagen" and "in 2 Tagen"
+            (Language::De, Unit::Day) => ("Tag", "Tagen"),
+        };
+        if one {
+            singular
+        } else {
+            plural
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utc() {
+        assert_eq!(utc(1609459200), "2021-01-01 00:00:00 UTC");
+        assert_eq!(utc(1640995200), "2022-01-01 00:00:00 UTC");
+        assert_eq!(utc(1609462861), "2021-01-01 01:01:01 UTC");
+        // Leap day
+        assert_eq!(utc(1582934400), "2020-02-29 00:00:00 UTC");
+        assert_eq!(utc(0), "1970-01-01 00:00:00 UTC");
+    }
+
+    #[test]
+    fn test_iso8601() {
+        assert_eq!(iso8601(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601(1708425600), "2024-02-20T10:40:00Z");
+        assert_eq!(iso8601(951782400), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn test_formats() {
+        let unix = 1708437909; // 2024-02-20 14:05:09 UTC
+        let now = unix + 300;
+        let cases = [
+            (TimeFormat::Iso8601, Language::En, "2024-02-20T14:05:09Z"),
+            (TimeFormat::Locale, Language::En, "Feb 20, 2024, 14:05 UTC"),
+            (TimeFormat::Locale, Language::Es, "20 feb 2024, 14:05 UTC"),
+            (TimeFormat::Locale, Language::De, "20. Feb. 2024, 14:05 UTC"),
+            (TimeFormat::Relative, Language::En, "5 minutes ago"),
+            (TimeFormat::Relative, Language::It, "5 minuti fa"),
+        ];
+        for (time_format, language, expected) in cases {
+            assert_eq!(format(unix, time_format, language, now), expected);
+        }
+    }
+
+    #[test]
+    fn test_relative() {
+        assert_eq!(relative(-30, Language::En), "just now");
+        assert_eq!(relative(-3600, Language::En), "1 hour ago");
+        assert_eq!(relative(3 * 86400 + 5, Language::En), "in 3 days");
+        assert_eq!(relative(-2 * 86400, Language::De), "vor 2 Tagen");
+        assert_eq!(relative(7200, Language::Pt), "em 2 horas");
+        assert_eq!(relative(-60, Language::Es), "hace 1 minuto");
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(784111777)
+        );
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(1709164800)
+        );
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 CET"), None);
+        assert_eq!(parse_http_date(""), None);
+    }
+}
@@ -0,0 +1,90 @@
+//! TLS for the HTTP endpoint, from PEM files or a generated self-signed certificate.
+
+use crate::config::HttpTlsConfig;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use tokio_rustls::rustls::{crypto, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tracing::{info, warn};
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+/// Build the acceptor for the configured certificate
+pub fn acceptor(config: &HttpTlsConfig) -> Result<TlsAcceptor, Error> {
+    let (certs, key) = match (&config.cert, &config.key) {
+        (Some(cert), Some(key)) => {
+            info!("🔒 Serving HTTPS with certificate {}", cert.display());
+            (load_certs(cert)?, load_key(key)?)
+        }
+        _ => {
+            warn!(
+                "🔒 Serving HTTPS with a generated self-signed certificate for {:?}; \
+                 clients must skip verification or pin it",
+                config.hostnames
+            );
+            self_signed(&config.hostnames)?
+        }
+    };
+
+    let mut server_config =
+        ServerConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)?;
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(format!("no certificates in {}", path.display()).into());
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| format!("no private key in {}", path.display()).into())
+}
+
+fn self_signed(
+    hostnames: &[String],
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), Error> {
+    let rcgen::CertifiedKey { cert, key_pair } =
+        rcgen::generate_simple_self_signed(hostnames.to_vec())?;
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key_pair.serialize_der()));
+    Ok((vec![cert.der().clone()], key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_signed_acceptor() {
+        let config = HttpTlsConfig {
+            cert: None,
+            key: None,
+            self_signed: true,
+            hostnames: vec!["localhost".into()],
+        };
+        assert!(acceptor(&config).is_ok());
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let config = HttpTlsConfig {
+            cert: Some("/nonexistent/cert.pem".into()),
+            key: Some("/nonexistent/key.pem".into()),
+            self_signed: false,
+            hostnames: Vec::new(),
+        };
+        assert!(acceptor(&config).is_err());
+    }
+}
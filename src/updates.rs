@@ -0,0 +1,117 @@
+//! Opt-in check for new mostro-watchdog releases on GitHub.
+
+use crate::templates::Templates;
+use crate::{send_markdown, Destination, VERSION};
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::Bot;
+use tracing::{info, warn};
+
+const LATEST_RELEASE_API: &str =
+    "https://api.github.com/repos/MostroP2P/mostro-watchdog/releases/latest";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+}
+
+/// Periodically check for a newer release and announce each new version once
+pub async fn run(
+    bot: Bot,
+    destination: Destination,
+    templates: Arc<Templates>,
+    interval: Duration,
+) {
+    let http = match reqwest::Client::builder()
+        .user_agent(concat!("mostro-watchdog/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(30))
+        .build()
+    {
+        Ok(http) => http,
+        Err(e) => {
+            warn!("Update check disabled, failed to create HTTP client: {}", e);
+            return;
+        }
+    };
+
+    let mut announced: Option<String> = None;
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        ticker.tick().await;
+
+        let release = match latest_release(&http).await {
+            Ok(release) => release,
+            Err(e) => {
+                warn!("Failed to check for new releases: {}", e);
+                continue;
+            }
+        };
+
+        if !is_newer(&release.tag_name, VERSION)
+            || announced.as_deref() == Some(release.tag_name.as_str())
+        {
+            continue;
+        }
+
+        info!("🆕 New release available: {}", release.tag_name);
+        let mut context = tera::Context::new();
+        context.insert("latest", release.tag_name.trim_start_matches('v'));
+        context.insert("version", VERSION);
+        context.insert("url", &release.html_url);
+        let message = templates.render("update_available", &context);
+
+        match send_markdown(&bot, destination, &message).await {
+            Ok(_) => announced = Some(release.tag_name),
+            Err(e) => warn!("Failed to send update notification: {}", e),
+        }
+    }
+}
+
+async fn latest_release(http: &reqwest::Client) -> Result<Release, reqwest::Error> {
+    http.get(LATEST_RELEASE_API)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+}
+
+/// Whether release tag `latest` (e.g. "v0.3.0") is newer than version `current`
+fn is_newer(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Parse `[v]MAJOR.MINOR.PATCH`, ignoring any pre-release or build suffix
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts.next()?.parse().ok()?;
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.2.6", "0.2.5"));
+        assert!(is_newer("v0.10.0", "0.9.9"));
+        assert!(is_newer("1.0.0", "0.2.5"));
+        assert!(!is_newer("v0.2.5", "0.2.5"));
+        assert!(!is_newer("v0.2.4", "0.2.5"));
+        assert!(!is_newer("nightly", "0.2.5"));
+        assert_eq!(parse_version("v1.2.3-rc1"), Some((1, 2, 3)));
+    }
+}
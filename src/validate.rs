@@ -0,0 +1,114 @@
+//! `mostro-watchdog validate [config]`: check a config file without starting the
+//! watchdog, for CI and pre-deploy checks.
+//!
+//! Runs every check `Config::load` does, plus asking Telegram to accept the bot
+//! token. Problems are printed with the file and line they come from, and the
+//! process exits non-zero.
+
+use crate::cli::Overrides;
+use crate::config::ConfigErrors;
+use crate::proxy;
+use std::path::Path;
+use teloxide::prelude::*;
+
+/// Validate the config at `path`, printing a report. Returns whether it is valid.
+pub async fn run(path: &Path, overrides: &Overrides) -> bool {
+    let config = match overrides.load(path) {
+        Ok(config) => config,
+        Err(e) => {
+            match e.downcast_ref::<ConfigErrors>() {
+                Some(errors) => {
+                    let content = std::fs::read_to_string(path).unwrap_or_default();
+                    for problem in locate(path, &content, errors) {
+                        eprintln!("❌ {}", problem);
+                    }
+                }
+                None => eprintln!("❌ {}: {}", path.display(), e),
+            }
+            return false;
+        }
+    };
+
+    let bot = match proxy::bot(&config.telegram) {
+        Ok(bot) => bot,
+        Err(e) => {
+            eprintln!("❌ {}: {}", path.display(), e);
+            return false;
+        }
+    };
+    match bot.get_me().await {
+        Ok(me) => println!("✅ Telegram bot token accepted (@{})", me.username()),
+        Err(e) => {
+            eprintln!(
+                "❌ {}: telegram.bot_token: rejected by Telegram: {}",
+                path.display(),
+                e
+            );
+            return false;
+        }
+    }
+    println!("✅ {} is valid", path.display());
+    true
+}
+
+/// Each problem as `path:line: key: message`, or without the line when the value
+/// can't be found in the file
+fn locate(path: &Path, content: &str, errors: &ConfigErrors) -> Vec<String> {
+    errors
+        .0
+        .iter()
+        .map(|error| {
+            match error
+                .value
+                .as_deref()
+                .and_then(|value| line_of(content, value))
+            {
+                Some(line) => format!("{}:{}: {}", path.display(), line, error),
+                None => format!("{}: {}", path.display(), error),
+            }
+        })
+        .collect()
+}
+
+/// 1-based line of the first quoted occurrence of `value` in the file
+fn line_of(content: &str, value: &str) -> Option<usize> {
+    let quoted = format!("\"{value}\"");
+    content
+        .lines()
+        .position(|line| line.contains(&quoted))
+        .map(|i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    const CONFIG: &str = r#"[mostro]
+pubkey = "not-a-key"
+
+[nostr]
+relays = [
+    "wss://relay.mostro.network",
+    "https://nos.lol",
+]
+
+[telegram]
+bot_token = "123:ABC"
+chat_id = -100
+"#;
+
+    #[test]
+    fn test_locate() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let errors = config.validate().unwrap_err();
+        let problems = locate(Path::new("config.toml"), CONFIG, &errors);
+        assert_eq!(
+            problems,
+            vec![
+                "config.toml:7: nostr.relays[1]: 'https://nos.lol' must use the ws:// or wss:// scheme",
+                "config.toml:2: mostro.pubkey: 'not-a-key' is not an npub, nprofile or hex public key",
+            ]
+        );
+    }
+}